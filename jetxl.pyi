@@ -37,7 +37,15 @@ Advanced Usage:
     ... )
 """
 
-from typing import Any, Optional, Literal, TypedDict, List, Dict, Tuple, Union
+from typing import Any, Callable, Optional, Literal, TypedDict, List, Dict, Tuple, Union
+import datetime
+
+CONFIG_VERSION: int
+"""Version of the formatting option semantics. It is bumped whenever an
+existing option changes meaning, so stored report definitions can record the
+version they were written for and be migrated when it changes. Also written to
+the generated summary sheet. Sheet dicts may record it as "config_version";
+a version newer than this one raises ValueError."""
 
 # =============================================================================
 # NUMBER FORMATS
@@ -53,13 +61,26 @@ NumberFormat = Literal[
     "percentage_integer",   # Percentage integer: 0%
     "currency",             # Currency: $#,##0.00
     "currency_rounded",     # Rounded currency: $#,##0
+    "currency_eur",         # Euro: €#,##0.00 (see Locale Currencies below)
+    "currency_gbp",         # Pound sterling: £#,##0.00
+    "currency_jpy",         # Yen: ¥#,##0
+    "currency_inr",         # Indian rupee: ₹#,##0.00
+    "decimal2_red_negative",  # Red negatives in parentheses: 0.00;[Red](0.00)
+    "currency_red_negative",  # $#,##0.00;[Red]($#,##0.00); see Negative Numbers below
     "date",                 # Date: yyyy-mm-dd
+    "date_iso",             # ISO date: yyyy-mm-dd
+    "date_us",              # US date: mm/dd/yyyy
+    "date_long",            # Long date: dddd, mmmm d, yyyy (Monday, March 4, 2024)
+    "month_year",           # Month and year: mmm yyyy (Mar 2024)
+    "week_day",             # Day of the week: dddd (Monday)
     "datetime",             # Date and time: yyyy-mm-dd hh:mm:ss
     "time",                 # Time: hh:mm:ss
     "scientific",           # Scientific notation: 0.00E+00
     "fraction",             # Fraction: # ?/?
     "fraction_two_digits",  # Fraction with 2 digits: # ??/??
     "thousands",            # Thousands separator: #,##0
+    "bool_yes_no",          # Boolean columns as Yes/No: "Yes";"Yes";"No"
+    "text",                 # Text: @
 ] | str                     # Any string not matching above becomes a custom Excel format code
 
 """
@@ -182,19 +203,157 @@ Testing Custom Formats:
 Reference: https://support.microsoft.com/en-us/office/number-format-codes-5026bbd6-04bc-48cd-bf33-80f18b4eae68
 """
 
+class ColumnFormat(TypedDict, total=False):
+    """Column format with an optional display timezone or boolean labels.
+    
+    Use instead of a plain format string in column_formats when a timestamp
+    column should be shown in a specific timezone, or a boolean column with
+    labels other than TRUE/FALSE.
+    
+    Attributes:
+        format: Built-in format name or format code (default: "datetime" for timestamps)
+        code: Custom format code; takes precedence over format
+        tz: IANA timezone name or fixed offset ("America/New_York", "+05:30").
+            Timestamps are read as UTC instants and written as wall-clock time
+            in this zone. Invalid names raise ValueError.
+        true: Label for true values of a boolean column (requires false)
+        false: Label for false values of a boolean column (requires true).
+            The column is written as 1/0 so the labels show; like "bool_yes_no"
+    
+    Example:
+        >>> column_formats = {
+        ...     "CreatedAt": {"format": "datetime", "tz": "America/New_York", "code": "yyyy-mm-dd hh:mm"},
+        ...     "Enabled": {"true": "On", "false": "Off"},
+        ...     "Price": "currency",
+        ... }
+    """
+    format: NumberFormat
+    code: str
+    tz: str
+    true: str
+    false: str
+
+# =============================================================================
+# AUTOFILTER
+# =============================================================================
+
+class AutoFilterCriteria(TypedDict, total=False):
+    """Autofilter criteria for one column (the `filters` option).
+    
+    Attributes:
+        type: "values" (default), "range", "top10" or "contains"
+        values: For "values": values to show, matched case-insensitively.
+            None in the list also shows blank cells
+        blanks: For "values": show blank cells (default: True if None is in values)
+        min: For "range": inclusive lower bound, a number or date/datetime
+        max: For "range": inclusive upper bound, a number or date/datetime
+        rank: For "top10": how many items (1-500) or percent (1-100) to show (default: 10)
+        bottom: For "top10": show the smallest values instead (default: False)
+        percent: For "top10": rank is a percentage of rows (default: False)
+        text: For "contains": substring to look for, case-insensitive
+    
+    Example:
+        >>> filters = {
+        ...     "Region": ["East", "West"],
+        ...     "Sales": {"type": "range", "min": 1000, "max": 5000},
+        ...     "OrderDate": {"type": "range", "min": datetime.date(2024, 1, 1)},
+        ...     "Score": {"type": "top10", "rank": 5},
+        ...     "Product": {"type": "contains", "text": "pro"},
+        ... }
+    """
+    type: Literal["values", "range", "top10", "contains"]
+    values: List[Any]
+    blanks: bool
+    min: Union[float, datetime.date, datetime.datetime]
+    max: Union[float, datetime.date, datetime.datetime]
+    rank: int
+    bottom: bool
+    percent: bool
+    text: str
+
+class SortState(TypedDict):
+    """A sort already applied to the data (the `sort_state` option).
+    
+    Records the sort so Excel shows the sort arrows and re-applies it after
+    edits (Data > Reapply). Rows are written in the order given; sort the
+    data before writing.
+    
+    Attributes:
+        ref: Sorted range in A1 notation, without the header row (e.g. "A2:F100")
+        keys: Sort columns, first key first. Each is a column index inside
+            ref, or a (column, "ascending" | "descending") tuple
+    
+    Example:
+        >>> sort_state = {"ref": "A2:F100", "keys": [(2, "descending"), 0]}
+    """
+    ref: str
+    keys: List[Union[int, Tuple[int, Literal["ascending", "descending"]]]]
+
 # =============================================================================
 # FONT STYLING
 # =============================================================================
 
+class ThemeColor(TypedDict, total=False):
+    """Reference to a color of the workbook theme.
+    
+    Theme colors follow the workbook when users switch Office themes,
+    unlike fixed ARGB colors. Accepted anywhere a font, fill or border
+    color is.
+    
+    Attributes:
+        theme: Theme color index (required): 0 background 1 (light 1),
+            1 text 1 (dark 1), 2 background 2, 3 text 2, 4-9 accent 1-6,
+            10 hyperlink, 11 followed hyperlink
+        tint: -1.0 to 1.0; negative darkens, positive lightens
+    
+    Example:
+        >>> color = {"theme": 4, "tint": -0.25}  # Accent 1, 25% darker
+    """
+    theme: int
+    tint: float
+
+class IndexedColor(TypedDict):
+    """Slot of Excel's legacy 64-color palette, as used by workbooks
+    migrated from .xls files.
+    
+    Attributes:
+        indexed: Palette index 0-63. Refers to the default palette, or to
+            indexed_colors when the workbook sets one
+    
+    Example:
+        >>> color = {"indexed": 10}  # Red in the default palette
+    """
+    indexed: int
+
+class WorkbookTheme(TypedDict, total=False):
+    """Overrides for the workbook's Office theme.
+    
+    Attributes:
+        accents: Up to 6 "RRGGBB" or "AARRGGBB" colors replacing accent1-6
+            (default 4472C4, ED7D31, A5A5A5, FFC000, 5B9BD5, 70AD47)
+        major_font: Heading typeface (default "Calibri Light")
+        minor_font: Body typeface (default "Calibri")
+    
+    Example:
+        >>> theme = {"accents": ["1F4E78", "C00000"], "major_font": "Georgia"}
+    """
+    accents: List[str]
+    major_font: str
+    minor_font: str
+
 class FontStyle(TypedDict, total=False):
     """Font styling options.
     
     Attributes:
         bold: Bold text
         italic: Italic text
-        underline: Underlined text
+        underline: True/"single", "double", "single_accounting" or
+            "double_accounting" (accounting underlines span the cell)
+        strike: Strikethrough
+        vert_align: "superscript", "subscript" or "baseline"
         size: Font size in points (e.g., 11.0)
         color: Font color in ARGB hex format (e.g., "FFFF0000" for red)
+            or a ThemeColor/IndexedColor dict
         name: Font name (e.g., "Calibri", "Arial")
     
     Color Format (ARGB):
@@ -222,9 +381,11 @@ class FontStyle(TypedDict, total=False):
     """
     bold: bool
     italic: bool
-    underline: bool
+    underline: Union[bool, Literal["single", "double", "single_accounting", "double_accounting"]]
+    strike: bool
+    vert_align: Literal["superscript", "subscript", "baseline"]
     size: float
-    color: str  # ARGB hex: "FFFF0000" for red
+    color: Union[str, ThemeColor, IndexedColor]  # ARGB hex: "FFFF0000" for red
     name: str
 
 # =============================================================================
@@ -236,8 +397,8 @@ class FillStyle(TypedDict, total=False):
     
     Attributes:
         pattern: Fill pattern type
-        fg_color: Foreground color in ARGB hex
-        bg_color: Background color in ARGB hex
+        fg_color: Foreground color in ARGB hex or a ThemeColor/IndexedColor dict
+        bg_color: Background color in ARGB hex or a ThemeColor/IndexedColor dict
     
     Pattern Types:
         - "solid": Solid fill
@@ -252,8 +413,8 @@ class FillStyle(TypedDict, total=False):
         ... }
     """
     pattern: Literal["solid", "gray125", "none"]
-    fg_color: str  # ARGB hex
-    bg_color: str  # ARGB hex
+    fg_color: Union[str, ThemeColor, IndexedColor]  # ARGB hex
+    bg_color: Union[str, ThemeColor, IndexedColor]  # ARGB hex
 
 # =============================================================================
 # BORDER STYLING
@@ -264,7 +425,7 @@ class BorderSide(TypedDict, total=False):
     
     Attributes:
         style: Line style for the border
-        color: Border color in ARGB hex
+        color: Border color in ARGB hex or a ThemeColor/IndexedColor dict
     
     Border Styles:
         - "thin": Thin line
@@ -273,6 +434,13 @@ class BorderSide(TypedDict, total=False):
         - "double": Double line
         - "dotted": Dotted line
         - "dashed": Dashed line
+        - "hair": Hairline (finest dotted line)
+        - "medium_dashed": Medium dashed line
+        - "dash_dot": Dash-dot line
+        - "medium_dash_dot": Medium dash-dot line
+        - "dash_dot_dot": Dash-dot-dot line
+        - "slant_dash_dot": Medium slanted dash-dot line
+        Excel's camelCase names ("mediumDashed", ...) are accepted too
     
     Example:
         >>> border_side = {
@@ -280,8 +448,12 @@ class BorderSide(TypedDict, total=False):
         ...     "color": "FF000000"  # Black
         ... }
     """
-    style: Literal["thin", "medium", "thick", "double", "dotted", "dashed"]
-    color: str  # ARGB hex
+    style: Literal[
+        "thin", "medium", "thick", "double", "dotted", "dashed", "hair",
+        "medium_dashed", "dash_dot", "medium_dash_dot", "dash_dot_dot", "slant_dash_dot",
+        "mediumDashed", "dashDot", "mediumDashDot", "dashDotDot", "slantDashDot",
+    ]
+    color: Union[str, ThemeColor, IndexedColor]  # ARGB hex
 
 class BorderStyle(TypedDict, total=False):
     """Cell border styling for all four sides.
@@ -313,10 +485,20 @@ class AlignmentStyle(TypedDict, total=False):
     """Cell alignment options.
     
     Attributes:
-        horizontal: Horizontal alignment
-        vertical: Vertical alignment
+        horizontal: Horizontal alignment. "fill" repeats the text across the
+            cell, "center_continuous" centers across the empty cells to the
+            right, "distributed" spreads the words over the cell width.
+            "centerContinuous" is accepted too
+        vertical: Vertical alignment; "justify" and "distributed" spread
+            wrapped lines over the cell height
         wrap_text: Enable text wrapping
         text_rotation: Text rotation in degrees (0-180)
+        indent: Indent level 0-250, about three characters each; cells
+            without a horizontal alignment are left aligned
+        shrink_to_fit: Shrink the font until the text fits the column width
+        reading_order: Text direction of the cell: "context" (default, from
+            the first strong character), "ltr" or "rtl". Unlike the sheet's
+            right_to_left it applies to single cells
     
     Example:
         >>> alignment = {
@@ -326,10 +508,15 @@ class AlignmentStyle(TypedDict, total=False):
         ...     "text_rotation": 0
         ... }
     """
-    horizontal: Literal["left", "center", "right", "justify"]
-    vertical: Literal["top", "center", "bottom"]
+    horizontal: Literal[
+        "general", "left", "center", "right", "justify", "fill", "center_continuous", "distributed",
+    ]
+    vertical: Literal["top", "center", "bottom", "justify", "distributed"]
     wrap_text: bool
     text_rotation: int  # 0-180 degrees
+    indent: int
+    shrink_to_fit: bool
+    reading_order: Literal["context", "ltr", "rtl"]
 
 # =============================================================================
 # COMPLETE CELL STYLE
@@ -391,6 +578,8 @@ class CellStyleMap(TypedDict):
         border: Border styling (optional)
         alignment: Alignment styling (optional)
         number_format: Number format (optional)
+        style: Name of a style registered with `styles`, used instead of
+            the keys above (optional)
     
     Example - Style cell B3:
         >>> cell_style = {
@@ -407,11 +596,16 @@ class CellStyleMap(TypedDict):
     border: BorderStyle
     alignment: AlignmentStyle
     number_format: NumberFormat
+    style: str    # name of a registered style
 
 # =============================================================================
 # DATA VALIDATION
 # =============================================================================
 
+# An A1 range ("B2:D10") or a (start_row, start_col, end_row, end_col) tuple.
+# A1 ranges are absolute sheet positions: indexing and coordinates don't apply.
+CellRange = Union[str, Tuple[int, int, int, int]]
+
 class DataValidationList(TypedDict):
     """Dropdown list validation.
     
@@ -420,11 +614,24 @@ class DataValidationList(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         type: Validation type (must be "list")
-        items: List of valid options
+        items: List of valid options. Excel limits the joined list to 255 characters
+        source: Cell range holding the options, e.g. "Lookups!$A$1:$A$50".
+            Use instead of items for long lists; quote sheet names with spaces
+            ("'My Sheet'!$A$1:$A$50"). The sheet must exist in the workbook
         show_dropdown: Show dropdown arrow
         error_title: Error dialog title
         error_message: Error dialog message
+        prompt_title: Title of the input message shown when a cell is selected
+        prompt_message: Input message shown when a cell is selected
+        show_input_message: Show the input message (default: True when a
+            prompt title or message is given)
+        error_style: "stop" (default) rejects invalid input; "warning" and
+            "information" alert but let the user keep it
+        show_error_message: Show the error alert for invalid input (default: True)
+        allow_blank: Accept empty cells (default: True)
     
     Example - Status dropdown:
         >>> validation = {
@@ -434,18 +641,28 @@ class DataValidationList(TypedDict):
         ...     "items": ["Pending", "In Progress", "Complete", "Cancelled"],
         ...     "show_dropdown": True,
         ...     "error_title": "Invalid Status",
-        ...     "error_message": "Please select a valid status from the list"
+        ...     "error_message": "Please select a valid status from the list",
+        ...     "prompt_title": "Status",
+        ...     "prompt_message": "Pick the current status of the task"
         ... }
     """
     start_row: int
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     type: Literal["list"]
     items: List[str]
+    source: str
     show_dropdown: bool
     error_title: str
     error_message: str
+    prompt_title: str
+    prompt_message: str
+    show_input_message: bool
+    error_style: Literal["stop", "warning", "information"]
+    show_error_message: bool
+    allow_blank: bool
 
 class DataValidationNumber(TypedDict):
     """Number range validation.
@@ -455,12 +672,22 @@ class DataValidationNumber(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         type: Validation type ("whole_number" or "decimal")
         min: Minimum allowed value
         max: Maximum allowed value
         show_dropdown: Show dropdown arrow
         error_title: Error dialog title
         error_message: Error dialog message
+        prompt_title: Title of the input message shown when a cell is selected
+        prompt_message: Input message shown when a cell is selected
+        show_input_message: Show the input message (default: True when a
+            prompt title or message is given)
+        error_style: "stop" (default) rejects invalid input; "warning" and
+            "information" alert but let the user keep it
+        show_error_message: Show the error alert for invalid input (default: True)
+        allow_blank: Accept empty cells (default: True)
     
     Example - Age validation:
         >>> validation = {
@@ -478,12 +705,19 @@ class DataValidationNumber(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     type: Literal["whole_number", "decimal"]
     min: float
     max: float
     show_dropdown: bool
     error_title: str
     error_message: str
+    prompt_title: str
+    prompt_message: str
+    show_input_message: bool
+    error_style: Literal["stop", "warning", "information"]
+    show_error_message: bool
+    allow_blank: bool
 
 class DataValidationTextLength(TypedDict):
     """Text length validation.
@@ -493,12 +727,22 @@ class DataValidationTextLength(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         type: Validation type (must be "text_length")
         min: Minimum text length
         max: Maximum text length
         show_dropdown: Show dropdown arrow
         error_title: Error dialog title
         error_message: Error dialog message
+        prompt_title: Title of the input message shown when a cell is selected
+        prompt_message: Input message shown when a cell is selected
+        show_input_message: Show the input message (default: True when a
+            prompt title or message is given)
+        error_style: "stop" (default) rejects invalid input; "warning" and
+            "information" alert but let the user keep it
+        show_error_message: Show the error alert for invalid input (default: True)
+        allow_blank: Accept empty cells (default: True)
     
     Example - Username validation:
         >>> validation = {
@@ -516,12 +760,19 @@ class DataValidationTextLength(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     type: Literal["text_length"]
     min: int
     max: int
     show_dropdown: bool
     error_title: str
     error_message: str
+    prompt_title: str
+    prompt_message: str
+    show_input_message: bool
+    error_style: Literal["stop", "warning", "information"]
+    show_error_message: bool
+    allow_blank: bool
 
 DataValidation = DataValidationList | DataValidationNumber | DataValidationTextLength
 
@@ -537,6 +788,8 @@ class ConditionalFormatCellValue(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         rule_type: Must be "cell_value"
         operator: Comparison operator
         value: Value to compare against (as string)
@@ -570,6 +823,7 @@ class ConditionalFormatCellValue(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     rule_type: Literal["cell_value"]
     operator: Literal[
         "greater_than",
@@ -582,7 +836,8 @@ class ConditionalFormatCellValue(TypedDict):
     ]
     value: str
     priority: int
-    style: CellStyle
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
 
 class ConditionalFormatColorScale(TypedDict):
     """Color scale conditional formatting (gradient).
@@ -592,11 +847,21 @@ class ConditionalFormatColorScale(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         rule_type: Must be "color_scale"
         min_color: Color for minimum values (ARGB hex)
         max_color: Color for maximum values (ARGB hex)
         mid_color: Optional color for midpoint values (ARGB hex)
         priority: Rule priority (lower = higher priority)
+        min_type: Threshold for min_color: "min" (default), "num", "percent",
+            "percentile" or "formula"
+        min_value: Threshold value, required for num/percent/percentile/formula
+        mid_type: Threshold for mid_color (default: percentile 50)
+        mid_value: Threshold value for mid_type
+        max_type: Threshold for max_color: "max" (default), "num", "percent",
+            "percentile" or "formula"
+        max_value: Threshold value, required for num/percent/percentile/formula
     
     Example - Red-Yellow-Green scale:
         >>> color_scale = {
@@ -613,11 +878,19 @@ class ConditionalFormatColorScale(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     rule_type: Literal["color_scale"]
     min_color: str  # ARGB hex
     max_color: str  # ARGB hex
     mid_color: str  # Optional, ARGB hex
     priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    min_type: Literal["min", "num", "percent", "percentile", "formula"]
+    min_value: Union[int, float, str]
+    mid_type: Literal["num", "percent", "percentile", "formula"]
+    mid_value: Union[int, float, str]
+    max_type: Literal["max", "num", "percent", "percentile", "formula"]
+    max_value: Union[int, float, str]
 
 class ConditionalFormatDataBar(TypedDict):
     """Data bar conditional formatting (horizontal bars in cells).
@@ -627,10 +900,24 @@ class ConditionalFormatDataBar(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         rule_type: Must be "data_bar"
         color: Bar color (ARGB hex)
         show_value: Show cell value alongside bar
         priority: Rule priority (lower = higher priority)
+        min_type: Shortest bar threshold: "auto_min" (default), "min", "num",
+            "percent", "percentile" or "formula"
+        min_value: Threshold value, required for num/percent/percentile/formula
+        max_type: Longest bar threshold: "auto_max" (default), "max", "num",
+            "percent", "percentile" or "formula"
+        max_value: Threshold value, required for num/percent/percentile/formula
+        border_color: Bar border color (ARGB hex); no border when omitted
+        negative_color: Fill of negative bars (default "FFFF0000")
+        negative_border_color: Border of negative bars (default: border_color)
+        gradient: Gradient fill (default True); False draws solid bars
+        axis_color: Axis color between negative and positive bars (default "FF000000")
+        axis_position: "automatic" (default), "middle" or "none"
     
     Example - Blue data bars:
         >>> data_bar = {
@@ -646,10 +933,22 @@ class ConditionalFormatDataBar(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     rule_type: Literal["data_bar"]
     color: str  # ARGB hex
     show_value: bool
     priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    min_type: Literal["auto_min", "min", "num", "percent", "percentile", "formula"]
+    min_value: Union[int, float, str]
+    max_type: Literal["auto_max", "max", "num", "percent", "percentile", "formula"]
+    max_value: Union[int, float, str]
+    border_color: str  # ARGB hex
+    negative_color: str  # ARGB hex
+    negative_border_color: str  # ARGB hex
+    gradient: bool
+    axis_color: str  # ARGB hex
+    axis_position: Literal["automatic", "middle", "none"]
 
 class ConditionalFormatTop10(TypedDict):
     """Top/Bottom N values conditional formatting.
@@ -659,6 +958,8 @@ class ConditionalFormatTop10(TypedDict):
         start_col: Starting column (0-based)
         end_row: Ending row (1-based)
         end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
         rule_type: Must be "top10"
         rank: Number of top/bottom values to highlight
         bottom: If True, highlight bottom N; if False, highlight top N
@@ -682,17 +983,205 @@ class ConditionalFormatTop10(TypedDict):
     start_col: int
     end_row: int
     end_col: int
+    range: CellRange
     rule_type: Literal["top10"]
     rank: int
     bottom: bool  # False = top N, True = bottom N
     priority: int
-    style: CellStyle
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
+
+class ConditionalFormatExpression(TypedDict):
+    """Formula-based conditional formatting rule.
+    
+    The formula is evaluated for every cell in the range, relative to the
+    top-left cell. Use `$` to lock a column so a whole row can be styled based
+    on one column's value.
+    
+    Attributes:
+        start_row: Starting row (1-based)
+        start_col: Starting column (0-based)
+        end_row: Ending row (1-based)
+        end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
+        rule_type: Must be "expression"
+        formula: Boolean Excel formula (leading "=" optional)
+        priority: Rule priority (lower = higher priority)
+        style: Style to apply when the formula is TRUE
+    
+    Example - Highlight entire row where column G is "FAIL":
+        >>> row_rule = {
+        ...     "start_row": 2, "start_col": 0,
+        ...     "end_row": 100, "end_col": 7,
+        ...     "rule_type": "expression",
+        ...     "formula": '$G2="FAIL"',
+        ...     "priority": 1,
+        ...     "style": {
+        ...         "fill": {"pattern": "solid", "fg_color": "FFFFC7CE"}
+        ...     }
+        ... }
+    """
+    start_row: int
+    start_col: int
+    end_row: int
+    end_col: int
+    range: CellRange
+    rule_type: Literal["expression"]
+    formula: str
+    priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
+
+class ConditionalFormatText(TypedDict):
+    """Text-matching conditional formatting rule (case-insensitive).
+    
+    Attributes:
+        start_row: Starting row (1-based)
+        start_col: Starting column (0-based)
+        end_row: Ending row (1-based)
+        end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
+        rule_type: "contains_text", "not_contains_text", "begins_with" or "ends_with"
+        text: Text to look for
+        priority: Rule priority (lower = higher priority)
+        style: Style to apply when the text matches
+    
+    Example - Highlight cells containing "error":
+        >>> text_rule = {
+        ...     "start_row": 2, "start_col": 3,
+        ...     "end_row": 100, "end_col": 3,
+        ...     "rule_type": "contains_text",
+        ...     "text": "error",
+        ...     "priority": 1
+        ... }
+    """
+    start_row: int
+    start_col: int
+    end_row: int
+    end_col: int
+    range: CellRange
+    rule_type: Literal["contains_text", "not_contains_text", "begins_with", "ends_with"]
+    text: str
+    priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
+
+class ConditionalFormatHighlight(TypedDict):
+    """Duplicate, unique or blank cell conditional formatting rule.
+    
+    Attributes:
+        start_row: Starting row (1-based)
+        start_col: Starting column (0-based)
+        end_row: Ending row (1-based)
+        end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
+        rule_type: "duplicate_values", "unique_values" or "contains_blanks"
+        priority: Rule priority (lower = higher priority)
+        style: Style to apply to matching cells
+    
+    Example - Flag duplicate IDs:
+        >>> dup_rule = {
+        ...     "start_row": 2, "start_col": 0,
+        ...     "end_row": 1000, "end_col": 0,
+        ...     "rule_type": "duplicate_values",
+        ...     "priority": 1
+        ... }
+    """
+    start_row: int
+    start_col: int
+    end_row: int
+    end_col: int
+    range: CellRange
+    rule_type: Literal["duplicate_values", "unique_values", "contains_blanks"]
+    priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
+
+class ConditionalFormatTimePeriod(TypedDict):
+    """Date-occurring conditional formatting rule, relative to today.
+    
+    Attributes:
+        start_row: Starting row (1-based)
+        start_col: Starting column (0-based)
+        end_row: Ending row (1-based)
+        end_col: Ending column (0-based)
+        range: The range as one value instead of the four keys above: an A1
+            string like "D2:D100" or a tuple
+        rule_type: Must be "time_period"
+        period: "yesterday", "today", "tomorrow", "last_7_days", "last_week",
+            "this_week", "next_week", "last_month", "this_month" or "next_month"
+        priority: Rule priority (lower = higher priority)
+        style: Style to apply to dates in the period
+    
+    Example - Highlight dates from the last 7 days:
+        >>> recent = {
+        ...     "start_row": 2, "start_col": 1,
+        ...     "end_row": 500, "end_col": 1,
+        ...     "rule_type": "time_period",
+        ...     "period": "last_7_days",
+        ...     "priority": 1
+        ... }
+    """
+    start_row: int
+    start_col: int
+    end_row: int
+    end_col: int
+    range: CellRange
+    rule_type: Literal["time_period"]
+    period: Literal[
+        "yesterday", "today", "tomorrow", "last_7_days", "last_week",
+        "this_week", "next_week", "last_month", "this_month", "next_month",
+    ]
+    priority: int
+    stop_if_true: bool  # Optional: skip lower-priority rules where this one matches
+    style: Union[CellStyle, str]  # dict, or the name of a registered style
+
+class ConditionalFormatGroup(TypedDict):
+    """Several rules sharing one range, written as a single Excel rule block.
+    
+    The range is given by the start_row/start_col/end_row/end_col keys or a
+    single ``range`` ("D2:D100" or a tuple). Each entry in ``rules`` is a rule
+    dict without the range keys; keys it
+    leaves out (such as ``style``) are taken from the group. Rules without a
+    priority are numbered in list order starting at the group's ``priority``
+    (default 1). Rules that target the same range are also grouped when given
+    as separate entries.
+    
+    Example - Stop at the first match, like Excel's "Stop If True":
+        >>> cascade = {
+        ...     "start_row": 2, "start_col": 3,
+        ...     "end_row": 100, "end_col": 3,
+        ...     "rules": [
+        ...         {"rule_type": "cell_value", "operator": "less_than", "value": "0",
+        ...          "stop_if_true": True,
+        ...          "style": {"font": {"color": "FFFF0000"}}},
+        ...         {"rule_type": "color_scale",
+        ...          "min_color": "FFFFFFFF", "max_color": "FF63BE7B"},
+        ...     ],
+        ... }
+    """
+    start_row: int
+    start_col: int
+    end_row: int
+    end_col: int
+    range: CellRange
+    rules: List[Dict[str, Any]]
+    priority: int  # Optional: priority of the first rule (default: 1)
+    style: CellStyle  # Optional: default style for rules that need one
 
 ConditionalFormat = (
     ConditionalFormatCellValue
     | ConditionalFormatColorScale
     | ConditionalFormatDataBar
     | ConditionalFormatTop10
+    | ConditionalFormatExpression
+    | ConditionalFormatText
+    | ConditionalFormatHighlight
+    | ConditionalFormatTimePeriod
+    | ConditionalFormatGroup
 )
 
 # =============================================================================
@@ -714,6 +1203,9 @@ class ExcelTable(TypedDict, total=False):
         start_col: First column of table (required, 0-based)
         end_row: Last row of table (required, 1-based)
         end_col: Last column of table (required, 0-based)
+        range: The range as one value instead of the four keys above, e.g.
+            "A1:F100". A single cell like "A1" anchors the header row and
+            sizes the table to the data
         display_name: User-friendly table name (optional)
         style: Excel table style name (optional)
         show_first_column: Bold first column (optional, default: False)
@@ -721,16 +1213,21 @@ class ExcelTable(TypedDict, total=False):
         show_row_stripes: Alternating row colors (optional, default: True)
         show_column_stripes: Alternating column colors (optional, default: False)
         show_header_row: Show header row with filter dropdowns (optional, default: True)
-        show_totals_row: Add totals row at bottom (optional, default: False)
+        show_totals_row: Add a totals row below the last data row; the table
+            range grows by one row to hold it (optional, default: False)
+        totals_row_label: Text in the first column of the totals row (optional, default: "Total")
+        totals_row_functions: Totals function per column name: "sum", "average",
+            "count", "count_nums", "max", "min", "std_dev" or "var" (optional;
+            by default the last column is summed, or counted if it isn't numeric)
         column_names: Custom column names (optional, auto-detected from data if not provided)
 
      
     Auto-Calculation:
         If end_row is 0 or omitted, it's automatically calculated as:
-            start_row + total_data_rows
+            start_row + total_data_rows (+ 1 with show_totals_row)
         
-        If end_col is 0 or omitted, it's automatically calculated as:
-            start_col + num_columns - 1
+        If end_col is 0 or omitted, the table runs to the last column, or up to
+        the empty gap before the next side-by-side table
         
         This allows tables to adapt to your DataFrame size without manual counting.    
 
@@ -789,7 +1286,8 @@ class ExcelTable(TypedDict, total=False):
         ...     "end_col": 3,
         ...     "style": "TableStyleMedium2",
         ...     "show_header_row": True,
-        ...     "show_totals_row": True,  # Add totals row
+        ...     "show_totals_row": True,  # Totals in row 101
+        ...     "totals_row_functions": {"Amount": "sum", "Units": "average"},
         ...     "show_row_stripes": True
         ... }
 
@@ -800,6 +1298,7 @@ class ExcelTable(TypedDict, total=False):
     start_col: int              # Required: 0-based column
     end_row: int                # Required: 1-based row
     end_col: int                # Required: 0-based column
+    range: CellRange            # Alternative to the four keys: "A1:F100" or "A1"
     display_name: str           # Optional: user-friendly name
     style: str                  # Optional: table style name
     show_first_column: bool     # Optional: bold first column
@@ -807,7 +1306,9 @@ class ExcelTable(TypedDict, total=False):
     show_row_stripes: bool      # Optional: alternating rows
     show_column_stripes: bool   # Optional: alternating columns
     show_header_row: bool       # Optional: show header row with filters (default: True)
-    show_totals_row: bool       # Optional: add totals row at bottom (default: False)
+    show_totals_row: bool       # Optional: add totals row below the data (default: False)
+    totals_row_label: str       # Optional: first column text of the totals row (default: "Total")
+    totals_row_functions: Dict[str, Literal["sum", "average", "count", "count_nums", "max", "min", "std_dev", "var"]]  # Optional: totals function by column name
     column_names: List[str]     # Optional: custom column names (auto-detected if not provided)
 
 # =============================================================================
@@ -845,22 +1346,169 @@ class ChartPosition(TypedDict):
     to_col: int
     to_row: int
 
+class ChartSeries(TypedDict, total=False):
+    """One series of a chart, used to build combo charts.
+
+    A series can also be given as a (sheet, values, categories, name) tuple;
+    categories and name are optional.
+
+    Attributes:
+        col: Data column of the series (0-based; required without values)
+        values: A1 range of the values, one row or column, e.g. "C2:C13"
+        sheet: Sheet holding values and categories (default: the chart's sheet)
+        categories: A1 range of the category labels (default: category_col)
+        name: Series name (default: the column's header cell)
+        chart_type: Type for this series; defaults to the chart's chart_type.
+            Only column, bar, line and area can be combined, and bar series
+            can only be mixed with other bar series.
+        secondary: Plot against the secondary value axis on the right
+            (default: False). At least one series must stay on the primary axis.
+        trendline: Trendline fitted to the series, as a type name or a
+            Trendline dict. Not available on stacked charts.
+        color: Series color as "RRGGBB" or "AARRGGBB"; overrides series_colors
+        line_width: Line width in points; overrides series_line_widths
+        line_dash: Line pattern; overrides series_line_dashes
+        smooth: Smoothed curve for a line series; overrides the chart's smooth
+        marker: Marker of a line series, as a symbol name or a Marker dict;
+            overrides series_markers
+
+    Example - Revenue columns with a margin line:
+        >>> chart = {
+        ...     "chart_type": "column",
+        ...     "data_range": (1, 0, 12, 2),
+        ...     "from_col": 4, "from_row": 1,
+        ...     "to_col": 12, "to_row": 18,
+        ...     "category_col": 0,
+        ...     "series": [
+        ...         1,
+        ...         {"col": 2, "name": "Margin", "chart_type": "line", "secondary": True},
+        ...     ],
+        ...     "secondary_axis_number_format": "0%",
+        ... }
+    """
+    col: int
+    values: str
+    sheet: str
+    categories: str
+    name: str
+    chart_type: Literal["column", "bar", "line", "area"]
+    secondary: bool
+    trendline: Union[Literal["linear", "polynomial", "moving_average", "exponential"], "Trendline"]
+    color: str
+    line_width: float
+    line_dash: Literal["solid", "dot", "dash", "dash_dot", "long_dash", "long_dash_dot"]
+    marker: Union["MarkerSymbol", "Marker"]
+    smooth: bool
+
+MarkerSymbol = Literal["none", "auto", "circle", "square", "diamond", "triangle", "x", "star", "dash", "dot", "plus"]
+
+class Marker(TypedDict, total=False):
+    """Data point marker of a line or scatter series.
+
+    Attributes:
+        symbol: Marker shape (default: "auto", Excel's choice per series)
+        size: Marker size in points, 2 to 72
+        fill: Fill color as "RRGGBB" or "AARRGGBB" (default: series color)
+        border: Border color as "RRGGBB" or "AARRGGBB" (default: series color)
+    """
+    symbol: MarkerSymbol
+    size: int
+    fill: str
+    border: str
+
+class DateAxis(TypedDict, total=False):
+    """Date axis of a column, bar, line or area chart.
+
+    Attributes:
+        base_unit: Spacing of the points (default: picked by Excel)
+        major_unit: Distance between labels, in major_time_unit
+        major_time_unit: Unit of major_unit
+        number_format: Label format, e.g. "mmm yyyy" (default: the cells' format)
+    """
+    base_unit: Literal["days", "months", "years"]
+    major_unit: float
+    major_time_unit: Literal["days", "months", "years"]
+    number_format: str
+
+class DataLabels(TypedDict, total=False):
+    """Data labels of a chart.
+
+    Attributes:
+        value: Show the value (default: True)
+        category: Show the category name (default: False)
+        series_name: Show the series name (default: False)
+        percent: Show the share of the whole, for pie charts (default: False)
+        number_format: Number format of the values, e.g. "#,##0"
+        position: Label placement. Column and bar: center, inside_end,
+            inside_base, outside_end (not stacked). Line and scatter: center,
+            left, right, above, below. Pie: center, inside_end, outside_end,
+            best_fit. Area and 3D column/bar labels can't be positioned.
+        font_size: Font size in hundredths of a point (default: 900)
+        font_color: Font color as "RRGGBB" or "AARRGGBB"
+    """
+    value: bool
+    category: bool
+    series_name: bool
+    percent: bool
+    number_format: str
+    position: Literal["center", "inside_end", "inside_base", "outside_end", "left", "right", "above", "below", "best_fit"]
+    font_size: int
+    font_color: str
+
+class ChartBorder(TypedDict, total=False):
+    """Outline of the chart or plot area.
+
+    Attributes:
+        color: RRGGBB or AARRGGBB color (default: light gray)
+        width: Line width in points (default: 0.75)
+        dash: Line pattern (default: "solid")
+    """
+    color: str
+    width: float
+    dash: Literal["solid", "dot", "dash", "dash_dot", "long_dash", "long_dash_dot"]
+
+class Trendline(TypedDict, total=False):
+    """Trendline on a chart series.
+
+    Attributes:
+        type: "linear", "polynomial", "moving_average" or "exponential" (required)
+        order: Polynomial order, 2 to 6 (default: 2)
+        period: Moving average period, at least 2 (default: 2)
+        forward: Periods to extend the line past the last point
+        backward: Periods to extend the line before the first point
+        name: Legend entry (default: Excel's, e.g. "Linear (Revenue)")
+        show_equation: Show the fitted equation on the chart (default: False)
+        show_r_squared: Show the R² value on the chart (default: False)
+
+    Moving averages have no forecast, equation or R².
+    """
+    type: Literal["linear", "polynomial", "moving_average", "exponential"]
+    order: int
+    period: int
+    forward: float
+    backward: float
+    name: str
+    show_equation: bool
+    show_r_squared: bool
+
 class ExcelChart(TypedDict, total=False):
     """Excel chart definition with extensive customization options.
     
-    Jetxl supports six chart types with advanced styling:
+    Jetxl supports seven chart types with advanced styling:
     - column: Vertical bars for comparing categories
     - bar: Horizontal bars for comparing items
     - line: Trends over time or continuous data
     - pie: Proportions of a whole
+    - doughnut: A pie with a hole in the middle
     - scatter: Relationships between two variables
     - area: Filled areas showing trends
+    - column_3d, bar_3d, pie_3d: 3D variants of column, bar and pie
     
     Attributes:
         chart_type: Type of chart (required)
         
         # Data Range (choose one method):
-        data_range: Tuple (start_row, start_col, end_row, end_col) - alternative to individual params
+        data_range: A1 range like "A1:C13" or a tuple (start_row, start_col, end_row, end_col) - alternative to individual params
         start_row: First data row including header (1-based)
         start_col: First data column (0-based)
         end_row: Last data row (1-based)
@@ -876,6 +1524,19 @@ class ExcelChart(TypedDict, total=False):
         title: Chart title
         category_col: Column for X-axis labels (0-based)
         series_names: Custom names for data series
+        series: Explicit series list for combo charts (column index or ChartSeries)
+        secondary_axis_min: Minimum of the secondary value axis
+        secondary_axis_max: Maximum of the secondary value axis
+        secondary_axis_title: Title of the secondary value axis
+        secondary_axis_number_format: Number format of the secondary axis labels, e.g. "0%"
+        series_colors: Colors by series index ("RRGGBB" or "AARRGGBB"), replacing
+            the theme accent rotation; pie charts use them for slices
+        series_line_widths: Line widths in points by series index. Bar, column
+            and area series get an outline when a width or dash is set.
+        series_line_dashes: Line patterns by series index
+        series_markers: Line/scatter markers by series index, as symbol names or
+            Marker dicts. Lines have no markers and scatter Excel's automatic
+            ones unless set.
         show_legend: Show legend (default: True)
         legend_position: Legend placement
         x_axis_title: X-axis label
@@ -885,11 +1546,37 @@ class ExcelChart(TypedDict, total=False):
         stacked: Stack series (column, bar, line, area)
         percent_stacked: Stack as 100% (column, bar, line, area)
         show_data_labels: Display values on chart elements
+        data_labels: Label content, number format, position and font (DataLabels);
+            turns labels on without show_data_labels
         chart_style: Excel chart style number (1-48)
+        smooth: Draw line and scatter series as smoothed curves (default: False)
+        gap_width: Gap between column/bar categories as % of a bar width,
+            0 to 500 (default: 150)
+        overlap: Overlap of bars within a category, -100 to 100 (default: 100
+            when stacked, else 0). Not available on 3D charts.
+        
+        # Pie and Doughnut:
+        explosion: Slice offset as % of the radius, 0 to 400; one int for
+            every slice or a list per slice
+        first_slice_angle: Clockwise turn of the first slice in degrees,
+            0 to 360 (pie and doughnut; pie_3d uses rot_y)
+        hole_size: Doughnut hole as % of its diameter, 10 to 90 (default: 75)
+        
+        # 3D View (column_3d, bar_3d, pie_3d):
+        rot_x: Tilt in degrees, -90 to 90 (default: 15, pies 30)
+        rot_y: Turn in degrees, 0 to 360 (default: 20, pies 0)
+        perspective: Field of view, 0 to 240 (default: 30)
+        right_angle_axes: Draw column/bar axes at right angles without
+            perspective (default: True unless perspective is given)
         
         # Axis Scaling:
         axis_min: Minimum Y-axis value
         axis_max: Maximum Y-axis value
+        y_axis_log_base: Logarithmic value axis with this base, 2 to 1000
+        date_axis: Category axis as a date axis. Detected from a date or
+            timestamp category column by default; True/False forces it on or
+            off, and a DateAxis dict sets the time units
+        x_axis_log_base: Logarithmic x axis with this base (scatter only)
         
         # Title Styling:
         title_bold: Bold title text
@@ -901,6 +1588,14 @@ class ExcelChart(TypedDict, total=False):
         axis_title_font_size: Axis title font size (hundredths of point)
         axis_title_color: Axis title color (ARGB hex without alpha)
         
+        # Chart and Plot Area:
+        chart_fill: Chart background, "RRGGBB" or "none" (default: white)
+        chart_border: Chart outline (ChartBorder), or "none"/False to hide it
+            (default: thin light gray)
+        plot_area_fill: Plot area background, "RRGGBB" or "none" (default: none)
+        plot_area_border: Plot area outline (ChartBorder), or "none"/False
+            (default: none)
+        
         # Legend Styling:
         legend_bold: Bold legend text
         legend_font_size: Legend font size (hundredths of point)
@@ -1075,10 +1770,10 @@ class ExcelChart(TypedDict, total=False):
         - Use consistent scales when comparing multiple charts
     """
     # Required
-    chart_type: Literal["column", "bar", "line", "pie", "scatter", "area"]
+    chart_type: Literal["column", "bar", "line", "pie", "scatter", "area", "column_3d", "bar_3d", "pie_3d", "doughnut"]
     
-    # Data Range - Option 1: Tuple format
-    data_range: Tuple[int, int, int, int]  # (start_row, start_col, end_row, end_col)
+    # Data Range - Option 1: A1 string or tuple
+    data_range: CellRange               # "A1:C13" or (start_row, start_col, end_row, end_col)
     
     # Data Range - Option 2: Individual parameters
     start_row: int                      # 1-based
@@ -1096,6 +1791,15 @@ class ExcelChart(TypedDict, total=False):
     title: str                          # Chart title
     category_col: int                   # 0-based column for categories
     series_names: List[str]             # Custom series names
+    series: List[Union[int, ChartSeries, Tuple[Optional[str], str], Tuple[Optional[str], str, Optional[str]], Tuple[Optional[str], str, Optional[str], Optional[str]]]]  # Explicit series (combo charts)
+    secondary_axis_min: float           # Secondary value axis minimum
+    secondary_axis_max: float           # Secondary value axis maximum
+    secondary_axis_title: str           # Secondary value axis title
+    secondary_axis_number_format: str   # Secondary axis label format, e.g. "0%"
+    series_colors: List[str]            # Series colors by index, e.g. "FF1F77B4"
+    series_line_widths: List[float]     # Series line widths in points
+    series_line_dashes: List[Literal["solid", "dot", "dash", "dash_dot", "long_dash", "long_dash_dot"]]
+    series_markers: List[Union[MarkerSymbol, Marker]]  # Line/scatter markers
     show_legend: bool                   # Show legend
     legend_position: Literal["right", "left", "top", "bottom", "none"]
     x_axis_title: str                   # X-axis label
@@ -1105,11 +1809,29 @@ class ExcelChart(TypedDict, total=False):
     stacked: bool                       # Stack series (column, bar, line, area)
     percent_stacked: bool               # Stack as 100% (column, bar, line, area)
     show_data_labels: bool              # Display values on chart elements
+    data_labels: "DataLabels"           # Label content, format and position
     chart_style: int                    # Excel chart style (1-48)
+    smooth: bool                        # Smoothed line/scatter curves
+    gap_width: int                      # Column/bar gap, 0-500 (% of bar width)
+    overlap: int                        # Column/bar overlap, -100 to 100
+    
+    # Pie and Doughnut
+    explosion: Union[int, List[int]]    # Slice offset, 0-400 (% of radius)
+    first_slice_angle: int              # First slice turn, 0 to 360
+    hole_size: int                      # Doughnut hole, 10-90 (% of diameter)
+    
+    # 3D View (column_3d, bar_3d, pie_3d)
+    rot_x: int                          # Tilt, -90 to 90
+    rot_y: int                          # Turn, 0 to 360
+    perspective: int                    # Field of view, 0 to 240
+    right_angle_axes: bool              # Column/bar axes at right angles
     
     # Axis Scaling
     axis_min: float                     # Minimum Y-axis value
     axis_max: float                     # Maximum Y-axis value
+    y_axis_log_base: float              # Log scale base for the value axis, 2-1000
+    x_axis_log_base: float              # Log scale base for the x axis (scatter)
+    date_axis: Union[bool, "DateAxis"]  # Date axis for time series (auto-detected)
     
     # Title Styling
     title_bold: bool                    # Bold title text
@@ -1121,6 +1843,12 @@ class ExcelChart(TypedDict, total=False):
     axis_title_font_size: int           # Axis title font size (hundredths of point)
     axis_title_color: str               # Axis title color (ARGB hex without alpha)
     
+    # Chart and Plot Area
+    chart_fill: str                     # Background color or "none"
+    chart_border: Union["ChartBorder", Literal["none", False]]  # Outline
+    plot_area_fill: str                 # Plot area color or "none"
+    plot_area_border: Union["ChartBorder", Literal["none", False]]  # Outline
+    
     # Legend Styling
     legend_bold: bool                   # Bold legend text
     legend_font_size: int               # Legend font size (hundredths of point)
@@ -1160,7 +1888,44 @@ class ImagePosition(TypedDict):
     to_col: int
     to_row: int
 
-class ExcelImageFromPath(TypedDict):
+class ImageAnchor(TypedDict, total=False):
+    """How an image is tied to the sheet.
+    
+    Lengths are pixels at 96 DPI, or strings like "120px" or "3.5cm".
+    
+    Attributes:
+        anchor: "two_cell" stretches the image between the from and to cells
+            and resizes it with them (default when to_col is given). "one_cell"
+            puts its top-left corner on the from cell with a fixed size, so
+            resizing columns doesn't distort it (default otherwise).
+            "absolute" places it at x/y on the sheet, independent of any cell.
+        width: Image width (one_cell, absolute)
+        height: Image height (one_cell, absolute). Given only one of width and
+            height, the other keeps the aspect ratio; given neither, the
+            image's own size is used (PNG, JPEG, GIF and BMP at their DPI,
+            SVG width/height or viewBox, EMF frame).
+        scale: Multiplies the size, e.g. 0.5 for half size (default: 1.0)
+        x: Distance from the sheet's left edge (absolute)
+        y: Distance from the sheet's top edge (absolute)
+        col_off: Offset of the top-left corner into the from column
+        row_off: Offset of the top-left corner into the from row
+        fallback: PNG path or bytes shown for an SVG by apps that can't draw
+            SVG (default: a blank placeholder)
+    
+    from_col/from_row are only needed for two_cell and one_cell images, and
+    to_col/to_row only for two_cell ones.
+    """
+    anchor: Literal["two_cell", "one_cell", "absolute"]
+    width: Union[int, float, str]
+    height: Union[int, float, str]
+    scale: float
+    x: Union[int, float, str]
+    y: Union[int, float, str]
+    col_off: Union[int, float, str]
+    row_off: Union[int, float, str]
+    fallback: Union[str, bytes]
+
+class ExcelImageFromPath(ImageAnchor):
     """Excel image loaded from a file path.
     
     This is the recommended method for adding images as it's simpler
@@ -1179,6 +1944,8 @@ class ExcelImageFromPath(TypedDict):
         - GIF (.gif)
         - BMP (.bmp)
         - TIFF (.tiff, .tif)
+        - SVG (.svg), with an optional PNG fallback
+        - EMF (.emf)
     
     Example - Add logo from file:
         >>> image = {
@@ -1195,7 +1962,7 @@ class ExcelImageFromPath(TypedDict):
     to_col: int         # Required: 0-based column
     to_row: int         # Required: 0-based row
 
-class ExcelImageFromBytes(TypedDict):
+class ExcelImageFromBytes(ImageAnchor):
     """Excel image from raw image data bytes.
     
     Use this method when you have image data in memory (e.g., from
@@ -1215,6 +1982,7 @@ class ExcelImageFromBytes(TypedDict):
         - "gif"
         - "bmp"
         - "tiff" or "tif"
+        - "svg" or "emf"
     
     Example - Add image from bytes:
         >>> import requests
@@ -1266,27 +2034,67 @@ def write_sheet_arrow(
     auto_width: bool = False,
     styled_headers: bool = False,
     write_header_row: bool = True,
-    column_widths: Optional[Dict[str, Union[float, str]]] = None,
-    column_formats: Optional[Dict[str, str]] = None,
-    merge_cells: Optional[List[Tuple[int, int, int, int]]] = None,
+    column_widths: Optional[Dict[Union[str, int], Union[float, str]]] = None,
+    column_formats: Optional[Dict[Union[str, int], Union[NumberFormat, ColumnFormat]]] = None,
+    format_totals_rows: bool = False,
+    format_whole_columns: bool = False,
+    text_columns: Optional[List[str]] = None,
+    merge_cells: Optional[Union[str, List[Union[str, Tuple[int, int, int, int]]]]] = None,
     data_validations: Optional[List[DataValidation]] = None,
     hyperlinks: Optional[List[Tuple[int, int, str, Optional[str]]]] = None,
     row_heights: Optional[Dict[int, float]] = None,
+    row_formats: Optional[Dict[int, Union[NumberFormat, str]]] = None,
+    styles: Optional[Dict[str, CellStyle]] = None,
     cell_styles: Optional[List[CellStyleMap]] = None,
+    range_styles: Optional[List[Dict[str, Any]]] = None,
     formulas: Optional[List[Tuple[int, int, str, Optional[str]]]] = None,
+    rich_text: Optional[List[Tuple[int, int, List[Tuple[str, Optional[Dict[str, Any]]]]]]] = None,
+    shared_formulas: Optional[List[Tuple[Union[str, Tuple[int, int, int, int]], str]]] = None,
+    column_formulas: Optional[Dict[str, str]] = None,
     conditional_formats: Optional[List[ConditionalFormat]] = None,
     tables: Optional[List[ExcelTable]] = None,
     charts: Optional[List[ExcelChart]] = None,
     images: Optional[List[ExcelImage]] = None,
     gridlines_visible: bool = True,              
     zoom_scale: Optional[int] = None,            
+    active_cell: Optional[str] = None,
+    selection: Optional[str] = None,
+    top_left_cell: Optional[str] = None,
     tab_color: Optional[str] = None,             
     default_row_height: Optional[float] = None,  
-    hidden_columns: Optional[List[int]] = None,  
+    hidden_columns: Optional[List[Union[int, str]]] = None,  
     hidden_rows: Optional[List[int]] = None,     
+    row_groups: Optional[List[Union[Tuple[int, int], Tuple[int, int, int], Tuple[int, int, int, bool]]]] = None,
+    col_groups: Optional[List[Union[Tuple[int, int], Tuple[int, int, int], Tuple[int, int, int, bool]]]] = None,
     right_to_left: bool = False,                 
     data_start_row: int = 0,        
     header_content: Optional[List[Tuple[int, int, str]]] = None,             
+    indexing: Optional[Literal["zero", "one"]] = None,
+    pre_1900_dates: Optional[Literal["text", "clamp", "error"]] = None,
+    invalid_text: Optional[Literal["replace", "error"]] = None,
+    list_separator: Optional[str] = None,
+    binary_encoding: Optional[Literal["hex", "base64"]] = None,
+    binary_max_length: Optional[int] = None,
+    filters: Optional[Dict[str, Union[List[Any], AutoFilterCriteria]]] = None,
+    sort_state: Optional[SortState] = None,
+    coordinates: Optional[Literal["sheet", "data"]] = None,
+    split_rows: Optional[int] = None,
+    column_gap: int = 1,
+    flatten_structs: bool = False,
+    zip_threads: int = 0,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+    progress: Optional[Callable[[int, Optional[int], Literal["writing", "compressing", "done"]], Any]] = None,
+    progress_interval: int = 100000,
+    modify_password: Optional[str] = None,
+    read_only_recommended: bool = False,
+    application: Optional[str] = None,
+    company: Optional[str] = None,
+    app_version: Optional[str] = None,
+    summary_sheet: bool = False,
+    summary_metadata: Optional[Dict[str, Any]] = None,
+    indexed_colors: Optional[List[str]] = None,
+    theme: Optional[WorkbookTheme] = None,
+    date_1904: bool = False,
 ) -> None:
     """Write Arrow data to Excel with advanced formatting.
     
@@ -1299,7 +2107,15 @@ def write_sheet_arrow(
     - PyArrow: native Table/RecordBatch
     
     Args:
-        arrow_data: PyArrow Table or RecordBatch (from DataFrame.to_arrow())
+        arrow_data: Polars DataFrame, PyArrow Table or RecordBatch, or anything
+            else with the Arrow PyCapsule interface, or a list of them to place
+            side by side. Polars strings and categoricals are read as is, no
+            to_arrow() needed. List entries may also be dicts
+            {"data": table, "column_formats": {...}, "column_widths": {...}} whose
+            formats and widths apply to that table's columns only. A
+            RecordBatchReader is written one batch at a time without being
+            collected, unless tables, charts, filters, split_rows or
+            summary_sheet need every row first
         filename: Output Excel file path (.xlsx)
         sheet_name: Sheet name (default: "Sheet1")
         auto_filter: Enable autofilter dropdowns on header row
@@ -1308,30 +2124,163 @@ def write_sheet_arrow(
         auto_width: Automatically calculate column widths from content
         styled_headers: Apply bold text + gray background to headers
         write_header_row: Write column names as first row (default: True)
-        column_widths: Manual column widths by name, e.g. {"Name": 20.0, "Age": 10.0}
-        column_formats: Number formats by column, e.g. {"Price": "currency", "Date": "date"}
-        merge_cells: List of (start_row, start_col, end_row, end_col) to merge
+        column_widths: Manual column widths by name, e.g. {"Name": 20.0, "Age": 10.0}.
+            Keys may also be column indexes (following `indexing`), for tables
+            with duplicate or empty column names; an index key wins over a name.
+            An uppercase key that isn't a column name is a column letter
+            ("A", "BC"), always counted from column A
+        column_formats: Number formats by column, e.g. {"Price": "currency", "Date": "date"}.
+            Applied to data cells only; header rows keep the general format.
+            A ColumnFormat dict adds a display timezone for timestamp columns.
+            Keys may be column names, indexes or letters, as in column_widths
+        format_totals_rows: Also apply column_formats to table totals rows (default: False)
+        format_whole_columns: Also set column_formats on the whole column, so empty
+            cells and rows typed below the data get the format (default: False)
+        text_columns: Columns written as text whatever their type, with the "@"
+            format, e.g. ["CustomerID", "Zip"]. Keeps long numeric IDs exact and
+            stops Excel from turning the values into numbers
+        merge_cells: Ranges to merge, as A1 strings ("B2:D4") or
+            (start_row, start_col, end_row, end_col) tuples. A1 ranges are
+            absolute and ignore `indexing`
         data_validations: List of validation rules (dropdowns, number ranges, etc.)
-        hyperlinks: List of (row, col, url, display_text) for clickable links
+        hyperlinks: List of (row, col, url, display_text) for clickable links.
+            Web URLs, mailto:, file:/// URIs and UNC/drive paths are accepted;
+            spaces, "&" and non-ASCII characters are escaped for you
         row_heights: Custom row heights by row number, e.g. {1: 30.0, 5: 25.0}
+        row_formats: Number format for every data cell of a row, by row
+            number, e.g. {4: "percentage"} for a margin row of a transposed
+            layout. Takes the same names and codes as column_formats and wins
+            over them on that row; cell_styles and range_styles win over it
+        styles: Named styles registered once and used by name, e.g.
+            {"kpi_red": {"font": {"bold": True, "color": "FFFF0000"}}}. A
+            cell_styles entry or conditional format takes {"style": "kpi_red"}
+            instead of repeating the style dict
         cell_styles: Custom styles with positions for individual cells
+        range_styles: One style over a rectangle of cells, e.g.
+            [{"range": "A1:F1", "style": {...}}]. The range is an A1 string or a
+            (start_row, start_col, end_row, end_col) tuple; the style is a dict
+            or a named style. Empty cells in the range are written styled;
+            later ranges win over earlier ones and cell_styles win over both
         formulas: List of (row, col, formula, cached_value) for Excel formulas
+        rich_text: List of (row, col, runs) for cells mixing fonts. Each run is
+            a (text, font) tuple, where font is a dict like cell_styles' "font"
+            (bold, italic, underline, size, color, name) or None to keep the
+            cell's font. Replaces the data value of the cell
+        shared_formulas: List of (range, formula) filling one formula over a
+            range, e.g. [("D2:D1000", "=B2*C2")]. The range is an A1 string or a
+            (start_row, start_col, end_row, end_col) tuple and the formula is the
+            one of its top-left cell; Excel adjusts relative references for the
+            rest. The text is stored once instead of once per cell
+        column_formulas: Formula template per column name, written on every data
+            row with "{row}" replaced by the row number, e.g.
+            {"Total": "=A{row}*B{row}"}. The column must exist in the data
         conditional_formats: Conditional formatting rules (color scales, data bars, etc.)
         tables: Excel table definitions with filtering and styling
         charts: Excel chart definitions (column, bar, line, pie, scatter, area)
         images: Excel image definitions (from file path or bytes)
         gridlines_visible: Show worksheet gridlines (default: True)
         zoom_scale: Zoom percentage 10-400 (default: 100)
+        active_cell: Cell the cursor starts on, e.g. "B2" (default: first selected cell)
+        selection: Ranges selected on open, e.g. "B2:D10" or "B2:D10 F2:F10"
+            (default: the active cell)
+        top_left_cell: Cell scrolled to the window's top-left corner; with frozen
+            panes, the top-left cell of the scrolling pane
         tab_color: Sheet tab color in ARGB hex format (e.g., "FFFF0000" for red)
         default_row_height: Default height for all rows in points
-        hidden_columns: List of column indices to hide (0-based)
+        hidden_columns: Columns to hide, as indexes (0-based), names or
+            column letters like "C"
         hidden_rows: List of row indices to hide (1-based)
+        row_groups: Outline groups as (start_row, end_row, level, collapsed)
+            tuples, rows as in hidden_rows. level is 1-7 (default 1) for
+            nesting; collapsed (default False) hides the rows behind a + button
+        col_groups: Column outline groups, as row_groups (columns 0-based)
         right_to_left: Enable right-to-left worksheet layout
         data_start_row: Skip this many rows when auto-calculating column widths
         header_content: Optional[List[Tuple[int, int, str]]] - Arbitrary text content to write before data rows.
         Each tuple is (row, col, text). Useful for titles, metadata, or template headers.
         These rows are written BEFORE the DataFrame data and headers.
         Example: [(1, 0, "Company Report"), (2, 0, "Q4 2024")]
+        indexing: Base used for every row/column parameter. "zero" makes all rows
+            and columns 0-based, "one" makes them all 1-based (Excel-style, so
+            row 1 / column 1 is cell A1). When omitted, the legacy convention is
+            kept: cell rows are 1-based and columns 0-based, while chart/image
+            anchors and chart data_range rows are 0-based. With "one", passing 0
+            raises ValueError naming the offending parameter.
+        pre_1900_dates: Dates before 1900-01-01 have no Excel serial number.
+            "text" (default) writes them as ISO text ("1850-07-04"), "clamp"
+            writes 1900-01-01 and "error" fails the write. Dates in
+            January-February 1900 account for Excel's phantom 1900-02-29.
+        invalid_text: Strings with invalid UTF-8 or characters XML cannot store
+            (control characters other than tab, CR and LF). "replace" (default)
+            writes U+FFFD in their place, "error" fails the write naming the cell.
+        list_separator: Joins the elements of List/LargeList columns into one
+            text cell, skipping nulls (default: ", ")
+        binary_encoding: Text for Binary/LargeBinary/FixedSizeBinary columns:
+            "hex" (lowercase, default) or "base64"
+        binary_max_length: Longest text written for a binary cell; longer values
+            are cut and end in "…" (default: no limit)
+        filters: Autofilter criteria by column name, so the file opens filtered.
+            A list of values, or an AutoFilterCriteria dict. Turns the autofilter
+            on; rows that don't match are written hidden. Unknown columns raise IOError.
+        sort_state: Sort already applied to the data, e.g.
+            {"ref": "A2:F100", "keys": [(2, "descending")]}. Excel shows the sort
+            arrows and can re-apply it; rows are not reordered (see SortState)
+        coordinates: What cell rows count from. "sheet" (default) is the top of
+            the sheet; "data" is the first DataFrame row, so row 1 (row 0 with
+            indexing="zero") is the first data row wherever header_content and
+            data_start_row put it. Applies to merges, validations, hyperlinks,
+            cell styles, range_styles, formulas, shared_formulas, rich_text, conditional formats, row_heights, row_formats, hidden_rows, row_groups,
+            table ranges (start_row is the table's first data row) and chart
+            data_range. header_content, anchors and A1 strings are not shifted
+        split_rows: Data rows per sheet. Longer data continues on "Name (2)",
+            "Name (3)", ... sheets with a "Continued from" note on row 1 and the
+            header row repeated on row 2. Column formats, widths, header style,
+            autofilter and frozen rows carry over; cell-level options (merges,
+            cell styles, formulas, tables, charts, ...) stay on the first sheet
+        column_gap: Empty columns between side-by-side tables (default: 1)
+        flatten_structs: Expand struct columns into one column per field, named
+            "parent.child" (nested structs recursively), before anything else
+            sees the data. Options name the expanded columns (default: False)
+        zip_threads: Threads used to compress the file. 0 (default) uses every core
+        compression: Deflate level of the file: "fast" (default, level 1), "default"
+            (6), "best" (9) or an int 0-9. Higher levels write smaller files more slowly.
+            "stored" (or 0) skips deflate entirely for the fastest writes
+        progress: Called as progress(rows_written, total_rows, phase) while the
+            file is written, e.g. to drive a progress bar. phase is "writing"
+            every progress_interval data rows, "compressing" once all rows are
+            generated, and "done" at the end. total_rows is None for streamed
+            input of unknown length. An exception raised by the callback stops
+            the write and is re-raised
+        progress_interval: Data rows between "writing" calls (default: 100000)
+        modify_password: "Password to modify" (write reservation). Anyone can open
+            the file read-only, but Excel asks for this password before allowing
+            edits. Stored as a salted SHA-512 hash, not encryption.
+        read_only_recommended: Prompt users to open the file read-only
+        application: Application name recorded in docProps/app.xml
+            (default: "jetxl <version>")
+        company: Company recorded in docProps/app.xml (omitted when None)
+        app_version: AppVersion recorded in docProps/app.xml
+            (default: "16.0300")
+        summary_sheet: Add a "Summary" sheet in front with the generation time
+            (UTC), the jetxl version, a digest of the formatting options and a
+            table of rows and columns per sheet. A data sheet named "Summary"
+            raises IOError
+        summary_metadata: Extra label/value rows for the summary sheet, such as
+            the source query or pipeline run id. Values are converted with str().
+            Passing it turns summary_sheet on
+        indexed_colors: Custom legacy palette (`<indexedColors>`) of up to 64
+            "RRGGBB" or "AARRGGBB" colors. Font, fill and border colors that
+            match an entry are written by index for readers that ignore ARGB.
+            Raises ValueError for more than 64 entries or a malformed color
+        theme: Overrides for the generated Office theme (xl/theme/theme1.xml):
+            "accents" replaces accent1-6 in order, "major_font" and
+            "minor_font" set the heading and body typefaces. Chart series
+            without a color and ThemeColor cell colors resolve to these
+            (optional)
+        date_1904: Use the 1904 date system (`<workbookPr date1904="1"/>`) of
+            workbooks from old Mac Excel, for systems that still expect it. Date
+            serials count from 1904-01-01, and pre_1900_dates applies to dates
+            before 1904-01-01 instead (default: False)
     
     Examples:
         Basic Usage (Polars):
@@ -1675,7 +2624,26 @@ def write_sheet_arrow(
 def write_sheets_arrow(
     arrow_sheets: List[Dict[str, Any]],
     filename: str,
-    num_threads: int,
+    num_threads: int = 1,
+    xml_threads: Optional[int] = None,
+    zip_threads: int = 0,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+    progress: Optional[Callable[[int, Optional[int], Literal["writing", "compressing", "done"]], Any]] = None,
+    progress_interval: int = 100000,
+    modify_password: Optional[str] = None,
+    read_only_recommended: bool = False,
+    application: Optional[str] = None,
+    company: Optional[str] = None,
+    app_version: Optional[str] = None,
+    summary_sheet: bool = False,
+    summary_metadata: Optional[Dict[str, Any]] = None,
+    indexed_colors: Optional[List[str]] = None,
+    theme: Optional[WorkbookTheme] = None,
+    sheet_order: Optional[List[str]] = None,
+    active_sheet_index: int = 0,
+    first_sheet: int = 0,
+    date_1904: bool = False,
+    styles: Optional[Dict[str, CellStyle]] = None,
 ) -> None:
     """Write multiple Arrow tables to Excel sheets with parallel processing.
     
@@ -1684,7 +2652,9 @@ def write_sheets_arrow(
     
     Args:
         arrow_sheets: List of sheet configurations, each containing:
-            - data: PyArrow Table/RecordBatch (required)
+            - data: PyArrow Table/RecordBatch, or a list for side-by-side tables (required)
+            - column_gap: Empty columns between side-by-side tables (optional)
+            - flatten_structs: Expand struct columns into "parent.child" columns (optional)
             - name: Sheet name (required)
             - auto_filter: Enable autofilter (optional)
             - freeze_rows: Rows to freeze (optional)
@@ -1692,28 +2662,85 @@ def write_sheets_arrow(
             - auto_width: Auto-calculate column widths (optional)
             - styled_headers: Bold headers with gray background (optional)
             - write_header_row: Write column names as first row (optional)
-            - column_widths: Dict[str, float|str] - manual widths (optional)
-            - column_formats: Dict[str, str] - number formats (optional)
-            - merge_cells: List[(row, col, row, col)] - merge ranges (optional)
+            - column_widths: Dict[str|int, float|str] - manual widths by name, column index or letter (optional)
+            - column_formats: Dict[str|int, str|ColumnFormat] - number formats by name, column index or letter (optional)
+            - format_totals_rows: bool - apply column_formats to table totals rows (optional)
+            - format_whole_columns: bool - also set column_formats on the whole column (optional)
+            - text_columns: List[str] - columns written as text (optional)
+            - merge_cells: List of "B2:D4" strings or (row, col, row, col) tuples (optional)
             - data_validations: List[dict] - validation rules (optional)
             - hyperlinks: List[(row, col, url, display)] (optional)
             - row_heights: Dict[int, float] - row heights (optional)
+            - row_formats: Dict[int, str] - number format per row (optional)
+            - styles: Dict[str, dict] - named styles, added to the workbook's `styles` (optional)
             - cell_styles: List[dict] - individual cell styles (optional)
+            - range_styles: List[dict] - {"range", "style"} over a range (optional)
             - formulas: List[(row, col, formula, cached_value)] (optional)
+            - rich_text: List[(row, col, [(text, font), ...])] (optional)
+            - shared_formulas: List[(range, formula)] (optional)
+            - column_formulas: Dict[str, str] - formula templates with {row} (optional)
             - conditional_formats: List[dict] - conditional formatting (optional)
             - tables: List[dict] - Excel table definitions (optional)
             - charts: List[dict] - chart definitions (optional)
             - images: List[dict] - image definitions (optional)
             - gridlines_visible: Show gridlines (optional)
             - zoom_scale: Zoom percentage 10-400 (optional)
+            - active_cell: Cell the cursor starts on, e.g. "B2" (optional)
+            - selection: Ranges selected on open, e.g. "B2:D10" (optional)
+            - top_left_cell: Cell scrolled to the window's top-left corner (optional)
             - tab_color: Sheet tab color in ARGB hex (optional)
             - default_row_height: Default row height (optional)
-            - hidden_columns: List[int] - column indices to hide (optional)
+            - hidden_columns: List[int|str] - column indices, names or letters to hide (optional)
             - hidden_rows: List[int] - row indices to hide (optional)
+            - row_groups: List[tuple] - (start, end, level, collapsed) row outline groups (optional)
+            - col_groups: List[tuple] - column outline groups (optional)
             - right_to_left: RTL layout (optional)
             - data_start_row: Skip rows for auto-width calculation (optional)
+            - indexing: "zero" or "one" - row/column base for this sheet (optional)
+            - pre_1900_dates: "text", "clamp" or "error" (optional)
+            - invalid_text: "replace" or "error" (optional)
+            - list_separator: Joins list elements in one cell (optional)
+            - binary_encoding: "hex" or "base64" for binary columns (optional)
+            - binary_max_length: Longest binary cell text (optional)
+            - filters: Dict[str, list | AutoFilterCriteria] - autofilter criteria (optional)
+            - sort_state: SortState - sort already applied to the data (optional)
+            - coordinates: "sheet" or "data" - what cell rows count from (optional)
+            - split_rows: int - data rows per sheet before continuation sheets (optional)
+            - config_version: int - CONFIG_VERSION the options were written for;
+              newer or unknown versions raise ValueError (optional)
         filename: Output Excel file path (.xlsx)
-        num_threads: Number of parallel threads for XML generation
+        num_threads: Number of parallel threads for XML generation (default: 1).
+            Each thread builds whole sheets, so more threads than sheets don't help.
+            Finished sheets are compressed while the rest are still generated
+        xml_threads: Same as num_threads; takes precedence when given (optional)
+        zip_threads: Threads used to compress the file. 0 (default) uses every core
+        compression: Deflate level of the file: "fast" (default, level 1), "default"
+            (6), "best" (9) or an int 0-9. Higher levels write smaller files more slowly.
+            "stored" (or 0) skips deflate entirely for the fastest writes
+        progress: Called as progress(rows_written, total_rows, phase) while the
+            file is written, e.g. to drive a progress bar. phase is "writing"
+            every progress_interval data rows, "compressing" once all rows are
+            generated, and "done" at the end. total_rows is None for streamed
+            input of unknown length. An exception raised by the callback stops
+            the write and is re-raised
+        progress_interval: Data rows between "writing" calls (default: 100000)
+        modify_password: Password required to edit the workbook (optional)
+        read_only_recommended: Prompt users to open the file read-only (optional)
+        application: Application name recorded in docProps/app.xml (optional)
+        company: Company recorded in docProps/app.xml (optional)
+        app_version: AppVersion recorded in docProps/app.xml (optional)
+        summary_sheet: Add a generated "Summary" sheet first; see write_sheet_arrow()
+        summary_metadata: Extra rows for the summary sheet; turns it on (optional)
+        indexed_colors: Custom legacy palette; see write_sheet_arrow() (optional)
+        theme: Theme accents and fonts; see write_sheet_arrow() (optional)
+        sheet_order: Sheet names in the order their tabs should appear. Sheets
+            not listed follow in their original order (optional)
+        active_sheet_index: Tab the workbook opens on, counted in the final tab
+            order including any summary sheet (default: 0)
+        first_sheet: Leftmost tab shown in the tab bar (default: 0)
+        date_1904: Use the 1904 date system; see write_sheet_arrow() (default: False)
+        styles: Named styles every sheet can use by name; a sheet's own "styles"
+            key adds to or overrides them
     
     Examples:
         Basic Multi-Sheet:
@@ -1862,11 +2889,81 @@ def write_sheets_arrow(
         - Full feature parity with write_sheet_arrow()
         - Each sheet has completely independent configuration
         - Style registry is shared across sheets for deduplication
-        - Parallel XML generation across num_threads
+        - Parallel XML generation across num_threads, then compression across zip_threads
         - Performance: minimal overhead vs single-sheet (<1%)
     """
     ...
 
+def write_workbooks(
+    workbooks: List[Tuple[str, List[Dict[str, Any]]]],
+    num_threads: int = 1,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+) -> None:
+    """Write many independent workbooks in parallel.
+    
+    Args:
+        workbooks: (filename, sheets) pairs; sheets are the dicts write_sheets_arrow()
+            takes
+        num_threads: Threads shared by every file's sheet generation and
+            compression; 0 uses one per core (default: 1)
+        compression: Same as write_sheets_arrow(), applied to every file
+    
+    Raises:
+        IOError: Listing every file that failed; the other files are still written
+    
+    Example:
+        >>> jetxl.write_workbooks(
+        ...     [(f"{c}.xlsx", [{"data": t, "name": "Orders"}]) for c, t in exports.items()],
+        ...     num_threads=8,
+        ... )
+    """
+    ...
+
+class Workbook:
+    """Workbook built up one sheet at a time and written when it is closed.
+    
+    As a context manager the file is written on leaving the `with` block.
+    If the block raised, nothing is written and the exception propagates.
+    
+    Example:
+        >>> with jetxl.Workbook("report.xlsx", num_threads=2) as wb:
+        ...     wb.add_sheet(sales.to_arrow(), "Sales", auto_filter=True)
+        ...     wb.add_sheet(costs.to_arrow(), "Costs", freeze_rows=1)
+    """
+    
+    def __init__(
+        self,
+        filename: str,
+        num_threads: int = 1,
+        compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+    ) -> None: ...
+    
+    def add_sheet(self, data: Any, name: str, **options: Any) -> None:
+        """Add a sheet; options are the keys of a write_sheets_arrow() sheet dict.
+        
+        The data is read now and the file written on close().
+        
+        Raises:
+            ValueError: If the workbook is already closed
+        """
+        ...
+    
+    def close(self) -> None:
+        """Write the file. Later calls do nothing once it was written; after
+        a failed write the sheets are kept, so close() can be retried.
+        
+        Raises:
+            ValueError: If no sheets were added
+            IOError: If the file cannot be written
+        """
+        ...
+    
+    @property
+    def closed(self) -> bool: ...
+    
+    def __enter__(self) -> "Workbook": ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
+
 def write_sheet_arrow_to_bytes(
     arrow_data: Any,
     sheet_name: Optional[str] = None,
@@ -1876,27 +2973,67 @@ def write_sheet_arrow_to_bytes(
     auto_width: bool = False,
     styled_headers: bool = False,
     write_header_row: bool = True,
-    column_widths: Optional[Dict[str, Union[float, int, str]]] = None,
-    column_formats: Optional[Dict[str, str]] = None,
-    merge_cells: Optional[List[Tuple[int, int, int, int]]] = None,
+    column_widths: Optional[Dict[Union[str, int], Union[float, int, str]]] = None,
+    column_formats: Optional[Dict[Union[str, int], Union[NumberFormat, ColumnFormat]]] = None,
+    format_totals_rows: bool = False,
+    format_whole_columns: bool = False,
+    text_columns: Optional[List[str]] = None,
+    merge_cells: Optional[Union[str, List[Union[str, Tuple[int, int, int, int]]]]] = None,
     data_validations: Optional[List[DataValidation]] = None,
     hyperlinks: Optional[List[Tuple[int, int, str, Optional[str]]]] = None,
     row_heights: Optional[Dict[int, float]] = None,
+    row_formats: Optional[Dict[int, Union[NumberFormat, str]]] = None,
+    styles: Optional[Dict[str, CellStyle]] = None,
     cell_styles: Optional[List[CellStyleDict]] = None,
+    range_styles: Optional[List[Dict[str, Any]]] = None,
     formulas: Optional[List[Tuple[int, int, str, Optional[str]]]] = None,
+    rich_text: Optional[List[Tuple[int, int, List[Tuple[str, Optional[Dict[str, Any]]]]]]] = None,
+    shared_formulas: Optional[List[Tuple[Union[str, Tuple[int, int, int, int]], str]]] = None,
+    column_formulas: Optional[Dict[str, str]] = None,
     conditional_formats: Optional[List[ConditionalFormat]] = None,
     tables: Optional[List[ExcelTable]] = None,
     charts: Optional[List[ExcelChart]] = None,
     images: Optional[List[ExcelImage]] = None,
     gridlines_visible: bool = True,
     zoom_scale: Optional[int] = None,
+    active_cell: Optional[str] = None,
+    selection: Optional[str] = None,
+    top_left_cell: Optional[str] = None,
     tab_color: Optional[str] = None,
     default_row_height: Optional[float] = None,
-    hidden_columns: Optional[List[int]] = None,
+    hidden_columns: Optional[List[Union[int, str]]] = None,
     hidden_rows: Optional[List[int]] = None,
+    row_groups: Optional[List[Union[Tuple[int, int], Tuple[int, int, int], Tuple[int, int, int, bool]]]] = None,
+    col_groups: Optional[List[Union[Tuple[int, int], Tuple[int, int, int], Tuple[int, int, int, bool]]]] = None,
     right_to_left: bool = False,
     data_start_row: int = 0,
     header_content: Optional[List[Tuple[int, int, str]]] = None,
+    indexing: Optional[Literal["zero", "one"]] = None,
+    pre_1900_dates: Optional[Literal["text", "clamp", "error"]] = None,
+    invalid_text: Optional[Literal["replace", "error"]] = None,
+    list_separator: Optional[str] = None,
+    binary_encoding: Optional[Literal["hex", "base64"]] = None,
+    binary_max_length: Optional[int] = None,
+    filters: Optional[Dict[str, Union[List[Any], AutoFilterCriteria]]] = None,
+    sort_state: Optional[SortState] = None,
+    coordinates: Optional[Literal["sheet", "data"]] = None,
+    split_rows: Optional[int] = None,
+    column_gap: int = 1,
+    flatten_structs: bool = False,
+    zip_threads: int = 0,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+    progress: Optional[Callable[[int, Optional[int], Literal["writing", "compressing", "done"]], Any]] = None,
+    progress_interval: int = 100000,
+    modify_password: Optional[str] = None,
+    read_only_recommended: bool = False,
+    application: Optional[str] = None,
+    company: Optional[str] = None,
+    app_version: Optional[str] = None,
+    summary_sheet: bool = False,
+    summary_metadata: Optional[Dict[str, Any]] = None,
+    indexed_colors: Optional[List[str]] = None,
+    theme: Optional[WorkbookTheme] = None,
+    date_1904: bool = False,
 ) -> bytes:
     """Write Arrow data to Excel bytes (in-memory, no file I/O).
     
@@ -2015,6 +3152,24 @@ def write_sheet_arrow_to_bytes(
 def write_sheets_arrow_to_bytes(
     sheets_data: List[SheetConfig],
     num_threads: int = 1,
+    xml_threads: Optional[int] = None,
+    zip_threads: int = 0,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
+    progress: Optional[Callable[[int, Optional[int], Literal["writing", "compressing", "done"]], Any]] = None,
+    progress_interval: int = 100000,
+    modify_password: Optional[str] = None,
+    read_only_recommended: bool = False,
+    application: Optional[str] = None,
+    company: Optional[str] = None,
+    app_version: Optional[str] = None,
+    summary_sheet: bool = False,
+    summary_metadata: Optional[Dict[str, Any]] = None,
+    indexed_colors: Optional[List[str]] = None,
+    theme: Optional[WorkbookTheme] = None,
+    sheet_order: Optional[List[str]] = None,
+    active_sheet_index: int = 0,
+    first_sheet: int = 0,
+    date_1904: bool = False,
 ) -> bytes:
     """Write multiple sheets to Excel bytes (in-memory, no file I/O).
     
@@ -2089,6 +3244,7 @@ def write_sheet(
         - int/float: Numeric values
         - bool: Boolean values
         - datetime: Date/time values
+        - list of (text, font) tuples: Rich text mixing fonts in one cell
         - None: Empty cells
     
     Examples:
@@ -2160,7 +3316,10 @@ def write_sheet(
 def write_sheets(
     sheets_data: List[Dict[str, Any]],
     filename: str,
-    num_threads: int,
+    num_threads: int = 1,
+    xml_threads: Optional[int] = None,
+    zip_threads: int = 0,
+    compression: Optional[Union[Literal["stored", "fast", "default", "best"], int]] = None,
 ) -> None:
     """Write multiple dict-based sheets to Excel (legacy API).
     
@@ -2168,9 +3327,17 @@ def write_sheets(
     backward compatibility. For new code, prefer write_sheets_arrow().
     
     Args:
-        sheets_data: List of dicts with "name" and "columns" keys
+        sheets_data: List of dicts with "name" and "columns" keys, plus optional
+            view options as in write_sheets_arrow(): auto_filter, freeze_rows,
+            freeze_cols, gridlines_visible, zoom_scale, tab_color,
+            default_row_height and right_to_left
         filename: Output Excel file path (.xlsx)
-        num_threads: Number of parallel threads for generation
+        num_threads: Number of parallel threads for generation (default: 1)
+        xml_threads: Same as num_threads; takes precedence when given
+        zip_threads: Threads used to compress the file. 0 (default) uses every core
+        compression: Deflate level of the file: "fast" (default, level 1), "default"
+            (6), "best" (9) or an int 0-9. Higher levels write smaller files more slowly.
+            "stored" (or 0) skips deflate entirely for the fastest writes
     
     Examples:
         Basic Multi-Sheet:
@@ -2231,4 +3398,66 @@ def write_sheets(
         - All columns in a sheet must have the same length
         - For better performance, use write_sheets_arrow() instead
     """
-    ...
\ No newline at end of file
+    ...
+
+class SizeEstimate(TypedDict):
+    """Result of estimate()."""
+    rows: int
+    columns: int
+    xml_bytes: int          # Uncompressed worksheet XML
+    file_bytes: int         # Size of the .xlsx file
+    peak_memory_bytes: int  # Allocated by the write on top of the Arrow data
+
+def estimate(arrow_data: Any, **options: Any) -> SizeEstimate:
+    """Estimate the output size and memory of a write_sheet_arrow() call.
+    
+    Counts the worksheet XML from the data (exact string lengths, integer
+    digits, nulls) and applies the compression ratio measured on the first
+    10,000 rows, so services can reject oversized exports before starting.
+    Estimates land within about 15% for plain data; styles, formulas and
+    charts add a little on top.
+    
+    Args:
+        arrow_data: Same input as write_sheet_arrow(). A RecordBatchReader is
+            read to the end, so pass a table when the data is needed afterwards
+        **options: The keyword arguments the write would get. compression,
+            column_gap and flatten_structs change the estimate; the rest are
+            ignored, so the same dict can be passed to both calls
+    
+    Returns:
+        SizeEstimate dict. write_sheet_arrow_to_bytes() also holds the returned
+        bytes, adding file_bytes to the memory peak
+    
+    Example:
+        >>> options = {"compression": "best", "auto_width": True}
+        >>> est = jetxl.estimate(df.to_arrow(), **options)
+        >>> if est["file_bytes"] > 100 * 1024 * 1024:
+        ...     raise ValueError("Export too large")
+        >>> jetxl.write_sheet_arrow(df.to_arrow(), "out.xlsx", **options)
+    """
+    ...
+
+def generate_feature_gallery(directory: str) -> List[str]:
+    """Write a matrix of small workbooks exercising every supported feature.
+    
+    One workbook is produced per feature area: data types, number formats,
+    cell styles, layout (freeze panes, merges, hidden rows/columns, hyperlinks,
+    formulas), data validations, conditional formats, tables, each chart type,
+    and images in every supported raster format. Mass-open the results in
+    Excel/LibreOffice to verify compatibility after upgrading.
+    
+    Args:
+        directory: Target directory (created if it does not exist)
+    
+    Returns:
+        List of generated file paths, in generation order
+    
+    Example:
+        >>> paths = jetxl.generate_feature_gallery("gallery/")
+        >>> len(paths)
+        14
+    
+    Raises:
+        IOError: If the directory or a workbook cannot be written
+    """
+    ...
@@ -0,0 +1,503 @@
+use crate::styles::*;
+use crate::types::WriteError;
+use crate::writer;
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, RecordBatch, StringArray,
+    Time64MicrosecondArray, TimestampMicrosecondArray,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+// ============================================================================
+// FEATURE GALLERY - One small workbook per feature for compatibility checks
+// ============================================================================
+
+/// Write a matrix of small workbooks into `dir`, each exercising one feature
+/// area (formats, charts, validations, tables, images, ...). Returns the paths
+/// written, in generation order.
+pub fn generate_feature_gallery(dir: &Path) -> Result<Vec<String>, WriteError> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    let mut write = |file: &str, sheet: &str, batch: RecordBatch, config: StyleConfig| -> Result<(), WriteError> {
+        let path = dir.join(file);
+        let path_str = path.to_string_lossy().to_string();
+        writer::write_single_sheet_arrow_with_config(
+            &[batch], sheet, &path_str, &config, &WorkbookConfig::default(),
+        )?;
+        written.push(path_str);
+        Ok(())
+    };
+
+    write("01_data_types.xlsx", "DataTypes", data_types_batch()?, data_types_config())?;
+    write("02_number_formats.xlsx", "NumberFormats", number_formats_batch()?, number_formats_config())?;
+    write("03_cell_styles.xlsx", "CellStyles", sales_batch()?, cell_styles_config())?;
+    write("04_layout.xlsx", "Layout", sales_batch()?, layout_config())?;
+    write("05_data_validations.xlsx", "Validations", sales_batch()?, validations_config())?;
+    write("06_conditional_formats.xlsx", "Conditional", sales_batch()?, conditional_config())?;
+    write("07_tables.xlsx", "Tables", sales_batch()?, tables_config())?;
+
+    let chart_types = [
+        ("column", ChartType::Column),
+        ("bar", ChartType::Bar),
+        ("line", ChartType::Line),
+        ("pie", ChartType::Pie),
+        ("scatter", ChartType::Scatter),
+        ("area", ChartType::Area),
+        ("doughnut", ChartType::Doughnut),
+        ("column_3d", ChartType::Column3D),
+        ("bar_3d", ChartType::Bar3D),
+        ("pie_3d", ChartType::Pie3D),
+    ];
+    let mut file_no = 8;
+    for (label, chart_type) in chart_types {
+        let file = format!("{:02}_chart_{}.xlsx", file_no, label);
+        write(&file, "Chart", sales_batch()?, chart_config(chart_type, label))?;
+        file_no += 1;
+    }
+    write(&format!("{:02}_chart_combo.xlsx", file_no), "Chart", sales_batch()?, combo_chart_config())?;
+    file_no += 1;
+
+    write(&format!("{:02}_images.xlsx", file_no), "Images", sales_batch()?, images_config()?)?;
+
+    Ok(written)
+}
+
+fn batch(columns: Vec<(&str, ArrayRef)>) -> Result<RecordBatch, WriteError> {
+    RecordBatch::try_from_iter(columns).map_err(|e| WriteError::Validation(e.to_string()))
+}
+
+/// Five rows of region/units/revenue/margin data shared by most gallery sheets
+fn sales_batch() -> Result<RecordBatch, WriteError> {
+    batch(vec![
+        ("Region", Arc::new(StringArray::from(vec!["North", "South", "East", "West", "Central"])) as ArrayRef),
+        ("Units", Arc::new(Int64Array::from(vec![120, 85, 240, 60, 175])) as ArrayRef),
+        ("Revenue", Arc::new(Float64Array::from(vec![1520.5, 980.25, 3105.0, 640.75, 2210.0])) as ArrayRef),
+        ("Margin", Arc::new(Float64Array::from(vec![0.21, 0.15, 0.32, 0.08, 0.27])) as ArrayRef),
+    ])
+}
+
+fn data_types_batch() -> Result<RecordBatch, WriteError> {
+    let decimal = Decimal128Array::from(vec![Some(123_456), Some(-5), None])
+        .with_precision_and_scale(10, 2)
+        .map_err(|e| WriteError::Validation(e.to_string()))?;
+    batch(vec![
+        ("String", Arc::new(StringArray::from(vec![Some("text"), Some("<&\"escaped\">"), None])) as ArrayRef),
+        ("Int64", Arc::new(Int64Array::from(vec![Some(1), Some(-42), None])) as ArrayRef),
+        ("Float64", Arc::new(Float64Array::from(vec![Some(2.5), Some(-0.5), None])) as ArrayRef),
+        ("Decimal128", Arc::new(decimal) as ArrayRef),
+        ("Boolean", Arc::new(BooleanArray::from(vec![Some(true), Some(false), None])) as ArrayRef),
+        ("BooleanYesNo", Arc::new(BooleanArray::from(vec![Some(true), Some(false), None])) as ArrayRef),
+        ("Date32", Arc::new(Date32Array::from(vec![Some(19_000), Some(0), None])) as ArrayRef),
+        ("Timestamp", Arc::new(TimestampMicrosecondArray::from(vec![Some(1_700_000_000_000_000), Some(0), None])) as ArrayRef),
+        ("Time64", Arc::new(Time64MicrosecondArray::from(vec![Some(45_296_000_000), Some(0), None])) as ArrayRef),
+    ])
+}
+
+fn data_types_config() -> StyleConfig {
+    StyleConfig {
+        column_formats: Some([("BooleanYesNo".to_string(), NumberFormat::BoolYesNo)].into_iter().collect()),
+        ..StyleConfig::default()
+    }
+}
+
+fn number_formats() -> Vec<(&'static str, NumberFormat)> {
+    vec![
+        ("general", NumberFormat::General),
+        ("integer", NumberFormat::Integer),
+        ("decimal2", NumberFormat::Decimal2),
+        ("decimal4", NumberFormat::Decimal4),
+        ("percentage", NumberFormat::Percentage),
+        ("percentage_decimal", NumberFormat::PercentageDecimal),
+        ("percentage_integer", NumberFormat::PercentageInteger),
+        ("currency", NumberFormat::Currency),
+        ("currency_rounded", NumberFormat::CurrencyRounded),
+        ("date", NumberFormat::Date),
+        ("datetime", NumberFormat::DateTime),
+        ("time", NumberFormat::Time),
+        ("scientific", NumberFormat::Scientific),
+        ("fraction", NumberFormat::Fraction),
+        ("fraction_two_digits", NumberFormat::FractionTwoDigits),
+        ("thousands", NumberFormat::ThousandsSeparator),
+        ("currency_eur", NumberFormat::currency("eur", false).unwrap()),
+        ("currency_jpy", NumberFormat::currency("jpy", false).unwrap()),
+        ("currency_sek", NumberFormat::currency("sek", false).unwrap()),
+        ("decimal2_red_negative", NumberFormat::Decimal2.with_negatives(NegativeStyle::RedParens).unwrap()),
+        ("currency_parens", NumberFormat::Currency.with_negatives(NegativeStyle::Parens).unwrap()),
+        ("custom", NumberFormat::Custom("[Blue]#,##0.00;[Red]-#,##0.00".to_string())),
+    ]
+}
+
+fn number_formats_batch() -> Result<RecordBatch, WriteError> {
+    let values = vec![45_123.456, -0.125, 1234567.891];
+    batch(
+        number_formats()
+            .into_iter()
+            .map(|(name, _)| (name, Arc::new(Float64Array::from(values.clone())) as ArrayRef))
+            .collect(),
+    )
+}
+
+fn number_formats_config() -> StyleConfig {
+    StyleConfig {
+        auto_width: true,
+        styled_headers: true,
+        column_formats: Some(
+            number_formats()
+                .into_iter()
+                .map(|(name, fmt)| (name.to_string(), fmt))
+                .collect(),
+        ),
+        ..StyleConfig::default()
+    }
+}
+
+fn border_side(style: BorderLineStyle) -> Option<BorderSide> {
+    Some(BorderSide { style, color: Some(Color::Rgb("FF000000".to_string())) })
+}
+
+fn cell_styles_config() -> StyleConfig {
+    let styles = vec![
+        CellStyle {
+            font: Some(FontStyle {
+                bold: true,
+                italic: true,
+                underline: Some(Underline::Double),
+                strike: true,
+                vert_align: None,
+                size: Some(14.0),
+                color: Some(Color::Rgb("FFFF0000".to_string())),
+                name: Some("Arial".to_string()),
+            }),
+            fill: None,
+            border: None,
+            alignment: None,
+            number_format: None,
+        },
+        CellStyle {
+            font: None,
+            fill: Some(FillStyle {
+                pattern_type: PatternType::Solid,
+                fg_color: Some(Color::Rgb("FFFFFF00".to_string())),
+                bg_color: None,
+            }),
+            border: None,
+            alignment: None,
+            number_format: None,
+        },
+        CellStyle {
+            font: Some(FontStyle {
+                bold: false,
+                italic: false,
+                underline: None,
+                strike: false,
+                vert_align: None,
+                size: None,
+                color: Some(Color::Theme { theme: 4, tint: Some(-0.25) }),
+                name: None,
+            }),
+            fill: Some(FillStyle { pattern_type: PatternType::Gray125, fg_color: None, bg_color: None }),
+            border: None,
+            alignment: None,
+            number_format: None,
+        },
+        CellStyle {
+            font: None,
+            fill: None,
+            border: Some(BorderStyle {
+                left: border_side(BorderLineStyle::Thin),
+                right: border_side(BorderLineStyle::Medium),
+                top: border_side(BorderLineStyle::Thick),
+                bottom: border_side(BorderLineStyle::Double),
+            }),
+            alignment: None,
+            number_format: None,
+        },
+        CellStyle {
+            font: None,
+            fill: None,
+            border: Some(BorderStyle {
+                left: border_side(BorderLineStyle::Dotted),
+                right: border_side(BorderLineStyle::Dashed),
+                top: None,
+                bottom: None,
+            }),
+            alignment: Some(AlignmentStyle {
+                horizontal: Some(HorizontalAlignment::Center),
+                vertical: Some(VerticalAlignment::Center),
+                wrap_text: true,
+                text_rotation: Some(45),
+                indent: None,
+                shrink_to_fit: false,
+                reading_order: ReadingOrder::Context,
+            }),
+            number_format: Some(NumberFormat::Currency),
+        },
+        CellStyle {
+            font: None,
+            fill: None,
+            border: Some(BorderStyle {
+                left: border_side(BorderLineStyle::Hair),
+                right: border_side(BorderLineStyle::MediumDashed),
+                top: border_side(BorderLineStyle::DashDot),
+                bottom: border_side(BorderLineStyle::MediumDashDot),
+            }),
+            alignment: None,
+            number_format: None,
+        },
+        CellStyle {
+            font: None,
+            fill: None,
+            border: Some(BorderStyle {
+                left: border_side(BorderLineStyle::DashDotDot),
+                right: border_side(BorderLineStyle::SlantDashDot),
+                top: None,
+                bottom: None,
+            }),
+            alignment: None,
+            number_format: None,
+        },
+    ];
+
+    StyleConfig {
+        cell_styles: styles
+            .into_iter()
+            .enumerate()
+            .map(|(idx, style)| CellStyleMap { row: idx + 2, col: idx % 4, style })
+            .collect(),
+        ..StyleConfig::default()
+    }
+}
+
+fn layout_config() -> StyleConfig {
+    let mut config = StyleConfig {
+        auto_filter: true,
+        freeze_rows: 3,
+        freeze_cols: 1,
+        styled_headers: true,
+        auto_width: true,
+        merge_cells: vec![MergeRange { start_row: 1, start_col: 0, end_row: 1, end_col: 3 }],
+        hyperlinks: vec![Hyperlink {
+            row: 4,
+            col: 0,
+            url: "https://example.com".to_string(),
+            display: Some("Example".to_string()),
+        }],
+        row_heights: Some([(1, 30.0)].into_iter().collect()),
+        formulas: vec![Formula {
+            row: 9,
+            col: 2,
+            formula: "SUM(C4:C8)".to_string(),
+            cached_value: None,
+        }],
+        zoom_scale: Some(125),
+        tab_color: Some("FF00B050".to_string()),
+        default_row_height: Some(18.0),
+        data_start_row: 3,
+        header_content: vec![(1, 0, "Feature gallery: layout".to_string())],
+        ..StyleConfig::default()
+    };
+    config.hidden_columns.insert(3);
+    config.hidden_rows.insert(2);
+    config
+}
+
+fn validations_config() -> StyleConfig {
+    let validation = |col: usize, validation_type: ValidationType| DataValidation {
+        start_row: 2,
+        start_col: col,
+        end_row: 6,
+        end_col: col,
+        validation_type,
+        error_title: Some("Invalid value".to_string()),
+        error_message: Some("See the feature gallery rules".to_string()),
+        prompt_title: Some("Allowed values".to_string()),
+        prompt_message: Some("Select the cell to see which values this column accepts".to_string()),
+        show_input_message: true,
+        show_dropdown: true,
+        error_style: ValidationErrorStyle::Stop,
+        show_error_message: true,
+        allow_blank: true,
+    };
+
+    StyleConfig {
+        data_validations: vec![
+            validation(0, ValidationType::List(vec![
+                "North".to_string(), "South".to_string(), "East".to_string(),
+                "West".to_string(), "Central".to_string(),
+            ])),
+            validation(1, ValidationType::WholeNumber { min: 0, max: 1000 }),
+            // Out-of-range amounts only warn
+            DataValidation {
+                error_style: ValidationErrorStyle::Warning,
+                ..validation(2, ValidationType::Decimal { min: 0.0, max: 10_000.0 })
+            },
+            validation(3, ValidationType::TextLength { min: 1, max: 10 }),
+            // Rows below the data pick from the regions already entered above
+            DataValidation {
+                start_row: 7,
+                end_row: 10,
+                ..validation(0, ValidationType::ListRef("Validations!$A$2:$A$6".to_string()))
+            },
+        ],
+        ..StyleConfig::default()
+    }
+}
+
+fn conditional_config() -> StyleConfig {
+    let highlight = CellStyle {
+        font: Some(FontStyle {
+            bold: true,
+            italic: false,
+            underline: None,
+            strike: false,
+            vert_align: None,
+            size: None,
+            color: Some(Color::Rgb("FF9C0006".to_string())),
+            name: None,
+        }),
+        fill: Some(FillStyle {
+            pattern_type: PatternType::Solid,
+            fg_color: Some(Color::Rgb("FFFFC7CE".to_string())),
+            bg_color: None,
+        }),
+        border: None,
+        alignment: None,
+        number_format: None,
+    };
+    let format = |col: usize, rule: ConditionalRule, priority: u32| ConditionalFormat {
+        start_row: 2,
+        start_col: col,
+        end_row: 6,
+        end_col: col,
+        rule,
+        style: highlight.clone(),
+        priority,
+        stop_if_true: false,
+    };
+
+    StyleConfig {
+        conditional_formats: vec![
+            ConditionalFormat {
+                stop_if_true: true,
+                ..format(1, ConditionalRule::CellValue {
+                    operator: ComparisonOperator::GreaterThan,
+                    value: "100".to_string(),
+                }, 1)
+            },
+            format(2, ConditionalRule::DataBar {
+                color: "FF638EC6".to_string(),
+                show_value: true,
+                min: Cfvo::new(CfvoType::AutoMin),
+                max: Cfvo::new(CfvoType::AutoMax),
+                border_color: Some("FF3A6FB0".to_string()),
+                negative_color: None,
+                negative_border_color: None,
+                gradient: false,
+                axis_color: None,
+                axis_position: DataBarAxisPosition::Automatic,
+            }, 2),
+            format(3, ConditionalRule::ColorScale {
+                min_color: "FFF8696B".to_string(),
+                max_color: "FF63BE7B".to_string(),
+                mid_color: Some("FFFFEB84".to_string()),
+                min: Cfvo { kind: CfvoType::Num, value: Some("0".to_string()) },
+                mid: Cfvo { kind: CfvoType::Percent, value: Some("50".to_string()) },
+                max: Cfvo::new(CfvoType::Max),
+            }, 3),
+            format(2, ConditionalRule::Top10 { rank: 2, bottom: false }, 4),
+            ConditionalFormat {
+                start_col: 0,
+                end_col: 3,
+                ..format(0, ConditionalRule::Expression { formula: "$B2>150".to_string() }, 5)
+            },
+            format(0, ConditionalRule::ContainsText { text: "th".to_string() }, 6),
+            format(1, ConditionalRule::UniqueValues, 7),
+        ],
+        ..StyleConfig::default()
+    }
+}
+
+fn tables_config() -> StyleConfig {
+    let mut table = ExcelTable::new("GallerySales".to_string(), (1, 0, 0, 0));
+    table.style_name = Some("TableStyleMedium9".to_string());
+    table.show_first_column = true;
+    StyleConfig {
+        tables: vec![table],
+        ..StyleConfig::default()
+    }
+}
+
+fn chart_config(chart_type: ChartType, label: &str) -> StyleConfig {
+    // Pie charts plot a single series
+    let data_range = match chart_type {
+        ChartType::Pie | ChartType::Pie3D | ChartType::Doughnut => (1, 1, 5, 1),
+        _ => (1, 1, 5, 2),
+    };
+    let mut chart = ExcelChart::new(
+        chart_type,
+        data_range,
+        ChartPosition { from_col: 5, from_row: 1, to_col: 13, to_row: 16 },
+    );
+    chart.title = Some(format!("Gallery {} chart", label));
+    chart.category_col = Some(0);
+    chart.series_names = vec!["Units".to_string(), "Revenue".to_string()];
+    chart.x_axis_title = Some("Region".to_string());
+    chart.y_axis_title = Some("Value".to_string());
+
+    StyleConfig {
+        charts: vec![chart],
+        ..StyleConfig::default()
+    }
+}
+
+/// Units as columns with Revenue as a line on the secondary axis
+fn combo_chart_config() -> StyleConfig {
+    let mut config = chart_config(ChartType::Column, "combo");
+    let series = |col: usize, chart_type: ChartType, secondary: bool| ChartSeries {
+        col,
+        name: None,
+        chart_type: Some(chart_type),
+        secondary,
+        trendline: None,
+        color: None,
+        line_width: None,
+        line_dash: None,
+        marker: None,
+        range: None,
+        smooth: None,
+    };
+    config.charts[0].series = vec![
+        series(1, ChartType::Column, false),
+        series(2, ChartType::Line, true),
+    ];
+    config
+}
+
+fn images_config() -> Result<StyleConfig, WriteError> {
+    let pixels = image::RgbImage::from_fn(32, 32, |x, y| {
+        image::Rgb([(x * 8) as u8, (y * 8) as u8, 160])
+    });
+
+    let formats = [
+        ("png", image::ImageFormat::Png),
+        ("jpeg", image::ImageFormat::Jpeg),
+        ("gif", image::ImageFormat::Gif),
+        ("bmp", image::ImageFormat::Bmp),
+    ];
+
+    let mut config = StyleConfig::default();
+    for (idx, (ext, format)) in formats.into_iter().enumerate() {
+        let mut data = std::io::Cursor::new(Vec::new());
+        pixels
+            .write_to(&mut data, format)
+            .map_err(|e| WriteError::Validation(format!("Failed to encode {} image: {}", ext, e)))?;
+        let col = 5 + idx * 3;
+        config.images.push(ExcelImage::from_bytes(
+            data.into_inner(),
+            ext.to_string(),
+            ImagePosition { from_col: col, from_row: 1, to_col: col + 2, to_row: 6, col_off: 0, row_off: 0 },
+        ));
+    }
+    Ok(config)
+}
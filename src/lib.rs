@@ -2,14 +2,18 @@ mod types;
 mod writer;
 mod xml;
 mod styles;
+mod zip;
+mod gallery;
 
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyDict, PyList, PyTuple};
 use pyo3_arrow::input::AnyRecordBatch;
 use arrow_array::RecordBatch;
-use types::{CellValue, SheetData};
+use arrow_array::timezone::Tz;
+use types::{CellValue, SheetData, WriteError};
 use styles::*;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 // ============================================================================
 // LEGACY API - Dict-based (backward compatibility)
@@ -35,27 +39,30 @@ fn write_sheet(
     let mut config = StyleConfig::default();
     if let Some(charts_vec) = charts {
         for chart_dict in charts_vec {
-            if let Ok(chart) = extract_chart(&chart_dict) {
-                config.charts.push(chart);
-            }
+            let (chart, _) = extract_chart(&chart_dict)?;
+            config.charts.push(chart);
         }
     }
 
     py.detach(|| {
-        writer::write_single_sheet_with_config(&sheet, &filename, &config)
+        writer::write_single_sheet_with_config(&sheet, &filename, &config, &WorkbookConfig::default())
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
     })
 }
 
 #[pyfunction]
-#[pyo3(signature = (sheets_data, filename, num_threads))]
+#[pyo3(signature = (sheets_data, filename, num_threads = 1, xml_threads = None, zip_threads = 0, compression = None))]
 fn write_sheets(
     py: Python,
     sheets_data: Vec<Bound<PyDict>>,
     filename: String,
-    num_threads: usize,  
+    num_threads: usize,
+    xml_threads: Option<usize>,
+    zip_threads: usize,
+    compression: Option<Bound<PyAny>>,
 ) -> PyResult<()> {
-    let sheets: Result<Vec<_>, _> = sheets_data
+    let compression = extract_compression(compression)?;
+    let sheets: PyResult<Vec<_>> = sheets_data
         .into_iter()
         .enumerate()
         .map(|(i, sheet_dict)| {
@@ -69,7 +76,9 @@ fn write_sheets(
                 .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'columns' key"))?;
             let cols = cols_item.downcast::<PyDict>()?;
 
-            extract_sheet_data(py, cols, Some(name))
+            let mut config = StyleConfig::default();
+            extract_sheet_view_options(&sheet_dict, &mut config)?;
+            Ok((extract_sheet_data(py, cols, Some(name))?, config))
         })
         .collect();
 
@@ -77,7 +86,8 @@ fn write_sheets(
 
 
     py.detach(|| {
-        writer::write_multiple_sheets(&sheets, &filename, num_threads)
+        let workbook = WorkbookConfig { zip_threads, compression, ..WorkbookConfig::default() };
+        writer::write_multiple_sheets(&sheets, &filename, xml_threads.unwrap_or(num_threads), &workbook)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
     })
 }
@@ -99,30 +109,70 @@ fn write_sheets(
     write_header_row = true,
     column_widths = None,
     column_formats = None,
+    format_totals_rows = false,
+    format_whole_columns = false,
+    text_columns = None,
     merge_cells = None,
     data_validations = None,
     hyperlinks = None,
     row_heights = None,
+    row_formats = None,
+    styles = None,
     cell_styles = None,
+    range_styles = None,
     formulas = None,
+    rich_text = None,
+    shared_formulas = None,
+    column_formulas = None,
     conditional_formats = None,
     tables = None, 
     charts = None,
     images = None,
     gridlines_visible = true,
     zoom_scale = None,
+    active_cell = None,
+    selection = None,
+    top_left_cell = None,
     tab_color = None,
     default_row_height = None,
     hidden_columns = None,
     hidden_rows = None,
+    row_groups = None,
+    col_groups = None,
     right_to_left = false,
     data_start_row = 0,
     header_content = None,
+    indexing = None,
+    pre_1900_dates = None,
+    invalid_text = None,
+    list_separator = None,
+    binary_encoding = None,
+    binary_max_length = None,
+    filters = None,
+    sort_state = None,
+    coordinates = None,
+    split_rows = None,
+    column_gap = 1,
+    flatten_structs = false,
+    zip_threads = 0,
+    compression = None,
+    progress = None,
+    progress_interval = 100000,
+    modify_password = None,
+    read_only_recommended = false,
+    application = None,
+    company = None,
+    app_version = None,
+    summary_sheet = false,
+    summary_metadata = None,
+    indexed_colors = None,
+    theme = None,
+    date_1904 = false,
 ))]
 /// Write Arrow data to an Excel file with advanced formatting options.
 /// 
 /// Args:
-///     arrow_data: PyArrow Table or RecordBatch
+///     arrow_data: Polars DataFrame, PyArrow Table, RecordBatch or RecordBatchReader (read one batch at a time)
 ///     filename (str): Output file path
 ///     sheet_name (str, optional): Sheet name. Defaults to "Sheet1"
 ///     auto_filter (bool): Enable autofilter on headers
@@ -135,25 +185,81 @@ fn write_sheets(
 ///         - float/int: Excel character units (e.g., 15.5)
 ///         - "150px": Pixel width (converted to characters)
 ///         - "auto": Auto-calculate from data
-///     column_formats (dict[str, str], optional): Number formats: "integer", "decimal2", "currency", "date", "percentage", etc.
-///     merge_cells (list[tuple], optional): List of (start_row, start_col, end_row, end_col)
+///     column_formats (dict[str, str | dict], optional): Number formats: "integer", "decimal2", "currency", "date", "percentage", etc.
+///         A dict {"format", "code", "tz"} also shows a timestamp column in the given timezone.
+///         Keys of column_widths and column_formats are names, indexes or column letters ("C")
+///     format_totals_rows (bool): Also apply column_formats to table totals rows (default: False)
+///     format_whole_columns (bool): Also set column_formats on the whole column, so empty cells
+///         and rows typed below the data get the format (default: False)
+///     text_columns (list[str], optional): Columns written as text even when numeric (IDs, zip codes)
+///     merge_cells (str|list, optional): A1 ranges like "B2:D4" or (start_row, start_col, end_row, end_col) tuples
 ///     data_validations (list[dict], optional): Data validation rules
 ///     hyperlinks (list[tuple], optional): List of (row, col, url, display_text)
 ///     row_heights (dict[int, float], optional): Custom row heights
+///     row_formats (dict[int, str], optional): Number format for every data cell of a row; wins over column_formats
 ///     cell_styles (list[dict], optional): Custom cell styles with font, fill, border, alignment
 ///     formulas (list[tuple], optional): List of (row, col, formula, cached_value)
+///     rich_text (list[tuple], optional): List of (row, col, runs); runs are (text, font) tuples
+///     shared_formulas (list[tuple], optional): List of (range, formula); the formula is stored once per range
+///     column_formulas (dict[str, str], optional): Formula per column for every data row; "{row}" is the row number
 ///     conditional_formats (list[dict], optional): Conditional formatting rules
 ///     tables (list[dict], optional): Excel table definitions
 ///     charts (list[dict], optional): Chart definitions
 ///     images (list[dict], optional): Image definitions
 ///     gridlines_visible (bool): Show gridlines (default: True)
 ///     zoom_scale (int, optional): Zoom level 10-400%
+///     active_cell (str, optional): Cell the cursor starts on, e.g. "B2"
+///     selection (str, optional): Ranges selected on open, e.g. "B2:D10" or "B2:D10 F2:F10";
+///         defaults to the active cell, which in turn defaults to its first cell
+///     top_left_cell (str, optional): Cell scrolled to the window's top-left corner
 ///     tab_color (str, optional): Sheet tab color in RGB format (e.g., "FFFF0000")
 ///     default_row_height (float, optional): Default row height for all rows
-///     hidden_columns (list[int], optional): Column indices to hide
+///     hidden_columns (list[int|str], optional): Columns to hide, by index, name or letter ("C")
 ///     hidden_rows (list[int], optional): Row indices to hide
+///     row_groups (list[tuple], optional): Outline groups as (start_row, end_row, level, collapsed);
+///         level (1-7, default 1) and collapsed (default False) may be left off
+///     col_groups (list[tuple], optional): Column outline groups, as row_groups
 ///     right_to_left (bool): Enable right-to-left layout (default: False)
 ///     data_start_row (int): Skip this many rows when calculating auto_width (for dummy rows)
+///     indexing (str, optional): "zero" or "one" to use a single base for every row/column
+///         parameter. Default keeps the legacy convention (rows 1-based, columns 0-based)
+///     pre_1900_dates (str, optional): Dates before 1900-01-01, which Excel cannot store:
+///         "text" (ISO text, default), "clamp" (1900-01-01) or "error"
+///     invalid_text (str, optional): Strings with invalid UTF-8 or characters XML cannot
+///         store: "replace" (with U+FFFD, default) or "error"
+///     list_separator (str, optional): Joins the elements of list columns into one cell (default: ", ")
+///     binary_encoding (str, optional): Text for binary columns: "hex" (default) or "base64"
+///     binary_max_length (int, optional): Longest binary cell text; longer values are cut and end in "…"
+///     filters (dict[str, list | dict], optional): Autofilter criteria by column name; a list of
+///         values or {"type": "values" | "range" | "top10" | "contains", ...}. Turns the autofilter on
+///     sort_state (dict, optional): Sort already applied to the data, e.g.
+///         {"ref": "A2:F100", "keys": [(2, "descending")]}, so Excel shows the sort arrows
+///     coordinates (str, optional): "data" to count cell, table and chart rows from the first
+///         DataFrame row instead of the top of the sheet (default: "sheet")
+///     split_rows (int, optional): Data rows per sheet; the rest continues on "Name (2)", ...
+///         sheets that repeat the header row
+///     column_gap (int): Empty columns between tables when arrow_data is a list (default: 1)
+///     flatten_structs (bool): Expand struct columns into "parent.child" columns (default: False)
+///     zip_threads (int): Threads used to compress the file (default: 0, every core)
+///     compression (str | int, optional): "fast" (default), "default", "best", a deflate level 0-9,
+///         or "stored" (same as 0) to skip deflate entirely
+///     progress (callable, optional): Called as progress(rows_written, total_rows, phase) while
+///         writing; phase is "writing", "compressing" or "done". Raising stops the write
+///     progress_interval (int): Data rows between "writing" calls (default: 100000)
+///     modify_password (str, optional): Password required to edit; the file still opens read-only without it
+///     read_only_recommended (bool): Ask users to open the file read-only (default: False)
+///     application (str, optional): Application name written to docProps/app.xml (default: "jetxl <version>")
+///     company (str, optional): Company written to docProps/app.xml
+///     app_version (str, optional): AppVersion written to docProps/app.xml (default: "16.0300")
+///     summary_sheet (bool): Add a "Summary" sheet first with row counts, generation time
+///         and a config digest (default: False)
+///     summary_metadata (dict, optional): Extra rows for the summary sheet, e.g. {"Source": "warehouse"};
+///         turns the summary sheet on
+///     indexed_colors (list[str], optional): Custom legacy palette of up to 64 colors; style
+///         colors matching an entry are written by index for readers that ignore ARGB
+///     theme (dict, optional): Theme overrides: "accents" (up to 6 colors for accent1-6),
+///         "major_font" (headings) and "minor_font" (body)
+///     date_1904 (bool): Write dates in the 1904 date system of old Mac workbooks (default: False)
 #[allow(clippy::too_many_arguments)]
 fn write_sheet_arrow(
     py: Python,
@@ -166,37 +272,82 @@ fn write_sheet_arrow(
     auto_width: bool,
     styled_headers: bool,
     write_header_row: bool,
-    column_widths: Option<HashMap<String, Bound<PyAny>>>,
-    column_formats: Option<HashMap<String, String>>,
-    merge_cells: Option<Vec<(usize, usize, usize, usize)>>,
+    column_widths: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    column_formats: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    format_totals_rows: bool,
+    format_whole_columns: bool,
+    text_columns: Option<Vec<String>>,
+    merge_cells: Option<Bound<PyAny>>,
     data_validations: Option<Vec<Bound<PyDict>>>,
     hyperlinks: Option<Vec<(usize, usize, String, Option<String>)>>,
     row_heights: Option<HashMap<usize, f64>>,
+    row_formats: Option<HashMap<usize, String>>,
+    styles: Option<HashMap<String, Bound<PyDict>>>,
     cell_styles: Option<Vec<Bound<PyDict>>>,
+    range_styles: Option<Vec<Bound<PyDict>>>,
     formulas: Option<Vec<(usize, usize, String, Option<String>)>>,
+    rich_text: Option<Vec<(usize, usize, Bound<PyAny>)>>,
+    shared_formulas: Option<Vec<(Bound<PyAny>, String)>>,
+    column_formulas: Option<HashMap<String, String>>,
     conditional_formats: Option<Vec<Bound<PyDict>>>,
     tables: Option<Vec<Bound<PyDict>>>,
     charts: Option<Vec<Bound<PyDict>>>,
     images: Option<Vec<Bound<PyDict>>>,
     gridlines_visible: bool,
     zoom_scale: Option<u16>,
+    active_cell: Option<String>,
+    selection: Option<String>,
+    top_left_cell: Option<String>,
     tab_color: Option<String>,
     default_row_height: Option<f64>,
-    hidden_columns: Option<Vec<usize>>,
+    hidden_columns: Option<Vec<ColumnKey>>,
     hidden_rows: Option<Vec<usize>>,
+    row_groups: Option<Bound<PyAny>>,
+    col_groups: Option<Bound<PyAny>>,
     right_to_left: bool,
     data_start_row: usize,
-    header_content: Option<Vec<(usize, usize, String)>>
+    header_content: Option<Vec<(usize, usize, String)>>,
+    indexing: Option<String>,
+    pre_1900_dates: Option<String>,
+    invalid_text: Option<String>,
+    list_separator: Option<String>,
+    binary_encoding: Option<String>,
+    binary_max_length: Option<usize>,
+    filters: Option<HashMap<String, Bound<PyAny>>>,
+    sort_state: Option<Bound<PyDict>>,
+    coordinates: Option<String>,
+    split_rows: Option<usize>,
+    column_gap: usize,
+    flatten_structs: bool,
+    zip_threads: usize,
+    compression: Option<Bound<PyAny>>,
+    progress: Option<Py<PyAny>>,
+    progress_interval: usize,
+    modify_password: Option<String>,
+    read_only_recommended: bool,
+    application: Option<String>,
+    company: Option<String>,
+    app_version: Option<String>,
+    summary_sheet: bool,
+    summary_metadata: Option<Bound<PyDict>>,
+    indexed_colors: Option<Vec<String>>,
+    theme: Option<Bound<PyDict>>,
+    date_1904: bool,
 ) -> PyResult<()> {
-    // Convert PyArrow data to RecordBatch
-    let any_batch = AnyRecordBatch::extract_bound(arrow_data)?;
-    let reader = any_batch.into_reader()?;
-    
-    let batches: Vec<RecordBatch> = reader
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            format!("Failed to read Arrow data: {}", e)
-        ))?;
+    // A single table or stream is read lazily, one batch at a time, so a
+    // RecordBatchReader is never collected when the sheet can be streamed.
+    // A list places tables side by side and is read up front.
+    let (mut batches, lazy_batches, group_formats, group_widths, group_timezones) = if arrow_data.downcast::<PyList>().is_ok() {
+        let writer::CombinedColumns {
+            batches, column_formats, column_widths, column_timezones,
+        } = read_arrow_input(arrow_data, column_gap, flatten_structs)?;
+        (batches, None, column_formats, column_widths, column_timezones)
+    } else {
+        let mut lazy_batches = read_arrow_lazily(arrow_data, flatten_structs)?;
+        let first = lazy_batches.next().transpose()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        (first.into_iter().collect(), Some(lazy_batches), HashMap::new(), HashMap::new(), HashMap::new())
+    };
     
     if batches.is_empty() {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
@@ -206,31 +357,43 @@ fn write_sheet_arrow(
 
     let name = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
 
-    // Parse column_widths - supports float, "auto", or "150px"
-    let parsed_column_widths = column_widths.map(|cw| {
-        cw.into_iter()
-            .filter_map(|(k, v)| {
-                let width = if let Ok(s) = v.extract::<String>() {
-                    if s.to_lowercase() == "auto" {
-                        ColumnWidth::Auto
-                    } else if s.ends_with("px") {
-                        let px: f64 = s.trim_end_matches("px").parse().unwrap_or(50.0);
-                        ColumnWidth::Pixels(px)
-                    } else {
-                        // Try parsing as number string
-                        ColumnWidth::Characters(s.parse().unwrap_or(8.43))
-                    }
-                } else if let Ok(f) = v.extract::<f64>() {
-                    ColumnWidth::Characters(f)
-                } else if let Ok(i) = v.extract::<i64>() {
-                    ColumnWidth::Characters(i as f64)
-                } else {
-                    return None;
-                };
-                Some((k, width))
-            })
-            .collect()
-    });
+    let mut a1 = A1Options::default();
+    let merge_cells = match merge_cells {
+        Some(value) => {
+            let (indexed, a1_ranges) = extract_merge_cells(&value)?;
+            a1.merge_cells = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
+    let shared_formulas = match shared_formulas {
+        Some(items) => {
+            let (indexed, a1_ranges) = extract_shared_formulas(items)?;
+            a1.shared_formulas = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
+    let named_styles = extract_named_styles(styles.unwrap_or_default())?;
+    let range_styles = match range_styles {
+        Some(items) => {
+            let (indexed, a1_ranges) = extract_range_styles(&items, &named_styles)?;
+            a1.range_styles = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
+
+    // Widths take a float, "auto" or "150px"; keys are names, letters or column indexes
+    let column_options = extract_column_options(column_widths, column_formats, &batches)?;
+    let hidden_columns = match hidden_columns {
+        Some(keys) => {
+            let (indexed, absolute) = extract_hidden_columns(keys, &batches)?;
+            a1.hidden_columns = absolute;
+            indexed
+        }
+        None => Default::default(),
+    };
 
     // Build config
     let mut config = StyleConfig {
@@ -239,54 +402,68 @@ fn write_sheet_arrow(
         freeze_cols,
         styled_headers,
         write_header_row,
-        column_widths: parsed_column_widths,
+        column_widths: column_options.widths,
         auto_width,
-        column_formats: column_formats.map(|cf| {
-            cf.into_iter()
-                .filter_map(|(k, v)| parse_number_format(&v).map(|fmt| (k, fmt)))
-                .collect()
-        }),
-        merge_cells: merge_cells.unwrap_or_default().into_iter().map(|(sr, sc, er, ec)| {
-            MergeRange { start_row: sr, start_col: sc, end_row: er, end_col: ec }
-        }).collect(),
+        column_formats: column_options.formats,
+        format_totals_rows,
+        format_whole_columns,
+        text_columns: text_columns.unwrap_or_default().into_iter().collect(),
+        column_formats_by_index: group_formats,
+        column_widths_by_index: group_widths,
+        column_timezones: column_options.timezones,
+        column_timezones_by_index: group_timezones,
+        merge_cells,
         data_validations: Vec::new(),
         hyperlinks: hyperlinks.unwrap_or_default().into_iter().map(|(row, col, url, display)| {
             Hyperlink { row, col, url, display }
         }).collect(),
         row_heights,
+        row_formats: extract_row_formats(row_formats.unwrap_or_default()),
         cell_styles: Vec::new(),
+        range_styles,
         formulas: Vec::new(),
+        shared_formulas,
+        column_formulas: extract_column_formulas(column_formulas.unwrap_or_default()),
+        rich_text: Vec::new(),
         conditional_formats: Vec::new(),
-        cond_format_dxf_ids: HashMap::new(), 
         tables: Vec::new(), 
         charts: Vec::new(),
         images: Vec::new(),
         gridlines_visible,
         zoom_scale,
+        selection: extract_selection(active_cell.as_deref(), selection.as_deref())?,
+        top_left_cell: top_left_cell.as_deref().map(parse_view_cell).transpose()?,
         tab_color,
         default_row_height,
-        hidden_columns: hidden_columns.map(|v| v.into_iter().collect()).unwrap_or_default(),
+        hidden_columns,
         hidden_rows: hidden_rows.map(|v| v.into_iter().collect()).unwrap_or_default(),
+        row_groups: row_groups.map(|v| extract_outline_groups(&v, "row_groups")).transpose()?.unwrap_or_default(),
+        col_groups: col_groups.map(|v| extract_outline_groups(&v, "col_groups")).transpose()?.unwrap_or_default(),
         right_to_left,
         data_start_row,
         header_content: header_content.unwrap_or_default(),
+        pre_1900_dates: Pre1900Dates::Text,
+        date_1904,
+        invalid_text: InvalidText::Replace,
+        list_separator: list_separator.unwrap_or_else(|| ", ".to_string()),
+        binary_encoding: parse_binary_encoding(binary_encoding.as_deref())?,
+        binary_max_length,
+        filter_columns: HashMap::new(),
+        sort_state: None,
+        split_rows,
         };
 
     // Parse data validations
     if let Some(validations) = data_validations {
         for val_dict in validations {
-            if let Ok(validation) = extract_data_validation(&val_dict) {
-                config.data_validations.push(validation);
-            }
+            push_ranged(extract_data_validation(&val_dict)?, &mut config.data_validations, &mut a1.data_validations);
         }
     }
 
     // Parse cell styles
     if let Some(styles) = cell_styles {
         for style_dict in styles {
-            if let Ok(cell_style) = extract_cell_style(&style_dict) {
-                config.cell_styles.push(cell_style);
-            }
+            config.cell_styles.push(extract_cell_style(&style_dict, &named_styles)?);
         }
     }
 
@@ -297,11 +474,16 @@ fn write_sheet_arrow(
         }
     }
 
+    // Parse rich text cells
+    if let Some(cells) = rich_text {
+        config.rich_text = extract_rich_text(cells)?;
+    }
+
     // Parse conditional formats
     if let Some(cond_formats) = conditional_formats {
         for cond_dict in cond_formats {
-            if let Ok(cond_format) = extract_conditional_format(&cond_dict) {
-                config.conditional_formats.push(cond_format);
+            for format in extract_conditional_formats(&cond_dict, &named_styles)? {
+                push_ranged(format, &mut config.conditional_formats, &mut a1.conditional_formats);
             }
         }
     }
@@ -309,18 +491,14 @@ fn write_sheet_arrow(
     // Parse tables
     if let Some(tables_vec) = tables {
         for table_dict in tables_vec {
-            if let Ok(table) = extract_table(&table_dict) {
-                config.tables.push(table);
-            }
+            push_ranged(extract_table(&table_dict)?, &mut config.tables, &mut a1.tables);
         }
     }
 
     // Parse charts
     if let Some(charts_vec) = charts {
         for chart_dict in charts_vec {
-            if let Ok(chart) = extract_chart(&chart_dict) {
-                config.charts.push(chart);
-            }
+            push_ranged(extract_chart(&chart_dict)?, &mut config.charts, &mut a1.charts);
         }
     }
 
@@ -333,267 +511,527 @@ fn write_sheet_arrow(
         }
     }
 
+    config.sort_state = sort_state.map(|s| extract_sort_state(&s)).transpose()?;
+    let indexing = parse_indexing(indexing.as_deref())?;
+    apply_indexing(&mut config, indexing)?;
+    column_options.by_index.apply(&mut config, indexing)?;
+    column_options.by_letter.apply(&mut config, Indexing::Zero)?;
+    apply_coordinates(&mut config, parse_coordinates(coordinates.as_deref())?);
+    a1.append_to(&mut config);
+    config.pre_1900_dates = parse_pre_1900_dates(pre_1900_dates.as_deref())?;
+    config.invalid_text = parse_invalid_text(invalid_text.as_deref())?;
+    if let Some(filters) = filters {
+        config.filter_columns = extract_filters(filters, config.date_1904)?;
+    }
+
+    let (progress, progress_error) = extract_progress(progress, progress_interval);
+    let workbook = WorkbookConfig {
+        modify_password,
+        read_only_recommended,
+        application,
+        company,
+        app_version,
+        zip_threads,
+        compression: extract_compression(compression)?,
+        progress,
+        summary_sheet: extract_summary_sheet(summary_sheet, summary_metadata)?,
+        indexed_colors: extract_indexed_colors(indexed_colors)?,
+        theme: extract_theme(theme)?,
+        date_1904,
+        ..WorkbookConfig::default()
+    };
+
     py.detach(|| {
-        writer::write_single_sheet_arrow_with_config(&batches, &name, &filename, &config)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+        let written = match lazy_batches {
+            Some(rest) if writer::can_stream_sheet(&config, &workbook) => {
+                let first = batches.remove(0);
+                writer::write_single_sheet_arrow_streaming(first, rest, &name, &filename, &config, &workbook)
+            }
+            Some(rest) => rest.collect::<Result<Vec<_>, _>>().and_then(|rest| {
+                batches.extend(rest);
+                writer::write_single_sheet_arrow_with_config(&batches, &name, &filename, &config, &workbook)
+            }),
+            None => writer::write_single_sheet_arrow_with_config(&batches, &name, &filename, &config, &workbook),
+        };
+        finish_write(written, &workbook, &progress_error)
     })
 }
 
 #[pyfunction]
-#[pyo3(signature = (arrow_sheets, filename, num_threads))]
+#[pyo3(signature = (arrow_sheets, filename, num_threads = 1, xml_threads = None, zip_threads = 0, compression = None, progress = None, progress_interval = 100000, modify_password = None, read_only_recommended = false, application = None, company = None, app_version = None, summary_sheet = false, summary_metadata = None, indexed_colors = None, theme = None, sheet_order = None, active_sheet_index = 0, first_sheet = 0, date_1904 = false, styles = None))]
 /// Write multiple Arrow tables to Excel with parallel processing.
 ///
 /// Args:
 ///     arrow_sheets (list[dict]): List of dicts with keys: data, name, and optional formatting params
 ///     filename (str): Output file path
-///     num_threads (int): Number of parallel threads for XML generation
+///     num_threads (int): Number of parallel threads for XML generation (default: 1)
+///     xml_threads (int, optional): Same as num_threads; takes precedence when given
+///     zip_threads (int): Threads used to compress the file (default: 0, every core)
+///     compression (str | int, optional): "fast" (default), "default", "best", a deflate level 0-9,
+///         or "stored" (same as 0) to skip deflate entirely
+///     progress (callable, optional): Called as progress(rows_written, total_rows, phase) while
+///         writing; phase is "writing", "compressing" or "done". Raising stops the write
+///     progress_interval (int): Data rows between "writing" calls (default: 100000)
+///     modify_password (str, optional): Password required to edit the workbook
+///     read_only_recommended (bool): Ask users to open the file read-only
+///     application (str, optional): Application name written to docProps/app.xml
+///     company (str, optional): Company written to docProps/app.xml
+///     app_version (str, optional): AppVersion written to docProps/app.xml
+///     summary_sheet (bool): Add a "Summary" sheet first (default: False)
+///     summary_metadata (dict, optional): Extra rows for the summary sheet; turns it on
+///     indexed_colors (list[str], optional): Custom legacy palette of up to 64 colors
+///     theme (dict, optional): Theme accent colors and major/minor fonts
+///     sheet_order (list[str], optional): Sheet names in tab order; unlisted sheets follow
+///     active_sheet_index (int): Tab the workbook opens on, 0-based (default: 0)
+///     first_sheet (int): Leftmost tab shown in the tab bar, 0-based (default: 0)
+///     date_1904 (bool): Write dates in the 1904 date system of old Mac workbooks (default: False)
+///     styles (dict[str, dict], optional): Named styles every sheet can use by name; a sheet's "styles" key adds to them
+#[allow(clippy::too_many_arguments)]
 fn write_sheets_arrow(
     py: Python,
     arrow_sheets: Vec<Bound<PyDict>>,
     filename: String,
     num_threads: usize,
+    xml_threads: Option<usize>,
+    zip_threads: usize,
+    compression: Option<Bound<PyAny>>,
+    progress: Option<Py<PyAny>>,
+    progress_interval: usize,
+    modify_password: Option<String>,
+    read_only_recommended: bool,
+    application: Option<String>,
+    company: Option<String>,
+    app_version: Option<String>,
+    summary_sheet: bool,
+    summary_metadata: Option<Bound<PyDict>>,
+    indexed_colors: Option<Vec<String>>,
+    theme: Option<Bound<PyDict>>,
+    sheet_order: Option<Vec<String>>,
+    active_sheet_index: usize,
+    first_sheet: usize,
+    date_1904: bool,
+    styles: Option<HashMap<String, Bound<PyDict>>>,
 ) -> PyResult<()> {
+    let workbook_styles = extract_named_styles(styles.unwrap_or_default())?;
+
     // Collect sheets with owned data first
-    let mut sheets_data: Vec<(Vec<RecordBatch>, String, StyleConfig)> = Vec::new();
+    let sheets_data = arrow_sheets.iter()
+        .map(|sheet_dict| extract_arrow_sheet(sheet_dict, date_1904, &workbook_styles))
+        .collect::<PyResult<Vec<_>>>()?;
     
-    for sheet_dict in arrow_sheets {
-        let arrow_data = sheet_dict.get_item("data")?.ok_or_else(|| 
-            PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'data' key"))?;
-        let name: String = sheet_dict.get_item("name")?.ok_or_else(|| 
-            PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'name' key"))?.extract()?;
-        
-        let any_batch = AnyRecordBatch::extract_bound(&arrow_data)?;
-        let reader = any_batch.into_reader()?;
-        let batches: Vec<RecordBatch> = reader
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                format!("Failed to read Arrow data: {}", e)
-            ))?;
-        
-        // Build config from optional parameters
-        let mut config = StyleConfig::default();
-        
-        // Basic options
-        if let Some(auto_filter) = sheet_dict.get_item("auto_filter")?.and_then(|v| v.extract().ok()) {
-            config.auto_filter = auto_filter;
-        }
-        if let Some(freeze_rows) = sheet_dict.get_item("freeze_rows")?.and_then(|v| v.extract().ok()) {
-            config.freeze_rows = freeze_rows;
-        }
-        if let Some(freeze_cols) = sheet_dict.get_item("freeze_cols")?.and_then(|v| v.extract().ok()) {
-            config.freeze_cols = freeze_cols;
-        }
-        if let Some(auto_width) = sheet_dict.get_item("auto_width")?.and_then(|v| v.extract().ok()) {
-            config.auto_width = auto_width;
-        }
-        if let Some(styled_headers) = sheet_dict.get_item("styled_headers")?.and_then(|v| v.extract().ok()) {
-            config.styled_headers = styled_headers;
-        }
-        if let Some(write_header_row) = sheet_dict.get_item("write_header_row")?.and_then(|v| v.extract().ok()) {
-            config.write_header_row = write_header_row;
-        }
-
-        // Column widths - parse "auto", "150px", or float values
-        if let Some(widths) = sheet_dict.get_item("column_widths")? {
-            let widths_dict = widths.downcast::<PyDict>()?;
-            let parsed_widths: HashMap<String, ColumnWidth> = widths_dict.iter()
-                .filter_map(|(k, v)| {
-                    let col_name: String = k.extract().ok()?;
-                    let width = if let Ok(s) = v.extract::<String>() {
-                        if s.to_lowercase() == "auto" {
-                            ColumnWidth::Auto
-                        } else if s.ends_with("px") {
-                            let px: f64 = s.trim_end_matches("px").parse().unwrap_or(50.0);
-                            ColumnWidth::Pixels(px)
-                        } else {
-                            ColumnWidth::Characters(s.parse().unwrap_or(8.43))
-                        }
-                    } else if let Ok(f) = v.extract::<f64>() {
-                        ColumnWidth::Characters(f)
-                    } else if let Ok(i) = v.extract::<i64>() {
-                        ColumnWidth::Characters(i as f64)
-                    } else {
-                        return None;
-                    };
-                    Some((col_name, width))
-                })
-                .collect();
-            config.column_widths = Some(parsed_widths);
-        }
-
-        // Extract column_formats
-        if let Some(formats) = sheet_dict.get_item("column_formats")? {
-            let formats_dict = formats.downcast::<PyDict>()?;
-            let mut col_fmts = HashMap::new();
-            for (key, value) in formats_dict.iter() {
-                let col_name: String = key.extract()?;
-                let fmt_str: String = value.extract()?;
-                if let Some(fmt) = parse_number_format(&fmt_str) {
-                    col_fmts.insert(col_name, fmt);
-                }
-            }
-            config.column_formats = Some(col_fmts);
-        }
-
-        // Merge cells
-        if let Some(merge) = sheet_dict.get_item("merge_cells")? {
-            let merge_list = merge.downcast::<pyo3::types::PyList>()?;
-            for item in merge_list.iter() {
-                if let Ok(tuple) = item.extract::<(usize, usize, usize, usize)>() {
-                    config.merge_cells.push(MergeRange {
-                        start_row: tuple.0,
-                        start_col: tuple.1,
-                        end_row: tuple.2,
-                        end_col: tuple.3,
-                    });
-                }
-            }
-        }
+    // Create references for the writer
+    let sheets_refs: Vec<(&[RecordBatch], &str, &StyleConfig)> = sheets_data.iter()
+        .map(|(b, n, c)| (b.as_slice(), n.as_str(), c))
+        .collect();
 
-        // Data validations
-        if let Some(validations) = sheet_dict.get_item("data_validations")? {
-            let validations_list = validations.downcast::<pyo3::types::PyList>()?;
-            for val_dict in validations_list.iter() {
-                if let Ok(val_dict) = val_dict.downcast::<PyDict>() {
-                    if let Ok(validation) = extract_data_validation(&val_dict) {
-                        config.data_validations.push(validation);
-                    }
-                }
+    let (progress, progress_error) = extract_progress(progress, progress_interval);
+    let workbook = WorkbookConfig {
+        modify_password,
+        read_only_recommended,
+        application,
+        company,
+        app_version,
+        zip_threads,
+        compression: extract_compression(compression)?,
+        progress,
+        summary_sheet: extract_summary_sheet(summary_sheet, summary_metadata)?,
+        indexed_colors: extract_indexed_colors(indexed_colors)?,
+        theme: extract_theme(theme)?,
+        sheet_order: sheet_order.unwrap_or_default(),
+        active_sheet: active_sheet_index,
+        first_sheet,
+        date_1904,
+    };
+
+    py.detach(|| {
+        let written = writer::write_multiple_sheets_arrow_with_configs(&sheets_refs, &filename, xml_threads.unwrap_or(num_threads), &workbook);
+        finish_write(written, &workbook, &progress_error)
+    })
+}
+
+/// One `arrow_sheets` entry of write_sheets_arrow(): its batches, name and
+/// config, with `workbook_styles` available to its styles by name
+fn extract_arrow_sheet(
+    sheet_dict: &Bound<PyDict>,
+    date_1904: bool,
+    workbook_styles: &HashMap<String, CellStyle>,
+) -> PyResult<(Vec<RecordBatch>, String, StyleConfig)> {
+    check_config_version(sheet_dict)?;
+    let arrow_data = sheet_dict.get_item("data")?.ok_or_else(|| 
+        PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'data' key"))?;
+    let name: String = sheet_dict.get_item("name")?.ok_or_else(|| 
+        PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'name' key"))?.extract()?;
+    
+    let column_gap = sheet_dict.get_item("column_gap")?.map(|v| v.extract()).unwrap_or(Ok(1))?;
+    let flatten_structs = sheet_dict.get_item("flatten_structs")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+    let writer::CombinedColumns {
+        batches, column_formats: group_formats, column_widths: group_widths, column_timezones: group_timezones,
+    } = read_arrow_input(&arrow_data, column_gap, flatten_structs)?;
+    
+    // Build config from optional parameters
+    let mut config = StyleConfig {
+        column_formats_by_index: group_formats,
+        column_widths_by_index: group_widths,
+        column_timezones_by_index: group_timezones,
+        date_1904,
+        ..StyleConfig::default()
+    };
+    
+    // Basic options
+    extract_sheet_view_options(sheet_dict, &mut config)?;
+    if let Some(auto_width) = sheet_dict.get_item("auto_width")?.and_then(|v| v.extract().ok()) {
+        config.auto_width = auto_width;
+    }
+    if let Some(styled_headers) = sheet_dict.get_item("styled_headers")?.and_then(|v| v.extract().ok()) {
+        config.styled_headers = styled_headers;
+    }
+    if let Some(write_header_row) = sheet_dict.get_item("write_header_row")?.and_then(|v| v.extract().ok()) {
+        config.write_header_row = write_header_row;
+    }
+
+    // Column widths ("auto", "150px" or a float) and formats, keyed by
+    // column name or index
+    let column_options = extract_column_options(
+        sheet_dict.get_item("column_widths")?.map(|v| v.extract()).transpose()?,
+        sheet_dict.get_item("column_formats")?.map(|v| v.extract()).transpose()?,
+        &batches,
+    )?;
+    config.column_widths = column_options.widths;
+    config.column_formats = column_options.formats;
+    config.column_timezones = column_options.timezones;
+    if let Some(format_totals_rows) = sheet_dict.get_item("format_totals_rows")?.and_then(|v| v.extract().ok()) {
+        config.format_totals_rows = format_totals_rows;
+    }
+    if let Some(format_whole_columns) = sheet_dict.get_item("format_whole_columns")?.and_then(|v| v.extract().ok()) {
+        config.format_whole_columns = format_whole_columns;
+    }
+    if let Some(columns) = sheet_dict.get_item("text_columns")? {
+        config.text_columns = columns.extract::<Vec<String>>()?.into_iter().collect();
+    }
+
+    // Merge cells
+    let mut a1 = A1Options::default();
+    if let Some(merge) = sheet_dict.get_item("merge_cells")? {
+        (config.merge_cells, a1.merge_cells) = extract_merge_cells(&merge)?;
+    }
+
+    // Shared formulas
+    if let Some(items) = sheet_dict.get_item("shared_formulas")? {
+        (config.shared_formulas, a1.shared_formulas) = extract_shared_formulas(items.extract()?)?;
+    }
+    if let Some(templates) = sheet_dict.get_item("column_formulas")? {
+        config.column_formulas = extract_column_formulas(templates.extract()?);
+    }
+
+    // Data validations
+    if let Some(validations) = sheet_dict.get_item("data_validations")? {
+        let validations_list = validations.downcast::<pyo3::types::PyList>()?;
+        for val_dict in validations_list.iter() {
+            if let Ok(val_dict) = val_dict.downcast::<PyDict>() {
+                push_ranged(extract_data_validation(&val_dict)?, &mut config.data_validations, &mut a1.data_validations);
             }
         }
+    }
 
-        // Hyperlinks
-        if let Some(hyperlinks) = sheet_dict.get_item("hyperlinks")? {
-            let hyperlinks_list = hyperlinks.downcast::<pyo3::types::PyList>()?;
-            for item in hyperlinks_list.iter() {
-                if let Ok((row, col, url, display)) = item.extract::<(usize, usize, String, Option<String>)>() {
-                    config.hyperlinks.push(Hyperlink { row, col, url, display });
-                }
+    // Hyperlinks
+    if let Some(hyperlinks) = sheet_dict.get_item("hyperlinks")? {
+        let hyperlinks_list = hyperlinks.downcast::<pyo3::types::PyList>()?;
+        for item in hyperlinks_list.iter() {
+            if let Ok((row, col, url, display)) = item.extract::<(usize, usize, String, Option<String>)>() {
+                config.hyperlinks.push(Hyperlink { row, col, url, display });
             }
         }
+    }
 
-        // Row heights
-        if let Some(heights) = sheet_dict.get_item("row_heights")? {
-            let heights_dict = heights.downcast::<PyDict>()?;
-            let mut row_heights = HashMap::new();
-            for (key, value) in heights_dict.iter() {
-                let row: usize = key.extract()?;
-                let height: f64 = value.extract()?;
-                row_heights.insert(row, height);
-            }
-            config.row_heights = Some(row_heights);
+    // Row heights
+    if let Some(heights) = sheet_dict.get_item("row_heights")? {
+        let heights_dict = heights.downcast::<PyDict>()?;
+        let mut row_heights = HashMap::new();
+        for (key, value) in heights_dict.iter() {
+            let row: usize = key.extract()?;
+            let height: f64 = value.extract()?;
+            row_heights.insert(row, height);
         }
+        config.row_heights = Some(row_heights);
+    }
 
-        // Cell styles
-        if let Some(styles) = sheet_dict.get_item("cell_styles")? {
-            let styles_list = styles.downcast::<pyo3::types::PyList>()?;
-            for style_dict in styles_list.iter() {
-                if let Ok(style_dict) = style_dict.downcast::<PyDict>() {
-                    if let Ok(cell_style) = extract_cell_style(&style_dict) {
-                        config.cell_styles.push(cell_style);
-                    }
-                }
+    if let Some(row_formats) = sheet_dict.get_item("row_formats")? {
+        config.row_formats = extract_row_formats(row_formats.extract()?);
+    }
+
+    // Named styles
+    let mut named_styles = workbook_styles.clone();
+    if let Some(styles) = sheet_dict.get_item("styles")? {
+        named_styles.extend(extract_named_styles(styles.extract()?)?);
+    }
+
+    // Cell styles
+    if let Some(styles) = sheet_dict.get_item("cell_styles")? {
+        let styles_list = styles.downcast::<pyo3::types::PyList>()?;
+        for style_dict in styles_list.iter() {
+            if let Ok(style_dict) = style_dict.downcast::<PyDict>() {
+                config.cell_styles.push(extract_cell_style(style_dict, &named_styles)?);
             }
         }
+    }
 
-        // Formulas
-        if let Some(formulas) = sheet_dict.get_item("formulas")? {
-            let formulas_list = formulas.downcast::<pyo3::types::PyList>()?;
-            for item in formulas_list.iter() {
-                if let Ok((row, col, formula, cached_value)) = item.extract::<(usize, usize, String, Option<String>)>() {
-                    config.formulas.push(Formula { row, col, formula, cached_value });
-                }
+    // Range styles
+    if let Some(items) = sheet_dict.get_item("range_styles")? {
+        (config.range_styles, a1.range_styles) = extract_range_styles(&items.extract::<Vec<Bound<PyDict>>>()?, &named_styles)?;
+    }
+
+    // Formulas
+    if let Some(formulas) = sheet_dict.get_item("formulas")? {
+        let formulas_list = formulas.downcast::<pyo3::types::PyList>()?;
+        for item in formulas_list.iter() {
+            if let Ok((row, col, formula, cached_value)) = item.extract::<(usize, usize, String, Option<String>)>() {
+                config.formulas.push(Formula { row, col, formula, cached_value });
             }
         }
+    }
 
-        // Conditional formats
-        if let Some(cond_formats) = sheet_dict.get_item("conditional_formats")? {
-            let cond_list = cond_formats.downcast::<pyo3::types::PyList>()?;
-            for cond_dict in cond_list.iter() {
-                if let Ok(cond_dict) = cond_dict.downcast::<PyDict>() {
-                    if let Ok(cond_format) = extract_conditional_format(&cond_dict) {
-                        config.conditional_formats.push(cond_format);
-                    }
+    // Rich text
+    if let Some(cells) = sheet_dict.get_item("rich_text")? {
+        config.rich_text = extract_rich_text(cells.extract()?)?;
+    }
+
+    // Conditional formats
+    if let Some(cond_formats) = sheet_dict.get_item("conditional_formats")? {
+        let cond_list = cond_formats.downcast::<pyo3::types::PyList>()?;
+        for cond_dict in cond_list.iter() {
+            if let Ok(cond_dict) = cond_dict.downcast::<PyDict>() {
+                for format in extract_conditional_formats(cond_dict, &named_styles)? {
+                    push_ranged(format, &mut config.conditional_formats, &mut a1.conditional_formats);
                 }
             }
         }
+    }
 
-        // Tables
-        if let Some(tables_vec) = sheet_dict.get_item("tables")? {
-            let tables_list = tables_vec.downcast::<pyo3::types::PyList>()?;
-            for table_dict in tables_list.iter() {
-                if let Ok(table_dict) = table_dict.downcast::<PyDict>() {
-                    if let Ok(table) = extract_table(&table_dict) {
-                        config.tables.push(table);
-                    }
-                }
+    // Tables
+    if let Some(tables_vec) = sheet_dict.get_item("tables")? {
+        let tables_list = tables_vec.downcast::<pyo3::types::PyList>()?;
+        for table_dict in tables_list.iter() {
+            if let Ok(table_dict) = table_dict.downcast::<PyDict>() {
+                push_ranged(extract_table(&table_dict)?, &mut config.tables, &mut a1.tables);
             }
         }
+    }
 
-        // Charts
-        if let Some(charts_vec) = sheet_dict.get_item("charts")? {
-            let charts_list = charts_vec.downcast::<pyo3::types::PyList>()?;
-            for chart_dict in charts_list.iter() {
-                if let Ok(chart_dict) = chart_dict.downcast::<PyDict>() {
-                    if let Ok(chart) = extract_chart(&chart_dict) {
-                        config.charts.push(chart);
-                    }
-                }
+    // Charts
+    if let Some(charts_vec) = sheet_dict.get_item("charts")? {
+        let charts_list = charts_vec.downcast::<pyo3::types::PyList>()?;
+        for chart_dict in charts_list.iter() {
+            if let Ok(chart_dict) = chart_dict.downcast::<PyDict>() {
+                push_ranged(extract_chart(&chart_dict)?, &mut config.charts, &mut a1.charts);
             }
         }
+    }
 
-        // Images
-        if let Some(images_vec) = sheet_dict.get_item("images")? {
-            let images_list = images_vec.downcast::<pyo3::types::PyList>()?;
-            for image_dict in images_list.iter() {
-                if let Ok(image_dict) = image_dict.downcast::<PyDict>() {
-                    if let Ok(image) = extract_image(&image_dict) {
-                        config.images.push(image);
-                    }
+    // Images
+    if let Some(images_vec) = sheet_dict.get_item("images")? {
+        let images_list = images_vec.downcast::<pyo3::types::PyList>()?;
+        for image_dict in images_list.iter() {
+            if let Ok(image_dict) = image_dict.downcast::<PyDict>() {
+                if let Ok(image) = extract_image(&image_dict) {
+                    config.images.push(image);
                 }
             }
         }
-        
-        // Appearance options
-        if let Some(val) = sheet_dict.get_item("gridlines_visible")?.and_then(|v| v.extract().ok()) {
-            config.gridlines_visible = val;
-        }
-        if let Some(val) = sheet_dict.get_item("zoom_scale")?.and_then(|v| v.extract().ok()) {
-            config.zoom_scale = Some(val);
-        }
-        if let Some(val) = sheet_dict.get_item("tab_color")?.and_then(|v| v.extract().ok()) {
-            config.tab_color = Some(val);
-        }
-        if let Some(val) = sheet_dict.get_item("default_row_height")?.and_then(|v| v.extract().ok()) {
-            config.default_row_height = Some(val);
-        }
-        if let Some(val) = sheet_dict.get_item("hidden_columns")?.and_then(|v| v.extract().ok()) {
-            config.hidden_columns = val;
-        }
-        if let Some(val) = sheet_dict.get_item("hidden_rows")?.and_then(|v| v.extract().ok()) {
-            config.hidden_rows = val;
-        }
-        if let Some(val) = sheet_dict.get_item("right_to_left")?.and_then(|v| v.extract().ok()) {
-            config.right_to_left = val;
-        }
-        if let Some(val) = sheet_dict.get_item("data_start_row")?.and_then(|v| v.extract().ok()) {
-            config.data_start_row = val;
-        }
-        
-        sheets_data.push((batches, name, config));
     }
     
-    // Create references for the writer
-    let sheets_refs: Vec<(&[RecordBatch], &str, StyleConfig)> = sheets_data.iter()
-        .map(|(b, n, c)| (b.as_slice(), n.as_str(), c.clone()))
+    // Appearance options
+    if let Some(keys) = sheet_dict.get_item("hidden_columns")?.and_then(|v| v.extract().ok()) {
+        (config.hidden_columns, a1.hidden_columns) = extract_hidden_columns(keys, &batches)?;
+    }
+    if let Some(val) = sheet_dict.get_item("hidden_rows")?.and_then(|v| v.extract().ok()) {
+        config.hidden_rows = val;
+    }
+    if let Some(val) = sheet_dict.get_item("data_start_row")?.and_then(|v| v.extract().ok()) {
+        config.data_start_row = val;
+    }
+
+    if let Some(groups) = sheet_dict.get_item("row_groups")? {
+        config.row_groups = extract_outline_groups(&groups, "row_groups")?;
+    }
+    if let Some(groups) = sheet_dict.get_item("col_groups")? {
+        config.col_groups = extract_outline_groups(&groups, "col_groups")?;
+    }
+    if let Some(sort_state) = sheet_dict.get_item("sort_state")?.map(|v| v.extract::<Bound<PyDict>>()).transpose()? {
+        config.sort_state = Some(extract_sort_state(&sort_state)?);
+    }
+    if let Some(val) = sheet_dict.get_item("split_rows")?.map(|v| v.extract()).transpose()? {
+        config.split_rows = Some(val);
+    }
+    let indexing: Option<String> = sheet_dict.get_item("indexing")?.and_then(|v| v.extract().ok());
+    let indexing = parse_indexing(indexing.as_deref())?;
+    apply_indexing(&mut config, indexing)?;
+    column_options.by_index.apply(&mut config, indexing)?;
+    column_options.by_letter.apply(&mut config, Indexing::Zero)?;
+    let coordinates: Option<String> = sheet_dict.get_item("coordinates")?.and_then(|v| v.extract().ok());
+    apply_coordinates(&mut config, parse_coordinates(coordinates.as_deref())?);
+    let pre_1900_dates: Option<String> = sheet_dict.get_item("pre_1900_dates")?.and_then(|v| v.extract().ok());
+    config.pre_1900_dates = parse_pre_1900_dates(pre_1900_dates.as_deref())?;
+    let invalid_text: Option<String> = sheet_dict.get_item("invalid_text")?.and_then(|v| v.extract().ok());
+    config.invalid_text = parse_invalid_text(invalid_text.as_deref())?;
+    if let Some(separator) = sheet_dict.get_item("list_separator")?.map(|v| v.extract()).transpose()? {
+        config.list_separator = separator;
+    }
+    let binary_encoding: Option<String> = sheet_dict.get_item("binary_encoding")?.map(|v| v.extract()).transpose()?;
+    config.binary_encoding = parse_binary_encoding(binary_encoding.as_deref())?;
+    config.binary_max_length = sheet_dict.get_item("binary_max_length")?.map(|v| v.extract()).transpose()?;
+    if let Some(filters) = sheet_dict.get_item("filters")?.map(|v| v.extract()).transpose()? {
+        config.filter_columns = extract_filters(filters, config.date_1904)?;
+    }
+    a1.append_to(&mut config);
+
+    Ok((batches, name, config))
+}
+
+#[pyfunction]
+#[pyo3(signature = (workbooks, num_threads = 1, compression = None))]
+/// Write many independent workbooks in parallel.
+///
+/// Args:
+///     workbooks (list[tuple[str, list[dict]]]): (filename, sheets) pairs, where sheets
+///         are the dicts write_sheets_arrow() takes
+///     num_threads (int): Threads shared by every file's generation and compression;
+///         0 uses one per core (default: 1)
+///     compression (str | int, optional): "fast" (default), "default", "best", a deflate level 0-9,
+///         or "stored" (same as 0) to skip deflate entirely
+///
+/// Raises:
+///     IOError: Listing every file that failed; the other files are still written
+fn write_workbooks(
+    py: Python,
+    workbooks: Vec<(String, Vec<Bound<PyDict>>)>,
+    num_threads: usize,
+    compression: Option<Bound<PyAny>>,
+) -> PyResult<()> {
+    let no_styles = HashMap::new();
+    let workbooks_data = workbooks.iter()
+        .map(|(filename, sheets)| {
+            let sheets = sheets.iter()
+                .map(|sheet_dict| extract_arrow_sheet(sheet_dict, false, &no_styles))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok((filename.as_str(), sheets))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let specs: Vec<writer::WorkbookSpec> = workbooks_data.iter()
+        .map(|(filename, sheets)| (*filename, sheets.iter().map(|(b, n, c)| (b.as_slice(), n.as_str(), c)).collect()))
         .collect();
 
+    let workbook = WorkbookConfig {
+        compression: extract_compression(compression)?,
+        ..WorkbookConfig::default()
+    };
+
     py.detach(|| {
-        writer::write_multiple_sheets_arrow_with_configs(&sheets_refs, &filename, num_threads)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+        let results = writer::write_workbooks(&specs, num_threads, &workbook)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+        let failures: Vec<String> = specs.iter().zip(results)
+            .filter_map(|((filename, _), result)| result.err().map(|e| format!("{}: {}", filename, e)))
+            .collect();
+        if failures.is_empty() {
+            return Ok(());
+        }
+        Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+            "Failed to write {} of {} workbooks: {}", failures.len(), specs.len(), failures.join("; ")
+        )))
     })
 }
 
+/// Workbook built up one sheet at a time and written when it is closed.
+/// As a context manager the file is written on leaving the `with` block.
+#[pyclass]
+struct Workbook {
+    filename: String,
+    num_threads: usize,
+    compression: Option<u32>,
+    sheets: Vec<(Vec<RecordBatch>, String, StyleConfig)>,
+    closed: bool,
+}
+
+#[pymethods]
+impl Workbook {
+    #[new]
+    #[pyo3(signature = (filename, num_threads = 1, compression = None))]
+    fn new(filename: String, num_threads: usize, compression: Option<Bound<PyAny>>) -> PyResult<Self> {
+        Ok(Self {
+            filename,
+            num_threads,
+            compression: extract_compression(compression)?,
+            sheets: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Add a sheet; takes the keys of a write_sheets_arrow() sheet dict as
+    /// keyword arguments. The data is read now, the file written on close().
+    #[pyo3(signature = (data, name, **options))]
+    fn add_sheet(&mut self, py: Python, data: Bound<PyAny>, name: String, options: Option<&Bound<PyDict>>) -> PyResult<()> {
+        if self.closed {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Cannot add sheet '{}': workbook '{}' is already closed", name, self.filename
+            )));
+        }
+        let sheet_dict = match options {
+            Some(options) => options.copy()?,
+            None => PyDict::new(py),
+        };
+        sheet_dict.set_item("data", data)?;
+        sheet_dict.set_item("name", name)?;
+        self.sheets.push(extract_arrow_sheet(&sheet_dict, false, &HashMap::new())?);
+        Ok(())
+    }
+
+    /// Write the file. Later calls do nothing once a write succeeded; after a
+    /// failed one the sheets are kept so close() can be retried.
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        if self.closed {
+            return Ok(());
+        }
+        if self.sheets.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Workbook '{}' has no sheets", self.filename
+            )));
+        }
+        let sheets_refs: Vec<(&[RecordBatch], &str, &StyleConfig)> = self.sheets.iter()
+            .map(|(b, n, c)| (b.as_slice(), n.as_str(), c))
+            .collect();
+        let workbook = WorkbookConfig { compression: self.compression, ..WorkbookConfig::default() };
+        py.detach(|| writer::write_multiple_sheets_arrow_with_configs(&sheets_refs, &self.filename, self.num_threads, &workbook))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+        self.closed = true;
+        self.sheets.clear();
+        Ok(())
+    }
+
+    #[getter]
+    fn closed(&self) -> bool {
+        self.closed
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Writes the file when the block completed; a block that raised leaves
+    /// no half-built file behind and its exception propagates
+    #[pyo3(signature = (exc_type = None, _exc_value = None, _traceback = None))]
+    fn __exit__(
+        &mut self,
+        py: Python,
+        exc_type: Option<Bound<PyAny>>,
+        _exc_value: Option<Bound<PyAny>>,
+        _traceback: Option<Bound<PyAny>>,
+    ) -> PyResult<bool> {
+        if exc_type.is_none() {
+            self.close(py)?;
+        }
+        Ok(false)
+    }
+}
+
 #[pyfunction]
 #[pyo3(signature = (
     arrow_data,
@@ -606,25 +1044,65 @@ fn write_sheets_arrow(
     write_header_row = true,
     column_widths = None,
     column_formats = None,
+    format_totals_rows = false,
+    format_whole_columns = false,
+    text_columns = None,
     merge_cells = None,
     data_validations = None,
     hyperlinks = None,
     row_heights = None,
+    row_formats = None,
+    styles = None,
     cell_styles = None,
+    range_styles = None,
     formulas = None,
+    rich_text = None,
+    shared_formulas = None,
+    column_formulas = None,
     conditional_formats = None,
     tables = None,
     charts = None,
     images = None,
     gridlines_visible = true,
     zoom_scale = None,
+    active_cell = None,
+    selection = None,
+    top_left_cell = None,
     tab_color = None,
     default_row_height = None,
     hidden_columns = None,
     hidden_rows = None,
+    row_groups = None,
+    col_groups = None,
     right_to_left = false,
     data_start_row = 0,
     header_content = None,
+    indexing = None,
+    pre_1900_dates = None,
+    invalid_text = None,
+    list_separator = None,
+    binary_encoding = None,
+    binary_max_length = None,
+    filters = None,
+    sort_state = None,
+    coordinates = None,
+    split_rows = None,
+    column_gap = 1,
+    flatten_structs = false,
+    zip_threads = 0,
+    compression = None,
+    progress = None,
+    progress_interval = 100000,
+    modify_password = None,
+    read_only_recommended = false,
+    application = None,
+    company = None,
+    app_version = None,
+    summary_sheet = false,
+    summary_metadata = None,
+    indexed_colors = None,
+    theme = None,
+    date_1904 = false,
 ))]
 /// Write Arrow data to Excel bytes (in-memory, no file I/O).
 /// Returns bytes that can be directly base64 encoded or sent over HTTP.
@@ -639,81 +1117,117 @@ fn write_sheet_arrow_to_bytes(
     auto_width: bool,
     styled_headers: bool,
     write_header_row: bool,
-    column_widths: Option<HashMap<String, Bound<PyAny>>>,
-    column_formats: Option<HashMap<String, String>>,
-    merge_cells: Option<Vec<(usize, usize, usize, usize)>>,
+    column_widths: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    column_formats: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    format_totals_rows: bool,
+    format_whole_columns: bool,
+    text_columns: Option<Vec<String>>,
+    merge_cells: Option<Bound<PyAny>>,
     data_validations: Option<Vec<Bound<PyDict>>>,
     hyperlinks: Option<Vec<(usize, usize, String, Option<String>)>>,
     row_heights: Option<HashMap<usize, f64>>,
+    row_formats: Option<HashMap<usize, String>>,
+    styles: Option<HashMap<String, Bound<PyDict>>>,
     cell_styles: Option<Vec<Bound<PyDict>>>,
+    range_styles: Option<Vec<Bound<PyDict>>>,
     formulas: Option<Vec<(usize, usize, String, Option<String>)>>,
+    rich_text: Option<Vec<(usize, usize, Bound<PyAny>)>>,
+    shared_formulas: Option<Vec<(Bound<PyAny>, String)>>,
+    column_formulas: Option<HashMap<String, String>>,
     conditional_formats: Option<Vec<Bound<PyDict>>>,
     tables: Option<Vec<Bound<PyDict>>>,
     charts: Option<Vec<Bound<PyDict>>>,
     images: Option<Vec<Bound<PyDict>>>,
     gridlines_visible: bool,
     zoom_scale: Option<u16>,
+    active_cell: Option<String>,
+    selection: Option<String>,
+    top_left_cell: Option<String>,
     tab_color: Option<String>,
     default_row_height: Option<f64>,
-    hidden_columns: Option<Vec<usize>>,
+    hidden_columns: Option<Vec<ColumnKey>>,
     hidden_rows: Option<Vec<usize>>,
+    row_groups: Option<Bound<PyAny>>,
+    col_groups: Option<Bound<PyAny>>,
     right_to_left: bool,
     data_start_row: usize,
-    header_content: Option<Vec<(usize, usize, String)>>
+    header_content: Option<Vec<(usize, usize, String)>>,
+    indexing: Option<String>,
+    pre_1900_dates: Option<String>,
+    invalid_text: Option<String>,
+    list_separator: Option<String>,
+    binary_encoding: Option<String>,
+    binary_max_length: Option<usize>,
+    filters: Option<HashMap<String, Bound<PyAny>>>,
+    sort_state: Option<Bound<PyDict>>,
+    coordinates: Option<String>,
+    split_rows: Option<usize>,
+    column_gap: usize,
+    flatten_structs: bool,
+    zip_threads: usize,
+    compression: Option<Bound<PyAny>>,
+    progress: Option<Py<PyAny>>,
+    progress_interval: usize,
+    modify_password: Option<String>,
+    read_only_recommended: bool,
+    application: Option<String>,
+    company: Option<String>,
+    app_version: Option<String>,
+    summary_sheet: bool,
+    summary_metadata: Option<Bound<PyDict>>,
+    indexed_colors: Option<Vec<String>>,
+    theme: Option<Bound<PyDict>>,
+    date_1904: bool,
 ) -> PyResult<Py<pyo3::types::PyBytes>> {
-    // Convert PyArrow data to RecordBatch
-    let any_batch = AnyRecordBatch::extract_bound(arrow_data)?;
-    let reader = any_batch.into_reader()?;
-    
-    let batches: Vec<RecordBatch> = reader
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
-            format!("Failed to read Arrow data: {}", e)
-        ))?;
+    // Convert PyArrow data to RecordBatch (a list places tables side by side)
+    let writer::CombinedColumns {
+        batches, column_formats: group_formats, column_widths: group_widths, column_timezones: group_timezones,
+    } = read_arrow_input(arrow_data, column_gap, flatten_structs)?;
     
     if batches.is_empty() {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Empty data"));
     }
 
     let sheet = sheet_name.as_deref().unwrap_or("Sheet1");
+    let mut a1 = A1Options::default();
 
-    // Parse column_widths - supports float, "auto", or "150px"
-    let parsed_column_widths = column_widths.map(|cw| {
-        cw.into_iter()
-            .filter_map(|(k, v)| {
-                let width = if let Ok(s) = v.extract::<String>() {
-                    if s.to_lowercase() == "auto" {
-                        ColumnWidth::Auto
-                    } else if s.ends_with("px") {
-                        let px: f64 = s.trim_end_matches("px").parse().unwrap_or(50.0);
-                        ColumnWidth::Pixels(px)
-                    } else {
-                        // Try parsing as number string
-                        ColumnWidth::Characters(s.parse().unwrap_or(8.43))
-                    }
-                } else if let Ok(f) = v.extract::<f64>() {
-                    ColumnWidth::Characters(f)
-                } else if let Ok(i) = v.extract::<i64>() {
-                    ColumnWidth::Characters(i as f64)
-                } else {
-                    return None;
-                };
-                Some((k, width))
-            })
-            .collect()
-    });
-
-    // Parse column_formats
-    let parsed_column_formats = column_formats.map(|cf| {
-        cf.into_iter()
-            .filter_map(|(k, v)| parse_number_format(&v).map(|fmt| (k, fmt)))
-            .collect()
-    });
+    // Widths take a float, "auto" or "150px"; keys are names, letters or column indexes
+    let column_options = extract_column_options(column_widths, column_formats, &batches)?;
+    let hidden_columns = match hidden_columns {
+        Some(keys) => {
+            let (indexed, absolute) = extract_hidden_columns(keys, &batches)?;
+            a1.hidden_columns = absolute;
+            indexed
+        }
+        None => Default::default(),
+    };
 
     // Parse merge_cells
-    let parsed_merge_cells = merge_cells.unwrap_or_default().into_iter().map(|(sr, sc, er, ec)| {
-        MergeRange { start_row: sr, start_col: sc, end_row: er, end_col: ec }
-    }).collect();
+    let parsed_merge_cells = match merge_cells {
+        Some(value) => {
+            let (indexed, a1_ranges) = extract_merge_cells(&value)?;
+            a1.merge_cells = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
+    let shared_formulas = match shared_formulas {
+        Some(items) => {
+            let (indexed, a1_ranges) = extract_shared_formulas(items)?;
+            a1.shared_formulas = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
+    let named_styles = extract_named_styles(styles.unwrap_or_default())?;
+    let range_styles = match range_styles {
+        Some(items) => {
+            let (indexed, a1_ranges) = extract_range_styles(&items, &named_styles)?;
+            a1.range_styles = a1_ranges;
+            indexed
+        }
+        None => Vec::new(),
+    };
 
     // Parse hyperlinks
     let parsed_hyperlinks = hyperlinks.unwrap_or_default().into_iter().map(|(row, col, url, display)| {
@@ -728,30 +1242,69 @@ fn write_sheet_arrow_to_bytes(
         auto_width,
         styled_headers,
         write_header_row,
-        column_widths: parsed_column_widths,
-        column_formats: parsed_column_formats,
+        column_widths: column_options.widths,
+        column_formats: column_options.formats,
+        format_totals_rows,
+        format_whole_columns,
+        text_columns: text_columns.unwrap_or_default().into_iter().collect(),
+        column_formats_by_index: group_formats,
+        column_widths_by_index: group_widths,
+        column_timezones: column_options.timezones,
+        column_timezones_by_index: group_timezones,
         merge_cells: parsed_merge_cells,
-        data_validations: data_validations.map(|v| v.iter().filter_map(|d| extract_data_validation(d).ok()).collect()).unwrap_or_default(),
+        data_validations: Vec::new(),
         hyperlinks: parsed_hyperlinks,
         row_heights,
-        cell_styles: cell_styles.map(|v| v.iter().filter_map(|d| extract_cell_style(d).ok()).collect()).unwrap_or_default(),
+        row_formats: extract_row_formats(row_formats.unwrap_or_default()),
+        cell_styles: cell_styles.unwrap_or_default().iter().map(|d| extract_cell_style(d, &named_styles)).collect::<PyResult<_>>()?,
+        range_styles,
         formulas: Vec::new(),
-        conditional_formats: conditional_formats.map(|v| v.iter().filter_map(|d| extract_conditional_format(d).ok()).collect()).unwrap_or_default(),
-        tables: tables.map(|v| v.iter().filter_map(|d| extract_table(d).ok()).collect()).unwrap_or_default(),
-        charts: charts.map(|v| v.iter().filter_map(|d| extract_chart(d).ok()).collect()).unwrap_or_default(),
+        shared_formulas,
+        column_formulas: extract_column_formulas(column_formulas.unwrap_or_default()),
+        rich_text: Vec::new(),
+        conditional_formats: Vec::new(),
+        tables: Vec::new(),
+        charts: Vec::new(),
         images: images.map(|v| v.iter().filter_map(|d| extract_image(d).ok()).collect()).unwrap_or_default(),
         gridlines_visible,
         zoom_scale,
+        selection: extract_selection(active_cell.as_deref(), selection.as_deref())?,
+        top_left_cell: top_left_cell.as_deref().map(parse_view_cell).transpose()?,
         tab_color,
         default_row_height,
-        hidden_columns: hidden_columns.map(|v| v.into_iter().collect()).unwrap_or_default(),
+        hidden_columns,
         hidden_rows: hidden_rows.map(|v| v.into_iter().collect()).unwrap_or_default(),
+        row_groups: row_groups.map(|v| extract_outline_groups(&v, "row_groups")).transpose()?.unwrap_or_default(),
+        col_groups: col_groups.map(|v| extract_outline_groups(&v, "col_groups")).transpose()?.unwrap_or_default(),
         right_to_left,
         data_start_row,
         header_content: header_content.unwrap_or_default(),
-        cond_format_dxf_ids: HashMap::new(),
+        pre_1900_dates: Pre1900Dates::Text,
+        date_1904,
+        invalid_text: InvalidText::Replace,
+        list_separator: list_separator.unwrap_or_else(|| ", ".to_string()),
+        binary_encoding: parse_binary_encoding(binary_encoding.as_deref())?,
+        binary_max_length,
+        filter_columns: HashMap::new(),
+        sort_state: None,
+        split_rows,
     };
 
+    for d in data_validations.iter().flatten() {
+        push_ranged(extract_data_validation(d)?, &mut config.data_validations, &mut a1.data_validations);
+    }
+    for d in conditional_formats.iter().flatten() {
+        for format in extract_conditional_formats(d, &named_styles)? {
+            push_ranged(format, &mut config.conditional_formats, &mut a1.conditional_formats);
+        }
+    }
+    for d in tables.iter().flatten() {
+        push_ranged(extract_table(d)?, &mut config.tables, &mut a1.tables);
+    }
+    for d in charts.iter().flatten() {
+        push_ranged(extract_chart(d)?, &mut config.charts, &mut a1.charts);
+    }
+
     // Parse formulas
     if let Some(formulas_vec) = formulas {
         for (row, col, formula, cached_value) in formulas_vec {
@@ -759,26 +1312,81 @@ fn write_sheet_arrow_to_bytes(
         }
     }
 
+    // Parse rich text cells
+    if let Some(cells) = rich_text {
+        config.rich_text = extract_rich_text(cells)?;
+    }
+
+    config.sort_state = sort_state.map(|s| extract_sort_state(&s)).transpose()?;
+    let indexing = parse_indexing(indexing.as_deref())?;
+    apply_indexing(&mut config, indexing)?;
+    column_options.by_index.apply(&mut config, indexing)?;
+    column_options.by_letter.apply(&mut config, Indexing::Zero)?;
+    apply_coordinates(&mut config, parse_coordinates(coordinates.as_deref())?);
+    a1.append_to(&mut config);
+    config.pre_1900_dates = parse_pre_1900_dates(pre_1900_dates.as_deref())?;
+    config.invalid_text = parse_invalid_text(invalid_text.as_deref())?;
+    if let Some(filters) = filters {
+        config.filter_columns = extract_filters(filters, config.date_1904)?;
+    }
+
+    let (progress, progress_error) = extract_progress(progress, progress_interval);
+    let workbook = WorkbookConfig {
+        modify_password,
+        read_only_recommended,
+        application,
+        company,
+        app_version,
+        zip_threads,
+        compression: extract_compression(compression)?,
+        progress,
+        summary_sheet: extract_summary_sheet(summary_sheet, summary_metadata)?,
+        indexed_colors: extract_indexed_colors(indexed_colors)?,
+        theme: extract_theme(theme)?,
+        date_1904,
+        ..WorkbookConfig::default()
+    };
+
     let bytes = py.detach(|| {
-        writer::write_single_sheet_arrow_to_bytes(&batches, sheet, &config)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+        let written = writer::write_single_sheet_arrow_to_bytes(&batches, sheet, &config, &workbook);
+        finish_write(written, &workbook, &progress_error)
     })?;
 
     Ok(pyo3::types::PyBytes::new(py, &bytes).into())
 }
 
 #[pyfunction]
-#[pyo3(signature = (sheets_data, num_threads = 1))]
+#[pyo3(signature = (sheets_data, num_threads = 1, xml_threads = None, zip_threads = 0, compression = None, progress = None, progress_interval = 100000, modify_password = None, read_only_recommended = false, application = None, company = None, app_version = None, summary_sheet = false, summary_metadata = None, indexed_colors = None, theme = None, sheet_order = None, active_sheet_index = 0, first_sheet = 0, date_1904 = false))]
 /// Write multiple sheets to Excel bytes (in-memory, no file I/O).
+#[allow(clippy::too_many_arguments)]
 fn write_sheets_arrow_to_bytes(
     py: Python,
     sheets_data: Vec<Bound<PyDict>>,
     num_threads: usize,
+    xml_threads: Option<usize>,
+    zip_threads: usize,
+    compression: Option<Bound<PyAny>>,
+    progress: Option<Py<PyAny>>,
+    progress_interval: usize,
+    modify_password: Option<String>,
+    read_only_recommended: bool,
+    application: Option<String>,
+    company: Option<String>,
+    app_version: Option<String>,
+    summary_sheet: bool,
+    summary_metadata: Option<Bound<PyDict>>,
+    indexed_colors: Option<Vec<String>>,
+    theme: Option<Bound<PyDict>>,
+    sheet_order: Option<Vec<String>>,
+    active_sheet_index: usize,
+    first_sheet: usize,
+    date_1904: bool,
 ) -> PyResult<Py<pyo3::types::PyBytes>> {
     let sheets: Result<Vec<_>, PyErr> = sheets_data
         .into_iter()
         .enumerate()
         .map(|(i, sheet_dict)| -> PyResult<(Vec<RecordBatch>, String, StyleConfig)> {
+            check_config_version(&sheet_dict)?;
             let name = sheet_dict
                 .get_item("name")?
                 .and_then(|n| n.extract::<String>().ok())
@@ -788,15 +1396,12 @@ fn write_sheets_arrow_to_bytes(
                 .get_item("data")?
                 .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'data' key"))?;
 
-            // Convert PyArrow data to RecordBatch
-            let any_batch = AnyRecordBatch::extract_bound(&arrow_item)?;
-            let reader = any_batch.into_reader()?;
-            
-            let batches: Vec<RecordBatch> = reader
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(
-                    format!("Failed to read Arrow data: {}", e)
-                ))?;
+            // Convert PyArrow data to RecordBatch (a list places tables side by side)
+            let column_gap = sheet_dict.get_item("column_gap")?.map(|v| v.extract()).unwrap_or(Ok(1))?;
+            let flatten_structs = sheet_dict.get_item("flatten_structs")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+            let writer::CombinedColumns {
+                batches, column_formats: group_formats, column_widths: group_widths, column_timezones: group_timezones,
+            } = read_arrow_input(&arrow_item, column_gap, flatten_structs)?;
 
             let auto_filter = sheet_dict.get_item("auto_filter")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
             let freeze_rows = sheet_dict.get_item("freeze_rows")?.map(|v| v.extract()).unwrap_or(Ok(0))?;
@@ -806,85 +1411,128 @@ fn write_sheets_arrow_to_bytes(
             let write_header_row = sheet_dict.get_item("write_header_row")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
             let data_start_row = sheet_dict.get_item("data_start_row")?.map(|v| v.extract()).unwrap_or(Ok(0))?;
 
-            let column_widths: Option<HashMap<String, Bound<PyAny>>> = sheet_dict.get_item("column_widths")?.and_then(|v| v.extract().ok());
-            let column_formats: Option<HashMap<String, String>> = sheet_dict.get_item("column_formats")?.and_then(|v| v.extract().ok());
-
-            // Parse column_widths - supports float, "auto", or "150px"
-            let parsed_column_widths = column_widths.map(|cw| {
-                cw.into_iter()
-                    .filter_map(|(k, v)| {
-                        let width = if let Ok(s) = v.extract::<String>() {
-                            if s.to_lowercase() == "auto" {
-                                ColumnWidth::Auto
-                            } else if s.ends_with("px") {
-                                let px: f64 = s.trim_end_matches("px").parse().unwrap_or(50.0);
-                                ColumnWidth::Pixels(px)
-                            } else {
-                                // Try parsing as number string
-                                ColumnWidth::Characters(s.parse().unwrap_or(8.43))
-                            }
-                        } else if let Ok(f) = v.extract::<f64>() {
-                            ColumnWidth::Characters(f)
-                        } else if let Ok(i) = v.extract::<i64>() {
-                            ColumnWidth::Characters(i as f64)
-                        } else {
-                            return None;
-                        };
-                        Some((k, width))
-                    })
-                    .collect()
-            });
-
-            // Parse column_formats
-            let parsed_column_formats = column_formats.map(|cf| {
-                cf.into_iter()
-                    .filter_map(|(k, v)| parse_number_format(&v).map(|fmt| (k, fmt)))
-                    .collect()
-            });
-
-            let config = StyleConfig {
+            let column_widths: Option<HashMap<ColumnKey, Bound<PyAny>>> = sheet_dict.get_item("column_widths")?.and_then(|v| v.extract().ok());
+            let column_formats: Option<HashMap<ColumnKey, Bound<PyAny>>> = sheet_dict.get_item("column_formats")?.and_then(|v| v.extract().ok());
+            let format_totals_rows = sheet_dict.get_item("format_totals_rows")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+            let format_whole_columns = sheet_dict.get_item("format_whole_columns")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+
+            // Widths take a float, "auto" or "150px"; keys are names, letters or column indexes
+            let column_options = extract_column_options(column_widths, column_formats, &batches)?;
+
+            let mut config = StyleConfig {
                 auto_filter,
                 freeze_rows,
                 freeze_cols,
                 auto_width,
                 styled_headers,
                 write_header_row,
-                column_widths: parsed_column_widths,
-                column_formats: parsed_column_formats,
+                column_widths: column_options.widths,
+                column_formats: column_options.formats,
+                format_totals_rows,
+                format_whole_columns,
+                text_columns: std::collections::HashSet::new(),
+                column_formats_by_index: group_formats,
+                column_widths_by_index: group_widths,
+                column_timezones: column_options.timezones,
+                column_timezones_by_index: group_timezones,
                 merge_cells: vec![],
                 data_validations: vec![],
                 hyperlinks: vec![],
                 row_heights: None,
+                row_formats: HashMap::new(),
                 cell_styles: vec![],
+                range_styles: vec![],
                 formulas: vec![],
+                shared_formulas: vec![],
+                column_formulas: HashMap::new(),
+                rich_text: vec![],
                 conditional_formats: vec![],
                 tables: vec![],
                 charts: vec![],
                 images: vec![],
                 gridlines_visible: true,
                 zoom_scale: None,
+                selection: None,
+                top_left_cell: None,
                 tab_color: None,
                 default_row_height: None,
                 hidden_columns: std::collections::HashSet::new(),
                 hidden_rows: std::collections::HashSet::new(),
+                row_groups: Vec::new(),
+                col_groups: Vec::new(),
                 right_to_left: false,
                 data_start_row,
                 header_content: vec![],
-                cond_format_dxf_ids: HashMap::new(),
+                pre_1900_dates: Pre1900Dates::Text,
+                date_1904,
+                invalid_text: InvalidText::Replace,
+                list_separator: ", ".to_string(),
+                binary_encoding: BinaryEncoding::Hex,
+                binary_max_length: None,
+                filter_columns: HashMap::new(),
+                sort_state: None,
+                split_rows: sheet_dict.get_item("split_rows")?.map(|v| v.extract()).transpose()?,
             };
 
+            if let Some(groups) = sheet_dict.get_item("row_groups")? {
+                config.row_groups = extract_outline_groups(&groups, "row_groups")?;
+            }
+            if let Some(groups) = sheet_dict.get_item("col_groups")? {
+                config.col_groups = extract_outline_groups(&groups, "col_groups")?;
+            }
+            if let Some(sort_state) = sheet_dict.get_item("sort_state")?.map(|v| v.extract::<Bound<PyDict>>()).transpose()? {
+                config.sort_state = Some(extract_sort_state(&sort_state)?);
+            }
+            let indexing: Option<String> = sheet_dict.get_item("indexing")?.and_then(|v| v.extract().ok());
+            let indexing = parse_indexing(indexing.as_deref())?;
+            apply_indexing(&mut config, indexing)?;
+            column_options.by_index.apply(&mut config, indexing)?;
+            column_options.by_letter.apply(&mut config, Indexing::Zero)?;
+            let pre_1900_dates: Option<String> = sheet_dict.get_item("pre_1900_dates")?.and_then(|v| v.extract().ok());
+            config.pre_1900_dates = parse_pre_1900_dates(pre_1900_dates.as_deref())?;
+            let invalid_text: Option<String> = sheet_dict.get_item("invalid_text")?.and_then(|v| v.extract().ok());
+            config.invalid_text = parse_invalid_text(invalid_text.as_deref())?;
+            if let Some(separator) = sheet_dict.get_item("list_separator")?.map(|v| v.extract()).transpose()? {
+                config.list_separator = separator;
+            }
+            let binary_encoding: Option<String> = sheet_dict.get_item("binary_encoding")?.map(|v| v.extract()).transpose()?;
+            config.binary_encoding = parse_binary_encoding(binary_encoding.as_deref())?;
+            config.binary_max_length = sheet_dict.get_item("binary_max_length")?.map(|v| v.extract()).transpose()?;
+            if let Some(filters) = sheet_dict.get_item("filters")?.map(|v| v.extract()).transpose()? {
+                config.filter_columns = extract_filters(filters, config.date_1904)?;
+            }
+
             Ok((batches, name, config))
         })
         .collect();
 
     let sheets = sheets?;
     let sheets_ref: Vec<_> = sheets.iter()
-        .map(|(batches, name, config)| (batches.clone(), name.as_str(), config.clone()))
+        .map(|(batches, name, config)| (batches.as_slice(), name.as_str(), config))
         .collect();
 
+    let (progress, progress_error) = extract_progress(progress, progress_interval);
+    let workbook = WorkbookConfig {
+        modify_password,
+        read_only_recommended,
+        application,
+        company,
+        app_version,
+        zip_threads,
+        compression: extract_compression(compression)?,
+        progress,
+        summary_sheet: extract_summary_sheet(summary_sheet, summary_metadata)?,
+        indexed_colors: extract_indexed_colors(indexed_colors)?,
+        theme: extract_theme(theme)?,
+        sheet_order: sheet_order.unwrap_or_default(),
+        active_sheet: active_sheet_index,
+        first_sheet,
+        date_1904,
+    };
+
     let bytes = py.detach(|| {
-        writer::write_multiple_sheets_arrow_to_bytes(&sheets_ref, num_threads)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+        let written = writer::write_multiple_sheets_arrow_to_bytes(&sheets_ref, xml_threads.unwrap_or(num_threads), &workbook);
+        finish_write(written, &workbook, &progress_error)
     })?;
 
     Ok(pyo3::types::PyBytes::new(py, &bytes).into())
@@ -893,63 +1541,1067 @@ fn write_sheets_arrow_to_bytes(
 
 
 // ============================================================================
-// Helper functions - Extraction from Python
+// Indexing policy
 // ============================================================================
 
-fn extract_sheet_data(
-    py: Python,
-    columns: &Bound<PyDict>,
-    sheet_name: Option<String>,
-) -> PyResult<SheetData> {
-    let mut cols = Vec::with_capacity(columns.len());
+/// How row/column numbers passed from Python are interpreted.
+///
+/// `Legacy` keeps the historical mixed convention (cell rows 1-based, columns
+/// 0-based, chart/image anchors and chart data rows 0-based). `Zero` and `One`
+/// apply a single base to every row and column parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Indexing {
+    Legacy,
+    Zero,
+    One,
+}
 
-    for (key, value) in columns.iter() {
-        let col_name = key.extract::<String>()?;
-        let col_data = extract_column(py, &value)?;
-        cols.push((col_name, col_data));
+fn parse_indexing(value: Option<&str>) -> PyResult<Indexing> {
+    match value {
+        None => Ok(Indexing::Legacy),
+        Some("zero") => Ok(Indexing::Zero),
+        Some("one") => Ok(Indexing::One),
+        Some(other) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid indexing '{}': expected 'zero' or 'one'", other)
+        )),
     }
-
-    Ok(SheetData {
-        name: sheet_name.unwrap_or_else(|| "Sheet1".to_string()),
-        columns: cols,
-    })
 }
 
-fn extract_column(py: Python, value: &Bound<PyAny>) -> PyResult<Vec<CellValue>> {
-    if let Ok(list) = value.downcast::<PyList>() {
-        let len = list.len();
-        let mut result = Vec::with_capacity(len);
+fn check_one_based(value: usize, field: &str) -> PyResult<()> {
+    if value == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("{} must be >= 1 with indexing='one' (rows and columns are 1-based), got 0", field)
+        ));
+    }
+    Ok(())
+}
 
-        for item in list.iter() {
-            result.push(CellValue::from_py(py, &item)?);
-        }
+/// Convert a user row to the 1-based Excel row number used internally
+fn to_excel_row(value: usize, field: &str, indexing: Indexing) -> PyResult<usize> {
+    match indexing {
+        Indexing::Legacy => Ok(value),
+        Indexing::Zero => Ok(value + 1),
+        Indexing::One => check_one_based(value, field).map(|_| value),
+    }
+}
 
-        Ok(result)
-    } else {
-        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
-            "Column must be a list",
-        ))
+/// Convert a user row or column to the 0-based index used internally
+fn to_zero_based(value: usize, field: &str, indexing: Indexing) -> PyResult<usize> {
+    match indexing {
+        Indexing::Legacy | Indexing::Zero => Ok(value),
+        Indexing::One => check_one_based(value, field).map(|_| value - 1),
     }
 }
 
-fn parse_number_format(s: &str) -> Option<NumberFormat> {
-    match s.to_lowercase().as_str() {
-        "general" => Some(NumberFormat::General),
-        "integer" | "0" => Some(NumberFormat::Integer),
-        "decimal2" | "0.00" => Some(NumberFormat::Decimal2),
-        "decimal4" | "0.0000" => Some(NumberFormat::Decimal4),
-        "percentage" | "0%" => Some(NumberFormat::Percentage),
-        "percentage_decimal" | "0.00%" => Some(NumberFormat::PercentageDecimal),
-        "percentage_integer" => Some(NumberFormat::PercentageInteger),
-        "currency" | "$#,##0.00" => Some(NumberFormat::Currency),
-        "currency_rounded" | "$#,##0" => Some(NumberFormat::CurrencyRounded),
-        "date" => Some(NumberFormat::Date),
-        "datetime" | "yyyy-mm-dd hh:mm:ss" => Some(NumberFormat::DateTime),
-        "time" | "hh:mm:ss" => Some(NumberFormat::Time),
+/// Rewrite every row/column coordinate in `config` from the user's indexing
+/// base to the internal convention. A no-op for `Indexing::Legacy`.
+fn apply_indexing(config: &mut StyleConfig, indexing: Indexing) -> PyResult<()> {
+    if indexing == Indexing::Legacy {
+        return Ok(());
+    }
+    let row = |v: usize, field: &str| to_excel_row(v, field, indexing);
+    let col = |v: usize, field: &str| to_zero_based(v, field, indexing);
+
+    for m in &mut config.merge_cells {
+        m.start_row = row(m.start_row, "merge_cells start_row")?;
+        m.start_col = col(m.start_col, "merge_cells start_col")?;
+        m.end_row = row(m.end_row, "merge_cells end_row")?;
+        m.end_col = col(m.end_col, "merge_cells end_col")?;
+    }
+    for v in &mut config.data_validations {
+        v.start_row = row(v.start_row, "data_validations start_row")?;
+        v.start_col = col(v.start_col, "data_validations start_col")?;
+        v.end_row = row(v.end_row, "data_validations end_row")?;
+        v.end_col = col(v.end_col, "data_validations end_col")?;
+    }
+    for h in &mut config.hyperlinks {
+        h.row = row(h.row, "hyperlinks row")?;
+        h.col = col(h.col, "hyperlinks col")?;
+    }
+    if let Some(heights) = config.row_heights.take() {
+        let mut converted = HashMap::with_capacity(heights.len());
+        for (r, height) in heights {
+            converted.insert(row(r, "row_heights row")?, height);
+        }
+        config.row_heights = Some(converted);
+    }
+    let row_formats = std::mem::take(&mut config.row_formats);
+    for (r, fmt) in row_formats {
+        config.row_formats.insert(row(r, "row_formats row")?, fmt);
+    }
+    for s in &mut config.cell_styles {
+        s.row = row(s.row, "cell_styles row")?;
+        s.col = col(s.col, "cell_styles col")?;
+    }
+    for r in &mut config.range_styles {
+        r.start_row = row(r.start_row, "range_styles start_row")?;
+        r.start_col = col(r.start_col, "range_styles start_col")?;
+        r.end_row = row(r.end_row, "range_styles end_row")?;
+        r.end_col = col(r.end_col, "range_styles end_col")?;
+    }
+    for f in &mut config.formulas {
+        f.row = row(f.row, "formulas row")?;
+        f.col = col(f.col, "formulas col")?;
+    }
+    for r in &mut config.rich_text {
+        r.row = row(r.row, "rich_text row")?;
+        r.col = col(r.col, "rich_text col")?;
+    }
+    for f in &mut config.shared_formulas {
+        f.start_row = row(f.start_row, "shared_formulas start_row")?;
+        f.start_col = col(f.start_col, "shared_formulas start_col")?;
+        f.end_row = row(f.end_row, "shared_formulas end_row")?;
+        f.end_col = col(f.end_col, "shared_formulas end_col")?;
+    }
+    for cf in &mut config.conditional_formats {
+        cf.start_row = row(cf.start_row, "conditional_formats start_row")?;
+        cf.start_col = col(cf.start_col, "conditional_formats start_col")?;
+        cf.end_row = row(cf.end_row, "conditional_formats end_row")?;
+        cf.end_col = col(cf.end_col, "conditional_formats end_col")?;
+    }
+    for table in &mut config.tables {
+        // end_row/end_col of 0 are the "auto" sentinel and stay untouched
+        let (sr, sc, er, ec) = table.range;
+        table.range = (
+            row(sr, "tables start_row")?,
+            col(sc, "tables start_col")?,
+            if er == 0 { 0 } else { row(er, "tables end_row")? },
+            if ec == 0 { 0 } else { col(ec, "tables end_col")? },
+        );
+    }
+    for chart in &mut config.charts {
+        let (sr, sc, er, ec) = chart.data_range;
+        chart.data_range = (
+            to_zero_based(sr, "charts start_row", indexing)?,
+            col(sc, "charts start_col")?,
+            to_zero_based(er, "charts end_row", indexing)?,
+            col(ec, "charts end_col")?,
+        );
+        if let Some(c) = chart.category_col {
+            chart.category_col = Some(col(c, "charts category_col")?);
+        }
+        for series in &mut chart.series {
+            series.col = col(series.col, "charts series col")?;
+        }
+        let p = &mut chart.position;
+        p.from_col = col(p.from_col, "charts from_col")?;
+        p.from_row = to_zero_based(p.from_row, "charts from_row", indexing)?;
+        p.to_col = col(p.to_col, "charts to_col")?;
+        p.to_row = to_zero_based(p.to_row, "charts to_row", indexing)?;
+    }
+    for image in &mut config.images {
+        let p = &mut image.position;
+        p.from_col = col(p.from_col, "images from_col")?;
+        p.from_row = to_zero_based(p.from_row, "images from_row", indexing)?;
+        p.to_col = col(p.to_col, "images to_col")?;
+        p.to_row = to_zero_based(p.to_row, "images to_row", indexing)?;
+    }
+    config.hidden_columns = config.hidden_columns.iter()
+        .map(|&c| col(c, "hidden_columns"))
+        .collect::<PyResult<_>>()?;
+    config.hidden_rows = config.hidden_rows.iter()
+        .map(|&r| row(r, "hidden_rows"))
+        .collect::<PyResult<_>>()?;
+    for group in &mut config.row_groups {
+        group.start = row(group.start, "row_groups start")?;
+        group.end = row(group.end, "row_groups end")?;
+    }
+    for group in &mut config.col_groups {
+        group.start = col(group.start, "col_groups start")?;
+        group.end = col(group.end, "col_groups end")?;
+    }
+    if let Some(sort) = &mut config.sort_state {
+        for key in &mut sort.keys {
+            key.col = col(key.col, "sort_state key column")?;
+        }
+    }
+    for (r, c, _) in &mut config.header_content {
+        *r = row(*r, "header_content row")?;
+        *c = col(*c, "header_content col")?;
+    }
+    if config.data_start_row > 0 || indexing == Indexing::Zero {
+        config.data_start_row = row(config.data_start_row, "data_start_row")?;
+    }
+
+    Ok(())
+}
+
+/// What row coordinates count from: the top of the sheet, or the first
+/// DataFrame data row (row 1 in the default 1-based convention)
+#[derive(Clone, Copy, PartialEq)]
+enum Coordinates {
+    Sheet,
+    Data,
+}
+
+fn parse_coordinates(value: Option<&str>) -> PyResult<Coordinates> {
+    match value {
+        None | Some("sheet") => Ok(Coordinates::Sheet),
+        Some("data") => Ok(Coordinates::Data),
+        Some(other) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid coordinates '{}': expected 'sheet' or 'data'", other)
+        )),
+    }
+}
+
+/// Shift data-relative rows down past `header_content` and the header row.
+/// Runs after `apply_indexing`, so rows are already in the internal
+/// convention. `header_content`, `data_start_row`, drawing anchors and A1
+/// ranges stay sheet coordinates.
+fn apply_coordinates(config: &mut StyleConfig, coordinates: Coordinates) {
+    if coordinates == Coordinates::Sheet {
+        return;
+    }
+    let offset = config.first_data_row() - 1;
+
+    for m in &mut config.merge_cells {
+        m.start_row += offset;
+        m.end_row += offset;
+    }
+    for v in &mut config.data_validations {
+        v.start_row += offset;
+        v.end_row += offset;
+    }
+    for h in &mut config.hyperlinks {
+        h.row += offset;
+    }
+    if let Some(heights) = config.row_heights.take() {
+        config.row_heights = Some(heights.into_iter().map(|(r, height)| (r + offset, height)).collect());
+    }
+    config.row_formats = std::mem::take(&mut config.row_formats).into_iter()
+        .map(|(r, fmt)| (r + offset, fmt))
+        .collect();
+    for s in &mut config.cell_styles {
+        s.row += offset;
+    }
+    for r in &mut config.range_styles {
+        r.start_row += offset;
+        r.end_row += offset;
+    }
+    for f in &mut config.formulas {
+        f.row += offset;
+    }
+    for r in &mut config.rich_text {
+        r.row += offset;
+    }
+    for f in &mut config.shared_formulas {
+        f.start_row += offset;
+        f.end_row += offset;
+    }
+    for cf in &mut config.conditional_formats {
+        cf.start_row += offset;
+        cf.end_row += offset;
+    }
+    for table in &mut config.tables {
+        // start_row is the table's first data row; its header sits in the row above
+        let (sr, sc, er, ec) = table.range;
+        table.range = (
+            (sr + offset).saturating_sub(1).max(1),
+            sc,
+            if er == 0 { 0 } else { er + offset },
+            ec,
+        );
+    }
+    for chart in &mut config.charts {
+        let (sr, sc, er, ec) = chart.data_range;
+        chart.data_range = (sr + offset, sc, er + offset, ec);
+    }
+    config.hidden_rows = config.hidden_rows.iter().map(|&r| r + offset).collect();
+    for group in &mut config.row_groups {
+        group.start += offset;
+        group.end += offset;
+    }
+}
+
+// ============================================================================
+// Helper functions - Extraction from Python
+// ============================================================================
+
+fn extract_sheet_data(
+    py: Python,
+    columns: &Bound<PyDict>,
+    sheet_name: Option<String>,
+) -> PyResult<SheetData> {
+    let mut cols = Vec::with_capacity(columns.len());
+
+    for (key, value) in columns.iter() {
+        let col_name = key.extract::<String>()?;
+        let col_data = extract_column(py, &value)?;
+        cols.push((col_name, col_data));
+    }
+
+    Ok(SheetData {
+        name: sheet_name.unwrap_or_else(|| "Sheet1".to_string()),
+        columns: cols,
+    })
+}
+
+/// Sheet view options shared by the Arrow and legacy dict multi-sheet APIs:
+/// auto_filter, freeze panes, gridlines, zoom, selection, scroll position, tab
+/// color, default row height and right-to-left
+fn extract_sheet_view_options(sheet_dict: &Bound<PyDict>, config: &mut StyleConfig) -> PyResult<()> {
+    if let Some(auto_filter) = sheet_dict.get_item("auto_filter")?.and_then(|v| v.extract().ok()) {
+        config.auto_filter = auto_filter;
+    }
+    if let Some(freeze_rows) = sheet_dict.get_item("freeze_rows")?.and_then(|v| v.extract().ok()) {
+        config.freeze_rows = freeze_rows;
+    }
+    if let Some(freeze_cols) = sheet_dict.get_item("freeze_cols")?.and_then(|v| v.extract().ok()) {
+        config.freeze_cols = freeze_cols;
+    }
+    if let Some(val) = sheet_dict.get_item("gridlines_visible")?.and_then(|v| v.extract().ok()) {
+        config.gridlines_visible = val;
+    }
+    if let Some(val) = sheet_dict.get_item("zoom_scale")?.and_then(|v| v.extract().ok()) {
+        config.zoom_scale = Some(val);
+    }
+    let active_cell: Option<String> = sheet_dict.get_item("active_cell")?.map(|v| v.extract()).transpose()?;
+    let selection: Option<String> = sheet_dict.get_item("selection")?.map(|v| v.extract()).transpose()?;
+    if active_cell.is_some() || selection.is_some() {
+        config.selection = extract_selection(active_cell.as_deref(), selection.as_deref())?;
+    }
+    if let Some(val) = sheet_dict.get_item("top_left_cell")?.map(|v| v.extract::<String>()).transpose()? {
+        config.top_left_cell = Some(parse_view_cell(&val)?);
+    }
+    if let Some(val) = sheet_dict.get_item("tab_color")?.and_then(|v| v.extract().ok()) {
+        config.tab_color = Some(val);
+    }
+    if let Some(val) = sheet_dict.get_item("default_row_height")?.and_then(|v| v.extract().ok()) {
+        config.default_row_height = Some(val);
+    }
+    if let Some(val) = sheet_dict.get_item("right_to_left")?.and_then(|v| v.extract().ok()) {
+        config.right_to_left = val;
+    }
+    Ok(())
+}
+
+/// A1 cell for the sheet view, as a 1-based row and 0-based column
+fn parse_view_cell(cell: &str) -> PyResult<(usize, usize)> {
+    xml::parse_cell_ref(cell).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+        format!("Invalid cell '{}': expected an A1 reference like 'B2'", cell)
+    ))
+}
+
+/// "active_cell" and "selection" (A1 ranges separated by spaces or commas).
+/// Either works alone: the selection defaults to the active cell and the
+/// active cell to the selection's first cell.
+fn extract_selection(active_cell: Option<&str>, selection: Option<&str>) -> PyResult<Option<SheetSelection>> {
+    let ranges = match selection {
+        Some(text) => {
+            let ranges = text.split([' ', ','])
+                .filter(|part| !part.is_empty())
+                .map(|part| xml::parse_range_ref(part).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid selection range '{}': expected A1 ranges like 'B2:D10'", part)
+                )))
+                .collect::<PyResult<Vec<_>>>()?;
+            if ranges.is_empty() {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("selection is empty"));
+            }
+            ranges
+        }
+        None => Vec::new(),
+    };
+    let active_cell = match active_cell {
+        Some(cell) => parse_view_cell(cell)?,
+        None => match ranges.first() {
+            Some(&(row, col, _, _)) => (row, col),
+            None => return Ok(None),
+        },
+    };
+    let (row, col) = active_cell;
+    if ranges.is_empty() {
+        return Ok(Some(SheetSelection { active_cell, ranges: vec![(row, col, row, col)] }));
+    }
+    if !ranges.iter().any(|&(r1, c1, r2, c2)| (r1..=r2).contains(&row) && (c1..=c2).contains(&col)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "active_cell must be inside the selection"
+        ));
+    }
+    Ok(Some(SheetSelection { active_cell, ranges }))
+}
+
+/// Read Arrow input: one table or batch, or a list of tables (or
+/// {"data", "column_formats", "column_widths"} dicts) to place side by side
+/// with `column_gap` empty columns between them. `flatten_structs` expands
+/// struct columns first, so formats and widths can name "parent.child".
+fn read_arrow_input(value: &Bound<PyAny>, column_gap: usize, flatten_structs: bool) -> PyResult<writer::CombinedColumns> {
+    let Ok(list) = value.downcast::<PyList>() else {
+        return Ok(writer::CombinedColumns {
+            batches: read_arrow_batches(value, flatten_structs)?,
+            column_formats: HashMap::new(),
+            column_widths: HashMap::new(),
+            column_timezones: HashMap::new(),
+        });
+    };
+    
+    let mut groups = Vec::with_capacity(list.len());
+    for item in list.iter() {
+        let group = match item.downcast::<PyDict>() {
+            Ok(dict) => {
+                let data = dict.get_item("data")?.ok_or_else(||
+                    PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing 'data' key in column group"))?;
+                let column_formats: HashMap<String, Bound<PyAny>> = dict.get_item("column_formats")?
+                    .map(|v| v.extract()).unwrap_or(Ok(HashMap::new()))?;
+                let (column_formats, column_timezones) = parse_column_formats(column_formats)?;
+                let column_widths: HashMap<String, Bound<PyAny>> = dict.get_item("column_widths")?
+                    .map(|v| v.extract()).unwrap_or(Ok(HashMap::new()))?;
+                writer::ColumnGroup {
+                    batches: read_arrow_batches(&data, flatten_structs)?,
+                    column_formats,
+                    column_widths: column_widths.into_iter()
+                        .filter_map(|(k, v)| parse_column_width(&v).map(|width| (k, width)))
+                        .collect(),
+                    column_timezones,
+                }
+            }
+            Err(_) => writer::ColumnGroup {
+                batches: read_arrow_batches(&item, flatten_structs)?,
+                column_formats: HashMap::new(),
+                column_widths: HashMap::new(),
+                column_timezones: HashMap::new(),
+            },
+        };
+        groups.push(group);
+    }
+    
+    writer::combine_column_groups(groups, column_gap)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+fn read_arrow_batches(value: &Bound<PyAny>, flatten_structs: bool) -> PyResult<Vec<RecordBatch>> {
+    read_arrow_lazily(value, flatten_structs)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
+}
+
+/// Batches of a table or stream, read from the source as the iterator is
+/// consumed (a RecordBatchReader is never collected)
+fn read_arrow_lazily(
+    value: &Bound<PyAny>,
+    flatten_structs: bool,
+) -> PyResult<impl Iterator<Item = Result<RecordBatch, types::WriteError>> + Send> {
+    let reader = AnyRecordBatch::extract_bound(&arrow_source(value)?)?.into_reader()?;
+    Ok(reader.map(move |batch| {
+        let batch = batch.map_err(|e| types::WriteError::Validation(format!("Failed to read Arrow data: {}", e)))?;
+        let batch = if flatten_structs { writer::flatten_structs(batch)? } else { batch };
+        writer::decode_string_views(writer::decode_dictionaries(batch)?)
+    }))
+}
+
+/// The object to read Arrow data from. Anything with the Arrow PyCapsule
+/// interface (PyArrow, Polars 1.x) is read directly; an older Polars
+/// DataFrame is converted with its `to_arrow()`.
+fn arrow_source<'py>(value: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    if value.hasattr("__arrow_c_stream__")? || value.hasattr("__arrow_c_array__")? {
+        return Ok(value.clone());
+    }
+    let module = value.get_type().module()?;
+    if module.to_str()?.starts_with("polars") && value.hasattr("to_arrow")? {
+        return value.call_method0("to_arrow");
+    }
+    Ok(value.clone())
+}
+
+/// Column width from a float/int (characters), "150px" or "auto"
+fn parse_column_width(value: &Bound<PyAny>) -> Option<ColumnWidth> {
+    if let Ok(s) = value.extract::<String>() {
+        if s.to_lowercase() == "auto" {
+            Some(ColumnWidth::Auto)
+        } else if s.ends_with("px") {
+            Some(ColumnWidth::Pixels(s.trim_end_matches("px").parse().unwrap_or(50.0)))
+        } else {
+            Some(ColumnWidth::Characters(s.parse().unwrap_or(8.43)))
+        }
+    } else if let Ok(f) = value.extract::<f64>() {
+        Some(ColumnWidth::Characters(f))
+    } else {
+        None
+    }
+}
+
+fn extract_column(py: Python, value: &Bound<PyAny>) -> PyResult<Vec<CellValue>> {
+    if let Ok(list) = value.downcast::<PyList>() {
+        let len = list.len();
+        let mut result = Vec::with_capacity(len);
+
+        for item in list.iter() {
+            result.push(CellValue::from_py(py, &item)?);
+        }
+
+        Ok(result)
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+            "Column must be a list",
+        ))
+    }
+}
+
+fn parse_pre_1900_dates(value: Option<&str>) -> PyResult<Pre1900Dates> {
+    match value {
+        None | Some("text") => Ok(Pre1900Dates::Text),
+        Some("clamp") => Ok(Pre1900Dates::Clamp),
+        Some("error") => Ok(Pre1900Dates::Error),
+        Some(other) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid pre_1900_dates '{}': expected 'text', 'clamp' or 'error'", other)
+        )),
+    }
+}
+
+/// Autofilter criteria by column name. A list is shorthand for
+/// {"type": "values", "values": [...]}; None in the list also shows blanks.
+fn extract_filters(filters: HashMap<String, Bound<PyAny>>, date_1904: bool) -> PyResult<HashMap<String, FilterCriteria>> {
+    let mut criteria = HashMap::with_capacity(filters.len());
+    for (column, spec) in filters {
+        let parsed = if let Ok(dict) = spec.downcast::<PyDict>() {
+            let filter_type: String = dict.get_item("type")?.map(|v| v.extract()).unwrap_or(Ok("values".to_string()))?;
+            match filter_type.as_str() {
+                "values" => {
+                    let values = dict.get_item("values")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                        format!("Filter on '{}' needs 'values'", column)
+                    ))?;
+                    let (values, none_given) = extract_filter_values(&values)?;
+                    let blanks = dict.get_item("blanks")?.map(|v| v.extract()).unwrap_or(Ok(none_given))?;
+                    FilterCriteria::Values { values, blanks }
+                }
+                "range" => {
+                    let min = dict.get_item("min")?.filter(|v| !v.is_none()).map(|v| extract_filter_bound(&v, date_1904)).transpose()?;
+                    let max = dict.get_item("max")?.filter(|v| !v.is_none()).map(|v| extract_filter_bound(&v, date_1904)).transpose()?;
+                    if min.is_none() && max.is_none() {
+                        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                            format!("Range filter on '{}' needs 'min' or 'max'", column)
+                        ));
+                    }
+                    FilterCriteria::Range { min, max }
+                }
+                "top10" => {
+                    let rank: u32 = dict.get_item("rank")?.map(|v| v.extract()).unwrap_or(Ok(10))?;
+                    let bottom: bool = dict.get_item("bottom")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+                    let percent: bool = dict.get_item("percent")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+                    let max_rank = if percent { 100 } else { 500 };
+                    if rank == 0 || rank > max_rank {
+                        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                            format!("Top 10 filter on '{}' needs a rank of 1-{}", column, max_rank)
+                        ));
+                    }
+                    FilterCriteria::Top10 { rank, bottom, percent }
+                }
+                "contains" => {
+                    let text: String = dict.get_item("text")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                        format!("Filter on '{}' needs 'text'", column)
+                    ))?.extract()?;
+                    FilterCriteria::Contains { text }
+                }
+                other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid filter type '{}': expected 'values', 'range', 'top10' or 'contains'", other)
+                )),
+            }
+        } else {
+            let (values, blanks) = extract_filter_values(&spec)?;
+            FilterCriteria::Values { values, blanks }
+        };
+        criteria.insert(column, parsed);
+    }
+    Ok(criteria)
+}
+
+/// Outline groups as (start, end, level, collapsed) tuples; level defaults
+/// to 1 and collapsed to False
+fn extract_outline_groups(value: &Bound<PyAny>, field: &str) -> PyResult<Vec<OutlineGroup>> {
+    let mut groups = Vec::new();
+    for item in value.try_iter()? {
+        let item = item?;
+        let (start, end, level, collapsed) = match item.len()? {
+            2 => {
+                let (start, end) = item.extract()?;
+                (start, end, 1, false)
+            }
+            3 => {
+                let (start, end, level) = item.extract()?;
+                (start, end, level, false)
+            }
+            _ => item.extract::<(usize, usize, u8, bool)>()?,
+        };
+        if start > end || !(1..=7).contains(&level) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid {} entry ({}, {}, {}): start must not be after end and level must be 1..7", field, start, end, level
+            )));
+        }
+        groups.push(OutlineGroup { start, end, level, collapsed });
+    }
+    Ok(groups)
+}
+
+/// {"ref": "A2:F100", "keys": [(2, "descending"), 0]}: the sorted range
+/// (without the header row) and its sort columns, first key first. A bare
+/// column sorts ascending.
+fn extract_sort_state(dict: &Bound<PyDict>) -> PyResult<SortState> {
+    let range: String = dict.get_item("ref")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+        "sort_state needs 'ref'"
+    ))?.extract()?;
+    let (start_row, start_col, end_row, end_col) = xml::parse_range_ref(&range).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid sort_state ref '{}': expected an A1 range like 'A2:F100'", range)
+        )
+    })?;
+    let keys_list = dict.get_item("keys")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+        "sort_state needs 'keys'"
+    ))?;
+    let mut keys = Vec::new();
+    for item in keys_list.try_iter()? {
+        let item = item?;
+        let (col, direction) = match item.extract::<usize>() {
+            Ok(col) => (col, "ascending".to_string()),
+            Err(_) => item.extract::<(usize, String)>()?,
+        };
+        let descending = match direction.as_str() {
+            "ascending" => false,
+            "descending" => true,
+            other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid sort direction '{}': expected 'ascending' or 'descending'", other)
+            )),
+        };
+        keys.push(SortKey { col, descending });
+    }
+    Ok(SortState { start_row, start_col, end_row, end_col, keys })
+}
+
+/// Filter values as text, and whether None (blank) was among them
+fn extract_filter_values(values: &Bound<PyAny>) -> PyResult<(Vec<String>, bool)> {
+    let mut texts = Vec::new();
+    let mut blanks = false;
+    for item in values.try_iter()? {
+        let item = item?;
+        if item.is_none() {
+            blanks = true;
+        } else if let Ok(b) = item.extract::<bool>() {
+            texts.push(if b { "TRUE" } else { "FALSE" }.to_string());
+        } else {
+            texts.push(item.str()?.to_string());
+        }
+    }
+    Ok((texts, blanks))
+}
+
+/// Range filter bound: a number, or a date/datetime as its Excel serial
+fn extract_filter_bound(value: &Bound<PyAny>, date_1904: bool) -> PyResult<f64> {
+    use pyo3::types::{PyDate, PyDateAccess, PyDateTime, PyTimeAccess};
+    if let Ok(n) = value.extract::<f64>() {
+        return Ok(n);
+    }
+    let date = value.downcast::<PyDate>()?;
+    let (h, m, s) = match value.downcast::<PyDateTime>() {
+        Ok(dt) => (dt.get_hour() as u32, dt.get_minute() as u32, dt.get_second() as u32),
+        Err(_) => (0, 0, 0),
+    };
+    chrono::NaiveDate::from_ymd_opt(date.get_year(), date.get_month() as u32, date.get_day() as u32)
+        .and_then(|d| d.and_hms_opt(h, m, s))
+        .and_then(|dt| xml::datetime_to_excel_serial(&dt, date_1904))
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Filter date {} is before {}", value, xml::first_serial_date(date_1904))
+        ))
+}
+
+/// Check a sheet config's `config_version` against CONFIG_VERSION. Configs
+/// without one are taken as current; a newer version may rely on option
+/// meanings this build doesn't have.
+fn check_config_version(sheet_dict: &Bound<PyDict>) -> PyResult<()> {
+    let Some(version) = sheet_dict.get_item("config_version")? else { return Ok(()) };
+    let version: u32 = version.extract().map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+        format!("config_version must be a positive integer, got {}", version)
+    ))?;
+    if version == 0 || version > CONFIG_VERSION {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "config_version {} is not supported; this jetxl understands versions 1 to {}",
+            version, CONFIG_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Summary sheet options; metadata alone also turns the sheet on.
+/// Dict order is kept and values are converted with str().
+fn extract_summary_sheet(enabled: bool, metadata: Option<Bound<PyDict>>) -> PyResult<Option<SummarySheet>> {
+    if !enabled && metadata.is_none() {
+        return Ok(None);
+    }
+    let metadata = metadata
+        .map(|dict| {
+            dict.iter()
+                .map(|(k, v)| Ok((k.str()?.to_string(), v.str()?.to_string())))
+                .collect::<PyResult<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    Ok(Some(SummarySheet { metadata }))
+}
+
+/// Legacy palette entries as uppercase ARGB; Excel's palette has 64 slots
+fn extract_indexed_colors(colors: Option<Vec<String>>) -> PyResult<Vec<String>> {
+    let colors = colors.unwrap_or_default();
+    if colors.len() > 64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("indexed_colors has {} entries; the palette holds at most 64", colors.len())
+        ));
+    }
+    colors.iter()
+        .map(|c| normalize_argb(c).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid indexed color '{}': expected 'RRGGBB' or 'AARRGGBB'", c)
+        )))
+        .collect()
+}
+
+/// First exception raised by a progress callback
+type ProgressError = Arc<Mutex<Option<PyErr>>>;
+
+/// Progress that calls `callback(rows_written, total_rows, phase)` with the
+/// GIL held. An exception stops the write and is kept for `finish_write`.
+fn extract_progress(callback: Option<Py<PyAny>>, interval: usize) -> (Option<Progress>, ProgressError) {
+    let error = ProgressError::default();
+    let progress = callback.map(|callback| {
+        let error = error.clone();
+        Progress::new(interval, Arc::new(move |rows, total, phase| {
+            Python::attach(|py| callback.call1(py, (rows, total, phase.as_str())).map(|_| ()))
+                .map_err(|e| {
+                    let message = format!("Progress callback failed: {}", e);
+                    if let Ok(mut error) = error.lock() {
+                        error.get_or_insert(e);
+                    }
+                    message
+                })
+        }))
+    });
+    (progress, error)
+}
+
+/// Report "done" after a successful write and convert writer errors, raising
+/// the progress callback's own exception when that is what stopped the write
+fn finish_write<T>(written: Result<T, WriteError>, workbook: &WorkbookConfig, progress_error: &ProgressError) -> PyResult<T> {
+    let written = written.and_then(|value| {
+        if let Some(progress) = &workbook.progress {
+            progress.report(ProgressPhase::Done).map_err(WriteError::Validation)?;
+        }
+        Ok(value)
+    });
+    written.map_err(|e| {
+        progress_error.lock().ok().and_then(|mut error| error.take())
+            .unwrap_or_else(|| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+    })
+}
+
+/// Deflate level from "stored" / "fast" / "default" / "best" or an int 0-9
+fn extract_compression(compression: Option<Bound<PyAny>>) -> PyResult<Option<u32>> {
+    let Some(compression) = compression else { return Ok(None) };
+    let level = if let Ok(name) = compression.extract::<String>() {
+        match name.as_str() {
+            "stored" => 0,
+            "fast" => 1,
+            "default" => 6,
+            "best" => 9,
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid compression '{}': use \"stored\", \"fast\", \"default\", \"best\" or a level 0-9", name)
+            )),
+        }
+    } else {
+        let level: u32 = compression.extract()?;
+        if level > 9 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("compression level must be 0-9, got {}", level)
+            ));
+        }
+        level
+    };
+    Ok(Some(level))
+}
+
+/// Theme overrides: {"accents": [...], "major_font": str, "minor_font": str}
+fn extract_theme(theme: Option<Bound<PyDict>>) -> PyResult<WorkbookTheme> {
+    let Some(theme) = theme else {
+        return Ok(WorkbookTheme::default());
+    };
+    for key in theme.keys() {
+        let key: String = key.extract()?;
+        if !matches!(key.as_str(), "accents" | "major_font" | "minor_font") {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown theme key '{}': use 'accents', 'major_font' or 'minor_font'", key)
+            ));
+        }
+    }
+    let accents: Vec<String> = theme.get_item("accents")?.map(|v| v.extract()).transpose()?.unwrap_or_default();
+    if accents.len() > 6 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("theme accents has {} entries; a theme has 6 accent colors", accents.len())
+        ));
+    }
+    Ok(WorkbookTheme {
+        // DrawingML colors are RRGGBB, so the alpha byte is dropped
+        accents: accents.iter()
+            .map(|c| normalize_argb(c).map(|argb| argb[2..].to_string()).ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid theme accent color '{}': expected 'RRGGBB' or 'AARRGGBB'", c)
+                )
+            }))
+            .collect::<PyResult<_>>()?,
+        major_font: theme.get_item("major_font")?.map(|v| v.extract()).transpose()?,
+        minor_font: theme.get_item("minor_font")?.map(|v| v.extract()).transpose()?,
+    })
+}
+
+fn parse_invalid_text(value: Option<&str>) -> PyResult<InvalidText> {
+    match value {
+        None | Some("replace") => Ok(InvalidText::Replace),
+        Some("error") => Ok(InvalidText::Error),
+        Some(other) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid invalid_text '{}': expected 'replace' or 'error'", other)
+        )),
+    }
+}
+
+fn parse_binary_encoding(value: Option<&str>) -> PyResult<BinaryEncoding> {
+    match value {
+        None | Some("hex") => Ok(BinaryEncoding::Hex),
+        Some("base64") => Ok(BinaryEncoding::Base64),
+        Some(other) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid binary_encoding '{}': expected 'hex' or 'base64'", other)
+        )),
+    }
+}
+
+/// Split `column_formats` values into number formats and display timezones.
+/// A value is a format name or code, or a dict like
+/// {"format": "datetime", "code": "yyyy-mm-dd hh:mm", "tz": "America/New_York"}
+/// where "code" takes precedence over "format".
+/// Key of column_formats / column_widths: a column name, a column letter
+/// ("A", "BC"), or a column index for tables whose names are duplicated or empty
+#[derive(FromPyObject, Clone, PartialEq, Eq, Hash)]
+enum ColumnKey {
+    Index(usize),
+    Name(String),
+}
+
+impl ColumnKey {
+    /// The 0-based column of a name key that spells a column letter and isn't
+    /// the name of a column in `batches`. Names win, so headers like "ID" keep
+    /// referring to their column.
+    fn letter_index(&self, batches: &[RecordBatch]) -> Option<usize> {
+        let ColumnKey::Name(name) = self else { return None };
+        let is_column = batches.first().is_some_and(|b| b.schema_ref().column_with_name(name).is_some());
+        if is_column { None } else { xml::parse_col_letters(name) }
+    }
+}
+
+impl std::fmt::Display for ColumnKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ColumnKey::Index(idx) => write!(f, "{}", idx),
+            ColumnKey::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// column_widths and column_formats, split by key type
+struct ColumnOptions {
+    widths: Option<HashMap<String, ColumnWidth>>,
+    formats: Option<HashMap<String, NumberFormat>>,
+    timezones: HashMap<String, Tz>,
+    by_index: IndexedColumnOptions,
+    /// Keyed by column letter, already 0-based, so applied with `Indexing::Zero`
+    by_letter: IndexedColumnOptions,
+}
+
+/// Index-keyed column options. They follow `indexing`, so they are added to
+/// the config once it is known.
+#[derive(Default)]
+struct IndexedColumnOptions {
+    widths: HashMap<usize, ColumnWidth>,
+    formats: HashMap<usize, NumberFormat>,
+    timezones: HashMap<usize, Tz>,
+}
+
+impl IndexedColumnOptions {
+    /// Add to the config's by-index maps, taking precedence over column groups
+    fn apply(self, config: &mut StyleConfig, indexing: Indexing) -> PyResult<()> {
+        for (col, width) in self.widths {
+            config.column_widths_by_index.insert(to_zero_based(col, "column_widths key", indexing)?, width);
+        }
+        for (col, fmt) in self.formats {
+            config.column_formats_by_index.insert(to_zero_based(col, "column_formats key", indexing)?, fmt);
+        }
+        for (col, tz) in self.timezones {
+            config.column_timezones_by_index.insert(to_zero_based(col, "column_formats key", indexing)?, tz);
+        }
+        Ok(())
+    }
+}
+
+/// Split column_widths / column_formats by key type. Column letters are
+/// resolved against the columns of `batches`.
+fn extract_column_options(
+    widths: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    formats: Option<HashMap<ColumnKey, Bound<PyAny>>>,
+    batches: &[RecordBatch],
+) -> PyResult<ColumnOptions> {
+    let mut options = ColumnOptions {
+        widths: None,
+        formats: None,
+        timezones: HashMap::new(),
+        by_index: IndexedColumnOptions::default(),
+        by_letter: IndexedColumnOptions::default(),
+    };
+    if let Some(widths) = widths {
+        let mut by_name = HashMap::new();
+        for (key, value) in widths {
+            let Some(width) = parse_column_width(&value) else { continue };
+            insert_column_option(key, width, batches, &mut by_name, &mut options.by_index.widths, &mut options.by_letter.widths);
+        }
+        options.widths = Some(by_name);
+    }
+    if let Some(formats) = formats {
+        let (formats, timezones) = parse_column_formats(formats)?;
+        let mut by_name = HashMap::new();
+        for (key, fmt) in formats {
+            insert_column_option(key, fmt, batches, &mut by_name, &mut options.by_index.formats, &mut options.by_letter.formats);
+        }
+        options.formats = Some(by_name);
+        for (key, tz) in timezones {
+            insert_column_option(key, tz, batches, &mut options.timezones, &mut options.by_index.timezones, &mut options.by_letter.timezones);
+        }
+    }
+    Ok(options)
+}
+
+/// File a column option under its name, index or letter
+fn insert_column_option<V>(
+    key: ColumnKey,
+    value: V,
+    batches: &[RecordBatch],
+    by_name: &mut HashMap<String, V>,
+    by_index: &mut HashMap<usize, V>,
+    by_letter: &mut HashMap<usize, V>,
+) {
+    if let Some(col) = key.letter_index(batches) {
+        by_letter.insert(col, value);
+        return;
+    }
+    match key {
+        ColumnKey::Index(col) => { by_index.insert(col, value); }
+        ColumnKey::Name(name) => { by_name.insert(name, value); }
+    }
+}
+
+/// Split hidden_columns into indexes, which follow `indexing`, and names or
+/// column letters, resolved against `batches` to absolute 0-based columns
+fn extract_hidden_columns(
+    keys: Vec<ColumnKey>,
+    batches: &[RecordBatch],
+) -> PyResult<(std::collections::HashSet<usize>, Vec<usize>)> {
+    let mut indexed = std::collections::HashSet::new();
+    let mut absolute = Vec::new();
+    for key in keys {
+        match key {
+            ColumnKey::Index(col) => { indexed.insert(col); }
+            ColumnKey::Name(name) => {
+                let col = batches.first()
+                    .and_then(|b| b.schema_ref().index_of(&name).ok())
+                    .or_else(|| xml::parse_col_letters(&name))
+                    .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Unknown hidden_columns entry '{}': expected a column name, letter or index", name)
+                    ))?;
+                absolute.push(col);
+            }
+        }
+    }
+    Ok((indexed, absolute))
+}
+
+fn parse_column_formats<K: Clone + Eq + std::hash::Hash + std::fmt::Display>(
+    formats: HashMap<K, Bound<PyAny>>,
+) -> PyResult<(HashMap<K, NumberFormat>, HashMap<K, Tz>)> {
+    let mut number_formats = HashMap::new();
+    let mut timezones = HashMap::new();
+    for (col_name, value) in formats {
+        let fmt_str: Option<String> = match value.downcast::<PyDict>() {
+            Ok(dict) => {
+                if let Some(tz) = dict.get_item("tz")? {
+                    let tz_name: String = tz.extract()?;
+                    let tz: Tz = tz_name.parse().map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        format!("Invalid timezone '{}' for column '{}'", tz_name, col_name)
+                    ))?;
+                    timezones.insert(col_name.clone(), tz);
+                }
+                if let Some(labels) = extract_bool_labels(&col_name.to_string(), dict)? {
+                    number_formats.insert(col_name, labels);
+                    continue;
+                }
+                match dict.get_item("code")? {
+                    Some(code) => Some(code.extract()?),
+                    None => dict.get_item("format")?.map(|v| v.extract()).transpose()?,
+                }
+            }
+            Err(_) => Some(value.extract()?),
+        };
+        if let Some(fmt) = fmt_str.as_deref().and_then(parse_number_format) {
+            number_formats.insert(col_name, fmt);
+        }
+    }
+    Ok((number_formats, timezones))
+}
+
+/// {"true": "On", "false": "Off"} column format for boolean columns
+fn extract_bool_labels(col_name: &str, dict: &Bound<PyDict>) -> PyResult<Option<NumberFormat>> {
+    let true_label: Option<String> = dict.get_item("true")?.map(|v| v.extract()).transpose()?;
+    let false_label: Option<String> = dict.get_item("false")?.map(|v| v.extract()).transpose()?;
+    let (Some(true_label), Some(false_label)) = (true_label, false_label) else {
+        if dict.contains("true")? || dict.contains("false")? {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Column format for '{}' needs both 'true' and 'false' labels", col_name)
+            ));
+        }
+        return Ok(None);
+    };
+    if true_label.contains('"') || false_label.contains('"') {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Boolean labels for '{}' cannot contain '\"'", col_name)
+        ));
+    }
+    Ok(Some(NumberFormat::bool_labels(&true_label, &false_label)))
+}
+
+fn parse_number_format(s: &str) -> Option<NumberFormat> {
+    if let Some(fmt) = parse_negative_format(&s.to_lowercase()) {
+        return Some(fmt);
+    }
+    match s.to_lowercase().as_str() {
+        "general" => Some(NumberFormat::General),
+        "integer" | "0" => Some(NumberFormat::Integer),
+        "decimal2" | "0.00" => Some(NumberFormat::Decimal2),
+        "decimal4" | "0.0000" => Some(NumberFormat::Decimal4),
+        "percentage" | "0%" => Some(NumberFormat::Percentage),
+        "percentage_decimal" | "0.00%" => Some(NumberFormat::PercentageDecimal),
+        "percentage_integer" => Some(NumberFormat::PercentageInteger),
+        "currency" | "$#,##0.00" => Some(NumberFormat::Currency),
+        "currency_rounded" | "$#,##0" => Some(NumberFormat::CurrencyRounded),
+        "date" => Some(NumberFormat::Date),
+        "date_iso" => Some(NumberFormat::Custom("yyyy-mm-dd".to_string())),
+        "date_us" => Some(NumberFormat::Custom("mm/dd/yyyy".to_string())),
+        "date_long" => Some(NumberFormat::Custom("dddd, mmmm d, yyyy".to_string())),
+        "month_year" => Some(NumberFormat::Custom("mmm yyyy".to_string())),
+        "week_day" => Some(NumberFormat::Custom("dddd".to_string())),
+        "datetime" | "yyyy-mm-dd hh:mm:ss" => Some(NumberFormat::DateTime),
+        "time" | "hh:mm:ss" => Some(NumberFormat::Time),
         "scientific" | "0.00e+00" => Some(NumberFormat::Scientific),
         "fraction" | "# ?/?" => Some(NumberFormat::Fraction),
         "fraction_two_digits" | "# ??/??" => Some(NumberFormat::FractionTwoDigits),
         "thousands" | "#,##0" => Some(NumberFormat::ThousandsSeparator),
+        "bool_yes_no" | "\"yes\";\"yes\";\"no\"" => Some(NumberFormat::BoolYesNo),
+        "text" | "@" => Some(NumberFormat::Text),
+        name if name.starts_with("currency_") => {
+            let iso = &name["currency_".len()..];
+            match iso.strip_suffix("_rounded") {
+                Some(iso) => NumberFormat::currency(iso, true),
+                None => NumberFormat::currency(iso, false),
+            }
+            .or_else(|| Some(NumberFormat::Custom(s.to_string())))
+        }
         _ => {
             if s.is_empty() {
                 None
@@ -959,18 +2611,43 @@ fn parse_number_format(s: &str) -> Option<NumberFormat> {
         }
     }
 }
-fn extract_data_validation(dict: &Bound<PyDict>) -> PyResult<DataValidation> {
-    let start_row: usize = dict.get_item("start_row")?.unwrap().extract()?;
-    let start_col: usize = dict.get_item("start_col")?.unwrap().extract()?;
-    let end_row: usize = dict.get_item("end_row")?.unwrap().extract()?;
-    let end_col: usize = dict.get_item("end_col")?.unwrap().extract()?;
+/// Named format plus a negatives suffix, e.g. "decimal2_red_negative" or
+/// "currency_eur_parens"
+fn parse_negative_format(name: &str) -> Option<NumberFormat> {
+    let (base, style) = [
+        ("_red_negative", NegativeStyle::RedParens),
+        ("_red_minus", NegativeStyle::RedMinus),
+        ("_parens", NegativeStyle::Parens),
+    ]
+    .into_iter()
+    .find_map(|(suffix, style)| name.strip_suffix(suffix).map(|base| (base, style)))?;
+    match parse_number_format(base)? {
+        // Not a named format, so the whole string is a custom code
+        NumberFormat::Custom(code) if code == base => None,
+        fmt => fmt.with_negatives(style),
+    }
+}
+
+/// Extract a data validation and whether its range was given in A1 notation
+fn extract_data_validation(dict: &Bound<PyDict>) -> PyResult<(DataValidation, bool)> {
+    let ((start_row, start_col, end_row, end_col), is_a1) =
+        extract_dict_range(dict, "range", "data_validations", "D2:D100")?.into_parts();
     let val_type: String = dict.get_item("type")?.unwrap().extract()?;
     
     let validation_type = match val_type.as_str() {
-        "list" => {
-            let items: Vec<String> = dict.get_item("items")?.unwrap().extract()?;
-            ValidationType::List(items)
-        }
+        "list" => match dict.get_item("source")? {
+            Some(source) => ValidationType::ListRef(source.extract()?),
+            None => {
+                let items: Vec<String> = dict.get_item("items")?.unwrap().extract()?;
+                // Excel rejects inline lists longer than 255 characters
+                if items.iter().map(|item| item.chars().count() + 1).sum::<usize>() > 256 {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "List validation items exceed Excel's 255-character limit; use a 'source' cell range instead"
+                    ));
+                }
+                ValidationType::List(items)
+            }
+        },
         "whole_number" => {
             let min: i64 = dict.get_item("min")?.unwrap().extract()?;
             let max: i64 = dict.get_item("max")?.unwrap().extract()?;
@@ -992,8 +2669,30 @@ fn extract_data_validation(dict: &Bound<PyDict>) -> PyResult<DataValidation> {
     let show_dropdown = dict.get_item("show_dropdown")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
     let error_title = dict.get_item("error_title")?.and_then(|v| v.extract().ok());
     let error_message = dict.get_item("error_message")?.and_then(|v| v.extract().ok());
+    let prompt_title: Option<String> = dict.get_item("prompt_title")?.and_then(|v| v.extract().ok());
+    let prompt_message: Option<String> = dict.get_item("prompt_message")?.and_then(|v| v.extract().ok());
+    let show_input_message = dict.get_item("show_input_message")?
+        .map(|v| v.extract())
+        .unwrap_or(Ok(prompt_title.is_some() || prompt_message.is_some()))?;
+    let error_style = match dict.get_item("error_style")?.map(|v| v.extract::<String>()).transpose()?.as_deref() {
+        None | Some("stop") => ValidationErrorStyle::Stop,
+        Some("warning") => ValidationErrorStyle::Warning,
+        Some("information") => ValidationErrorStyle::Information,
+        Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid error_style '{}': expected 'stop', 'warning' or 'information'", other)
+        )),
+    };
+    let show_error_message = dict.get_item("show_error_message")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
+    let allow_blank = dict.get_item("allow_blank")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
+    if prompt_title.as_ref().is_some_and(|t| t.chars().count() > 32)
+        || prompt_message.as_ref().is_some_and(|m| m.chars().count() > 255)
+    {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "prompt_title is limited to 32 characters and prompt_message to 255"
+        ));
+    }
     
-    Ok(DataValidation {
+    Ok((DataValidation {
         start_row,
         start_col,
         end_row,
@@ -1001,8 +2700,14 @@ fn extract_data_validation(dict: &Bound<PyDict>) -> PyResult<DataValidation> {
         validation_type,
         error_title,
         error_message,
+        prompt_title,
+        prompt_message,
+        show_input_message,
         show_dropdown,
-    })
+        error_style,
+        show_error_message,
+        allow_blank,
+    }, is_a1))
 }
 
 fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
@@ -1016,15 +2721,7 @@ fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
     
     // Extract font
     if let Some(font_dict) = dict.get_item("font")? {
-        let font_dict = font_dict.downcast::<PyDict>()?;
-        cell_style.font = Some(FontStyle {
-            bold: font_dict.get_item("bold")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
-            italic: font_dict.get_item("italic")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
-            underline: font_dict.get_item("underline")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
-            size: font_dict.get_item("size")?.and_then(|v| v.extract().ok()),
-            color: font_dict.get_item("color")?.and_then(|v| v.extract().ok()),
-            name: font_dict.get_item("name")?.and_then(|v| v.extract().ok()),
-        });
+        cell_style.font = Some(extract_font(font_dict.downcast::<PyDict>()?)?);
     }
     
     // Extract fill
@@ -1037,8 +2734,8 @@ fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
                 "gray125" => PatternType::Gray125,
                 _ => PatternType::None,
             },
-            fg_color: fill_dict.get_item("fg_color")?.and_then(|v| v.extract().ok()),
-            bg_color: fill_dict.get_item("bg_color")?.and_then(|v| v.extract().ok()),
+            fg_color: fill_dict.get_item("fg_color")?.map(|v| extract_color(&v)).transpose()?,
+            bg_color: fill_dict.get_item("bg_color")?.map(|v| extract_color(&v)).transpose()?,
         });
     }
     
@@ -1055,9 +2752,15 @@ fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
                     "double" => BorderLineStyle::Double,
                     "dotted" => BorderLineStyle::Dotted,
                     "dashed" => BorderLineStyle::Dashed,
+                    "hair" => BorderLineStyle::Hair,
+                    "medium_dashed" | "mediumDashed" => BorderLineStyle::MediumDashed,
+                    "dash_dot" | "dashDot" => BorderLineStyle::DashDot,
+                    "medium_dash_dot" | "mediumDashDot" => BorderLineStyle::MediumDashDot,
+                    "dash_dot_dot" | "dashDotDot" => BorderLineStyle::DashDotDot,
+                    "slant_dash_dot" | "slantDashDot" => BorderLineStyle::SlantDashDot,
                     _ => BorderLineStyle::Thin,
                 },
-                color: side_dict.get_item("color")?.and_then(|v| v.extract().ok()),
+                color: side_dict.get_item("color")?.map(|v| extract_color(&v)).transpose()?,
             })
         };
         
@@ -1113,32 +2816,54 @@ fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
     if let Some(align_dict) = dict.get_item("alignment")? {
         let align_dict = align_dict.downcast::<PyDict>()?;
         
-        let horizontal = align_dict.get_item("horizontal")?.and_then(|v| {
-            let s: String = v.extract().ok()?;
-            match s.as_str() {
-                "center" => Some(HorizontalAlignment::Center),
-                "right" => Some(HorizontalAlignment::Right),
-                "justify" => Some(HorizontalAlignment::Justify),
-                "left" => Some(HorizontalAlignment::Left),
-                _ => None,
-            }
-        });
+        let horizontal = match align_dict.get_item("horizontal")?.map(|v| v.extract::<String>()).transpose()?.as_deref() {
+            None | Some("general") => None,
+            Some("center") => Some(HorizontalAlignment::Center),
+            Some("right") => Some(HorizontalAlignment::Right),
+            Some("justify") => Some(HorizontalAlignment::Justify),
+            Some("left") => Some(HorizontalAlignment::Left),
+            Some("fill") => Some(HorizontalAlignment::Fill),
+            Some("center_continuous" | "centerContinuous") => Some(HorizontalAlignment::CenterContinuous),
+            Some("distributed") => Some(HorizontalAlignment::Distributed),
+            Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid horizontal alignment '{}': use 'left', 'center', 'right', 'justify', 'fill', 'center_continuous' or 'distributed'",
+                other
+            ))),
+        };
         
-        let vertical = align_dict.get_item("vertical")?.and_then(|v| {
-            let s: String = v.extract().ok()?;
-            match s.as_str() {
-                "center" => Some(VerticalAlignment::Center),
-                "bottom" => Some(VerticalAlignment::Bottom),
-                "top" => Some(VerticalAlignment::Top),
-                _ => None,
-            }
-        });
+        let vertical = match align_dict.get_item("vertical")?.map(|v| v.extract::<String>()).transpose()?.as_deref() {
+            None => None,
+            Some("center") => Some(VerticalAlignment::Center),
+            Some("bottom") => Some(VerticalAlignment::Bottom),
+            Some("top") => Some(VerticalAlignment::Top),
+            Some("justify") => Some(VerticalAlignment::Justify),
+            Some("distributed") => Some(VerticalAlignment::Distributed),
+            Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid vertical alignment '{}': use 'top', 'center', 'bottom', 'justify' or 'distributed'",
+                other
+            ))),
+        };
         
         cell_style.alignment = Some(AlignmentStyle {
             horizontal,
             vertical,
             wrap_text: align_dict.get_item("wrap_text")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
             text_rotation: align_dict.get_item("text_rotation")?.and_then(|v| v.extract().ok()),
+            indent: match align_dict.get_item("indent")?.map(|v| v.extract::<u32>()).transpose()? {
+                Some(indent) if indent > 250 => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid indent {}: Excel allows 0-250", indent)
+                )),
+                indent => indent,
+            },
+            shrink_to_fit: align_dict.get_item("shrink_to_fit")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+            reading_order: match align_dict.get_item("reading_order")?.map(|v| v.extract::<String>()).transpose()?.as_deref() {
+                None | Some("context") => ReadingOrder::Context,
+                Some("ltr") => ReadingOrder::LeftToRight,
+                Some("rtl") => ReadingOrder::RightToLeft,
+                Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid reading_order '{}': use 'context', 'ltr' or 'rtl'", other)
+                )),
+            },
         });
     }
     
@@ -1151,21 +2876,368 @@ fn extract_cell_style_inner(dict: &Bound<PyDict>) -> PyResult<CellStyle> {
     Ok(cell_style)
 }
 
-fn extract_cell_style(dict: &Bound<PyDict>) -> PyResult<CellStyleMap> {
+fn extract_font(font_dict: &Bound<PyDict>) -> PyResult<FontStyle> {
+    Ok(FontStyle {
+        bold: font_dict.get_item("bold")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        italic: font_dict.get_item("italic")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        underline: font_dict.get_item("underline")?.map(|v| parse_underline(&v)).transpose()?.flatten(),
+        strike: font_dict.get_item("strike")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        vert_align: match font_dict.get_item("vert_align")?.map(|v| v.extract::<String>()).transpose()?.as_deref() {
+            None | Some("baseline") => None,
+            Some("superscript") => Some(VertAlign::Superscript),
+            Some("subscript") => Some(VertAlign::Subscript),
+            Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid vert_align '{}': use 'superscript', 'subscript' or 'baseline'", other)
+            )),
+        },
+        size: font_dict.get_item("size")?.and_then(|v| v.extract().ok()),
+        color: font_dict.get_item("color")?.map(|v| extract_color(&v)).transpose()?,
+        name: font_dict.get_item("name")?.and_then(|v| v.extract().ok()),
+    })
+}
+
+/// Font, fill or border color: an "AARRGGBB" string, a theme reference
+/// dict like {"theme": 4, "tint": -0.25} or a palette index {"indexed": 10}
+fn extract_color(value: &Bound<PyAny>) -> PyResult<Color> {
+    let Ok(dict) = value.downcast::<PyDict>() else {
+        return Ok(Color::Rgb(value.extract()?));
+    };
+    if let Some(index) = dict.get_item("indexed")? {
+        let index: u32 = index.extract()?;
+        if index > 63 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid indexed color {}: must be 0-63", index)
+            ));
+        }
+        return Ok(Color::Indexed(index));
+    }
+    let theme: u32 = dict.get_item("theme")?
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Color dict requires a 'theme' (0-11) or 'indexed' (0-63) key"
+        ))?
+        .extract()?;
+    if theme > 11 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid theme color index {}: must be 0-11", theme)
+        ));
+    }
+    let tint: Option<f64> = dict.get_item("tint")?.map(|v| v.extract()).transpose()?;
+    if let Some(tint) = tint {
+        if !(-1.0..=1.0).contains(&tint) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid theme color tint {}: must be between -1.0 and 1.0", tint)
+            ));
+        }
+    }
+    Ok(Color::Theme { theme, tint })
+}
+
+/// Font underline: True/False, or "single", "double", "single_accounting"
+/// ("accounting") or "double_accounting"
+fn parse_underline(value: &Bound<PyAny>) -> PyResult<Option<Underline>> {
+    if let Ok(on) = value.extract::<bool>() {
+        return Ok(on.then_some(Underline::Single));
+    }
+    let kind: String = value.extract()?;
+    match kind.as_str() {
+        "none" => Ok(None),
+        "single" => Ok(Some(Underline::Single)),
+        "double" => Ok(Some(Underline::Double)),
+        "single_accounting" | "singleAccounting" | "accounting" => Ok(Some(Underline::SingleAccounting)),
+        "double_accounting" | "doubleAccounting" => Ok(Some(Underline::DoubleAccounting)),
+        other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid underline '{}': use True, 'single', 'double', 'single_accounting' or 'double_accounting'", other)
+        )),
+    }
+}
+
+/// Runs of a rich text cell: a list of (text, font) tuples, where font is a
+/// font dict like cell_styles' "font" or None. Plain strings keep the cell's font.
+fn extract_text_runs(value: &Bound<PyAny>) -> PyResult<Vec<TextRun>> {
+    let items = value.downcast::<PyList>().map_err(|_| PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+        "Rich text must be a list of (text, font) runs"
+    ))?;
+    items.iter().map(|item| {
+        if let Ok(text) = item.extract::<String>() {
+            return Ok(TextRun { text, font: None });
+        }
+        let (text, font): (String, Option<Bound<PyDict>>) = item.extract().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyTypeError, _>(
+                "Rich text runs must be (text, font) tuples with a font dict or None"
+            )
+        })?;
+        Ok(TextRun { text, font: font.as_ref().map(extract_font).transpose()? })
+    }).collect()
+}
+
+/// Rich text cells from (row, col, runs) tuples
+fn extract_rich_text(cells: Vec<(usize, usize, Bound<PyAny>)>) -> PyResult<Vec<RichTextCell>> {
+    cells.into_iter()
+        .map(|(row, col, runs)| Ok(RichTextCell { row, col, runs: extract_text_runs(&runs)? }))
+        .collect()
+}
+
+/// A cell_styles entry: row and col plus either the style keys themselves
+/// or `"style"` naming one of the registered `styles`
+fn extract_cell_style(dict: &Bound<PyDict>, named: &HashMap<String, CellStyle>) -> PyResult<CellStyleMap> {
     let row: usize = dict.get_item("row")?.unwrap().extract()?;
     let col: usize = dict.get_item("col")?.unwrap().extract()?;
-    let style = extract_cell_style_inner(dict)?;
+    let style = match dict.get_item("style")? {
+        Some(value) => extract_style_ref(&value, named)?,
+        None => extract_cell_style_inner(dict)?,
+    };
+    
+    Ok(CellStyleMap { row, col, style })
+}
+
+/// A range given as an A1 string ("B2:D10") or a
+/// (start_row, start_col, end_row, end_col) tuple
+enum RangeRef {
+    /// In the caller's `indexing` and `coordinates` conventions
+    Indexed((usize, usize, usize, usize)),
+    /// Absolute, with 1-based rows and 0-based columns
+    A1((usize, usize, usize, usize)),
+}
+
+impl RangeRef {
+    /// The range and whether it was given in A1 notation
+    fn into_parts(self) -> ((usize, usize, usize, usize), bool) {
+        match self {
+            RangeRef::Indexed(range) => (range, false),
+            RangeRef::A1(range) => (range, true),
+        }
+    }
+}
+
+/// Parse a range accepted as an A1 string or a tuple. `field` and `example`
+/// name the option in error messages.
+fn extract_range(value: &Bound<PyAny>, field: &str, example: &str) -> PyResult<RangeRef> {
+    if let Ok(text) = value.extract::<String>() {
+        return xml::parse_range_ref(&text).map(RangeRef::A1).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Invalid {} range '{}': expected an A1 range like '{}'", field, text, example)
+            )
+        });
+    }
+    value.extract::<(usize, usize, usize, usize)>().map(RangeRef::Indexed).map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "{} ranges must be A1 ranges like '{}' or (start_row, start_col, end_row, end_col) tuples",
+            field, example
+        ))
+    })
+}
+
+/// The range of a validation, conditional format, table or chart dict: a
+/// `key` entry holding an A1 string or a tuple, or separate
+/// start_row/start_col/end_row/end_col entries
+fn extract_dict_range(dict: &Bound<PyDict>, key: &str, field: &str, example: &str) -> PyResult<RangeRef> {
+    if let Some(range) = dict.get_item(key)? {
+        return extract_range(&range, field, example);
+    }
+    let get = |name: &str| -> PyResult<usize> {
+        dict.get_item(name)?
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                format!("{} entries need a '{}' or '{}' key", field, key, name)
+            ))?
+            .extract()
+    };
+    Ok(RangeRef::Indexed((get("start_row")?, get("start_col")?, get("end_row")?, get("end_col")?)))
+}
+
+/// Options given with A1 ranges or column letters. These are absolute sheet
+/// positions, so they skip `apply_indexing` and `apply_coordinates` and are
+/// added after.
+#[derive(Default)]
+struct A1Options {
+    merge_cells: Vec<MergeRange>,
+    shared_formulas: Vec<SharedFormula>,
+    range_styles: Vec<RangeStyle>,
+    data_validations: Vec<DataValidation>,
+    conditional_formats: Vec<ConditionalFormat>,
+    tables: Vec<ExcelTable>,
+    charts: Vec<ExcelChart>,
+    hidden_columns: Vec<usize>,
+}
+
+impl A1Options {
+    fn append_to(self, config: &mut StyleConfig) {
+        config.merge_cells.extend(self.merge_cells);
+        config.shared_formulas.extend(self.shared_formulas);
+        config.range_styles.extend(self.range_styles);
+        config.data_validations.extend(self.data_validations);
+        config.conditional_formats.extend(self.conditional_formats);
+        config.tables.extend(self.tables);
+        config.charts.extend(self.charts);
+        config.hidden_columns.extend(self.hidden_columns);
+    }
+}
+
+/// Push an extracted option to `indexed`, or to `a1` when its range was A1
+fn push_ranged<T>((item, is_a1): (T, bool), indexed: &mut Vec<T>, a1: &mut Vec<T>) {
+    if is_a1 {
+        a1.push(item);
+    } else {
+        indexed.push(item);
+    }
+}
+
+/// Extract range_styles: dicts with a "range" (an A1 range like "A1:F1" or a
+/// (start_row, start_col, end_row, end_col) tuple) and a "style" (a style
+/// dict or the name of a registered style).
+///
+/// Returns (tuple ranges, A1 ranges) like `extract_merge_cells`.
+fn extract_range_styles(
+    items: &[Bound<PyDict>],
+    named: &HashMap<String, CellStyle>,
+) -> PyResult<(Vec<RangeStyle>, Vec<RangeStyle>)> {
+    let mut indexed = Vec::new();
+    let mut a1 = Vec::new();
+    for dict in items {
+        let range = dict.get_item("range")?.ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyKeyError, _>("range_styles entries need a 'range' key")
+        })?;
+        let style = dict.get_item("style")?.ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyKeyError, _>("range_styles entries need a 'style' key")
+        })?;
+        let style = extract_style_ref(&style, named)?;
+        match extract_range(&range, "range_styles", "A1:F1")? {
+            RangeRef::A1((start_row, start_col, end_row, end_col)) => {
+                a1.push(RangeStyle { start_row, start_col, end_row, end_col, style });
+            }
+            RangeRef::Indexed((start_row, start_col, end_row, end_col)) => {
+                indexed.push(RangeStyle { start_row, start_col, end_row, end_col, style });
+            }
+        }
+    }
+    Ok((indexed, a1))
+}
+
+/// Parse the `styles` option: reusable style dicts by name, parsed once
+fn extract_named_styles(styles: HashMap<String, Bound<PyDict>>) -> PyResult<HashMap<String, CellStyle>> {
+    styles.into_iter()
+        .map(|(name, dict)| Ok((name, extract_cell_style_inner(&dict)?)))
+        .collect()
+}
+
+/// A style given by name (looked up in the registered `styles`) or as a dict
+fn extract_style_ref(value: &Bound<PyAny>, named: &HashMap<String, CellStyle>) -> PyResult<CellStyle> {
+    if let Ok(name) = value.extract::<String>() {
+        return named.get(&name).cloned().ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Unknown style '{}': register it in styles first", name)
+            )
+        });
+    }
+    extract_cell_style_inner(value.downcast::<PyDict>()?)
+}
+
+/// Extract merge_cells: (start_row, start_col, end_row, end_col) tuples or A1
+/// ranges like "B2:D4", either as a single string or a list mixing both.
+///
+/// Returns (tuple ranges, A1 ranges). A1 ranges are absolute and must be added
+/// after `apply_indexing` has converted the tuple ranges.
+fn extract_merge_cells(value: &Bound<PyAny>) -> PyResult<(Vec<MergeRange>, Vec<MergeRange>)> {
+    let items: Vec<Bound<PyAny>> = if value.extract::<String>().is_ok() {
+        vec![value.clone()]
+    } else {
+        value.try_iter()?.collect::<PyResult<_>>()?
+    };
+
+    let mut indexed = Vec::new();
+    let mut a1 = Vec::new();
+    for item in items {
+        match extract_range(&item, "merge_cells", "B2:D4")? {
+            RangeRef::A1((start_row, start_col, end_row, end_col)) => {
+                a1.push(MergeRange { start_row, start_col, end_row, end_col });
+            }
+            RangeRef::Indexed((start_row, start_col, end_row, end_col)) => {
+                indexed.push(MergeRange { start_row, start_col, end_row, end_col });
+            }
+        }
+    }
+    Ok((indexed, a1))
+}
+
+/// Strip the leading "=" from column_formulas templates
+/// Row number -> number format name or code
+fn extract_row_formats(formats: HashMap<usize, String>) -> HashMap<usize, NumberFormat> {
+    formats.into_iter()
+        .filter_map(|(row, fmt)| parse_number_format(&fmt).map(|fmt| (row, fmt)))
+        .collect()
+}
+
+fn extract_column_formulas(templates: HashMap<String, String>) -> HashMap<String, String> {
+    templates.into_iter()
+        .map(|(column, template)| {
+            let template = template.strip_prefix('=').unwrap_or(&template).to_string();
+            (column, template)
+        })
+        .collect()
+}
+
+/// Extract shared_formulas: (range, formula) pairs. The range is an A1 range
+/// like "D2:D1000" or a (start_row, start_col, end_row, end_col) tuple, and the
+/// formula is the one of its top-left cell.
+///
+/// Returns (tuple ranges, A1 ranges) like `extract_merge_cells`.
+fn extract_shared_formulas(items: Vec<(Bound<PyAny>, String)>) -> PyResult<(Vec<SharedFormula>, Vec<SharedFormula>)> {
+    let mut indexed = Vec::new();
+    let mut a1 = Vec::new();
+    for (range, formula) in items {
+        let formula = formula.strip_prefix('=').unwrap_or(&formula).to_string();
+        match extract_range(&range, "shared_formulas", "D2:D1000")? {
+            RangeRef::A1((start_row, start_col, end_row, end_col)) => {
+                a1.push(SharedFormula { start_row, start_col, end_row, end_col, formula });
+            }
+            RangeRef::Indexed((start_row, start_col, end_row, end_col)) => {
+                indexed.push(SharedFormula { start_row, start_col, end_row, end_col, formula });
+            }
+        }
+    }
+    Ok((indexed, a1))
+}
+
+/// Read a `<prefix>_type` / `<prefix>_value` threshold pair
+fn extract_cfvo(dict: &Bound<PyDict>, prefix: &str, default: CfvoType) -> PyResult<Cfvo> {
+    let kind: Option<String> = dict.get_item(format!("{}_type", prefix))?.and_then(|v| v.extract().ok());
+    let value: Option<String> = match dict.get_item(format!("{}_value", prefix))? {
+        Some(v) if v.is_none() => None,
+        Some(v) => Some(v.str()?.to_string()),
+        None => None,
+    };
+    
+    let kind = match kind.as_deref() {
+        None => default,
+        Some("min") => CfvoType::Min,
+        Some("max") => CfvoType::Max,
+        Some("auto_min") => CfvoType::AutoMin,
+        Some("auto_max") => CfvoType::AutoMax,
+        Some("num") => CfvoType::Num,
+        Some("percent") => CfvoType::Percent,
+        Some("percentile") => CfvoType::Percentile,
+        Some("formula") => CfvoType::Formula,
+        Some(other) => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid {}_type '{}'", prefix, other)
+        )),
+    };
+    
+    let needs_value = matches!(kind, CfvoType::Num | CfvoType::Percent | CfvoType::Percentile | CfvoType::Formula);
+    if needs_value && value.is_none() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("{}_value is required when {}_type is set", prefix, prefix)
+        ));
+    }
     
-    Ok(CellStyleMap { row, col, style })
+    Ok(Cfvo { kind, value })
 }
 
-fn extract_conditional_format(dict: &Bound<PyDict>) -> PyResult<ConditionalFormat> {
-    let start_row: usize = dict.get_item("start_row")?.unwrap().extract()?;
-    let start_col: usize = dict.get_item("start_col")?.unwrap().extract()?;
-    let end_row: usize = dict.get_item("end_row")?.unwrap().extract()?;
-    let end_col: usize = dict.get_item("end_col")?.unwrap().extract()?;
+/// Extract a conditional format and whether its range was given in A1 notation
+fn extract_conditional_format(dict: &Bound<PyDict>, named: &HashMap<String, CellStyle>) -> PyResult<(ConditionalFormat, bool)> {
+    let ((start_row, start_col, end_row, end_col), is_a1) =
+        extract_dict_range(dict, "range", "conditional_formats", "B2:B100")?.into_parts();
     let rule_type: String = dict.get_item("rule_type")?.unwrap().extract()?;
     let priority: u32 = dict.get_item("priority")?.map(|v| v.extract()).unwrap_or(Ok(1))?;
+    let stop_if_true: bool = dict.get_item("stop_if_true")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
     
     let rule = match rule_type.as_str() {
         "cell_value" => {
@@ -1190,13 +3262,49 @@ fn extract_conditional_format(dict: &Bound<PyDict>) -> PyResult<ConditionalForma
             let max_color: String = dict.get_item("max_color")?.unwrap().extract()?;
             let mid_color: Option<String> = dict.get_item("mid_color")?.and_then(|v| v.extract().ok());
             
-            ConditionalRule::ColorScale { min_color, max_color, mid_color }
+            let min = extract_cfvo(dict, "min", CfvoType::Min)?;
+            let mid = if dict.contains("mid_type")? || dict.contains("mid_value")? {
+                extract_cfvo(dict, "mid", CfvoType::Percentile)?
+            } else {
+                Cfvo { kind: CfvoType::Percentile, value: Some("50".to_string()) }
+            };
+            let max = extract_cfvo(dict, "max", CfvoType::Max)?;
+            
+            ConditionalRule::ColorScale { min_color, max_color, mid_color, min, mid, max }
         }
         "data_bar" => {
             let color: String = dict.get_item("color")?.unwrap().extract()?;
             let show_value: bool = dict.get_item("show_value")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
+            let min = extract_cfvo(dict, "min", CfvoType::AutoMin)?;
+            let max = extract_cfvo(dict, "max", CfvoType::AutoMax)?;
+            let border_color: Option<String> = dict.get_item("border_color")?.and_then(|v| v.extract().ok());
+            let negative_color: Option<String> = dict.get_item("negative_color")?.and_then(|v| v.extract().ok());
+            let negative_border_color: Option<String> = dict.get_item("negative_border_color")?.and_then(|v| v.extract().ok());
+            let gradient: bool = dict.get_item("gradient")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
+            let axis_color: Option<String> = dict.get_item("axis_color")?.and_then(|v| v.extract().ok());
+            let axis_position: String = dict.get_item("axis_position")?.map(|v| v.extract()).unwrap_or(Ok("automatic".to_string()))?;
             
-            ConditionalRule::DataBar { color, show_value }
+            let axis_position = match axis_position.as_str() {
+                "automatic" => DataBarAxisPosition::Automatic,
+                "middle" => DataBarAxisPosition::Middle,
+                "none" => DataBarAxisPosition::None,
+                other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid axis position '{}'", other)
+                )),
+            };
+            
+            ConditionalRule::DataBar {
+                color,
+                show_value,
+                min,
+                max,
+                border_color,
+                negative_color,
+                negative_border_color,
+                gradient,
+                axis_color,
+                axis_position,
+            }
         }
         "top10" => {
             let rank: u32 = dict.get_item("rank")?.unwrap().extract()?;
@@ -1204,22 +3312,68 @@ fn extract_conditional_format(dict: &Bound<PyDict>) -> PyResult<ConditionalForma
             
             ConditionalRule::Top10 { rank, bottom }
         }
+        "expression" => {
+            let formula: String = dict.get_item("formula")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                "conditional format 'expression' requires 'formula'"
+            ))?.extract()?;
+            
+            ConditionalRule::Expression { formula }
+        }
+        "contains_text" | "not_contains_text" | "begins_with" | "ends_with" => {
+            let text: String = dict.get_item("text")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                format!("conditional format '{}' requires 'text'", rule_type)
+            ))?.extract()?;
+            
+            match rule_type.as_str() {
+                "contains_text" => ConditionalRule::ContainsText { text },
+                "not_contains_text" => ConditionalRule::NotContainsText { text },
+                "begins_with" => ConditionalRule::BeginsWith { text },
+                _ => ConditionalRule::EndsWith { text },
+            }
+        }
+        "duplicate_values" => ConditionalRule::DuplicateValues,
+        "unique_values" => ConditionalRule::UniqueValues,
+        "contains_blanks" => ConditionalRule::ContainsBlanks,
+        "time_period" => {
+            let period: String = dict.get_item("period")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                "conditional format 'time_period' requires 'period'"
+            ))?.extract()?;
+            
+            let period = match period.as_str() {
+                "yesterday" => TimePeriod::Yesterday,
+                "today" => TimePeriod::Today,
+                "tomorrow" => TimePeriod::Tomorrow,
+                "last_7_days" => TimePeriod::Last7Days,
+                "last_week" => TimePeriod::LastWeek,
+                "this_week" => TimePeriod::ThisWeek,
+                "next_week" => TimePeriod::NextWeek,
+                "last_month" => TimePeriod::LastMonth,
+                "this_month" => TimePeriod::ThisMonth,
+                "next_month" => TimePeriod::NextMonth,
+                other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Invalid time period '{}'", other)
+                )),
+            };
+            
+            ConditionalRule::TimePeriod { period }
+        }
         _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid rule type")),
     };
     
     // Extract style or use default
-    let style = if let Some(style_dict) = dict.get_item("style")? {
-        let style_dict = style_dict.downcast::<PyDict>()?;
-        extract_cell_style_inner(style_dict)?
+    let style = if let Some(style) = dict.get_item("style")? {
+        extract_style_ref(&style, named)?
     } else {
         // Default: red bold text
         CellStyle {
             font: Some(FontStyle {
                 bold: true,
                 italic: false,
-                underline: false,
+                underline: None,
+                strike: false,
+                vert_align: None,
                 size: None,
-                color: Some("FFFF0000".to_string()),
+                color: Some(Color::Rgb("FFFF0000".to_string())),
                 name: None,
             }),
             fill: None,
@@ -1229,7 +3383,7 @@ fn extract_conditional_format(dict: &Bound<PyDict>) -> PyResult<ConditionalForma
         }
     };
     
-    Ok(ConditionalFormat {
+    Ok((ConditionalFormat {
         start_row,
         start_col,
         end_row,
@@ -1237,6 +3391,83 @@ fn extract_conditional_format(dict: &Bound<PyDict>) -> PyResult<ConditionalForma
         rule,
         style,
         priority,
+        stop_if_true,
+    }, is_a1))
+}
+
+/// A conditional format dict, or a range with a `rules` list sharing it.
+/// Rules inherit every key they don't set from the outer dict; missing
+/// priorities follow list order.
+fn extract_conditional_formats(dict: &Bound<PyDict>, named: &HashMap<String, CellStyle>) -> PyResult<Vec<(ConditionalFormat, bool)>> {
+    let Some(rules) = dict.get_item("rules")? else {
+        return Ok(vec![extract_conditional_format(dict, named)?]);
+    };
+    
+    let base_priority: u32 = dict.get_item("priority")?.map(|v| v.extract()).unwrap_or(Ok(1))?;
+    let mut formats = Vec::new();
+    for (i, rule) in rules.try_iter()?.enumerate() {
+        let rule = rule?;
+        let rule = rule.downcast::<PyDict>()?;
+        let merged = dict.copy()?;
+        merged.del_item("rules")?;
+        merged.set_item("priority", base_priority + i as u32)?;
+        merged.update(rule.as_mapping())?;
+        formats.push(extract_conditional_format(&merged, named)?);
+    }
+    Ok(formats)
+}
+
+#[pyfunction]
+#[pyo3(signature = (arrow_data, **options))]
+/// Estimate the size of a write_sheet_arrow() call before making it.
+///
+/// Args:
+///     arrow_data: Same input as write_sheet_arrow(); a stream is read to the end
+///     **options: The keyword arguments the write would get. compression,
+///         column_gap and flatten_structs are used; the rest are ignored
+///
+/// Returns:
+///     dict: rows, columns, xml_bytes (uncompressed worksheet), file_bytes and
+///     peak_memory_bytes (allocated by the write on top of the Arrow data)
+fn estimate(py: Python, arrow_data: &Bound<PyAny>, options: Option<&Bound<PyDict>>) -> PyResult<Py<PyDict>> {
+    let option = |key: &str| -> PyResult<Option<Bound<PyAny>>> {
+        match options {
+            Some(options) => Ok(options.get_item(key)?.filter(|v| !v.is_none())),
+            None => Ok(None),
+        }
+    };
+    let column_gap: usize = option("column_gap")?.map(|v| v.extract()).transpose()?.unwrap_or(1);
+    let flatten_structs: bool = option("flatten_structs")?.map(|v| v.extract()).transpose()?.unwrap_or(false);
+    let workbook = WorkbookConfig {
+        compression: extract_compression(option("compression")?)?,
+        ..WorkbookConfig::default()
+    };
+    let batches = read_arrow_input(arrow_data, column_gap, flatten_structs)?.batches;
+
+    let estimate = py.detach(|| writer::estimate_single_sheet(&batches, &workbook))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    let result = PyDict::new(py);
+    result.set_item("rows", estimate.rows)?;
+    result.set_item("columns", estimate.columns)?;
+    result.set_item("xml_bytes", estimate.xml_bytes)?;
+    result.set_item("file_bytes", estimate.file_bytes)?;
+    result.set_item("peak_memory_bytes", estimate.peak_memory_bytes)?;
+    Ok(result.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (directory))]
+/// Write a set of small workbooks, one per feature area, for compatibility checks.
+///
+/// Args:
+///     directory (str): Target directory (created if missing)
+///
+/// Returns:
+///     list[str]: Paths of the generated workbooks
+fn generate_feature_gallery(py: Python, directory: String) -> PyResult<Vec<String>> {
+    py.detach(|| {
+        gallery::generate_feature_gallery(std::path::Path::new(&directory))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
     })
 }
 
@@ -1249,10 +3480,20 @@ fn jetxl(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Arrow fast path API (file-based)
     m.add_function(wrap_pyfunction!(write_sheet_arrow, m)?)?;
     m.add_function(wrap_pyfunction!(write_sheets_arrow, m)?)?;
+    m.add_function(wrap_pyfunction!(write_workbooks, m)?)?;
+    m.add_class::<Workbook>()?;
     
     // Arrow fast path API (in-memory bytes)
     m.add_function(wrap_pyfunction!(write_sheet_arrow_to_bytes, m)?)?;
     m.add_function(wrap_pyfunction!(write_sheets_arrow_to_bytes, m)?)?;
+
+    // Size estimate for a write
+    m.add_function(wrap_pyfunction!(estimate, m)?)?;
+    
+    // Compatibility testing
+    m.add_function(wrap_pyfunction!(generate_feature_gallery, m)?)?;
+
+    m.add("CONFIG_VERSION", CONFIG_VERSION)?;
     
     Ok(())
 }
@@ -1282,20 +3523,36 @@ fn jetxl(m: &Bound<'_, PyModule>) -> PyResult<()> {
 //     Ok(table)
 // }
 
-fn extract_table(dict: &Bound<PyDict>) -> PyResult<ExcelTable> {
+/// Extract a table and whether its range was given in A1 notation. A single
+/// A1 cell ("A1") anchors the header and sizes the table to the data.
+fn extract_table(dict: &Bound<PyDict>) -> PyResult<(ExcelTable, bool)> {
     let name: String = dict.get_item("name")?.unwrap().extract()?;
-    let start_row: usize = dict.get_item("start_row")?.unwrap().extract()?;
-    let start_col: usize = dict.get_item("start_col")?.unwrap().extract()?;
-    
-    // Make end_row and end_col optional - extract as Option<i64> to allow None or -1
-    let end_row_opt: Option<i64> = dict.get_item("end_row")?.and_then(|v| v.extract().ok());
-    let end_col_opt: Option<i64> = dict.get_item("end_col")?.and_then(|v| v.extract().ok());
-    
-    // Use sentinel value of 0 for now, will be calculated in writer
-    let end_row = end_row_opt.filter(|&v| v >= 0).map(|v| v as usize).unwrap_or(0);
-    let end_col = end_col_opt.filter(|&v| v >= 0).map(|v| v as usize).unwrap_or(0);
-    
-    let mut table = ExcelTable::new(name, (start_row, start_col, end_row, end_col));
+    let (range, is_a1) = match dict.get_item("range")? {
+        Some(range) => {
+            let anchor_only = range.extract::<String>().is_ok_and(|text| !text.contains(':'));
+            let ((start_row, start_col, end_row, end_col), is_a1) = extract_range(&range, "tables", "A1:F100")?.into_parts();
+            if anchor_only {
+                ((start_row, start_col, 0, 0), is_a1)
+            } else {
+                ((start_row, start_col, end_row, end_col), is_a1)
+            }
+        }
+        None => {
+            let start_row: usize = dict.get_item("start_row")?.unwrap().extract()?;
+            let start_col: usize = dict.get_item("start_col")?.unwrap().extract()?;
+
+            // Make end_row and end_col optional - extract as Option<i64> to allow None or -1
+            let end_row_opt: Option<i64> = dict.get_item("end_row")?.and_then(|v| v.extract().ok());
+            let end_col_opt: Option<i64> = dict.get_item("end_col")?.and_then(|v| v.extract().ok());
+
+            // Use sentinel value of 0 for now, will be calculated in writer
+            let end_row = end_row_opt.filter(|&v| v >= 0).map(|v| v as usize).unwrap_or(0);
+            let end_col = end_col_opt.filter(|&v| v >= 0).map(|v| v as usize).unwrap_or(0);
+            ((start_row, start_col, end_row, end_col), false)
+        }
+    };
+
+    let mut table = ExcelTable::new(name, range);
     
     if let Some(display_name) = dict.get_item("display_name")?.and_then(|v| v.extract().ok()) {
         table.display_name = display_name;
@@ -1309,31 +3566,62 @@ fn extract_table(dict: &Bound<PyDict>) -> PyResult<ExcelTable> {
     table.show_last_column = dict.get_item("show_last_column")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
     table.show_row_stripes = dict.get_item("show_row_stripes")?.map(|v| v.extract()).unwrap_or(Ok(true))?;
     table.show_column_stripes = dict.get_item("show_column_stripes")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+    table.show_totals_row = dict.get_item("show_totals_row")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+    table.totals_row_label = dict.get_item("totals_row_label")?.map(|v| v.extract()).transpose()?;
+    if let Some(functions) = dict.get_item("totals_row_functions")? {
+        for (column, function) in functions.downcast::<PyDict>()?.iter() {
+            let function = parse_totals_row_function(&function.extract::<String>()?)?;
+            table.totals_row_functions.push((column.extract()?, function));
+        }
+    }
     
-    Ok(table)
+    Ok((table, is_a1))
+}
+
+fn parse_totals_row_function(value: &str) -> PyResult<TotalsRowFunction> {
+    match value {
+        "sum" => Ok(TotalsRowFunction::Sum),
+        "average" => Ok(TotalsRowFunction::Average),
+        "count" => Ok(TotalsRowFunction::Count),
+        "count_nums" => Ok(TotalsRowFunction::CountNums),
+        "max" => Ok(TotalsRowFunction::Max),
+        "min" => Ok(TotalsRowFunction::Min),
+        "std_dev" => Ok(TotalsRowFunction::StdDev),
+        "var" => Ok(TotalsRowFunction::Var),
+        other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid totals row function '{}': expected 'sum', 'average', 'count', 'count_nums', 'max', 'min', 'std_dev' or 'var'", other
+        ))),
+    }
+}
+
+fn parse_chart_type(value: &str) -> PyResult<ChartType> {
+    match value {
+        "column" => Ok(ChartType::Column),
+        "bar" => Ok(ChartType::Bar),
+        "line" => Ok(ChartType::Line),
+        "pie" => Ok(ChartType::Pie),
+        "scatter" => Ok(ChartType::Scatter),
+        "area" => Ok(ChartType::Area),
+        "column_3d" => Ok(ChartType::Column3D),
+        "bar_3d" => Ok(ChartType::Bar3D),
+        "pie_3d" => Ok(ChartType::Pie3D),
+        "doughnut" => Ok(ChartType::Doughnut),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid chart type")),
+    }
 }
 
-fn extract_chart(dict: &Bound<PyDict>) -> PyResult<ExcelChart> {
+/// Extract a chart and whether its data_range was given in A1 notation
+fn extract_chart(dict: &Bound<PyDict>) -> PyResult<(ExcelChart, bool)> {
     let chart_type_str: String = dict.get_item("chart_type")?.unwrap().extract()?;
-    let chart_type = match chart_type_str.as_str() {
-        "column" => ChartType::Column,
-        "bar" => ChartType::Bar,
-        "line" => ChartType::Line,
-        "pie" => ChartType::Pie,
-        "scatter" => ChartType::Scatter,
-        "area" => ChartType::Area,
-        _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid chart type")),
-    };
+    let chart_type = parse_chart_type(&chart_type_str)?;
     
-    // Handle both old API (start_row/start_col) and new API (data_range tuple)
-    let data_range = if let Some(range) = dict.get_item("data_range")? {
-        range.extract::<(usize, usize, usize, usize)>()?
-    } else {
-        let start_row: usize = dict.get_item("start_row")?.unwrap().extract()?;
-        let start_col: usize = dict.get_item("start_col")?.unwrap().extract()?;
-        let end_row: usize = dict.get_item("end_row")?.unwrap().extract()?;
-        let end_col: usize = dict.get_item("end_col")?.unwrap().extract()?;
-        (start_row, start_col, end_row, end_col)
+    // data_range takes an A1 string or a tuple; the old API passes start_row etc.
+    // Chart rows are 0-based internally.
+    let (data_range, is_a1) = match extract_dict_range(dict, "data_range", "charts", "A1:C13")? {
+        RangeRef::A1((start_row, start_col, end_row, end_col)) => {
+            ((start_row - 1, start_col, end_row - 1, end_col), true)
+        }
+        RangeRef::Indexed(range) => (range, false),
     };
     
     let from_col: usize = dict.get_item("from_col")?.unwrap().extract()?;
@@ -1361,6 +3649,19 @@ fn extract_chart(dict: &Bound<PyDict>) -> PyResult<ExcelChart> {
     chart.chart_style = dict.get_item("chart_style")?.and_then(|v| v.extract().ok());
     chart.axis_min = dict.get_item("axis_min")?.and_then(|v| v.extract().ok());
     chart.axis_max = dict.get_item("axis_max")?.and_then(|v| v.extract().ok());
+    chart.y_axis_log_base = dict.get_item("y_axis_log_base")?.map(|v| parse_log_base(v.extract()?)).transpose()?;
+    chart.x_axis_log_base = dict.get_item("x_axis_log_base")?.map(|v| parse_log_base(v.extract()?)).transpose()?;
+    if chart.x_axis_log_base.is_some() && chart_type != ChartType::Scatter {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "x_axis_log_base needs a scatter chart; other charts have a category x axis"
+        ));
+    }
+    let has_log_axis = chart.y_axis_log_base.is_some() || chart.x_axis_log_base.is_some();
+    if has_log_axis && chart.axis_min.is_some_and(|min| min <= 0.0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "axis_min must be positive on a logarithmic axis"
+        ));
+    }
     
     // Title formatting
     chart.title_bold = dict.get_item("title_bold")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
@@ -1379,18 +3680,567 @@ fn extract_chart(dict: &Bound<PyDict>) -> PyResult<ExcelChart> {
     if let Some(names) = dict.get_item("series_names")?.and_then(|v| v.extract::<Vec<String>>().ok()) {
         chart.series_names = names;
     }
+    if let Some(series) = dict.get_item("series")? {
+        chart.series = extract_chart_series(&series, chart_type)?;
+    }
+    if (chart.stacked || chart.percent_stacked) && chart.series.iter().any(|s| s.trendline.is_some()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Trendlines can't be added to stacked charts"
+        ));
+    }
+
+    chart.view_3d = extract_view_3d(dict)?;
+
+    if let Some(axis) = dict.get_item("date_axis")? {
+        chart.category_axis = extract_category_axis(&axis)?;
+        if matches!(chart.category_axis, CategoryAxis::Date(_)) && matches!(chart_type, ChartType::Pie | ChartType::Pie3D | ChartType::Doughnut | ChartType::Scatter) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "date_axis needs a column, bar, line or area chart"
+            ));
+        }
+    }
+
+    chart.gap_width = dict.get_item("gap_width")?.map(|v| v.extract()).transpose()?;
+    chart.overlap = dict.get_item("overlap")?.map(|v| v.extract()).transpose()?;
+    if chart.gap_width.is_some_and(|g| g > 500) || chart.overlap.is_some_and(|o| !(-100..=100).contains(&o)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "gap_width must be 0..500 and overlap -100..100"
+        ));
+    }
+    if chart.gap_width.is_some() || chart.overlap.is_some() {
+        let is_bar = |t: ChartType| matches!(t, ChartType::Column | ChartType::Bar | ChartType::Column3D | ChartType::Bar3D);
+        if !is_bar(chart_type) && !chart.series.iter().any(|s| s.chart_type.is_some_and(is_bar)) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "gap_width and overlap need column or bar series"
+            ));
+        }
+        if chart.overlap.is_some() && chart_type.is_3d() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "3D column and bar charts have no overlap"
+            ));
+        }
+    }
+
+    chart.smooth = dict.get_item("smooth")?.map(|v| v.extract()).unwrap_or(Ok(false))?;
+    let has_lines = matches!(chart_type, ChartType::Line | ChartType::Scatter)
+        || chart.series.iter().any(|s| s.chart_type == Some(ChartType::Line));
+    if chart.smooth && !has_lines {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "smooth needs a line or scatter chart"
+        ));
+    }
+
+    if let Some(explosion) = dict.get_item("explosion")? {
+        // A single offset explodes every slice, a list sets them one by one
+        match explosion.extract::<u32>() {
+            Ok(all) => chart.explosion = Some(all),
+            Err(_) => chart.slice_explosions = explosion.extract()?,
+        }
+    }
+    chart.first_slice_angle = dict.get_item("first_slice_angle")?.map(|v| v.extract()).transpose()?;
+    chart.hole_size = dict.get_item("hole_size")?.map(|v| v.extract()).transpose()?;
+    let is_pie = matches!(chart_type, ChartType::Pie | ChartType::Pie3D | ChartType::Doughnut);
+    if !is_pie && (chart.explosion.is_some() || !chart.slice_explosions.is_empty() || chart.first_slice_angle.is_some()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "explosion and first_slice_angle need a pie or doughnut chart"
+        ));
+    }
+    if chart.explosion.into_iter().chain(chart.slice_explosions.iter().copied()).any(|e| e > 400) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "explosion must be 0..400 (% of the radius)"
+        ));
+    }
+    if chart.first_slice_angle.is_some_and(|a| a > 360) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "first_slice_angle must be 0..360"
+        ));
+    }
+    if chart.first_slice_angle.is_some() && chart_type == ChartType::Pie3D {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "pie_3d charts turn with rot_y instead of first_slice_angle"
+        ));
+    }
+    if let Some(hole_size) = chart.hole_size {
+        if chart_type != ChartType::Doughnut || !(10..=90).contains(&hole_size) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "hole_size needs a doughnut chart and must be 10..90"
+            ));
+        }
+    }
+
+    if let Some(labels) = dict.get_item("data_labels")? {
+        chart.data_labels = Some(extract_data_labels(labels.downcast::<PyDict>()?, &chart)?);
+    }
+
+    // Per-series look by series index
+    if let Some(colors) = dict.get_item("series_colors")?.map(|v| v.extract::<Vec<String>>()).transpose()? {
+        chart.series_colors = colors.iter().map(|c| parse_series_color(c)).collect::<PyResult<_>>()?;
+    }
+    if let Some(widths) = dict.get_item("series_line_widths")?.map(|v| v.extract::<Vec<f64>>()).transpose()? {
+        chart.series_line_widths = widths.into_iter().map(parse_line_width).collect::<PyResult<_>>()?;
+    }
+    if let Some(dashes) = dict.get_item("series_line_dashes")?.map(|v| v.extract::<Vec<String>>()).transpose()? {
+        chart.series_line_dashes = dashes.iter().map(|d| parse_line_dash(d)).collect::<PyResult<_>>()?;
+    }
+    if let Some(markers) = dict.get_item("series_markers")? {
+        chart.series_markers = markers.try_iter()?.map(|m| extract_marker(&m?)).collect::<PyResult<_>>()?;
+    }
+
+    // Chart and plot area background and outline
+    chart.chart_fill = dict.get_item("chart_fill")?.map(|v| parse_chart_fill(&v.extract::<String>()?)).transpose()?;
+    chart.plot_area_fill = dict.get_item("plot_area_fill")?.map(|v| parse_chart_fill(&v.extract::<String>()?)).transpose()?;
+    chart.chart_border = dict.get_item("chart_border")?.map(|v| extract_chart_border(&v)).transpose()?;
+    chart.plot_area_border = dict.get_item("plot_area_border")?.map(|v| extract_chart_border(&v)).transpose()?;
+
+    // Secondary value axis, used by series with "secondary": True
+    chart.secondary_axis_min = dict.get_item("secondary_axis_min")?.and_then(|v| v.extract().ok());
+    chart.secondary_axis_max = dict.get_item("secondary_axis_max")?.and_then(|v| v.extract().ok());
+    chart.secondary_axis_title = dict.get_item("secondary_axis_title")?.and_then(|v| v.extract().ok());
+    chart.secondary_axis_number_format = dict.get_item("secondary_axis_number_format")?.and_then(|v| v.extract().ok());
     
-    Ok(chart)
+    Ok((chart, is_a1))
+}
+
+/// 3D rotation keys: "rot_x" (-90..90), "rot_y" (0..360), "perspective"
+/// (0..240) and "right_angle_axes"
+fn extract_view_3d(dict: &Bound<PyDict>) -> PyResult<View3D> {
+    let view = View3D {
+        rot_x: dict.get_item("rot_x")?.map(|v| v.extract()).transpose()?,
+        rot_y: dict.get_item("rot_y")?.map(|v| v.extract()).transpose()?,
+        perspective: dict.get_item("perspective")?.map(|v| v.extract()).transpose()?,
+        right_angle_axes: dict.get_item("right_angle_axes")?.map(|v| v.extract()).transpose()?,
+    };
+    let out_of_range = view.rot_x.is_some_and(|x| !(-90..=90).contains(&x))
+        || view.rot_y.is_some_and(|y| y > 360)
+        || view.perspective.is_some_and(|p| p > 240);
+    if out_of_range {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "3D view out of range: rot_x must be -90..90, rot_y 0..360 and perspective 0..240"
+        ));
+    }
+    Ok(view)
+}
+
+/// Chart "series": value columns, (sheet, values, categories, name) range
+/// tuples, or dicts with "col" (or "values" with optional "sheet" and
+/// "categories") and optional "name", "chart_type", "secondary",
+/// "trendline", "color", "line_width", "line_dash", "marker" and "smooth". Column, line and area series can share one
+/// chart; bar series (horizontal) can only be combined with each other.
+fn extract_chart_series(value: &Bound<PyAny>, chart_type: ChartType) -> PyResult<Vec<ChartSeries>> {
+    let mut series = Vec::new();
+    for item in value.try_iter()? {
+        let item = item?;
+        let parsed = if let Ok(dict) = item.downcast::<PyDict>() {
+            let range = match dict.get_item("values")? {
+                Some(values) => Some(parse_series_range(
+                    dict.get_item("sheet")?.map(|v| v.extract()).transpose()?,
+                    &values.extract::<String>()?,
+                    dict.get_item("categories")?.map(|v| v.extract::<String>()).transpose()?.as_deref(),
+                )?),
+                None => None,
+            };
+            let col: usize = match (dict.get_item("col")?, &range) {
+                (Some(col), _) => col.extract()?,
+                (None, Some(range)) => range.values.1,
+                (None, None) => return Err(PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+                    "Chart series needs 'col' or 'values'"
+                )),
+            };
+            let series_type = dict.get_item("chart_type")?
+                .map(|v| v.extract::<String>())
+                .transpose()?
+                .map(|t| parse_chart_type(&t))
+                .transpose()?;
+            ChartSeries {
+                col,
+                name: dict.get_item("name")?.map(|v| v.extract()).transpose()?,
+                chart_type: series_type,
+                secondary: dict.get_item("secondary")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+                trendline: dict.get_item("trendline")?.map(|v| extract_trendline(&v)).transpose()?,
+                color: dict.get_item("color")?.map(|v| parse_series_color(&v.extract::<String>()?)).transpose()?,
+                line_width: dict.get_item("line_width")?.map(|v| parse_line_width(v.extract()?)).transpose()?,
+                line_dash: dict.get_item("line_dash")?.map(|v| parse_line_dash(&v.extract::<String>()?)).transpose()?,
+                marker: dict.get_item("marker")?.map(|v| extract_marker(&v)).transpose()?,
+                range,
+                smooth: dict.get_item("smooth")?.map(|v| v.extract()).transpose()?,
+            }
+        } else if let Ok(tuple) = item.downcast::<PyTuple>() {
+            // (sheet, values, categories, name) with the last two optional
+            if !(2..=4).contains(&tuple.len()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Chart series tuples are (sheet, values, categories, name)"
+                ));
+            }
+            let optional = |i: usize| -> PyResult<Option<String>> {
+                match tuple.get_item(i) {
+                    Ok(v) => v.extract(),
+                    Err(_) => Ok(None),
+                }
+            };
+            let values: String = tuple.get_item(1)?.extract()?;
+            let range = parse_series_range(tuple.get_item(0)?.extract()?, &values, optional(2)?.as_deref())?;
+            ChartSeries {
+                col: range.values.1,
+                name: optional(3)?,
+                chart_type: None,
+                secondary: false,
+                trendline: None,
+                color: None,
+                line_width: None,
+                line_dash: None,
+                marker: None,
+                range: Some(range),
+                smooth: None,
+            }
+        } else {
+            ChartSeries {
+                col: item.extract()?,
+                name: None,
+                chart_type: None,
+                secondary: false,
+                trendline: None,
+                color: None,
+                line_width: None,
+                line_dash: None,
+                marker: None,
+                range: None,
+                smooth: None,
+            }
+        };
+        series.push(parsed);
+    }
+
+    let types: Vec<ChartType> = series.iter().map(|s| s.chart_type.unwrap_or(chart_type)).collect();
+    if series.iter().zip(&types).any(|(s, t)| s.smooth.is_some() && *t != ChartType::Line) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Only line series can be smooth"
+        ));
+    }
+    if types.iter().any(|t| matches!(t, ChartType::Pie | ChartType::Doughnut | ChartType::Scatter) || t.is_3d()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Chart series must be 'column', 'bar', 'line' or 'area'"
+        ));
+    }
+    if types.contains(&ChartType::Bar) && types.iter().any(|t| *t != ChartType::Bar) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Bar series can't be combined with other chart types; use 'column' for a combo chart"
+        ));
+    }
+    if !series.is_empty() && series.iter().all(|s| s.secondary) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "At least one chart series must stay on the primary axis"
+        ));
+    }
+    Ok(series)
+}
+
+/// A series' values (one row or column) and categories as A1 ranges on
+/// `sheet`, or on the chart's own sheet when None
+fn parse_series_range(sheet: Option<String>, values: &str, categories: Option<&str>) -> PyResult<SeriesRange> {
+    let parse = |range: &str| xml::parse_range_ref(range)
+        .filter(|&(r1, c1, r2, c2)| r1 == r2 || c1 == c2)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid series range '{}': expected one row or column like 'B2:B13'", range
+        )));
+    if sheet.as_deref().is_some_and(|s| s.is_empty()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Series sheet name can't be empty"));
+    }
+    Ok(SeriesRange {
+        sheet,
+        values: parse(values)?,
+        categories: categories.map(parse).transpose()?,
+    })
+}
+
+fn parse_series_color(value: &str) -> PyResult<String> {
+    normalize_argb(value).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+        format!("Invalid series color '{}': expected 'RRGGBB' or 'AARRGGBB'", value)
+    ))
+}
+
+/// Excel accepts log bases from 2 to 1000
+fn parse_log_base(value: f64) -> PyResult<f64> {
+    if !(2.0..=1000.0).contains(&value) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid log base {}: expected 2..1000", value)
+        ));
+    }
+    Ok(value)
+}
+
+/// Line width in points; Excel accepts 0..1584
+fn parse_line_width(value: f64) -> PyResult<f64> {
+    if !(0.0..=1584.0).contains(&value) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid line width {}: expected 0..1584 points", value)
+        ));
+    }
+    Ok(value)
+}
+
+fn parse_line_dash(value: &str) -> PyResult<LineDash> {
+    match value {
+        "solid" => Ok(LineDash::Solid),
+        "dot" => Ok(LineDash::Dot),
+        "dash" => Ok(LineDash::Dash),
+        "dash_dot" => Ok(LineDash::DashDot),
+        "long_dash" => Ok(LineDash::LongDash),
+        "long_dash_dot" => Ok(LineDash::LongDashDot),
+        other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid line dash '{}': expected 'solid', 'dot', 'dash', 'dash_dot', 'long_dash' or 'long_dash_dot'", other
+        ))),
+    }
+}
+
+/// Chart/plot area fill: "none" for no fill, or an RRGGBB/AARRGGBB color
+fn parse_chart_fill(value: &str) -> PyResult<ChartFill> {
+    if value == "none" {
+        return Ok(ChartFill::None);
+    }
+    normalize_argb(value).map(ChartFill::Solid).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+        format!("Invalid fill color '{}': expected 'none', 'RRGGBB' or 'AARRGGBB'", value)
+    ))
+}
+
+/// Chart/plot area border: "none" or False to hide it, or a dict with
+/// "color", "width" (points) and "dash"
+fn extract_chart_border(value: &Bound<PyAny>) -> PyResult<ChartBorder> {
+    let Ok(dict) = value.downcast::<PyDict>() else {
+        let hidden = value.extract::<String>().is_ok_and(|s| s == "none")
+            || value.extract::<bool>().is_ok_and(|b| !b);
+        if hidden {
+            return Ok(ChartBorder::None);
+        }
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "A chart border must be 'none', False or a dict with 'color', 'width' and 'dash'"
+        ));
+    };
+    let color = dict.get_item("color")?.map(|v| v.extract::<String>()).transpose()?;
+    let width = dict.get_item("width")?.map(|v| v.extract::<f64>()).transpose()?;
+    let dash = dict.get_item("dash")?.map(|v| v.extract::<String>()).transpose()?;
+    Ok(ChartBorder::Line {
+        color: color.map(|c| normalize_argb(&c).ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid border color '{}': expected 'RRGGBB' or 'AARRGGBB'", c)
+        ))).transpose()?,
+        width: width.map(parse_line_width).transpose()?,
+        dash: dash.map(|d| parse_line_dash(&d)).transpose()?,
+    })
+}
+
+/// "date_axis": True/False to force a date or text axis (dates are detected
+/// from the category column otherwise), or a dict with "base_unit",
+/// "major_unit", "major_time_unit" and "number_format"
+fn extract_category_axis(value: &Bound<PyAny>) -> PyResult<CategoryAxis> {
+    let Ok(dict) = value.downcast::<PyDict>() else {
+        let is_date: bool = value.extract()?;
+        return Ok(if is_date { CategoryAxis::Date(DateAxis::default()) } else { CategoryAxis::Text });
+    };
+    let time_unit = |key: &str| -> PyResult<Option<TimeUnit>> {
+        dict.get_item(key)?.map(|v| match v.extract::<String>()?.as_str() {
+            "days" => Ok(TimeUnit::Days),
+            "months" => Ok(TimeUnit::Months),
+            "years" => Ok(TimeUnit::Years),
+            other => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid {} '{}': expected 'days', 'months' or 'years'", key, other
+            ))),
+        }).transpose()
+    };
+    let major_unit: Option<f64> = dict.get_item("major_unit")?.map(|v| v.extract()).transpose()?;
+    if major_unit.is_some_and(|u| u <= 0.0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("major_unit must be positive"));
+    }
+    Ok(CategoryAxis::Date(DateAxis {
+        base_unit: time_unit("base_unit")?,
+        major_unit,
+        major_time_unit: time_unit("major_time_unit")?,
+        number_format: dict.get_item("number_format")?.map(|v| v.extract()).transpose()?,
+    }))
+}
+
+/// "data_labels" dict: "value" (default True), "category", "series_name",
+/// "percent", "number_format", "position", "font_size" and "font_color"
+fn extract_data_labels(dict: &Bound<PyDict>, chart: &ExcelChart) -> PyResult<DataLabels> {
+    let position = dict.get_item("position")?
+        .map(|v| v.extract::<String>())
+        .transpose()?
+        .map(|p| parse_label_position(&p, chart))
+        .transpose()?;
+    Ok(DataLabels {
+        show_value: dict.get_item("value")?.map(|v| v.extract()).unwrap_or(Ok(true))?,
+        show_category: dict.get_item("category")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        show_series_name: dict.get_item("series_name")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        show_percent: dict.get_item("percent")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        number_format: dict.get_item("number_format")?.map(|v| v.extract()).transpose()?,
+        position,
+        font_size: dict.get_item("font_size")?.map(|v| v.extract()).transpose()?,
+        font_color: dict.get_item("font_color")?.map(|v| parse_series_color(&v.extract::<String>()?)).transpose()?,
+    })
+}
+
+/// Label positions Excel allows differ per chart type, and a combo chart
+/// has to allow the position for every series type in it
+fn parse_label_position(value: &str, chart: &ExcelChart) -> PyResult<LabelPosition> {
+    let position = match value {
+        "center" => LabelPosition::Center,
+        "inside_end" => LabelPosition::InsideEnd,
+        "inside_base" => LabelPosition::InsideBase,
+        "outside_end" => LabelPosition::OutsideEnd,
+        "left" => LabelPosition::Left,
+        "right" => LabelPosition::Right,
+        "above" => LabelPosition::Above,
+        "below" => LabelPosition::Below,
+        "best_fit" => LabelPosition::BestFit,
+        other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid label position '{}': expected 'center', 'inside_end', 'inside_base', 'outside_end', 'left', 'right', 'above', 'below' or 'best_fit'", other
+        ))),
+    };
+    let stacked = chart.stacked || chart.percent_stacked;
+    let allowed = |chart_type: ChartType| match chart_type {
+        ChartType::Column | ChartType::Bar => matches!(position, LabelPosition::Center | LabelPosition::InsideEnd | LabelPosition::InsideBase)
+            || (position == LabelPosition::OutsideEnd && !stacked),
+        ChartType::Line | ChartType::Scatter => matches!(position,
+            LabelPosition::Center | LabelPosition::Left | LabelPosition::Right | LabelPosition::Above | LabelPosition::Below),
+        ChartType::Pie | ChartType::Pie3D => matches!(position,
+            LabelPosition::Center | LabelPosition::InsideEnd | LabelPosition::OutsideEnd | LabelPosition::BestFit),
+        ChartType::Area | ChartType::Column3D | ChartType::Bar3D | ChartType::Doughnut => false,
+    };
+    let mut types = vec![chart.chart_type];
+    types.extend(chart.series.iter().filter_map(|s| s.chart_type));
+    if let Some(chart_type) = types.into_iter().find(|t| !allowed(*t)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Label position '{}' isn't available on {:?}{} charts", value, chart_type, if stacked { " stacked" } else { "" }
+        )));
+    }
+    Ok(position)
+}
+
+/// Series "marker": a symbol name, or a dict with "symbol" and optional
+/// "size" (2..72), "fill" and "border" colors
+fn extract_marker(value: &Bound<PyAny>) -> PyResult<Marker> {
+    let dict = value.downcast::<PyDict>().ok();
+    let symbol_name: String = match dict {
+        Some(dict) => dict.get_item("symbol")?.map(|v| v.extract()).unwrap_or(Ok("auto".to_string()))?,
+        None => value.extract()?,
+    };
+    let symbol = match symbol_name.as_str() {
+        "none" => MarkerSymbol::None,
+        "auto" => MarkerSymbol::Auto,
+        "circle" => MarkerSymbol::Circle,
+        "square" => MarkerSymbol::Square,
+        "diamond" => MarkerSymbol::Diamond,
+        "triangle" => MarkerSymbol::Triangle,
+        "x" => MarkerSymbol::X,
+        "star" => MarkerSymbol::Star,
+        "dash" => MarkerSymbol::Dash,
+        "dot" => MarkerSymbol::Dot,
+        "plus" => MarkerSymbol::Plus,
+        other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid marker symbol '{}': expected 'none', 'auto', 'circle', 'square', 'diamond', 'triangle', 'x', 'star', 'dash', 'dot' or 'plus'", other
+        ))),
+    };
+    let Some(dict) = dict else {
+        return Ok(Marker { symbol, size: None, fill: None, border: None });
+    };
+
+    let size: Option<u8> = dict.get_item("size")?.map(|v| v.extract()).transpose()?;
+    if size.is_some_and(|s| !(2..=72).contains(&s)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Marker size must be 2..72"
+        ));
+    }
+    Ok(Marker {
+        symbol,
+        size,
+        fill: dict.get_item("fill")?.map(|v| parse_series_color(&v.extract::<String>()?)).transpose()?,
+        border: dict.get_item("border")?.map(|v| parse_series_color(&v.extract::<String>()?)).transpose()?,
+    })
 }
 
+/// Series "trendline": a type name, or a dict with "type" and optional
+/// "order" (polynomial), "period" (moving average), "forward", "backward",
+/// "name", "show_equation" and "show_r_squared"
+fn extract_trendline(value: &Bound<PyAny>) -> PyResult<Trendline> {
+    let invalid = |msg: &str| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg.to_string());
+    let dict = value.downcast::<PyDict>().ok();
+    let type_name: String = match dict {
+        Some(dict) => dict.get_item("type")?.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(
+            "Trendline needs 'type'"
+        ))?.extract()?,
+        None => value.extract()?,
+    };
+    let get = |key: &str| -> PyResult<Option<Bound<PyAny>>> {
+        match dict {
+            Some(dict) => dict.get_item(key),
+            None => Ok(None),
+        }
+    };
+
+    let trendline_type = match type_name.as_str() {
+        "linear" => TrendlineType::Linear,
+        "exponential" => TrendlineType::Exponential,
+        "polynomial" => {
+            let order: u8 = get("order")?.map(|v| v.extract()).unwrap_or(Ok(2))?;
+            if !(2..=6).contains(&order) {
+                return Err(invalid("Polynomial trendline order must be 2..6"));
+            }
+            TrendlineType::Polynomial(order)
+        }
+        "moving_average" => {
+            let period: u32 = get("period")?.map(|v| v.extract()).unwrap_or(Ok(2))?;
+            if period < 2 {
+                return Err(invalid("Moving average trendline period must be at least 2"));
+            }
+            TrendlineType::MovingAverage(period)
+        }
+        other => return Err(invalid(&format!(
+            "Invalid trendline type '{}': expected 'linear', 'polynomial', 'moving_average' or 'exponential'", other
+        ))),
+    };
+
+    let trendline = Trendline {
+        trendline_type,
+        name: get("name")?.map(|v| v.extract()).transpose()?,
+        forward: get("forward")?.map(|v| v.extract()).transpose()?,
+        backward: get("backward")?.map(|v| v.extract()).transpose()?,
+        show_equation: get("show_equation")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+        show_r_squared: get("show_r_squared")?.map(|v| v.extract()).unwrap_or(Ok(false))?,
+    };
+    let forecast = trendline.forward.is_some() || trendline.backward.is_some();
+    if matches!(trendline_type, TrendlineType::MovingAverage(_)) && (forecast || trendline.show_equation || trendline.show_r_squared) {
+        return Err(invalid("Moving average trendlines have no forecast, equation or R²"));
+    }
+    Ok(trendline)
+}
 
 fn extract_image(dict: &Bound<PyDict>) -> PyResult<ExcelImage> {
-    let from_col: usize = dict.get_item("from_col")?.unwrap().extract()?;
-    let from_row: usize = dict.get_item("from_row")?.unwrap().extract()?;
-    let to_col: usize = dict.get_item("to_col")?.unwrap().extract()?;
-    let to_row: usize = dict.get_item("to_row")?.unwrap().extract()?;
+    // Without a to cell the image keeps its own size from the from cell
+    let default_anchor = if dict.contains("to_col")? { "two_cell" } else { "one_cell" };
+    let anchor: String = dict.get_item("anchor")?.map(|v| v.extract()).unwrap_or(Ok(default_anchor.to_string()))?;
+    // One-cell images only sit on their top-left cell and absolute ones on none
+    let needs_cell = |key: &str| match anchor.as_str() {
+        "two_cell" => true,
+        "one_cell" => key.starts_with("from_"),
+        "absolute" => false,
+        _ => true,
+    };
+    let cell = |key: &str| -> PyResult<usize> {
+        match dict.get_item(key)? {
+            Some(v) => v.extract(),
+            None if !needs_cell(key) => Ok(0),
+            None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "{} images need '{}'", anchor, key
+            ))),
+        }
+    };
     
-    let position = ImagePosition { from_col, from_row, to_col, to_row };
+    let position = ImagePosition {
+        from_col: cell("from_col")?,
+        from_row: cell("from_row")?,
+        to_col: cell("to_col")?,
+        to_row: cell("to_row")?,
+        col_off: extract_image_length(dict, "col_off")?.unwrap_or(0),
+        row_off: extract_image_length(dict, "row_off")?.unwrap_or(0),
+    };
     
     let image = if let Some(path) = dict.get_item("path")? {
         let path_str: String = path.extract()?;
@@ -1404,5 +4254,137 @@ fn extract_image(dict: &Bound<PyDict>) -> PyResult<ExcelImage> {
         return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Image must have 'path' or 'data'"));
     };
     
-    Ok(image)
-}
\ No newline at end of file
+    // PNG shown for an SVG by apps that can't draw it: a path or bytes
+    let fallback = match dict.get_item("fallback")? {
+        Some(_) if !image.is_svg() => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Only SVG images take a 'fallback'"
+        )),
+        Some(value) => Some(match value.extract::<String>() {
+            Ok(path) => std::fs::read(&path)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to read fallback image: {}", e)))?,
+            Err(_) => value.extract::<Vec<u8>>()?,
+        }),
+        None => None,
+    };
+    
+    let anchor = extract_image_anchor(dict, &anchor, &image)?;
+    Ok(ExcelImage { anchor, fallback, ..image })
+}
+
+/// "anchor": "two_cell" (stretched between from and to cells), "one_cell"
+/// (fixed size from the from cell) or "absolute" (fixed size at "x"/"y" on the
+/// sheet). Fixed sizes come from "width"/"height" (one of them keeps the aspect
+/// ratio) or else the image itself, times "scale".
+fn extract_image_anchor(dict: &Bound<PyDict>, anchor: &str, image: &ExcelImage) -> PyResult<ImageAnchor> {
+    let sized = dict.contains("width")? || dict.contains("height")? || dict.contains("scale")?;
+    match anchor {
+        "two_cell" if sized => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "two_cell images are sized by their from and to cells; use anchor 'one_cell' for width, height or scale"
+        )),
+        "two_cell" => return Ok(ImageAnchor::TwoCell),
+        "one_cell" | "absolute" => {}
+        other => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid image anchor '{}': expected 'two_cell', 'one_cell' or 'absolute'", other
+        ))),
+    }
+    
+    let scale: f64 = dict.get_item("scale")?.map(|v| v.extract()).unwrap_or(Ok(1.0))?;
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Image scale must be above 0"));
+    }
+    let (width, height) = match (extract_image_length(dict, "width")?, extract_image_length(dict, "height")?) {
+        (Some(width), Some(height)) => (width, height),
+        (width, height) => {
+            let (native_width, native_height) = image.native_size().ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                format!("Can't read the size of this {} image; give 'width' and 'height'", image.extension)
+            ))?;
+            match (width, height) {
+                (Some(width), None) => (width, width * native_height / native_width.max(1)),
+                (None, Some(height)) => (height * native_width / native_height.max(1), height),
+                _ => (native_width, native_height),
+            }
+        }
+    };
+    let width = (width as f64 * scale).round() as u64;
+    let height = (height as f64 * scale).round() as u64;
+    
+    if anchor == "one_cell" {
+        return Ok(ImageAnchor::OneCell { width, height });
+    }
+    Ok(ImageAnchor::Absolute {
+        x: extract_image_length(dict, "x")?.unwrap_or(0),
+        y: extract_image_length(dict, "y")?.unwrap_or(0),
+        width,
+        height,
+    })
+}
+
+/// Image sizes and offsets in EMU: a number of pixels (at 96 DPI) or a string
+/// like "120px" or "3.5cm"
+fn extract_image_length(dict: &Bound<PyDict>, key: &str) -> PyResult<Option<u64>> {
+    const EMU_PER_PIXEL: f64 = 9525.0;
+    const EMU_PER_CM: f64 = 360000.0;
+    let Some(value) = dict.get_item(key)? else {
+        return Ok(None);
+    };
+    let emu = match value.extract::<f64>() {
+        Ok(pixels) => Some(pixels * EMU_PER_PIXEL),
+        Err(_) => {
+            let text: String = value.extract()?;
+            let text = text.trim();
+            if let Some(cm) = text.strip_suffix("cm") {
+                cm.trim().parse::<f64>().ok().map(|cm| cm * EMU_PER_CM)
+            } else if let Some(pixels) = text.strip_suffix("px") {
+                pixels.trim().parse::<f64>().ok().map(|pixels| pixels * EMU_PER_PIXEL)
+            } else {
+                None
+            }
+        }
+    };
+    match emu {
+        Some(emu) if emu.is_finite() && emu >= 0.0 => Ok(Some(emu.round() as u64)),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid image {} {}: expected pixels or a size like '120px' or '3.5cm'", key, value
+        ))),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::exceptions::PyValueError;
+
+    #[test]
+    fn out_of_range_a1_ranges_raise_value_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let dict = |items: &[(&str, &str)]| {
+                let dict = PyDict::new(py);
+                for (key, value) in items {
+                    dict.set_item(key, value).unwrap();
+                }
+                dict
+            };
+
+            let validation = dict(&[("range", "A1:ZZZZ9"), ("type", "list")]);
+            let err = extract_data_validation(&validation).err().unwrap();
+            assert!(err.is_instance_of::<PyValueError>(py), "{err}");
+
+            let table = dict(&[("name", "Sales"), ("range", "A1:ZZZZ9")]);
+            let err = extract_table(&table).err().unwrap();
+            assert!(err.is_instance_of::<PyValueError>(py), "{err}");
+
+            let chart = dict(&[("chart_type", "column"), ("data_range", "A1:ZZZZ9")]);
+            let err = extract_chart(&chart).err().unwrap();
+            assert!(err.is_instance_of::<PyValueError>(py), "{err}");
+
+            // Raised from the call site instead of dropping the chart
+            let columns = PyDict::new(py);
+            columns.set_item("a", vec![1, 2]).unwrap();
+            let path = std::env::temp_dir().join("jetxl_bad_chart_range.xlsx");
+            let err = write_sheet(py, columns, path.to_string_lossy().to_string(), None, Some(vec![chart]))
+                .err().unwrap();
+            assert!(err.is_instance_of::<PyValueError>(py), "{err}");
+            assert!(!path.exists());
+        });
+    }
+}
@@ -1,6 +1,11 @@
 use arrow_array::Array;
+use arrow_array::timezone::Tz;
 use arrow_schema::DataType;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use crate::zip::CompressionLevel;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 fn get_builtin_format_name(code: &str) -> Option<&'static str> {
     match code.to_lowercase().as_str() {
@@ -17,6 +22,7 @@ fn get_builtin_format_name(code: &str) -> Option<&'static str> {
         "# ?/?" => Some("fraction"),
         "# ??/??" => Some("fraction_two_digits"),
         "#,##0" => Some("thousands"),
+        "\"yes\";\"yes\";\"no\"" => Some("bool_yes_no"),
         _ => None,
     }
 }
@@ -56,6 +62,14 @@ pub enum NumberFormat {
     FractionTwoDigits,
     ThousandsSeparator,
     PercentageInteger,
+    /// Text ("@"): typed values stay text instead of turning into numbers
+    Text,
+    
+    // Boolean display; boolean columns with these are written as 1/0, since
+    // Excel ignores number formats on TRUE/FALSE cells
+    BoolYesNo,
+    /// Full format code, e.g. "On";"On";"Off"
+    BoolLabels(String),
     
     // Custom format string
     Custom(String),
@@ -82,15 +96,100 @@ impl NumberFormat {
             NumberFormat::FractionTwoDigits => (177, None),    // was: Some("# ??/??")
             NumberFormat::ThousandsSeparator => (173, None),
             NumberFormat::PercentageInteger => (174, None),
+            NumberFormat::Text => (49, None),
+            NumberFormat::BoolYesNo => (178, None),
+            NumberFormat::BoolLabels(ref code) => (0, Some(code.as_str())),
             NumberFormat::Custom(ref code) => (0, Some(code.as_str())), // ID assigned by registry
         }
     }
     
+    /// Format showing labels instead of TRUE/FALSE for boolean columns
+    pub fn bool_labels(true_label: &str, false_label: &str) -> Self {
+        NumberFormat::BoolLabels(format!("\"{0}\";\"{0}\";\"{1}\"", true_label, false_label))
+    }
+    
+    /// Default format for an Arrow decimal column: as many places as its
+    /// scale (Excel formats stop at 30)
+    pub fn decimal_places(scale: i8) -> Self {
+        match scale {
+            ..=0 => NumberFormat::Integer,
+            2 => NumberFormat::Decimal2,
+            4 => NumberFormat::Decimal4,
+            places => NumberFormat::Custom(format!("0.{}", "0".repeat(places.min(30) as usize))),
+        }
+    }
+    
+    /// Named locale currency, "currency_<iso>" or "currency_<iso>_rounded",
+    /// with the symbol where that currency's locale puts it
+    pub fn currency(iso: &str, rounded: bool) -> Option<Self> {
+        if iso == "usd" {
+            return Some(if rounded { NumberFormat::CurrencyRounded } else { NumberFormat::Currency });
+        }
+        let (_, before, after, minor_units) = CURRENCIES.iter().find(|(code, ..)| *code == iso)?;
+        let decimals = if *minor_units && !rounded { ".00" } else { "" };
+        Some(NumberFormat::Custom(format!("{}#,##0{}{}", before, decimals, after)))
+    }
+    
+    /// The format with a separate negative section, for P&L style negatives:
+    /// Decimal2 with `RedParens` is `0.00;[Red](0.00)`. None for formats
+    /// that aren't plain numbers or already have sections.
+    pub fn with_negatives(&self, style: NegativeStyle) -> Option<Self> {
+        let code = match self {
+            NumberFormat::Integer | NumberFormat::Decimal2 | NumberFormat::Decimal4
+            | NumberFormat::Percentage | NumberFormat::PercentageDecimal | NumberFormat::PercentageInteger
+            | NumberFormat::Currency | NumberFormat::CurrencyRounded | NumberFormat::ThousandsSeparator => {
+                builtin_format_code(self.fmt_info().0)?
+            }
+            NumberFormat::Custom(code) if !code.contains(';') => code.as_str(),
+            _ => return None,
+        };
+        Some(NumberFormat::Custom(match style {
+            NegativeStyle::RedParens => format!("{0};[Red]({0})", code),
+            NegativeStyle::RedMinus => format!("{0};[Red]-{0}", code),
+            NegativeStyle::Parens => format!("{0};({0})", code),
+        }))
+    }
+    
+    pub fn is_bool_display(&self) -> bool {
+        matches!(self, NumberFormat::BoolYesNo | NumberFormat::BoolLabels(_))
+    }
+    
     pub fn is_custom(&self) -> bool {
         matches!(self, NumberFormat::Custom(_))
     }
 }
 
+/// How `NumberFormat::with_negatives` shows negative numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NegativeStyle {
+    /// Red, in parentheses: "_red_negative"
+    RedParens,
+    /// Red, with a minus sign: "_red_minus"
+    RedMinus,
+    /// In parentheses: "_parens"
+    Parens,
+}
+
+/// (ISO code, text before the number, text after it, has minor units).
+/// `[$sym-LCID]` pins the symbol to its locale for symbols shared by
+/// several currencies.
+const CURRENCIES: [(&str, &str, &str, bool); 14] = [
+    ("eur", "€", "", true),
+    ("gbp", "£", "", true),
+    ("jpy", "¥", "", false),
+    ("cny", "[$¥-804]", "", true),
+    ("inr", "[$₹-4009]", "", true),
+    ("krw", "₩", "", false),
+    ("chf", "[$CHF] ", "", true),
+    ("cad", "[$$-1009]", "", true),
+    ("aud", "[$$-C09]", "", true),
+    ("brl", "[$R$-416] ", "", true),
+    ("sek", "", " [$kr-41D]", true),
+    ("nok", "", " [$kr-414]", true),
+    ("dkk", "", " [$kr.-406]", true),
+    ("pln", "", " [$zł-415]", true),
+];
+
 #[derive(Debug, Clone)]
 pub struct MergeRange {
     pub start_row: usize,
@@ -102,6 +201,8 @@ pub struct MergeRange {
 #[derive(Debug, Clone)]
 pub enum ValidationType {
     List(Vec<String>),
+    /// List whose items come from a cell range, e.g. "Lookups!$A$1:$A$50"
+    ListRef(String),
     WholeNumber { min: i64, max: i64 },
     Decimal { min: f64, max: f64 },
     TextLength { min: usize, max: usize },
@@ -116,7 +217,22 @@ pub struct DataValidation {
     pub validation_type: ValidationType,
     pub error_title: Option<String>,
     pub error_message: Option<String>,
+    pub prompt_title: Option<String>,
+    pub prompt_message: Option<String>,
+    pub show_input_message: bool,
     pub show_dropdown: bool,
+    pub error_style: ValidationErrorStyle,
+    pub show_error_message: bool,
+    pub allow_blank: bool,
+}
+
+/// Alert shown for invalid input: Stop rejects it, Warning and Information
+/// let the user keep it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValidationErrorStyle {
+    Stop,
+    Warning,
+    Information,
 }
 
 #[derive(Debug, Clone)]
@@ -127,7 +243,7 @@ pub struct Hyperlink {
     pub display: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CellStyle {
     pub font: Option<FontStyle>,
     pub fill: Option<FillStyle>,
@@ -136,31 +252,163 @@ pub struct CellStyle {
     pub number_format: Option<NumberFormat>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct FontStyle {
     pub bold: bool,
     pub italic: bool,
-    pub underline: bool,
+    pub underline: Option<Underline>,
+    pub strike: bool,
+    pub vert_align: Option<VertAlign>,
     pub size: Option<f64>,
-    pub color: Option<String>,
+    pub color: Option<Color>,
     pub name: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Float fields compare by bit pattern (see `f64_key`) so equality stays
+// total and agrees with the hash, even for NaN
+impl PartialEq for FontStyle {
+    fn eq(&self, other: &Self) -> bool {
+        self.bold == other.bold
+            && self.italic == other.italic
+            && self.underline == other.underline
+            && self.strike == other.strike
+            && self.vert_align == other.vert_align
+            && f64_key(self.size) == f64_key(other.size)
+            && self.color == other.color
+            && self.name == other.name
+    }
+}
+
+impl Eq for FontStyle {}
+
+impl Hash for FontStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bold.hash(state);
+        self.italic.hash(state);
+        self.underline.hash(state);
+        self.strike.hash(state);
+        self.vert_align.hash(state);
+        f64_key(self.size).hash(state);
+        self.color.hash(state);
+        self.name.hash(state);
+    }
+}
+
+/// Font, fill or border color
+#[derive(Debug, Clone)]
+pub enum Color {
+    /// "AARRGGBB" hex
+    Rgb(String),
+    /// Slot in the workbook theme's color scheme (0-11), lightened by a
+    /// positive tint or darkened by a negative one, so it follows theme changes
+    Theme { theme: u32, tint: Option<f64> },
+    /// Legacy palette slot (0-63), the default palette or `indexed_colors`
+    Indexed(u32),
+}
+
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Color::Rgb(a), Color::Rgb(b)) => a == b,
+            (Color::Theme { theme: a, tint: a_tint }, Color::Theme { theme: b, tint: b_tint }) => {
+                a == b && f64_key(*a_tint) == f64_key(*b_tint)
+            }
+            (Color::Indexed(a), Color::Indexed(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Color {}
+
+impl Hash for Color {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Color::Rgb(argb) => argb.hash(state),
+            Color::Theme { theme, tint } => {
+                theme.hash(state);
+                f64_key(*tint).hash(state);
+            }
+            Color::Indexed(index) => index.hash(state),
+        }
+    }
+}
+
+/// Bit pattern a style float is compared and hashed by. -0.0 folds into
+/// 0.0; a NaN equals itself, so a style holding one is still found again.
+fn f64_key(value: Option<f64>) -> Option<u64> {
+    value.map(|v| (v + 0.0).to_bits())
+}
+
+impl Color {
+    /// Attributes of a `<color>`-like element, e.g. ` rgb="FFFF0000"`
+    pub fn xml_attrs(&self) -> String {
+        match self {
+            Color::Rgb(argb) => {
+                let mut attrs = String::from(" rgb=\"");
+                xml_escape_format_code(argb, &mut attrs);
+                attrs.push('"');
+                attrs
+            }
+            Color::Theme { theme, tint: Some(tint) } => format!(" theme=\"{}\" tint=\"{}\"", theme, tint),
+            Color::Theme { theme, tint: None } => format!(" theme=\"{}\"", theme),
+            Color::Indexed(index) => format!(" indexed=\"{}\"", index),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Underline {
+    Single,
+    Double,
+    /// Accounting underlines run the full cell width below the descenders
+    SingleAccounting,
+    DoubleAccounting,
+}
+
+impl Underline {
+    /// `<u>` element for fonts, dxfs and rich text runs
+    pub fn xml(&self) -> &'static str {
+        match self {
+            Underline::Single => "<u/>",
+            Underline::Double => "<u val=\"double\"/>",
+            Underline::SingleAccounting => "<u val=\"singleAccounting\"/>",
+            Underline::DoubleAccounting => "<u val=\"doubleAccounting\"/>",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VertAlign {
+    Superscript,
+    Subscript,
+}
+
+impl VertAlign {
+    pub fn xml(&self) -> &'static str {
+        match self {
+            VertAlign::Superscript => "<vertAlign val=\"superscript\"/>",
+            VertAlign::Subscript => "<vertAlign val=\"subscript\"/>",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FillStyle {
     pub pattern_type: PatternType,
-    pub fg_color: Option<String>,
-    pub bg_color: Option<String>,
+    pub fg_color: Option<Color>,
+    pub bg_color: Option<Color>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PatternType {
     None,
     Solid,
     Gray125,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BorderStyle {
     pub left: Option<BorderSide>,
     pub right: Option<BorderSide>,
@@ -168,13 +416,13 @@ pub struct BorderStyle {
     pub bottom: Option<BorderSide>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BorderSide {
     pub style: BorderLineStyle,
-    pub color: Option<String>,
+    pub color: Option<Color>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BorderLineStyle {
     Thin,
     Medium,
@@ -182,29 +430,84 @@ pub enum BorderLineStyle {
     Double,
     Dotted,
     Dashed,
+    Hair,
+    MediumDashed,
+    DashDot,
+    MediumDashDot,
+    DashDotDot,
+    SlantDashDot,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AlignmentStyle {
     pub horizontal: Option<HorizontalAlignment>,
     pub vertical: Option<VerticalAlignment>,
     pub wrap_text: bool,
     pub text_rotation: Option<i32>,
+    /// Indent level (each step is about three characters wide); implies
+    /// left alignment when no horizontal alignment is set
+    pub indent: Option<u32>,
+    pub shrink_to_fit: bool,
+    /// Text direction of this cell, independent of the sheet's right_to_left
+    pub reading_order: ReadingOrder,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReadingOrder {
+    /// Follow the first strong character of the text
+    #[default]
+    Context,
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum HorizontalAlignment {
     Left,
     Center,
     Right,
     Justify,
+    /// Repeat the text to fill the cell width
+    Fill,
+    /// Center across this cell and the empty cells to its right
+    CenterContinuous,
+    /// Spread the words evenly across the cell width
+    Distributed,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl HorizontalAlignment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HorizontalAlignment::Left => "left",
+            HorizontalAlignment::Center => "center",
+            HorizontalAlignment::Right => "right",
+            HorizontalAlignment::Justify => "justify",
+            HorizontalAlignment::Fill => "fill",
+            HorizontalAlignment::CenterContinuous => "centerContinuous",
+            HorizontalAlignment::Distributed => "distributed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum VerticalAlignment {
     Top,
     Center,
     Bottom,
+    Justify,
+    Distributed,
+}
+
+impl VerticalAlignment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerticalAlignment::Top => "top",
+            VerticalAlignment::Center => "center",
+            VerticalAlignment::Bottom => "bottom",
+            VerticalAlignment::Justify => "justify",
+            VerticalAlignment::Distributed => "distributed",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -215,6 +518,39 @@ pub struct Formula {
     pub cached_value: Option<String>,
 }
 
+/// Formula written once for a block of cells. Excel adjusts its relative
+/// references for every cell, as if it were filled from the top-left cell.
+#[derive(Debug, Clone)]
+pub struct SharedFormula {
+    pub start_row: usize,
+    pub start_col: usize,
+    pub end_row: usize,
+    pub end_col: usize,
+    /// Formula of the top-left cell, without the leading "="
+    pub formula: String,
+}
+
+impl SharedFormula {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.start_row..=self.end_row).contains(&row) && (self.start_col..=self.end_col).contains(&col)
+    }
+}
+
+/// One segment of a rich text cell; `font` None keeps the cell's own font
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub font: Option<FontStyle>,
+}
+
+/// Cell whose text mixes fonts, written in place of the data value
+#[derive(Debug, Clone)]
+pub struct RichTextCell {
+    pub row: usize,
+    pub col: usize,
+    pub runs: Vec<TextRun>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConditionalFormat {
     pub start_row: usize,
@@ -224,6 +560,8 @@ pub struct ConditionalFormat {
     pub rule: ConditionalRule,
     pub style: CellStyle,
     pub priority: u32,
+    /// Skip lower-priority rules on cells where this one matches
+    pub stop_if_true: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -239,6 +577,53 @@ pub struct ExcelTable {
     pub show_header_row: bool,
     pub show_totals_row: bool,
     pub column_names: Vec<String>, // Auto-generated from headers if not provided
+    /// Text in the first column of the totals row (default "Total")
+    pub totals_row_label: Option<String>,
+    /// Totals row function by column name; empty sums (or counts) the last column like Excel does
+    pub totals_row_functions: Vec<(String, TotalsRowFunction)>,
+}
+
+/// Aggregate shown in a table's totals row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TotalsRowFunction {
+    Sum,
+    Average,
+    Count,
+    CountNums,
+    Max,
+    Min,
+    StdDev,
+    Var,
+}
+
+impl TotalsRowFunction {
+    /// `totalsRowFunction` attribute value
+    pub fn xml_name(self) -> &'static str {
+        match self {
+            TotalsRowFunction::Sum => "sum",
+            TotalsRowFunction::Average => "average",
+            TotalsRowFunction::Count => "count",
+            TotalsRowFunction::CountNums => "countNums",
+            TotalsRowFunction::Max => "max",
+            TotalsRowFunction::Min => "min",
+            TotalsRowFunction::StdDev => "stdDev",
+            TotalsRowFunction::Var => "var",
+        }
+    }
+
+    /// SUBTOTAL function number that skips filtered-out rows
+    pub fn subtotal_code(self) -> u32 {
+        match self {
+            TotalsRowFunction::Average => 101,
+            TotalsRowFunction::Count => 103,
+            TotalsRowFunction::CountNums => 102,
+            TotalsRowFunction::Max => 104,
+            TotalsRowFunction::Min => 105,
+            TotalsRowFunction::StdDev => 107,
+            TotalsRowFunction::Sum => 109,
+            TotalsRowFunction::Var => 110,
+        }
+    }
 }
 
 impl ExcelTable {
@@ -255,16 +640,139 @@ impl ExcelTable {
             show_header_row: true,
             show_totals_row: false,
             column_names: Vec::new(),
+            totals_row_label: None,
+            totals_row_functions: Vec::new(),
+        }
+    }
+
+    /// Final sheet range: fills in auto (0) end row/column and accounts for
+    /// the header row inserted above tables that start below row 1 and the
+    /// totals row added below the last data row. `num_cols` is the number of
+    /// columns an auto-sized table spans from its start column.
+    pub fn resolved_range(&self, data_rows: usize, num_cols: usize) -> (usize, usize, usize, usize) {
+        let (start_row, start_col, mut end_row, mut end_col) = self.range;
+        if end_row == 0 {
+            end_row = start_row + data_rows;
+        } else if start_row > 1 {
+            end_row += 1;
+        }
+        if self.show_totals_row {
+            end_row += 1;
+        }
+        if end_col == 0 && num_cols > 0 {
+            end_col = start_col + num_cols - 1;
+        }
+        (start_row, start_col, end_row, end_col)
+    }
+
+    /// What each table column shows in the totals row. Without
+    /// `totals_row_functions` the last column is summed, or counted when
+    /// `last_column_numeric` is false, as Excel does when the row is turned on.
+    pub fn totals_row_cells<'a>(&'a self, column_names: &[String], last_column_numeric: bool) -> Vec<TotalsCell<'a>> {
+        let mut cells: Vec<TotalsCell> = column_names.iter()
+            .map(|name| self.totals_row_functions.iter()
+                .find(|(column, _)| column == name)
+                .map_or(TotalsCell::Empty, |&(_, function)| TotalsCell::Function(function)))
+            .collect();
+        if self.totals_row_functions.is_empty() && cells.len() > 1 {
+            let function = if last_column_numeric { TotalsRowFunction::Sum } else { TotalsRowFunction::Count };
+            *cells.last_mut().unwrap() = TotalsCell::Function(function);
+        }
+        if let Some(first @ TotalsCell::Empty) = cells.first_mut() {
+            *first = TotalsCell::Label(self.totals_row_label.as_deref().unwrap_or("Total"));
         }
+        cells
     }
 }
 
+/// One cell of a table totals row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TotalsCell<'a> {
+    Empty,
+    Label(&'a str),
+    Function(TotalsRowFunction),
+}
+
 #[derive(Debug, Clone)]
 pub enum ConditionalRule {
     CellValue { operator: ComparisonOperator, value: String },
-    ColorScale { min_color: String, max_color: String, mid_color: Option<String> },
-    DataBar { color: String, show_value: bool },
+    ColorScale {
+        min_color: String,
+        max_color: String,
+        mid_color: Option<String>,
+        min: Cfvo,
+        mid: Cfvo, // only used with mid_color
+        max: Cfvo,
+    },
+    DataBar {
+        color: String,
+        show_value: bool,
+        min: Cfvo,
+        max: Cfvo,
+        border_color: Option<String>,
+        negative_color: Option<String>,
+        negative_border_color: Option<String>,
+        gradient: bool,
+        axis_color: Option<String>,
+        axis_position: DataBarAxisPosition,
+    },
     Top10 { rank: u32, bottom: bool },
+    Expression { formula: String }, // e.g. "$G2=\"FAIL\"", relative to the range's top-left cell
+    ContainsText { text: String },
+    NotContainsText { text: String },
+    BeginsWith { text: String },
+    EndsWith { text: String },
+    DuplicateValues,
+    UniqueValues,
+    ContainsBlanks,
+    TimePeriod { period: TimePeriod },
+}
+
+/// Threshold of a data bar or color scale (`<cfvo>`)
+#[derive(Debug, Clone)]
+pub struct Cfvo {
+    pub kind: CfvoType,
+    pub value: Option<String>, // number, percent or formula; unused for min/max
+}
+
+impl Cfvo {
+    pub fn new(kind: CfvoType) -> Self {
+        Self { kind, value: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CfvoType {
+    Min,
+    Max,
+    AutoMin, // Excel 2010+: zero or the lowest negative value
+    AutoMax,
+    Num,
+    Percent,
+    Percentile,
+    Formula,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataBarAxisPosition {
+    Automatic,
+    Middle,
+    None,
+}
+
+/// "A Date Occurring" periods, relative to TODAY()
+#[derive(Debug, Clone)]
+pub enum TimePeriod {
+    Yesterday,
+    Today,
+    Tomorrow,
+    Last7Days,
+    LastWeek,
+    ThisWeek,
+    NextWeek,
+    LastMonth,
+    ThisMonth,
+    NextMonth,
 }
 
 #[derive(Debug, Clone)]
@@ -288,26 +796,295 @@ pub struct StyleConfig {
     pub column_widths: Option<HashMap<String, ColumnWidth>>,
     pub auto_width: bool,
     pub column_formats: Option<HashMap<String, NumberFormat>>,
+    /// Apply `column_formats` to table totals rows too; by default they
+    /// only reach data cells
+    pub format_totals_rows: bool,
+    /// Also put `column_formats` on the `<col>` entries, so cells that aren't
+    /// written (empty cells, rows typed below the data) pick them up too
+    pub format_whole_columns: bool,
+    /// Columns written as text whatever their type, formatted "@" unless
+    /// `column_formats` says otherwise
+    pub text_columns: HashSet<String>,
+    /// Formats and widths by column position, checked before the name-keyed
+    /// maps. Set when tables are placed side by side and names may repeat.
+    pub column_formats_by_index: HashMap<usize, NumberFormat>,
+    pub column_widths_by_index: HashMap<usize, ColumnWidth>,
+    /// Timezones timestamp columns are displayed in. Values are read as UTC
+    /// instants and written as wall-clock time in the zone.
+    pub column_timezones: HashMap<String, Tz>,
+    pub column_timezones_by_index: HashMap<usize, Tz>,
     pub merge_cells: Vec<MergeRange>,
     pub data_validations: Vec<DataValidation>,
     pub hyperlinks: Vec<Hyperlink>,
     pub row_heights: Option<HashMap<usize, f64>>,
+    /// Number format for every data cell of a row, e.g. a margin row of a
+    /// transposed layout; wins over `column_formats` on that row
+    pub row_formats: HashMap<usize, NumberFormat>,
     pub cell_styles: Vec<CellStyleMap>,
+    /// Styles over ranges; a later range wins where ranges overlap and
+    /// `cell_styles` win over both
+    pub range_styles: Vec<RangeStyle>,
     pub formulas: Vec<Formula>,
+    pub shared_formulas: Vec<SharedFormula>,
+    /// Formula templates by column name, filled into every data row with
+    /// `{row}` replaced by the row number. Stored without the leading "="
+    pub column_formulas: HashMap<String, String>,
+    pub rich_text: Vec<RichTextCell>,
     pub conditional_formats: Vec<ConditionalFormat>,
-    pub cond_format_dxf_ids: HashMap<usize, u32>,
     pub tables: Vec<ExcelTable>,
     pub charts: Vec<ExcelChart>,
     pub images: Vec<ExcelImage>,
     pub gridlines_visible: bool,
     pub zoom_scale: Option<u16>, // 10-400
+    pub selection: Option<SheetSelection>,
+    /// Cell scrolled to the window's top-left (row 1-based, column 0-based)
+    pub top_left_cell: Option<(usize, usize)>,
     pub tab_color: Option<String>, // RGB like "FFFF0000"
     pub default_row_height: Option<f64>,
     pub hidden_columns: HashSet<usize>,
     pub hidden_rows: HashSet<usize>,
+    /// Outline groups; rows are sheet rows like hidden_rows, columns 0-based
+    pub row_groups: Vec<OutlineGroup>,
+    pub col_groups: Vec<OutlineGroup>,
     pub right_to_left: bool,
     pub data_start_row: usize,
     pub header_content: Vec<(usize, usize, String)>,
+    pub pre_1900_dates: Pre1900Dates,
+    /// Write date serials in the 1904 date system; must match
+    /// `WorkbookConfig::date_1904`
+    pub date_1904: bool,
+    /// Joins the elements of list columns into one text cell
+    pub list_separator: String,
+    pub binary_encoding: BinaryEncoding,
+    /// Longest text written for a binary cell; longer values are cut and
+    /// end in "…"
+    pub binary_max_length: Option<usize>,
+    pub invalid_text: InvalidText,
+    /// Autofilter criteria by column name; setting any turns the autofilter on
+    pub filter_columns: HashMap<String, FilterCriteria>,
+    pub sort_state: Option<SortState>,
+    /// Data rows per sheet. Longer data continues on "Name (2)", "Name (3)",
+    /// ... sheets that repeat the header row under a "Continued from" note.
+    pub split_rows: Option<usize>,
+}
+
+/// Version of the option semantics. Bumped whenever an existing option
+/// changes meaning, so stored report definitions can be checked against it
+/// and migrated.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Workbook-level settings that apply to the whole file rather than one sheet
+#[derive(Debug, Clone, Default)]
+pub struct WorkbookConfig {
+    /// "Password to modify" (write reservation). The file still opens without
+    /// a password but Excel asks for it before allowing edits.
+    pub modify_password: Option<String>,
+    pub read_only_recommended: bool,
+    /// docProps/app.xml overrides. Application defaults to "jetxl <version>" and
+    /// AppVersion to "16.0300"; Company is omitted when unset.
+    pub application: Option<String>,
+    pub company: Option<String>,
+    pub app_version: Option<String>,
+    /// Threads used to compress the archive; 0 uses every core
+    pub zip_threads: usize,
+    /// Deflate level 0-9 of every part; None uses the fast level 1 and 0
+    /// stores the parts uncompressed
+    pub compression: Option<u32>,
+    /// Reports rows written while the workbook is generated
+    pub progress: Option<Progress>,
+    /// Add a generated "Summary" sheet in front of the data sheets
+    pub summary_sheet: Option<SummarySheet>,
+    /// Custom legacy palette (`<indexedColors>`), as normalized ARGB. Style
+    /// colors that match an entry are written by index instead of rgb.
+    pub indexed_colors: Vec<String>,
+    /// Overrides for the generated xl/theme/theme1.xml
+    pub theme: WorkbookTheme,
+    /// Sheet names in tab order; sheets not listed follow in their original order
+    pub sheet_order: Vec<String>,
+    /// Tab the workbook opens on and the leftmost tab shown in the tab bar,
+    /// both as positions in the final tab order
+    pub active_sheet: usize,
+    pub first_sheet: usize,
+    /// 1904 date system (`<workbookPr date1904="1"/>`), as used by workbooks
+    /// from old Mac Excel: serial 0 is 1904-01-01
+    pub date_1904: bool,
+}
+
+impl WorkbookConfig {
+    pub fn compression(&self) -> CompressionLevel {
+        self.compression.map(CompressionLevel::new).unwrap_or_else(CompressionLevel::fast)
+    }
+}
+
+/// Stage of a write reported to the progress callback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressPhase {
+    /// Worksheet rows are being generated
+    Writing,
+    /// Every row is generated; the remaining parts are being compressed
+    Compressing,
+    /// The file is complete
+    Done,
+}
+
+impl ProgressPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProgressPhase::Writing => "writing",
+            ProgressPhase::Compressing => "compressing",
+            ProgressPhase::Done => "done",
+        }
+    }
+}
+
+/// Called with (rows_written, total_rows, phase); total_rows is None when a
+/// streamed sheet's length is unknown. An error stops the write.
+pub type ProgressFn = dyn Fn(usize, Option<usize>, ProgressPhase) -> Result<(), String> + Send + Sync;
+
+/// Row counter shared by every sheet of a workbook, which calls back each
+/// time another `interval` data rows are written. Sheets generated in
+/// parallel report to the same counter.
+#[derive(Clone)]
+pub struct Progress {
+    callback: Arc<ProgressFn>,
+    interval: usize,
+    rows: Arc<AtomicUsize>,
+    /// usize::MAX while the total is unknown
+    total: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Progress").field("interval", &self.interval).finish_non_exhaustive()
+    }
+}
+
+impl Progress {
+    pub fn new(interval: usize, callback: Arc<ProgressFn>) -> Self {
+        Self {
+            callback,
+            interval: interval.max(1),
+            rows: Arc::new(AtomicUsize::new(0)),
+            total: Arc::new(AtomicUsize::new(usize::MAX)),
+        }
+    }
+
+    /// Rows written between two reports
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// Start counting from zero towards `total` data rows
+    pub fn begin(&self, total: Option<usize>) {
+        self.rows.store(0, Ordering::Relaxed);
+        self.total.store(total.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    /// Count `rows` more data rows, reporting when they cross an interval
+    pub fn add_rows(&self, rows: usize) -> Result<(), String> {
+        let before = self.rows.fetch_add(rows, Ordering::Relaxed);
+        let after = before + rows;
+        if after / self.interval > before / self.interval {
+            self.report(ProgressPhase::Writing)?;
+        }
+        Ok(())
+    }
+
+    pub fn report(&self, phase: ProgressPhase) -> Result<(), String> {
+        let total = self.total.load(Ordering::Relaxed);
+        (self.callback)(self.rows.load(Ordering::Relaxed), (total != usize::MAX).then_some(total), phase)
+    }
+}
+
+/// Theme overrides. Unset parts keep the default Office theme (accents
+/// 4472C4, ED7D31, A5A5A5, FFC000, 5B9BD5, 70AD47; Calibri Light / Calibri).
+#[derive(Debug, Clone, Default)]
+pub struct WorkbookTheme {
+    /// accent1-6 as "RRGGBB", replacing the defaults in order; charts' and
+    /// theme cell colors' accent references resolve to these
+    pub accents: Vec<String>,
+    /// Latin typeface for headings
+    pub major_font: Option<String>,
+    /// Latin typeface for body text
+    pub minor_font: Option<String>,
+}
+
+/// Options for the generated summary sheet
+#[derive(Debug, Clone, Default)]
+pub struct SummarySheet {
+    /// Caller-supplied provenance rows, e.g. ("Source", "warehouse.sales")
+    pub metadata: Vec<(String, String)>,
+}
+
+/// How to write dates before 1900-01-01, Excel's first serial date
+/// (1904-01-01 in the 1904 date system)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pre1900Dates {
+    Text,  // ISO 8601 inline string
+    Clamp, // 1900-01-01
+    Error,
+}
+
+/// Criteria for one autofilter column (`<filterColumn>`)
+#[derive(Debug, Clone)]
+pub enum FilterCriteria {
+    /// Show rows whose value is one of `values`, plus empty cells if `blanks`
+    Values { values: Vec<String>, blanks: bool },
+    /// Inclusive number range; dates are Excel serials
+    Range { min: Option<f64>, max: Option<f64> },
+    Top10 { rank: u32, bottom: bool, percent: bool },
+    Contains { text: String },
+}
+
+/// Active cell and selected ranges when the sheet opens (`<selection>`).
+/// Rows are 1-based and columns 0-based, as from `xml::parse_range_ref`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetSelection {
+    pub active_cell: (usize, usize),
+    pub ranges: Vec<(usize, usize, usize, usize)>,
+}
+
+/// Rows or columns folded under an outline button (`outlineLevel`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineGroup {
+    pub start: usize,
+    pub end: usize,
+    /// 1..=7; groups nested inside another use a higher level
+    pub level: u8,
+    /// Hide the group so only its expand button shows
+    pub collapsed: bool,
+}
+
+/// A sort already applied to the data (`<sortState>`). Only records the
+/// sort so Excel shows the arrows and can re-apply it; rows are not reordered.
+#[derive(Debug, Clone)]
+pub struct SortState {
+    pub start_row: usize,
+    pub start_col: usize,
+    pub end_row: usize,
+    pub end_col: usize,
+    pub keys: Vec<SortKey>, // first key sorts first
+}
+
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    pub col: usize,
+    pub descending: bool,
+}
+
+/// How to write string cells holding invalid UTF-8 or characters XML 1.0
+/// cannot store (control characters other than tab, CR and LF)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidText {
+    Replace, // U+FFFD
+    Error,
+}
+
+/// Text encoding for binary columns (hashes, ids, raw bytes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryEncoding {
+    Hex,    // lowercase, two digits per byte
+    Base64, // standard alphabet with padding
 }
 
 #[derive(Debug, Clone)]
@@ -324,6 +1101,30 @@ pub struct CellStyleMap {
     pub style: CellStyle,
 }
 
+/// One style over a rectangle of cells, for banner rows and boxed sections.
+/// Cells inside it that hold nothing are written empty so the style shows.
+#[derive(Debug, Clone)]
+pub struct RangeStyle {
+    pub start_row: usize,
+    pub start_col: usize,
+    pub end_row: usize,
+    pub end_col: usize,
+    pub style: CellStyle,
+}
+
+impl RangeStyle {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.start_row..=self.end_row).contains(&row) && (self.start_col..=self.end_col).contains(&col)
+    }
+}
+
+impl StyleConfig {
+    /// Sheet row (1-based) of the first DataFrame data row
+    pub fn first_data_row(&self) -> usize {
+        self.data_start_row.max(1) + usize::from(self.write_header_row)
+    }
+}
+
 impl Default for StyleConfig {
     fn default() -> Self {
         Self {
@@ -335,41 +1136,73 @@ impl Default for StyleConfig {
             column_widths: None,
             auto_width: false,
             column_formats: None,
+            format_totals_rows: false,
+            format_whole_columns: false,
+            text_columns: HashSet::new(),
+            column_formats_by_index: HashMap::new(),
+            column_widths_by_index: HashMap::new(),
+            column_timezones: HashMap::new(),
+            column_timezones_by_index: HashMap::new(),
             merge_cells: Vec::new(),
             data_validations: Vec::new(),
             hyperlinks: Vec::new(),
             row_heights: None,
+            row_formats: HashMap::new(),
             cell_styles: Vec::new(),
+            range_styles: Vec::new(),
             formulas: Vec::new(),
+            shared_formulas: Vec::new(),
+            column_formulas: HashMap::new(),
+            rich_text: Vec::new(),
             conditional_formats: Vec::new(),
-            cond_format_dxf_ids: HashMap::new(),
             tables: Vec::new(),
             charts: Vec::new(),
             images: Vec::new(),
             gridlines_visible: true,
             zoom_scale: None,
+            selection: None,
+            top_left_cell: None,
             tab_color: None,
             default_row_height: None,
             hidden_columns: HashSet::new(),
             hidden_rows: HashSet::new(),
+            row_groups: Vec::new(),
+            col_groups: Vec::new(),
             right_to_left: false,
             data_start_row: 0,
             header_content: Vec::new(),
+            pre_1900_dates: Pre1900Dates::Text,
+            date_1904: false,
+            list_separator: ", ".to_string(),
+            binary_encoding: BinaryEncoding::Hex,
+            binary_max_length: None,
+            invalid_text: InvalidText::Replace,
+            filter_columns: HashMap::new(),
+            sort_state: None,
+            split_rows: None,
         }
     }
 }
 
+/// Each list keeps stylesheet order; the matching map interns its entries so
+/// registering a style is a hash lookup however many styles exist
 pub struct StyleRegistry {
     fonts: Vec<FontStyle>,
+    font_ids: HashMap<FontStyle, u32>,
     fills: Vec<FillStyle>,
+    fill_ids: HashMap<FillStyle, u32>,
     borders: Vec<BorderStyle>,
+    border_ids: HashMap<BorderStyle, u32>,
     cell_xfs: Vec<CellXfEntry>,
+    cell_xf_ids: HashMap<CellXfEntry, u32>,
     dxfs: Vec<CellStyle>,
+    dxf_ids: HashMap<CellStyle, u32>,
     custom_num_fmts: Vec<(u32, String)>, // (id, format_code)
+    custom_num_fmt_ids: HashMap<String, u32>,
     next_custom_fmt_id: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CellXfEntry {
     num_fmt_id: u32,
     font_id: u32,
@@ -379,57 +1212,71 @@ struct CellXfEntry {
 }
 
 impl StyleRegistry {
+    /// Start with only what every stylesheet needs: the Normal font, the two
+    /// mandatory fills, an empty border and the default cell xf. Everything
+    /// else is added when a sheet actually references it.
     pub fn new() -> Self {
         let mut registry = Self {
-            fonts: vec![
-                FontStyle { bold: false, italic: false, underline: false, size: Some(11.0), color: None, name: Some("Calibri".to_string()) },
-                FontStyle { bold: true, italic: false, underline: false, size: Some(11.0), color: None, name: Some("Calibri".to_string()) },
-                FontStyle { bold: false, italic: false, underline: true, size: Some(11.0), color: Some("FF0000FF".to_string()), name: Some("Calibri".to_string()) },
-            ],
-            fills: vec![
-                FillStyle { pattern_type: PatternType::None, fg_color: None, bg_color: None },
-                FillStyle { pattern_type: PatternType::Gray125, fg_color: None, bg_color: None },
-                FillStyle { pattern_type: PatternType::Solid, fg_color: Some("FFD9D9D9".to_string()), bg_color: None },
-            ],
-            borders: vec![
-                BorderStyle { left: None, right: None, top: None, bottom: None },
-            ],
-            cell_xfs: vec![],
+            fonts: Vec::new(),
+            font_ids: HashMap::new(),
+            fills: Vec::new(),
+            fill_ids: HashMap::new(),
+            borders: Vec::new(),
+            border_ids: HashMap::new(),
+            cell_xfs: Vec::new(),
+            cell_xf_ids: HashMap::new(),
             dxfs: Vec::new(),
+            dxf_ids: HashMap::new(),
             custom_num_fmts: Vec::new(),
-            next_custom_fmt_id: 178,
+            custom_num_fmt_ids: HashMap::new(),
+            next_custom_fmt_id: 179,
         };
-        
-        registry.build_default_xfs();
+        registry.get_or_add_font(&FontStyle { bold: false, italic: false, underline: None, strike: false, vert_align: None, size: Some(11.0), color: None, name: Some("Calibri".to_string()) });
+        registry.get_or_add_fill(&FillStyle { pattern_type: PatternType::None, fg_color: None, bg_color: None });
+        registry.get_or_add_fill(&FillStyle { pattern_type: PatternType::Gray125, fg_color: None, bg_color: None });
+        registry.get_or_add_border(&BorderStyle { left: None, right: None, top: None, bottom: None });
+        intern(&mut registry.cell_xfs, &mut registry.cell_xf_ids, CellXfEntry { num_fmt_id: 0, font_id: 0, fill_id: 0, border_id: 0, alignment: None });
         registry
     }
     
-    fn build_default_xfs(&mut self) {
-        self.cell_xfs = vec![
-            CellXfEntry { num_fmt_id: 0, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 164, font_id: 0, fill_id: 0, border_id: 0, alignment: None }, // datetime
-            CellXfEntry { num_fmt_id: 0, font_id: 1, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 0, font_id: 1, fill_id: 2, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 168, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 9, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 10, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 165, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 166, font_id: 0, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 0, font_id: 2, fill_id: 0, border_id: 0, alignment: None },
-            CellXfEntry { num_fmt_id: 14, font_id: 0, fill_id: 0, border_id: 0, alignment: None }, 
-        ];
+    /// Style id for a cell that only carries a number format
+    pub fn register_number_format(&mut self, fmt: &NumberFormat) -> Result<u32, String> {
+        self.register_cell_style(&CellStyle {
+            font: None,
+            fill: None,
+            border: None,
+            alignment: None,
+            number_format: Some(fmt.clone()),
+        })
+    }
+    
+    /// Style id for the bold header row written with `styled_headers`
+    pub fn register_header_style(&mut self) -> u32 {
+        let font = FontStyle { bold: true, italic: false, underline: None, strike: false, vert_align: None, size: Some(11.0), color: None, name: Some("Calibri".to_string()) };
+        self.register_font_only(font)
     }
+    
+    /// Style id for hyperlink cells (blue, underlined)
+    pub fn register_hyperlink_style(&mut self) -> u32 {
+        let font = FontStyle { bold: false, italic: false, underline: Some(Underline::Single), strike: false, vert_align: None, size: Some(11.0), color: Some(Color::Rgb("FF0000FF".to_string())), name: Some("Calibri".to_string()) };
+        self.register_font_only(font)
+    }
+    
+    fn register_font_only(&mut self, font: FontStyle) -> u32 {
+        let style = CellStyle { font: Some(font), fill: None, border: None, alignment: None, number_format: None };
+        // Cannot fail: only number formats are validated
+        self.register_cell_style(&style).unwrap_or(0)
+    }
+    
     fn get_or_add_num_fmt(&mut self, fmt: &NumberFormat) -> Result<u32, String> {
         let (base_id, code_opt) = fmt.fmt_info();
         
         match code_opt {
             None => Ok(base_id),
             Some(code) => {
-                // Check for duplicates FIRST - O(n) but n is small and avoids validation
-                for (id, existing_code) in &self.custom_num_fmts {
-                    if existing_code == code {
-                        return Ok(*id);
-                    }
+                // Check for duplicates FIRST - avoids validating known codes
+                if let Some(&id) = self.custom_num_fmt_ids.get(code) {
+                    return Ok(id);
                 }
                 
                 // Validate only NEW formats
@@ -448,6 +1295,7 @@ impl StyleRegistry {
                 // Add new custom format
                 let id = self.next_custom_fmt_id;
                 self.custom_num_fmts.push((id, code.to_string()));
+                self.custom_num_fmt_ids.insert(code.to_string(), id);
                 self.next_custom_fmt_id += 1;
                 Ok(id)
             }
@@ -487,59 +1335,111 @@ impl StyleRegistry {
             alignment: style.alignment.clone(),
         };
         
-        for (idx, xf) in self.cell_xfs.iter().enumerate() {
-            if xf.num_fmt_id == entry.num_fmt_id 
-                && xf.font_id == entry.font_id 
-                && xf.fill_id == entry.fill_id 
-                && xf.border_id == entry.border_id 
-                && xf.alignment == entry.alignment {
-                return Ok(idx as u32);
-            }
+        Ok(intern(&mut self.cell_xfs, &mut self.cell_xf_ids, entry))
+    }
+    
+    pub fn register_dxf(&mut self, style: &CellStyle) -> Result<u32, String> {
+        if let Some(&idx) = self.dxf_ids.get(style) {
+            return Ok(idx);
         }
-        
-        self.cell_xfs.push(entry);
-        Ok((self.cell_xfs.len() - 1) as u32)
+        // Custom codes need an id before the dxf can reference them
+        if let Some(ref fmt) = style.number_format {
+            self.get_or_add_num_fmt(fmt)?;
+        }
+        Ok(intern(&mut self.dxfs, &mut self.dxf_ids, style.clone()))
     }
     
-    pub fn register_dxf(&mut self, style: &CellStyle) -> u32 {
-        self.dxfs.push(style.clone());
-        (self.dxfs.len() - 1) as u32
+    /// numFmtId and format code of a number format already known to the registry
+    fn num_fmt_entry(&self, fmt: &NumberFormat) -> (u32, String) {
+        match fmt.fmt_info() {
+            (_, Some(code)) => {
+                let id = self.custom_num_fmt_ids.get(code).copied().unwrap_or(0);
+                (id, code.to_string())
+            }
+            (id, None) => (id, builtin_format_code(id).unwrap_or("General").to_string()),
+        }
     }
     
     fn get_or_add_font(&mut self, font: &FontStyle) -> u32 {
-        for (idx, f) in self.fonts.iter().enumerate() {
-            if f == font {
-                return idx as u32;
-            }
+        match self.font_ids.get(font) {
+            Some(&idx) => idx,
+            None => intern(&mut self.fonts, &mut self.font_ids, font.clone()),
         }
-        self.fonts.push(font.clone());
-        (self.fonts.len() - 1) as u32
     }
     
     fn get_or_add_fill(&mut self, fill: &FillStyle) -> u32 {
-        for (idx, f) in self.fills.iter().enumerate() {
-            if f == fill {
-                return idx as u32;
-            }
+        match self.fill_ids.get(fill) {
+            Some(&idx) => idx,
+            None => intern(&mut self.fills, &mut self.fill_ids, fill.clone()),
         }
-        self.fills.push(fill.clone());
-        (self.fills.len() - 1) as u32
     }
     
     fn get_or_add_border(&mut self, border: &BorderStyle) -> u32 {
-        for (idx, b) in self.borders.iter().enumerate() {
-            if b == border {
-                return idx as u32;
-            }
+        match self.border_ids.get(border) {
+            Some(&idx) => idx,
+            None => intern(&mut self.borders, &mut self.border_ids, border.clone()),
         }
-        self.borders.push(border.clone());
-        (self.borders.len() - 1) as u32
     }
 }
 
-pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
-    let base_count = 14; // Base built-in custom formats (164-174)
-    let total_count = base_count + registry.custom_num_fmts.len();
+/// Index of `item` in `items`, appending it when new
+fn intern<T: Clone + Eq + Hash>(items: &mut Vec<T>, ids: &mut HashMap<T, u32>, item: T) -> u32 {
+    if let Some(&idx) = ids.get(&item) {
+        return idx;
+    }
+    let idx = items.len() as u32;
+    ids.insert(item.clone(), idx);
+    items.push(item);
+    idx
+}
+
+/// Format codes behind the fixed ids returned by `NumberFormat::fmt_info`.
+/// Ids below 164 are Excel built-ins and never written to `<numFmts>`.
+const BUILTIN_NUM_FMTS: [(u32, &str); 19] = [
+    (0, "General"),
+    (9, "0%"),
+    (10, "0.00%"),
+    (14, "mm-dd-yy"),
+    (164, "yyyy-mm-dd hh:mm:ss"),
+    (165, "0"),
+    (166, "0.00"),
+    (167, "0.0000"),
+    (168, "$#,##0.00"),
+    (169, "$#,##0"),
+    (170, "hh:mm:ss"),
+    (171, "_(* #,##0.00_);_(* (#,##0.00);_(* \"-\"??_);_(@_)"),
+    (172, "_(* #,##0_);_(* (#,##0);_(* \"-\"_);_(@_)"),
+    (173, "#,##0"),
+    (174, "0%"),
+    (175, "0.00E+00"),
+    (176, "# ?/?"),
+    (177, "# ??/??"),
+    (178, "\"Yes\";\"Yes\";\"No\""),
+];
+
+fn builtin_format_code(id: u32) -> Option<&'static str> {
+    BUILTIN_NUM_FMTS.iter().find(|(fmt_id, _)| *fmt_id == id).map(|(_, code)| *code)
+}
+
+pub fn generate_styles_xml_enhanced(registry: &StyleRegistry, indexed_colors: &[String]) -> String {
+    // Only number formats some xf or dxf points at are written
+    let mut used_fmt_ids: Vec<u32> = registry.cell_xfs.iter().map(|xf| xf.num_fmt_id).collect();
+    used_fmt_ids.extend(
+        registry.dxfs.iter()
+            .filter_map(|dxf| dxf.number_format.as_ref())
+            .map(|fmt| registry.num_fmt_entry(fmt).0),
+    );
+    used_fmt_ids.sort_unstable();
+    used_fmt_ids.dedup();
+    
+    let num_fmts: Vec<(u32, &str)> = used_fmt_ids.iter()
+        .filter(|&&id| id >= 164)
+        .filter_map(|&id| {
+            builtin_format_code(id)
+                .or_else(|| registry.custom_num_fmts.iter().find(|(c, _)| *c == id).map(|(_, code)| code.as_str()))
+                .map(|code| (id, code))
+        })
+        .collect();
     
     let mut xml = String::with_capacity(
         2000 + registry.fonts.len() * 200 + 
@@ -550,34 +1450,15 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
     xml.push_str("<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">\n");
     
     // Number formats section
-    if total_count > 0 {
-        xml.push_str(&format!("<numFmts count=\"{}\">\n", total_count));
-        
-        // Base custom formats (164-174)
-        xml.push_str("  <numFmt numFmtId=\"164\" formatCode=\"yyyy-mm-dd hh:mm:ss\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"165\" formatCode=\"0\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"166\" formatCode=\"0.00\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"167\" formatCode=\"0.0000\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"168\" formatCode=\"$#,##0.00\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"169\" formatCode=\"$#,##0\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"170\" formatCode=\"hh:mm:ss\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"171\" formatCode=\"_(* #,##0.00_);_(* (#,##0.00);_(* &quot;-&quot;??_);_(@_)\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"172\" formatCode=\"_(* #,##0_);_(* (#,##0);_(* &quot;-&quot;_);_(@_)\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"173\" formatCode=\"#,##0\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"174\" formatCode=\"0%\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"175\" formatCode=\"0.00E+00\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"176\" formatCode=\"# ?/?\"/>\n");
-        xml.push_str("  <numFmt numFmtId=\"177\" formatCode=\"# ??/??\"/>\n");
-        
-        // User-defined custom formats (175+)
-        for (id, code) in &registry.custom_num_fmts {
+    if !num_fmts.is_empty() {
+        xml.push_str(&format!("<numFmts count=\"{}\">\n", num_fmts.len()));
+        for (id, code) in &num_fmts {
             xml.push_str("  <numFmt numFmtId=\"");
             xml.push_str(&id.to_string());
             xml.push_str("\" formatCode=\"");
             xml_escape_format_code(code, &mut xml);
             xml.push_str("\"/>\n");
         }
-        
         xml.push_str("</numFmts>\n");
     }
     
@@ -587,12 +1468,14 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
         xml.push_str("  <font>");
         if font.bold { xml.push_str("<b/>"); }
         if font.italic { xml.push_str("<i/>"); }
-        if font.underline { xml.push_str("<u/>"); }
+        if font.strike { xml.push_str("<strike/>"); }
+        if let Some(underline) = font.underline { xml.push_str(underline.xml()); }
+        if let Some(vert_align) = font.vert_align { xml.push_str(vert_align.xml()); }
         if let Some(size) = font.size {
             xml.push_str(&format!("<sz val=\"{}\"/>", size));
         }
         if let Some(ref color) = font.color {
-            xml.push_str(&format!("<color rgb=\"{}\"/>", color));
+            write_color(&mut xml, "color", color, indexed_colors);
         }
         if let Some(ref name) = font.name {
             xml.push_str(&format!("<name val=\"{}\"/>", name));
@@ -611,10 +1494,10 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
             PatternType::Solid => {
                 xml.push_str("<patternFill patternType=\"solid\">");
                 if let Some(ref fg) = fill.fg_color {
-                    xml.push_str(&format!("<fgColor rgb=\"{}\"/>", fg));
+                    write_color(&mut xml, "fgColor", fg, indexed_colors);
                 }
                 if let Some(ref bg) = fill.bg_color {
-                    xml.push_str(&format!("<bgColor rgb=\"{}\"/>", bg));
+                    write_color(&mut xml, "bgColor", bg, indexed_colors);
                 }
                 xml.push_str("</patternFill>");
             }
@@ -627,10 +1510,10 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
     xml.push_str(&format!("<borders count=\"{}\">\n", registry.borders.len()));
     for border in &registry.borders {
         xml.push_str("  <border>");
-        write_border_side(&mut xml, "left", &border.left);
-        write_border_side(&mut xml, "right", &border.right);
-        write_border_side(&mut xml, "top", &border.top);
-        write_border_side(&mut xml, "bottom", &border.bottom);
+        write_border_side(&mut xml, "left", &border.left, indexed_colors);
+        write_border_side(&mut xml, "right", &border.right, indexed_colors);
+        write_border_side(&mut xml, "top", &border.top, indexed_colors);
+        write_border_side(&mut xml, "bottom", &border.bottom, indexed_colors);
         xml.push_str("<diagonal/>");
         xml.push_str("</border>\n");
     }
@@ -661,19 +1544,13 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
             xml.push_str(">");
             xml.push_str("<alignment");
             if let Some(ref h) = align.horizontal {
-                xml.push_str(&format!(" horizontal=\"{}\"", match h {
-                    HorizontalAlignment::Left => "left",
-                    HorizontalAlignment::Center => "center",
-                    HorizontalAlignment::Right => "right",
-                    HorizontalAlignment::Justify => "justify",
-                }));
+                xml.push_str(&format!(" horizontal=\"{}\"", h.as_str()));
+            } else if align.indent.is_some_and(|indent| indent > 0) {
+                // Excel ignores the indent of general-aligned cells
+                xml.push_str(" horizontal=\"left\"");
             }
             if let Some(ref v) = align.vertical {
-                xml.push_str(&format!(" vertical=\"{}\"", match v {
-                    VerticalAlignment::Top => "top",
-                    VerticalAlignment::Center => "center",
-                    VerticalAlignment::Bottom => "bottom",
-                }));
+                xml.push_str(&format!(" vertical=\"{}\"", v.as_str()));
             }
             if align.wrap_text {
                 xml.push_str(" wrapText=\"1\"");
@@ -681,6 +1558,17 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
             if let Some(rotation) = align.text_rotation {
                 xml.push_str(&format!(" textRotation=\"{}\"", rotation));
             }
+            if let Some(indent) = align.indent.filter(|&indent| indent > 0) {
+                xml.push_str(&format!(" indent=\"{}\"", indent));
+            }
+            if align.shrink_to_fit {
+                xml.push_str(" shrinkToFit=\"1\"");
+            }
+            match align.reading_order {
+                ReadingOrder::Context => {}
+                ReadingOrder::LeftToRight => xml.push_str(" readingOrder=\"1\""),
+                ReadingOrder::RightToLeft => xml.push_str(" readingOrder=\"2\""),
+            }
             xml.push_str("/>");
             xml.push_str("</xf>\n");
         } else {
@@ -703,28 +1591,32 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
             xml.push_str("<font>");
             if font.bold { xml.push_str("<b/>"); }
             if font.italic { xml.push_str("<i/>"); }
-            if font.underline { xml.push_str("<u/>"); }
+            if font.strike { xml.push_str("<strike/>"); }
+            if let Some(underline) = font.underline { xml.push_str(underline.xml()); }
+            if let Some(vert_align) = font.vert_align { xml.push_str(vert_align.xml()); }
             if let Some(ref color) = font.color {
-                xml.push_str(&format!("<color rgb=\"{}\"/>", color));
+                write_color(&mut xml, "color", color, indexed_colors);
             }
             xml.push_str("</font>");
         }
         
         if let Some(ref fmt) = dxf.number_format {
-            let (num_fmt_id, _) = fmt.fmt_info();
-            xml.push_str(&format!("<numFmt numFmtId=\"{}\" formatCode=\"\"/>", num_fmt_id));
+            let (num_fmt_id, code) = registry.num_fmt_entry(fmt);
+            xml.push_str(&format!("<numFmt numFmtId=\"{}\" formatCode=\"", num_fmt_id));
+            xml_escape_format_code(&code, &mut xml);
+            xml.push_str("\"/>");
         }
         
         if let Some(ref fill) = dxf.fill {
             xml.push_str("<fill><patternFill patternType=\"solid\">");
             if let Some(ref fg) = fill.fg_color {
-                xml.push_str(&format!("<fgColor rgb=\"{}\"/>", fg));
+                write_color(&mut xml, "fgColor", fg, indexed_colors);
                 if fill.bg_color.is_none() {
-                    xml.push_str("<bgColor rgb=\"FFFFFFFF\"/>");
+                    write_color(&mut xml, "bgColor", &Color::Rgb("FFFFFFFF".to_string()), indexed_colors);
                 }
             }
             if let Some(ref bg) = fill.bg_color {
-                xml.push_str(&format!("<bgColor rgb=\"{}\"/>", bg));
+                write_color(&mut xml, "bgColor", bg, indexed_colors);
             }
             xml.push_str("</patternFill></fill>");
         }
@@ -732,29 +1624,20 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
         if let Some(ref align) = dxf.alignment {
             xml.push_str("<alignment");
             if let Some(ref h) = align.horizontal {
-                xml.push_str(&format!(" horizontal=\"{}\"", match h {
-                    HorizontalAlignment::Left => "left",
-                    HorizontalAlignment::Center => "center",
-                    HorizontalAlignment::Right => "right",
-                    HorizontalAlignment::Justify => "justify",
-                }));
+                xml.push_str(&format!(" horizontal=\"{}\"", h.as_str()));
             }
             if let Some(ref v) = align.vertical {
-                xml.push_str(&format!(" vertical=\"{}\"", match v {
-                    VerticalAlignment::Top => "top",
-                    VerticalAlignment::Center => "center",
-                    VerticalAlignment::Bottom => "bottom",
-                }));
+                xml.push_str(&format!(" vertical=\"{}\"", v.as_str()));
             }
             xml.push_str("/>");
         }
         
         if let Some(ref border) = dxf.border {
             xml.push_str("<border>");
-            write_border_side(&mut xml, "left", &border.left);
-            write_border_side(&mut xml, "right", &border.right);
-            write_border_side(&mut xml, "top", &border.top);
-            write_border_side(&mut xml, "bottom", &border.bottom);
+            write_border_side(&mut xml, "left", &border.left, indexed_colors);
+            write_border_side(&mut xml, "right", &border.right, indexed_colors);
+            write_border_side(&mut xml, "top", &border.top, indexed_colors);
+            write_border_side(&mut xml, "bottom", &border.bottom, indexed_colors);
             xml.push_str("</border>");
         }
         
@@ -762,13 +1645,22 @@ pub fn generate_styles_xml_enhanced(registry: &StyleRegistry) -> String {
     }
     xml.push_str("</dxfs>\n");
     
+    // Custom legacy palette, replacing Excel's 64 default indexed colors
+    if !indexed_colors.is_empty() {
+        xml.push_str("<colors><indexedColors>");
+        for color in indexed_colors {
+            xml.push_str(&format!("<rgbColor rgb=\"{}\"/>", color));
+        }
+        xml.push_str("</indexedColors></colors>\n");
+    }
+    
     xml.push_str("</styleSheet>");
     xml
 }
 
 // Helper function for XML escaping format codes
 #[inline]
-fn xml_escape_format_code(code: &str, xml: &mut String) {
+pub(crate) fn xml_escape_format_code(code: &str, xml: &mut String) {
     for ch in code.chars() {
         match ch {
             '&' => xml.push_str("&amp;"),
@@ -781,7 +1673,35 @@ fn xml_escape_format_code(code: &str, xml: &mut String) {
     }
 }
 
-fn write_border_side(xml: &mut String, side: &str, border: &Option<BorderSide>) {
+/// Normalize "RRGGBB", "AARRGGBB" or "#RRGGBB" to uppercase ARGB
+pub fn normalize_argb(color: &str) -> Option<String> {
+    let hex = color.trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => Some(format!("FF{}", hex.to_ascii_uppercase())),
+        8 => Some(hex.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+/// Write `<tag rgb=".."/>`, `<tag theme=".." tint=".."/>` or
+/// `<tag indexed=".."/>`. RGB colors that are in the custom palette are
+/// written by index too, so readers that ignore ARGB still get them right
+fn write_color(xml: &mut String, tag: &str, color: &Color, indexed_colors: &[String]) {
+    let index = match color {
+        Color::Rgb(rgb) => normalize_argb(rgb)
+            .and_then(|argb| indexed_colors.iter().position(|c| *c == argb)),
+        Color::Theme { .. } | Color::Indexed(_) => None,
+    };
+    match index {
+        Some(i) => xml.push_str(&format!("<{} indexed=\"{}\"/>", tag, i)),
+        None => xml.push_str(&format!("<{}{}/>", tag, color.xml_attrs())),
+    }
+}
+
+fn write_border_side(xml: &mut String, side: &str, border: &Option<BorderSide>, indexed_colors: &[String]) {
     if let Some(ref b) = border {
         xml.push_str(&format!("<{} style=\"{}\">", side, match b.style {
             BorderLineStyle::Thin => "thin",
@@ -790,9 +1710,15 @@ fn write_border_side(xml: &mut String, side: &str, border: &Option<BorderSide>)
             BorderLineStyle::Double => "double",
             BorderLineStyle::Dotted => "dotted",
             BorderLineStyle::Dashed => "dashed",
+            BorderLineStyle::Hair => "hair",
+            BorderLineStyle::MediumDashed => "mediumDashed",
+            BorderLineStyle::DashDot => "dashDot",
+            BorderLineStyle::MediumDashDot => "mediumDashDot",
+            BorderLineStyle::DashDotDot => "dashDotDot",
+            BorderLineStyle::SlantDashDot => "slantDashDot",
         }));
         if let Some(ref color) = b.color {
-            xml.push_str(&format!("<color rgb=\"{}\"/>", color));
+            write_color(xml, "color", color, indexed_colors);
         }
         xml.push_str(&format!("</{}>", side));
     } else {
@@ -800,9 +1726,11 @@ fn write_border_side(xml: &mut String, side: &str, border: &Option<BorderSide>)
     }
 }
 
+/// Stylesheet for the dict API, whose date cells always use style 1
 pub fn generate_styles_xml() -> String {
-    let registry = StyleRegistry::new();
-    generate_styles_xml_enhanced(&registry)
+    let mut registry = StyleRegistry::new();
+    let _ = registry.register_number_format(&NumberFormat::DateTime);
+    generate_styles_xml_enhanced(&registry, &[])
 }
 
 pub fn calculate_column_width(
@@ -837,7 +1765,8 @@ pub fn calculate_column_width(
             DataType::Int32 | DataType::Int64 => 12,
             DataType::UInt8 | DataType::UInt16 => 8,
             DataType::UInt32 | DataType::UInt64 => 12,
-            DataType::Float32 | DataType::Float64 => 12,
+            DataType::Float16 | DataType::Float32 | DataType::Float64 => 12,
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => 14,
             DataType::Boolean => 6,
             DataType::Date32 | DataType::Date64 => 12,
             DataType::Timestamp(_, _) => 20,
@@ -866,6 +1795,9 @@ pub struct ExcelChart {
     pub chart_style: Option<u32>,
     pub axis_min: Option<f64>,
     pub axis_max: Option<f64>,
+    /// Logarithmic value axis; x only applies to scatter charts
+    pub y_axis_log_base: Option<f64>,
+    pub x_axis_log_base: Option<f64>,
     pub title_bold: bool,
     pub title_font_size: Option<u32>,
     pub title_color: Option<String>,
@@ -874,6 +1806,263 @@ pub struct ExcelChart {
     pub axis_title_color: Option<String>,
     pub legend_bold: bool,
     pub legend_font_size: Option<u32>,
+    /// Explicit series; empty plots every data_range column but category_col
+    pub series: Vec<ChartSeries>,
+    /// Right-hand value axis used by series with `secondary` set
+    pub secondary_axis_min: Option<f64>,
+    pub secondary_axis_max: Option<f64>,
+    pub secondary_axis_title: Option<String>,
+    pub secondary_axis_number_format: Option<String>,
+    pub view_3d: View3D,
+    /// Per-series look by series index, ahead of the accent1-6 rotation.
+    /// Colors are ARGB hex; widths are in points.
+    pub series_colors: Vec<String>,
+    pub series_line_widths: Vec<f64>,
+    pub series_line_dashes: Vec<LineDash>,
+    /// Line and scatter markers by series index
+    pub series_markers: Vec<Marker>,
+    /// Label content and styling; None keeps the plain value labels
+    /// switched by show_data_labels
+    pub data_labels: Option<DataLabels>,
+    pub category_axis: CategoryAxis,
+    /// Column/bar spacing: gap between categories as % of a bar (0..=500)
+    /// and overlap of bars in a category (-100..=100)
+    pub gap_width: Option<u16>,
+    pub overlap: Option<i8>,
+    /// Draw line and scatter series as smoothed curves
+    pub smooth: bool,
+    /// Pie/doughnut slice offsets in % of the radius, for all slices or
+    /// per slice index
+    pub explosion: Option<u32>,
+    pub slice_explosions: Vec<u32>,
+    /// Degrees the first slice is turned clockwise from 12 o'clock
+    pub first_slice_angle: Option<u16>,
+    /// Doughnut hole as % of the chart's diameter (10..=90)
+    pub hole_size: Option<u8>,
+    /// Chart and plot area look; None keeps Excel's white chart with a
+    /// light gray border and a transparent plot area
+    pub chart_fill: Option<ChartFill>,
+    pub chart_border: Option<ChartBorder>,
+    pub plot_area_fill: Option<ChartFill>,
+    pub plot_area_border: Option<ChartBorder>,
+}
+
+/// Kind of category (x) axis. Auto turns into Date when the category column
+/// holds dates or timestamps, and Text otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CategoryAxis {
+    Auto,
+    Text,
+    Date(DateAxis),
+}
+
+/// `<c:dateAx>` options; Excel picks each unit from the data when None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateAxis {
+    pub base_unit: Option<TimeUnit>,
+    pub major_unit: Option<f64>,
+    pub major_time_unit: Option<TimeUnit>,
+    pub number_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeUnit {
+    Days,
+    Months,
+    Years,
+}
+
+impl TimeUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeUnit::Days => "days",
+            TimeUnit::Months => "months",
+            TimeUnit::Years => "years",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DataLabels {
+    pub show_value: bool,
+    pub show_category: bool,
+    pub show_series_name: bool,
+    pub show_percent: bool,
+    pub number_format: Option<String>,
+    pub position: Option<LabelPosition>,
+    pub font_size: Option<u32>, // hundredths of a point, like title_font_size
+    pub font_color: Option<String>,
+}
+
+/// Data label placement (`<c:dLblPos>`); which ones apply depends on the chart type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelPosition {
+    Center,
+    InsideEnd,
+    InsideBase,
+    OutsideEnd,
+    Left,
+    Right,
+    Above,
+    Below,
+    BestFit,
+}
+
+impl LabelPosition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LabelPosition::Center => "ctr",
+            LabelPosition::InsideEnd => "inEnd",
+            LabelPosition::InsideBase => "inBase",
+            LabelPosition::OutsideEnd => "outEnd",
+            LabelPosition::Left => "l",
+            LabelPosition::Right => "r",
+            LabelPosition::Above => "t",
+            LabelPosition::Below => "b",
+            LabelPosition::BestFit => "bestFit",
+        }
+    }
+}
+
+/// One value column of a chart, plotted as `chart_type` (the chart's own type
+/// when None). Mixing column, line and area series makes a combo chart.
+/// `secondary` plots the series against the secondary value axis.
+#[derive(Debug, Clone)]
+pub struct ChartSeries {
+    pub col: usize,
+    pub name: Option<String>,
+    pub chart_type: Option<ChartType>,
+    pub secondary: bool,
+    pub trendline: Option<Trendline>,
+    /// Override the chart's series_colors / series_line_* entry
+    pub color: Option<String>,
+    pub line_width: Option<f64>,
+    pub line_dash: Option<LineDash>,
+    pub marker: Option<Marker>,
+    /// Cells given directly instead of through data_range; `col` is unused
+    pub range: Option<SeriesRange>,
+    /// Overrides the chart's `smooth` for a line series
+    pub smooth: Option<bool>,
+}
+
+/// Explicit series cells, possibly on another sheet. Ranges are
+/// (start_row, start_col, end_row, end_col) with 1-based rows and 0-based
+/// columns, as parsed from A1 references.
+#[derive(Debug, Clone)]
+pub struct SeriesRange {
+    /// None for the chart's own sheet
+    pub sheet: Option<String>,
+    pub values: (usize, usize, usize, usize),
+    /// Falls back to the chart's category column when None
+    pub categories: Option<(usize, usize, usize, usize)>,
+}
+
+/// Data point marker of a line or scatter series (`<c:marker>`). Fill and
+/// border follow the series color when None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub symbol: MarkerSymbol,
+    pub size: Option<u8>, // points, 2..=72
+    pub fill: Option<String>,
+    pub border: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkerSymbol {
+    None,
+    Auto,
+    Circle,
+    Square,
+    Diamond,
+    Triangle,
+    X,
+    Star,
+    Dash,
+    Dot,
+    Plus,
+}
+
+impl MarkerSymbol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarkerSymbol::None => "none",
+            MarkerSymbol::Auto => "auto",
+            MarkerSymbol::Circle => "circle",
+            MarkerSymbol::Square => "square",
+            MarkerSymbol::Diamond => "diamond",
+            MarkerSymbol::Triangle => "triangle",
+            MarkerSymbol::X => "x",
+            MarkerSymbol::Star => "star",
+            MarkerSymbol::Dash => "dash",
+            MarkerSymbol::Dot => "dot",
+            MarkerSymbol::Plus => "plus",
+        }
+    }
+}
+
+/// Line pattern for a series line or bar outline (`<a:prstDash>`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineDash {
+    Solid,
+    Dot,
+    Dash,
+    DashDot,
+    LongDash,
+    LongDashDot,
+}
+
+impl LineDash {
+    pub fn prst_dash(&self) -> &'static str {
+        match self {
+            LineDash::Solid => "solid",
+            LineDash::Dot => "sysDot",
+            LineDash::Dash => "dash",
+            LineDash::DashDot => "dashDot",
+            LineDash::LongDash => "lgDash",
+            LineDash::LongDashDot => "lgDashDot",
+        }
+    }
+}
+
+/// Background of the chart or plot area
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartFill {
+    None,
+    /// AARRGGBB color
+    Solid(String),
+}
+
+/// Outline of the chart or plot area
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartBorder {
+    None,
+    /// Unset parts keep Excel's thin light gray line
+    Line {
+        color: Option<String>,
+        width: Option<f64>,
+        dash: Option<LineDash>,
+    },
+}
+
+/// Trendline fitted to one chart series (`<c:trendline>`)
+#[derive(Debug, Clone)]
+pub struct Trendline {
+    pub trendline_type: TrendlineType,
+    /// Legend entry; Excel generates one like "Linear (Revenue)" when None
+    pub name: Option<String>,
+    /// Periods to extend the line past the last / before the first point
+    pub forward: Option<f64>,
+    pub backward: Option<f64>,
+    pub show_equation: bool,
+    pub show_r_squared: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrendlineType {
+    Linear,
+    Polynomial(u8),    // order, 2..=6
+    MovingAverage(u32), // period, at least 2
+    Exponential,
 }
 
 #[derive(Debug, Clone)]
@@ -882,6 +2071,9 @@ pub struct ExcelImage {
     pub extension: String, // "png", "jpeg", etc.
     pub position: ImagePosition,
     pub description: Option<String>,
+    pub anchor: ImageAnchor,
+    /// PNG shown instead of an SVG by apps that can't draw SVG
+    pub fallback: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone)]
@@ -890,6 +2082,21 @@ pub struct ImagePosition {
     pub from_row: usize,
     pub to_col: usize,
     pub to_row: usize,
+    /// Offset of the top-left corner into the from cell, in EMU
+    pub col_off: u64,
+    pub row_off: u64,
+}
+
+/// How an image is tied to the sheet. Sizes and offsets are in EMU
+/// (9525 per pixel at 96 DPI).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageAnchor {
+    /// Stretched between the from and to cells, resizing with them
+    TwoCell,
+    /// Top-left corner at the from cell with a fixed size
+    OneCell { width: u64, height: u64 },
+    /// Fixed position and size, ignoring the cells underneath
+    Absolute { x: u64, y: u64, width: u64, height: u64 },
 }
 
 impl ExcelImage {
@@ -906,6 +2113,8 @@ impl ExcelImage {
             extension: ext,
             position,
             description: None,
+            anchor: ImageAnchor::TwoCell,
+            fallback: None,
         })
     }
 
@@ -915,14 +2124,143 @@ impl ExcelImage {
             extension,
             position,
             description: None,
+            anchor: ImageAnchor::TwoCell,
+            fallback: None,
+        }
+    }
+
+    pub fn is_svg(&self) -> bool {
+        self.extension.eq_ignore_ascii_case("svg")
+    }
+
+    /// Office reads SVGs through an extension on a regular picture, so each
+    /// SVG is paired with a PNG. Without a fallback that's a blank pixel.
+    pub fn fallback_png(&self) -> Option<Vec<u8>> {
+        if !self.is_svg() {
+            return None;
+        }
+        if let Some(fallback) = &self.fallback {
+            return Some(fallback.clone());
+        }
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbaImage::new(1, 1).write_to(&mut png, image::ImageFormat::Png).ok()?;
+        Some(png.into_inner())
+    }
+
+    /// Width and height in EMU at the image's own DPI (96 when it doesn't
+    /// say), or None when the format isn't recognized
+    pub fn native_size(&self) -> Option<(u64, u64)> {
+        match self.extension.to_ascii_lowercase().as_str() {
+            "svg" => return svg_size(&self.image_data).map(|(w, h)| ((w * 9525.0).round() as u64, (h * 9525.0).round() as u64)),
+            "emf" => return emf_size(&self.image_data),
+            _ => {}
         }
+        let (width, height) = image::ImageReader::new(std::io::Cursor::new(&self.image_data))
+            .with_guessed_format()
+            .ok()?
+            .into_dimensions()
+            .ok()?;
+        let (dpi_x, dpi_y) = image_dpi(&self.image_data).unwrap_or((96.0, 96.0));
+        let emu = |pixels: u32, dpi: f64| (f64::from(pixels) * 914400.0 / dpi).round() as u64;
+        Some((emu(width, dpi_x), emu(height, dpi_y)))
     }
 }
 
+/// Size of an SVG's root element in CSS pixels, from its width/height or
+/// else its viewBox
+fn svg_size(data: &[u8]) -> Option<(f64, f64)> {
+    let text = std::str::from_utf8(data).ok()?;
+    let start = text.find("<svg")?;
+    let tag = &text[start..start + text[start..].find('>')?];
+    let attr = |name: &str| -> Option<&str> {
+        let mut rest = tag;
+        while let Some(at) = rest.find(name) {
+            let standalone = rest[..at].ends_with(char::is_whitespace);
+            rest = &rest[at + name.len()..];
+            let Some(value) = rest.trim_start().strip_prefix('=').map(str::trim_start) else { continue };
+            let Some(quote) = value.chars().next().filter(|q| standalone && (*q == '"' || *q == '\'')) else { continue };
+            let value = &value[1..];
+            return value.find(quote).map(|end| value[..end].trim());
+        }
+        None
+    };
+    let length = |value: &str| -> Option<f64> {
+        let split = value.find(|c: char| c.is_ascii_alphabetic() || c == '%').unwrap_or(value.len());
+        let number: f64 = value[..split].trim().parse().ok()?;
+        let px_per_unit = match value[split..].trim() {
+            "" | "px" => 1.0,
+            "pt" => 96.0 / 72.0,
+            "pc" => 16.0,
+            "in" => 96.0,
+            "cm" => 96.0 / 2.54,
+            "mm" => 96.0 / 25.4,
+            _ => return None,
+        };
+        Some(number * px_per_unit)
+    };
+    if let (Some(width), Some(height)) = (attr("width").and_then(length), attr("height").and_then(length)) {
+        return Some((width, height));
+    }
+    let view_box: Vec<f64> = attr("viewBox")?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match view_box[..] {
+        [_, _, width, height] => Some((width, height)),
+        _ => None,
+    }
+}
 
+/// Size of an EMF picture in EMU, from the header's frame rectangle
+/// (in 0.01mm)
+fn emf_size(data: &[u8]) -> Option<(u64, u64)> {
+    let le_i32 = |at: usize| data.get(at..at + 4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    if le_i32(0)? != 1 || data.get(40..44)? != b" EMF" {
+        return None;
+    }
+    let width = le_i32(32)?.checked_sub(le_i32(24)?)?;
+    let height = le_i32(36)?.checked_sub(le_i32(28)?)?;
+    // 1mm = 36000 EMU
+    Some((u64::try_from(width).ok()? * 360, u64::try_from(height).ok()? * 360))
+}
 
+/// Resolution stored in a PNG `pHYs` chunk or a JPEG JFIF header
+fn image_dpi(data: &[u8]) -> Option<(f64, f64)> {
+    let be_u32 = |at: usize| data.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let be_u16 = |at: usize| data.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
 
-#[derive(Debug, Clone)]
+    let (x, y, per_inch) = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // pHYs must come before the first IDAT
+        let mut at = 8;
+        loop {
+            let len = be_u32(at)? as usize;
+            match data.get(at + 4..at + 8)? {
+                b"pHYs" if data.get(at + 16) == Some(&1) => {
+                    // Pixels per metre
+                    break (f64::from(be_u32(at + 8)?), f64::from(be_u32(at + 12)?), 0.0254);
+                }
+                b"IDAT" | b"IEND" => return None,
+                _ => at += 12 + len,
+            }
+        }
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF, 0xE0]) && data.get(6..11) == Some(b"JFIF\0") {
+        let per_inch = match data.get(13)? {
+            1 => 1.0,
+            2 => 2.54,
+            _ => return None,
+        };
+        (f64::from(be_u16(14)?), f64::from(be_u16(16)?), per_inch)
+    } else {
+        return None;
+    };
+    (x > 0.0 && y > 0.0).then_some((x * per_inch, y * per_inch))
+}
+
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ChartType {
     Column,
     Bar,
@@ -930,6 +2268,29 @@ pub enum ChartType {
     Pie,
     Scatter,
     Area,
+    Column3D,
+    Bar3D,
+    Pie3D,
+    Doughnut,
+}
+
+impl ChartType {
+    pub fn is_3d(self) -> bool {
+        matches!(self, ChartType::Column3D | ChartType::Bar3D | ChartType::Pie3D)
+    }
+}
+
+/// `<c:view3D>` for 3D charts; unset fields use Excel's defaults for the
+/// chart type (15°/20° with right-angle axes for bars, 30° tilt and 30°
+/// perspective for pies)
+#[derive(Debug, Clone, Default)]
+pub struct View3D {
+    pub rot_x: Option<i32>,       // -90..=90, tilt
+    pub rot_y: Option<u32>,       // 0..=360, turn
+    pub perspective: Option<u32>, // 0..=240, field of view in half-degrees
+    /// Draw axes at right angles (no perspective). Defaults to on for
+    /// column/bar charts unless a perspective is given.
+    pub right_angle_axes: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -972,6 +2333,8 @@ impl ExcelChart {
             chart_style: None,
             axis_min: None,
             axis_max: None,
+            y_axis_log_base: None,
+            x_axis_log_base: None,
             title_bold: false,
             title_font_size: None,
             title_color: None,
@@ -980,6 +2343,29 @@ impl ExcelChart {
             axis_title_color: None,
             legend_bold: false,
             legend_font_size: None,
+            series: Vec::new(),
+            secondary_axis_min: None,
+            secondary_axis_max: None,
+            secondary_axis_title: None,
+            secondary_axis_number_format: None,
+            view_3d: View3D::default(),
+            series_colors: Vec::new(),
+            series_line_widths: Vec::new(),
+            series_line_dashes: Vec::new(),
+            series_markers: Vec::new(),
+            data_labels: None,
+            category_axis: CategoryAxis::Auto,
+            gap_width: None,
+            overlap: None,
+            smooth: false,
+            explosion: None,
+            slice_explosions: Vec::new(),
+            first_slice_angle: None,
+            hole_size: None,
+            chart_fill: None,
+            chart_border: None,
+            plot_area_fill: None,
+            plot_area_border: None,
         }
     }
 }
\ No newline at end of file
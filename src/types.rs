@@ -1,6 +1,7 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use pyo3::prelude::*;
-use pyo3::types::PyDateTime;
+use pyo3::types::{PyDateTime, PyList, PyString};
+use crate::styles::TextRun;
 
 #[derive(Debug, Clone)]
 pub enum CellValue {
@@ -9,6 +10,8 @@ pub enum CellValue {
     Number(f64),
     Bool(bool),
     Date(NaiveDateTime),
+    /// Text in runs with their own fonts
+    RichText(Vec<TextRun>),
 }
 
 impl CellValue {
@@ -22,6 +25,11 @@ impl CellValue {
             return Ok(CellValue::String(s.to_string()));
         }
 
+        // Strings with lone surrogates can't be UTF-8 encoded; replace them with U+FFFD
+        if let Ok(s) = value.downcast::<PyString>() {
+            return Ok(CellValue::String(s.to_string_lossy().into_owned()));
+        }
+
         if let Ok(i) = value.extract::<i64>() {
             return Ok(CellValue::Number(i as f64));
         }
@@ -34,6 +42,13 @@ impl CellValue {
             return Ok(CellValue::Bool(b));
         }
 
+        // A list of (text, font) runs is one rich text cell
+        if value.downcast::<PyList>().is_ok() {
+            if let Ok(runs) = crate::extract_text_runs(value) {
+                return Ok(CellValue::RichText(runs));
+            }
+        }
+
         if let Ok(dt) = value.downcast::<PyDateTime>() {
             use pyo3::types::{PyDateAccess, PyTimeAccess};
             let datetime = NaiveDate::from_ymd_opt(
@@ -1,12 +1,18 @@
 use crate::types::{SheetData, WriteError};
-use crate::styles::{StyleConfig, generate_styles_xml, generate_styles_xml_enhanced, StyleRegistry, ConditionalRule, CellStyle, ExcelImage};
+use crate::styles::{CONFIG_VERSION, StyleConfig, ExcelChart, CategoryAxis, DateAxis, WorkbookConfig, SummarySheet, CellStyle, CellStyleMap, FontStyle, generate_styles_xml, generate_styles_xml_enhanced, StyleRegistry, ConditionalRule, ValidationType, NumberFormat, ColumnWidth, ExcelImage, ProgressPhase};
 // use crate::xml::{self, generate_drawing_xml_combined, generate_drawing_rels_combined};
-use crate::xml::{self, generate_drawing_xml_combined, generate_drawing_rels_combined};
-use mtzip::{level::CompressionLevel, ZipArchive};
-use std::fs::File;
+use crate::xml::{self, SheetStyles, generate_drawing_xml_combined, generate_drawing_rels_combined};
+use crate::zip::{self, ZipArchive, ZipStreamWriter};
+use std::fs::{File, OpenOptions};
 use std::io::Write;
-use std::collections::HashMap;
-use arrow_array::RecordBatch;
+use std::path::Path;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use arrow::compute::{concat, concat_batches, nullif};
+use arrow_array::{new_null_array, timezone::Tz, Array, ArrayRef, BooleanArray, RecordBatch, StringArray, StructArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use rayon::prelude::*;
 // ============================================================================
 // DICT API - Dict-based (backward compatibility)
@@ -18,16 +24,15 @@ pub fn write_single_sheet(
 ) -> Result<(), WriteError> {
     sheet.validate().map_err(WriteError::Validation)?;
 
-    let mut zipper = ZipArchive::new();
+    let mut zipper = ZipArchive::new(0, WorkbookConfig::default().compression());
     let sheet_names = vec![sheet.name.as_str()];
     
-    add_static_files(&mut zipper, &sheet_names, None, &[0], &[0], &[]);
+    add_static_files(&mut zipper, &sheet_names, None, &[0], &[0], &[], &WorkbookConfig::default());
     
     let config = StyleConfig::default();
     let xml_data = xml::generate_sheet_xml_from_dict(sheet, &config)?;
     zipper
         .add_file_from_memory(xml_data, "xl/worksheets/sheet1.xml".to_string())
-        .compression_level(CompressionLevel::fast())
         .done();
 
     
@@ -38,20 +43,20 @@ pub fn write_single_sheet_with_config(
     sheet: &SheetData,
     filename: &str,
     config: &StyleConfig,
+    workbook: &WorkbookConfig,
 ) -> Result<(), WriteError> {
     sheet.validate().map_err(WriteError::Validation)?;
 
-    let mut zipper = ZipArchive::new();
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
     let sheet_names = vec![sheet.name.as_str()];
     let charts_count = vec![config.charts.len()];
     let drawing_count = if config.charts.is_empty() && config.images.is_empty() { 0 } else { 1 };
     
-    add_static_files(&mut zipper, &sheet_names, None, &[0], &charts_count, &[(vec![], drawing_count)]);
+    add_static_files(&mut zipper, &sheet_names, None, &[0], &charts_count, &[(&[], drawing_count)], workbook);
     
     let xml_data = xml::generate_sheet_xml_from_dict(sheet, config)?;
     zipper
         .add_file_from_memory(xml_data, "xl/worksheets/sheet1.xml".to_string())
-        .compression_level(CompressionLevel::fast())
         .done();
 
     // Add chart files if any
@@ -59,13 +64,11 @@ pub fn write_single_sheet_with_config(
         let drawing_xml = xml::generate_drawing_xml(&config.charts);
         zipper
             .add_file_from_memory(drawing_xml.into_bytes(), "xl/drawings/drawing1.xml".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
         
         let drawing_rels = generate_drawing_rels_combined(config.charts.len(), &config.images, 1);
         zipper
             .add_file_from_memory(drawing_rels.into_bytes(), "xl/drawings/_rels/drawing1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
         
         for (idx, chart) in config.charts.iter().enumerate() {
@@ -75,7 +78,6 @@ pub fn write_single_sheet_with_config(
                     chart_xml.into_bytes(),
                     format!("xl/charts/chart{}.xml", idx + 1)
                 )
-                .compression_level(CompressionLevel::fast())
                 .done();
         }
         
@@ -86,23 +88,23 @@ pub fn write_single_sheet_with_config(
         
         zipper
             .add_file_from_memory(rels_xml.into_bytes(), "xl/worksheets/_rels/sheet1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
     }
     
     write_zip_to_file(zipper, filename)
 }
 
+/// Write dict-based sheets, each with its own view options (freeze panes,
+/// zoom, gridlines, tab color, ...)
 pub fn write_multiple_sheets(
-    sheets: &[SheetData],
+    sheets: &[(SheetData, StyleConfig)],
     filename: &str,
     num_threads: usize,
+    workbook: &WorkbookConfig,
 ) -> Result<(), WriteError> {
-    for sheet in sheets {
+    for (sheet, _) in sheets {
         sheet.validate().map_err(WriteError::Validation)?;
     }
-
-    let config = StyleConfig::default();
     
     // Generate XMLs in parallel if num_threads > 1 and multiple sheets
     let xml_sheets: Vec<Vec<u8>> = if num_threads > 1 && sheets.len() > 1 {
@@ -114,27 +116,26 @@ pub fn write_multiple_sheets(
         pool.install(|| {
             sheets
                 .par_iter()
-                .map(|sheet| xml::generate_sheet_xml_from_dict(sheet, &config))
+                .map(|(sheet, config)| xml::generate_sheet_xml_from_dict(sheet, config))
                 .collect::<Result<Vec<_>, _>>()
         })?
     } else {
         // Sequential fallback
         sheets
             .iter()
-            .map(|sheet| xml::generate_sheet_xml_from_dict(sheet, &config))
+            .map(|(sheet, config)| xml::generate_sheet_xml_from_dict(sheet, config))
             .collect::<Result<Vec<_>, _>>()?
     };
 
     // Build ZIP sequentially (not thread-safe)
-    let mut zipper = ZipArchive::new();
-    let sheet_names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
+    let sheet_names: Vec<&str> = sheets.iter().map(|(s, _)| s.name.as_str()).collect();
 
-    add_static_files(&mut zipper, &sheet_names, None, &vec![0; sheets.len()], &vec![0; sheets.len()], &vec![(vec![], 0); sheets.len()]);
+    add_static_files(&mut zipper, &sheet_names, None, &vec![0; sheets.len()], &vec![0; sheets.len()], &vec![(&[][..], 0); sheets.len()], workbook);
 
     for (idx, xml_data) in xml_sheets.into_iter().enumerate() {
         zipper
             .add_file_from_memory(xml_data, format!("xl/worksheets/sheet{}.xml", idx + 1))
-            .compression_level(CompressionLevel::fast())
             .done();
     }
 
@@ -150,7 +151,7 @@ pub fn write_single_sheet_arrow(
     sheet_name: &str,
     filename: &str,
 ) -> Result<(), WriteError> {
-    write_single_sheet_arrow_with_config(batches, sheet_name, filename, &StyleConfig::default())
+    write_single_sheet_arrow_with_config(batches, sheet_name, filename, &StyleConfig::default(), &WorkbookConfig::default())
 }
 
 pub fn write_single_sheet_arrow_with_config(
@@ -158,82 +159,66 @@ pub fn write_single_sheet_arrow_with_config(
     sheet_name: &str,
     filename: &str,
     config: &StyleConfig,
+    workbook: &WorkbookConfig,
 ) -> Result<(), WriteError> {
+    if workbook.summary_sheet.is_some() || needs_split(batches, config) {
+        return write_multiple_sheets_arrow_with_configs(&[(batches, sheet_name, config)], filename, 1, workbook);
+    }
     validate_sheet_name(sheet_name)?;
+    validate_sheet_references(&[sheet_name], config)?;
 
     let mut registry = StyleRegistry::new();
-    let mut updated_config = config.clone();
-
-    let schema = batches[0].schema();
-    let col_format_map: HashMap<usize, u32> = if let Some(formats) = &config.column_formats {
-        let mut map = HashMap::new();
-        for (idx, field) in schema.fields().iter().enumerate() {
-            if let Some(fmt) = formats.get(field.name()) {
-                let cell_style = CellStyle {
-                    font: None,
-                    fill: None,
-                    border: None,
-                    alignment: None,
-                    number_format: Some(fmt.clone()),
-                };
-                let style_id = registry.register_cell_style(&cell_style)
-                    .map_err(|e| WriteError::Validation(e))?;
-                map.insert(idx, style_id);
-            }
-        }
-        map
-    } else {
-        HashMap::new()
-    };
-
-    // Build cell style map - register and map user's custom cell styles
-    let mut cell_style_map: HashMap<(usize, usize), u32> = HashMap::new();
-    for cell_style in &config.cell_styles {
-        let style_id = registry.register_cell_style(&cell_style.style)
-            .map_err(|e| WriteError::Validation(e))?;
-        cell_style_map.insert((cell_style.row, cell_style.col), style_id);
-    }
-
-    if !config.conditional_formats.is_empty() {
-        let mut dxf_ids = HashMap::new();
-        for (idx, cond_format) in config.conditional_formats.iter().enumerate() {
-            match &cond_format.rule {
-                ConditionalRule::CellValue { .. } | ConditionalRule::Top10 { .. } => {
-                    registry.register_cell_style(&cond_format.style)
-                        .map_err(|e| WriteError::Validation(e))?;
-                    let dxf_id = registry.register_dxf(&cond_format.style);
-                    dxf_ids.insert(idx, dxf_id);
-                }
-                _ => {}
-            }
-        }
-        updated_config.cond_format_dxf_ids = dxf_ids;
-    }
+    let styles = register_sheet_styles(&mut registry, batches, config)?;
 
-    let mut zipper = ZipArchive::new();
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
     let sheet_names = vec![sheet_name];
     let charts_count = vec![config.charts.len()];
     // let images_data = vec![(config.images.clone(), if config.images.is_empty() { 0 } else { 1 })];
     let drawing_count = if config.charts.is_empty() && config.images.is_empty() { 0 } else { 1 };
-    let images_data = vec![(config.images.clone(), drawing_count)];
+    let images_data = [(config.images.as_slice(), drawing_count)];
     
 
-    add_static_files(&mut zipper, &sheet_names, Some(&registry), &vec![config.tables.len()], &charts_count, &images_data);
-    
-    let xml_data = xml::generate_sheet_xml_from_arrow(batches, &updated_config, &col_format_map, &cell_style_map)?;
-    
-    // DEBUG: Check for leading garbage
-    // if xml_data.len() > 0 {
-    //     eprintln!("First 100 bytes: {:?}", &xml_data[..xml_data.len().min(100)]);
-    //     eprintln!("Starts with '<?xml': {}", xml_data.starts_with(b"<?xml"));
-    // }
+    add_static_files(&mut zipper, &sheet_names, Some(&registry), &vec![config.tables.len()], &charts_count, &images_data, workbook);
+    add_single_sheet_parts(&mut zipper, sheet_name, config, batches);
 
-    
-    zipper
-        .add_file_from_memory(xml_data, "xl/worksheets/sheet1.xml".to_string())
-        .compression_level(CompressionLevel::fast())
-        .done();
+    write_file_atomically(filename, |file| write_sheet_zip(batches, zipper, file, config, &styles, workbook))
+}
 
+/// Write the parts in `zipper`, then sheet1.xml from the in-memory `batches`,
+/// compressed as it is generated so the sheet's XML is never held whole
+fn write_sheet_zip<W: Write>(
+    batches: &[RecordBatch],
+    mut zipper: ZipArchive,
+    writer: W,
+    config: &StyleConfig,
+    styles: &SheetStyles,
+    workbook: &WorkbookConfig,
+) -> Result<W, WriteError> {
+    let mut stream = ZipStreamWriter::new(writer, workbook.zip_threads)?;
+    stream.append(&mut zipper)?;
+    stream.start_entry("xl/worksheets/sheet1.xml".to_string(), workbook.compression())?;
+
+    let progress = workbook.progress.as_ref();
+    if let Some(progress) = progress {
+        progress.begin(Some(batches.iter().map(|b| b.num_rows()).sum()));
+    }
+    xml::stream_sheet_xml_from_arrow(batches, config, styles, progress, |xml| {
+        Ok(stream.write_entry_data(xml)?)
+    })?;
+    if let Some(progress) = progress {
+        progress.report(ProgressPhase::Compressing).map_err(WriteError::Validation)?;
+    }
+    stream.finish_entry()?;
+    Ok(stream.finish()?)
+}
+
+/// Worksheet rels, tables, drawing, charts and images of a one-sheet workbook
+fn add_single_sheet_parts(
+    zipper: &mut ZipArchive,
+    sheet_name: &str,
+    config: &StyleConfig,
+    batches: &[RecordBatch],
+) {
     let hyperlinks_with_idx: Vec<(String, usize)> = config.hyperlinks
         .iter()
         .enumerate()
@@ -246,7 +231,7 @@ pub fn write_single_sheet_arrow_with_config(
         let mut rels_xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
         
         for (url, idx) in &hyperlinks_with_idx {
-            rels_xml.push_str(&format!("<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>\n", idx, url));
+            rels_xml.push_str(&format!("<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>\n", idx, xml::hyperlink_target(url)));
         }
         
         for idx in 0..config.tables.len() {
@@ -261,38 +246,19 @@ pub fn write_single_sheet_arrow_with_config(
         
         zipper
             .add_file_from_memory(rels_xml.into_bytes(), "xl/worksheets/_rels/sheet1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
     }
     
     if !config.tables.is_empty() {
         // Calculate total rows once for all tables
         let total_data_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
-        let num_cols = if !batches.is_empty() { batches[0].schema().fields().len() } else { 0 };
         
         for (idx, table) in config.tables.iter().enumerate() {
             let table_id = (idx + 1) as u32;
             
+            let num_cols = batches.first().map_or(0, |b| xml::table_width(&b.schema(), table.range.1));
             let mut adjusted_table = table.clone();
-            
-            // Auto-calculate end_row if not specified (0 means auto)
-            if adjusted_table.range.2 == 0 {
-                // end_row = start_row + num_data_rows - 1 (inclusive)
-                adjusted_table.range.2 = adjusted_table.range.0 + total_data_rows;
-            }
-            
-            // Auto-calculate end_col if not specified (0 means auto)
-            if adjusted_table.range.3 == 0 {
-                if num_cols > 0 {
-                    adjusted_table.range.3 = adjusted_table.range.1 + num_cols - 1;
-                }
-            }
-            
-            // If table starts after row 1, we inserted a header row, so adjust end_row
-            // Only adjust if user manually specified end_row (not auto-calculated)
-            if adjusted_table.range.0 > 1 && table.range.2 != 0 {
-                adjusted_table.range.2 += 1; // end_row++
-            }
+            adjusted_table.range = table.resolved_range(total_data_rows, num_cols);
             
             let col_names = if table.column_names.is_empty() && !batches.is_empty() {
                 let schema = batches[0].schema();
@@ -305,13 +271,15 @@ pub fn write_single_sheet_arrow_with_config(
                 table.column_names.clone()
             };
             
-            let table_xml = xml::generate_table_xml(&adjusted_table, table_id, &col_names);
+            let last_column_numeric = batches.first()
+                .and_then(|b| b.schema().fields().get(adjusted_table.range.3).map(|f| f.data_type().is_numeric()))
+                .unwrap_or(false);
+            let table_xml = xml::generate_table_xml(&adjusted_table, table_id, &col_names, last_column_numeric);
             zipper
                 .add_file_from_memory(
                     table_xml.into_bytes(),
                     format!("xl/tables/table{}.xml", table_id)
                 )
-                .compression_level(CompressionLevel::fast())
                 .done();
         }
     }
@@ -322,23 +290,20 @@ pub fn write_single_sheet_arrow_with_config(
         let drawing_xml = generate_drawing_xml_combined(&config.charts, &config.images);
         zipper
             .add_file_from_memory(drawing_xml.into_bytes(), "xl/drawings/drawing1.xml".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
         
         let drawing_rels = generate_drawing_rels_combined(config.charts.len(), &config.images, 1);
         zipper
             .add_file_from_memory(drawing_rels.into_bytes(), "xl/drawings/_rels/drawing1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
             .done();
         
         for (idx, chart) in config.charts.iter().enumerate() {
-            let chart_xml = xml::generate_chart_xml(chart, sheet_name);
+            let chart_xml = chart_xml_for_data(chart, sheet_name, batches);
             zipper
                 .add_file_from_memory(
                     chart_xml.into_bytes(),
                     format!("xl/charts/chart{}.xml", idx + 1)
                 )
-                .compression_level(CompressionLevel::fast())
                 .done();
         }
         
@@ -349,76 +314,127 @@ pub fn write_single_sheet_arrow_with_config(
                     image.image_data.clone(),
                     format!("xl/media/image{}.{}", idx + 1, image.extension)
                 )
-                .compression_level(CompressionLevel::fast())
                 .done();
+            if let Some(fallback) = image.fallback_png() {
+                zipper
+                    .add_file_from_memory(fallback, format!("xl/media/image{}.png", idx + 1))
+                    .done();
+            }
         }
     }
+}
 
-    write_zip_to_file(zipper, filename)
+/// True when a sheet can be written by `write_single_sheet_arrow_streaming`.
+/// Tables, charts, filter criteria, `split_rows` and the summary sheet all
+/// need every row before the worksheet is written.
+pub fn can_stream_sheet(config: &StyleConfig, workbook: &WorkbookConfig) -> bool {
+    workbook.summary_sheet.is_none()
+        && config.split_rows.is_none()
+        && config.tables.is_empty()
+        && config.charts.is_empty()
+        && config.filter_columns.is_empty()
 }
 
-pub fn write_single_sheet_arrow_to_bytes(
-    batches: &[RecordBatch],
+/// Write a one-sheet workbook while its batches are still being read. The
+/// worksheet is serialized and compressed one batch at a time, so only the
+/// batch in hand is held in memory. `first` is the sheet's first batch; its
+/// schema applies to the rest. The worksheet has no `<dimension>` since the
+/// row count is unknown until the last batch. Check `can_stream_sheet` first.
+pub fn write_single_sheet_arrow_streaming<I>(
+    first: RecordBatch,
+    rest: I,
     sheet_name: &str,
+    filename: &str,
     config: &StyleConfig,
-) -> Result<Vec<u8>, WriteError> {
+    workbook: &WorkbookConfig,
+) -> Result<(), WriteError>
+where
+    I: Iterator<Item = Result<RecordBatch, WriteError>>,
+{
+    if !can_stream_sheet(config, workbook) {
+        return Err(WriteError::Validation(format!(
+            "Sheet '{}' uses options that need all rows up front and can't be streamed", sheet_name
+        )));
+    }
+    if first.num_columns() == 0 {
+        // Nothing to stream; rows without columns write an empty sheet
+        return write_single_sheet_arrow_with_config(&[first], sheet_name, filename, config, workbook);
+    }
     validate_sheet_name(sheet_name)?;
+    validate_sheet_references(&[sheet_name], config)?;
 
     let mut registry = StyleRegistry::new();
-    let mut updated_config = config.clone();
+    let styles = register_sheet_styles(&mut registry, std::slice::from_ref(&first), config)?;
 
-    let schema = batches[0].schema();
-    let col_format_map: HashMap<usize, u32> = if let Some(formats) = &config.column_formats {
-        let mut map = HashMap::new();
-        for (idx, field) in schema.fields().iter().enumerate() {
-            if let Some(fmt) = formats.get(field.name()) {
-                let cell_style = CellStyle {
-                    font: None,
-                    fill: None,
-                    border: None,
-                    alignment: None,
-                    number_format: Some(fmt.clone()),
-                };
-                let style_id = registry.register_cell_style(&cell_style)
-                    .map_err(|e| WriteError::Validation(e))?;
-                map.insert(idx, style_id);
-            }
-        }
-        map
-    } else {
-        HashMap::new()
-    };
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
+    let drawing_count = if config.images.is_empty() { 0 } else { 1 };
+    let images_data = [(config.images.as_slice(), drawing_count)];
+    add_static_files(&mut zipper, &[sheet_name], Some(&registry), &[0], &[0], &images_data, workbook);
+    add_single_sheet_parts(&mut zipper, sheet_name, config, &[]);
 
-    let mut cell_style_map: HashMap<(usize, usize), u32> = HashMap::new();
-    for cell_style in &config.cell_styles {
-        let style_id = registry.register_cell_style(&cell_style.style)
-            .map_err(|e| WriteError::Validation(e))?;
-        cell_style_map.insert((cell_style.row, cell_style.col), style_id);
-    }
-
-    if !config.conditional_formats.is_empty() {
-        let mut dxf_ids = HashMap::new();
-        for (idx, cond_format) in config.conditional_formats.iter().enumerate() {
-            match &cond_format.rule {
-                ConditionalRule::CellValue { .. } | ConditionalRule::Top10 { .. } => {
-                    registry.register_cell_style(&cond_format.style)
-                        .map_err(|e| WriteError::Validation(e))?;
-                    dxf_ids.insert(idx, idx);
-                }
-                _ => {}
-            }
-        }
-        updated_config.conditional_formats = config.conditional_formats.clone();
+    write_file_atomically(filename, |file| stream_sheet_zip(first, rest, zipper, file, config, &styles, workbook))
+}
+
+/// Write the parts in `zipper`, then sheet1.xml one batch at a time
+#[allow(clippy::too_many_arguments)]
+fn stream_sheet_zip<I, W>(
+    first: RecordBatch,
+    rest: I,
+    mut zipper: ZipArchive,
+    writer: W,
+    config: &StyleConfig,
+    styles: &SheetStyles,
+    workbook: &WorkbookConfig,
+) -> Result<W, WriteError>
+where
+    I: Iterator<Item = Result<RecordBatch, WriteError>>,
+    W: Write,
+{
+    let mut stream = ZipStreamWriter::new(writer, workbook.zip_threads)?;
+    stream.append(&mut zipper)?;
+    stream.start_entry("xl/worksheets/sheet1.xml".to_string(), workbook.compression())?;
+
+    // The row count is unknown until the last batch
+    let progress = workbook.progress.as_ref();
+    if let Some(progress) = progress {
+        progress.begin(None);
+    }
+    let mut sheet = xml::SheetXmlWriter::new(&first, None, config, styles);
+    let mut buf = Vec::new();
+    sheet.write_head(&first, &mut buf)?;
+    sheet.write_batch_reporting(&first, &mut buf, progress)?;
+    drop(first);
+    stream.write_entry_data(&buf)?;
+
+    for batch in rest {
+        buf.clear();
+        sheet.write_batch_reporting(&batch?, &mut buf, progress)?;
+        stream.write_entry_data(&buf)?;
+    }
+
+    buf.clear();
+    sheet.write_tail(&mut buf)?;
+    stream.write_entry_data(&buf)?;
+    stream.finish_entry()?;
+    Ok(stream.finish()?)
+}
+
+pub fn write_single_sheet_arrow_to_bytes(
+    batches: &[RecordBatch],
+    sheet_name: &str,
+    config: &StyleConfig,
+    workbook: &WorkbookConfig,
+) -> Result<Vec<u8>, WriteError> {
+    if workbook.summary_sheet.is_some() || needs_split(batches, config) {
+        return write_multiple_sheets_arrow_to_bytes(&[(batches, sheet_name, config)], 1, workbook);
     }
+    validate_sheet_name(sheet_name)?;
+    validate_sheet_references(&[sheet_name], config)?;
 
-    let xml_data = xml::generate_sheet_xml_from_arrow(
-        batches,
-        &updated_config,
-        &col_format_map,
-        &cell_style_map,
-    )?;
+    let mut registry = StyleRegistry::new();
+    let styles = register_sheet_styles(&mut registry, batches, config)?;
 
-    let mut zipper = ZipArchive::new();
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
     let sheet_names = vec![sheet_name];
     let charts_count = vec![config.charts.len()];
     let drawing_count = if config.charts.is_empty() && config.images.is_empty() { 0 } else { 1 };
@@ -429,252 +445,223 @@ pub fn write_single_sheet_arrow_to_bytes(
         Some(&registry), 
         &[config.tables.len()], 
         &charts_count, 
-        &[(config.images.clone(), drawing_count)]
+        &[(config.images.as_slice(), drawing_count)],
+        workbook,
     );
+    add_single_sheet_parts(&mut zipper, sheet_name, config, batches);
 
-    zipper
-        .add_file_from_memory(xml_data, "xl/worksheets/sheet1.xml".to_string())
-        .compression_level(CompressionLevel::fast())
-        .done();
+    write_sheet_zip(batches, zipper, Vec::new(), config, &styles, workbook)
+}
 
-    if !config.charts.is_empty() {
-        let drawing_xml = xml::generate_drawing_xml(&config.charts);
-        zipper
-            .add_file_from_memory(drawing_xml.into_bytes(), "xl/drawings/drawing1.xml".to_string())
-            .compression_level(CompressionLevel::fast())
-            .done();
-        
-        let drawing_rels = generate_drawing_rels_combined(config.charts.len(), &config.images, 1);
-        zipper
-            .add_file_from_memory(drawing_rels.into_bytes(), "xl/drawings/_rels/drawing1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
-            .done();
-        
-        for (idx, chart) in config.charts.iter().enumerate() {
-            let chart_xml = xml::generate_chart_xml(chart, sheet_name);
-            zipper
-                .add_file_from_memory(
-                    chart_xml.into_bytes(),
-                    format!("xl/charts/chart{}.xml", idx + 1)
-                )
-                .compression_level(CompressionLevel::fast())
-                .done();
-        }
-        
-        let mut rels_xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
-        rels_xml.push_str("<Relationship Id=\"rIdDraw1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing\" Target=\"../drawings/drawing1.xml\"/>\n");
-        rels_xml.push_str("</Relationships>");
-        
-        zipper
-            .add_file_from_memory(rels_xml.into_bytes(), "xl/worksheets/_rels/sheet1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
-            .done();
+pub fn write_multiple_sheets_arrow_to_bytes(
+    sheets: &[(&[RecordBatch], &str, &StyleConfig)],
+    num_threads: usize,
+    workbook: &WorkbookConfig,
+) -> Result<Vec<u8>, WriteError> {
+    if let Some(split) = split_long_sheets(sheets)? {
+        let parts: Vec<_> = split.iter().map(|(b, n, c)| (b.as_slice(), n.as_str(), c.as_ref())).collect();
+        return write_multiple_sheets_arrow_to_bytes(&parts, num_threads, workbook);
     }
-
-    if !config.tables.is_empty() {
-        for (idx, table) in config.tables.iter().enumerate() {
-            let col_names = if table.column_names.is_empty() {
-                let (_, start_col, _, end_col) = table.range;
-                schema.fields()[start_col..=end_col]
-                    .iter()
-                    .map(|f| f.name().clone())
-                    .collect()
-            } else {
-                table.column_names.clone()
-            };
-            
-            let table_xml = xml::generate_table_xml(table, (idx + 1) as u32, &col_names);
-            zipper
-                .add_file_from_memory(
-                    table_xml.into_bytes(),
-                    format!("xl/tables/table{}.xml", idx + 1)
-                )
-                .compression_level(CompressionLevel::fast())
-                .done();
-        }
-        
-        let mut table_rels = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
-        for idx in 0..config.tables.len() {
-            table_rels.push_str(&format!("<Relationship Id=\"rIdTable{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/table\" Target=\"../tables/table{}.xml\"/>\n", idx + 1, idx + 1));
-        }
-        if !config.charts.is_empty() || !config.images.is_empty() {
-            table_rels.push_str("<Relationship Id=\"rIdDraw1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing\" Target=\"../drawings/drawing1.xml\"/>\n");
-        }
-        table_rels.push_str("</Relationships>");
-        
-        zipper
-            .add_file_from_memory(table_rels.into_bytes(), "xl/worksheets/_rels/sheet1.xml.rels".to_string())
-            .compression_level(CompressionLevel::fast())
-            .done();
+    if let Some((batch, config)) = with_summary_sheet(sheets, workbook)? {
+        let mut all = Vec::with_capacity(sheets.len() + 1);
+        all.push((std::slice::from_ref(&batch), SUMMARY_SHEET_NAME, &config));
+        all.extend_from_slice(sheets);
+        let workbook = WorkbookConfig { summary_sheet: None, ..workbook.clone() };
+        return write_multiple_sheets_arrow_to_bytes(&all, num_threads, &workbook);
     }
-
-    if !config.images.is_empty() {
-        for (idx, image) in config.images.iter().enumerate() {
-            zipper
-                .add_file_from_memory(
-                    image.image_data.clone(),
-                    format!("xl/media/image{}.{}", idx + 1, image.extension)
-                )
-                .compression_level(CompressionLevel::fast())
-                .done();
-        }
+    if sheets.iter().any(|(batches, _, _)| batches.is_empty()) {
+        return Err(WriteError::Validation("Empty batches".to_string()));
     }
+    let prepared = prepare_sheets(sheets, workbook)?;
+    write_sheets_pipelined(sheets, prepared, Vec::new(), num_threads, workbook)
+}
+
 
-    write_zip_to_buffer(zipper)
+pub fn write_multiple_sheets_arrow(
+    sheets: &[(Vec<RecordBatch>, String)],
+    filename: &str,
+    num_threads: usize,
+) -> Result<(), WriteError> {
+    let config = StyleConfig::default();
+    write_multiple_sheets_arrow_with_configs(
+        &sheets.iter().map(|(b, n)| (b.as_slice(), n.as_str(), &config)).collect::<Vec<_>>(),
+        filename,
+        num_threads,
+        &WorkbookConfig::default(),
+    )
 }
 
-pub fn write_multiple_sheets_arrow_to_bytes(
-    sheets: &[(Vec<RecordBatch>, &str, StyleConfig)],
+pub fn write_multiple_sheets_arrow_with_configs(
+    sheets: &[(&[RecordBatch], &str, &StyleConfig)],
+    filename: &str,
     num_threads: usize,
-) -> Result<Vec<u8>, WriteError> {
-    for (batches, sheet_name, _) in sheets {
-        validate_sheet_name(sheet_name)?;
-        if batches.is_empty() {
-            return Err(WriteError::Validation("Empty batches".to_string()));
-        }
+    workbook: &WorkbookConfig,
+) -> Result<(), WriteError> {
+    if let Some(split) = split_long_sheets(sheets)? {
+        let parts: Vec<_> = split.iter().map(|(b, n, c)| (b.as_slice(), n.as_str(), c.as_ref())).collect();
+        return write_multiple_sheets_arrow_with_configs(&parts, filename, num_threads, workbook);
     }
+    if let Some((batch, config)) = with_summary_sheet(sheets, workbook)? {
+        let mut all = Vec::with_capacity(sheets.len() + 1);
+        all.push((std::slice::from_ref(&batch), SUMMARY_SHEET_NAME, &config));
+        all.extend_from_slice(sheets);
+        let workbook = WorkbookConfig { summary_sheet: None, ..workbook.clone() };
+        return write_multiple_sheets_arrow_with_configs(&all, filename, num_threads, &workbook);
+    }
+    let prepared = prepare_sheets(sheets, workbook)?;
+    write_file_atomically(filename, |file| write_sheets_pipelined(sheets, prepared, file, num_threads, workbook))
+}
 
-    let xml_results: Vec<_> = if num_threads > 1 && sheets.len() > 1 {
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(num_threads)
-            .build()
-            .map_err(|e| WriteError::Validation(format!("Thread pool error: {}", e)))?;
-        
-        pool.install(|| {
-            sheets
-                .par_iter()
-                .map(|(batches, _, config)| {
-                    let mut registry = StyleRegistry::new();
-                    let schema = batches[0].schema();
-                    let col_format_map: HashMap<usize, u32> = if let Some(formats) = &config.column_formats {
-                        let mut map = HashMap::new();
-                        for (idx, field) in schema.fields().iter().enumerate() {
-                            if let Some(fmt) = formats.get(field.name()) {
-                                let cell_style = CellStyle {
-                                    font: None,
-                                    fill: None,
-                                    border: None,
-                                    alignment: None,
-                                    number_format: Some(fmt.clone()),
-                                };
-                                if let Ok(style_id) = registry.register_cell_style(&cell_style) {
-                                    map.insert(idx, style_id);
-                                }
-                            }
-                        }
-                        map
-                    } else {
-                        HashMap::new()
-                    };
-
-                   let cell_style_map: HashMap<(usize, usize), u32> = HashMap::new();
-                   xml::generate_sheet_xml_from_arrow(batches, config, &col_format_map, &cell_style_map)
-                })
-                .collect::<Result<Vec<_>, _>>()
-        })?
-    } else {
-        sheets
-            .iter()
-            .map(|(batches, _, config)| {
-                let mut registry = StyleRegistry::new();
-                let schema = batches[0].schema();
-                let col_format_map: HashMap<usize, u32> = if let Some(formats) = &config.column_formats {
-                    let mut map = HashMap::new();
-                    for (idx, field) in schema.fields().iter().enumerate() {
-                        if let Some(fmt) = formats.get(field.name()) {
-                            let cell_style = CellStyle {
-                                font: None,
-                                fill: None,
-                                border: None,
-                                alignment: None,
-                                number_format: Some(fmt.clone()),
-                            };
-                            if let Ok(style_id) = registry.register_cell_style(&cell_style) {
-                                map.insert(idx, style_id);
-                            }
-                        }
-                    }
-                    map
-                } else {
-                    HashMap::new()
-                };
-
-                let cell_style_map: HashMap<(usize, usize), u32> = HashMap::new();
-                xml::generate_sheet_xml_from_arrow(batches, config, &col_format_map, &cell_style_map)
-            })
-            .collect::<Result<Vec<_>, _>>()?
+/// Write `filename` through a temporary file in the same directory, renamed
+/// over it only once `write` has succeeded. Worksheets are compressed as
+/// they are generated (or as batches arrive when streaming), so a failure
+/// part way must not truncate an existing workbook or leave a partial one
+/// behind.
+fn write_file_atomically<F>(filename: &str, write: F) -> Result<(), WriteError>
+where
+    F: FnOnce(File) -> Result<File, WriteError>,
+{
+    static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+    let path = Path::new(filename);
+    let Some(name) = path.file_name() else {
+        return Err(WriteError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("'{}' is not a file path", filename),
+        )));
     };
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.{}.tmp", std::process::id(), NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed)));
+    let temp_path = path.with_file_name(temp_name);
+
+    let file = OpenOptions::new().write(true).create_new(true).open(&temp_path)?;
+    // Replacing a workbook keeps its permissions
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let _ = file.set_permissions(metadata.permissions());
+    }
+    let written = write(file).and_then(|mut file| {
+        file.flush()?;
+        file.sync_all()?;
+        Ok(std::fs::rename(&temp_path, path)?)
+    });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// (filename, sheets) of one workbook passed to `write_workbooks`
+pub type WorkbookSpec<'a> = (&'a str, Vec<(&'a [RecordBatch], &'a str, &'a StyleConfig)>);
 
-    let mut zipper = ZipArchive::new();
+/// Write independent workbooks in parallel on one pool of `num_threads`
+/// threads. Every file's sheets are generated and compressed as tasks on
+/// that pool, so files, sheets and compression chunks share the threads
+/// instead of each file starting its own. A failed file doesn't stop the
+/// others; the result for each spec comes back in order.
+pub fn write_workbooks(
+    specs: &[WorkbookSpec],
+    num_threads: usize,
+    workbook: &WorkbookConfig,
+) -> Result<Vec<Result<(), WriteError>>, WriteError> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| WriteError::Validation(format!("Thread pool error: {}", e)))?;
+    // Thread counts of 0 generate and compress on the pool running the write
+    let workbook = WorkbookConfig { zip_threads: 0, ..workbook.clone() };
+    Ok(pool.install(|| {
+        specs
+            .par_iter()
+            .map(|(filename, sheets)| write_multiple_sheets_arrow_with_configs(sheets, filename, 0, &workbook))
+            .collect()
+    }))
+}
+
+/// Write a multi-sheet workbook as a pipeline: each sheet is a rayon task
+/// that generates its XML on the `num_threads` pool, deflates it on the
+/// `zip_threads` pool (0 for either uses the pool the caller runs in) and
+/// hands the compressed entry to an `OrderedSink`. Generation and compression
+/// of different sheets overlap, only compressed sheets wait for their turn,
+/// and no worker blocks waiting for another sheet. Sheets enter the archive
+/// in sheet order. XML buffers go back to a pool once compressed, so later
+/// sheets reuse their capacity instead of each allocating and faulting in a
+/// fresh multi-hundred-MB buffer.
+fn write_sheets_pipelined<W: Write + Send>(
+    sheets: &[(&[RecordBatch], &str, &StyleConfig)],
+    prepared: PreparedSheets,
+    writer: W,
+    num_threads: usize,
+    workbook: &WorkbookConfig,
+) -> Result<W, WriteError> {
+    let PreparedSheets { style_registry, sheet_styles } = prepared;
     let sheet_names: Vec<&str> = sheets.iter().map(|(_, name, _)| *name).collect();
-    let tables_count: Vec<usize> = sheets.iter().map(|(_, _, config)| config.tables.len()).collect();
-    let charts_count: Vec<usize> = sheets.iter().map(|(_, _, config)| config.charts.len()).collect();
-    let images_data: Vec<(Vec<ExcelImage>, usize)> = sheets.iter().map(|(_, _, config)| {
-        let drawing_count = if config.charts.is_empty() && config.images.is_empty() { 0 } else { 1 };
-        (config.images.clone(), drawing_count)
-    }).collect();
 
-    add_static_files(&mut zipper, &sheet_names, None, &tables_count, &charts_count, &images_data);
+    // Everything but the worksheets is small and known up front
+    let mut zipper = ZipArchive::new(workbook.zip_threads, workbook.compression());
+    let tables_per_sheet: Vec<usize> = sheets.iter().map(|(_, _, cfg)| cfg.tables.len()).collect();
+    let charts_per_sheet: Vec<usize> = sheets.iter().map(|(_, _, cfg)| cfg.charts.len()).collect();
 
-    for (idx, xml_data) in xml_results.into_iter().enumerate() {
-        zipper
-            .add_file_from_memory(xml_data, format!("xl/worksheets/sheet{}.xml", idx + 1))
-            .compression_level(CompressionLevel::fast())
-            .done();
-    }
+    let images_per_sheet: Vec<(&[ExcelImage], usize)> = sheets.iter()
+            .map(|(_, _, cfg)| {
+                // count drawing if charts OR images exist
+                let count = if cfg.charts.is_empty() && cfg.images.is_empty() { 0 } else { 1 };
+                (cfg.images.as_slice(), count)
+            })
+            .collect();
+    add_static_files(&mut zipper, &sheet_names, Some(&style_registry), &tables_per_sheet, &charts_per_sheet, &images_per_sheet, workbook);
 
     let mut global_chart_id = 1;
     let mut global_table_id = 1;
     let mut drawing_id = 1;
 
-    for (idx, (_, _, sheet_config)) in sheets.iter().enumerate() {
-        let has_charts = !sheet_config.charts.is_empty();
+    for (idx, &(_, _, sheet_config)) in sheets.iter().enumerate() {
+        let hyperlinks: Vec<(String, usize)> = sheet_config.hyperlinks
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.url.clone(), i + 1))
+            .collect();
+
+        let has_hyperlinks = !hyperlinks.is_empty();
         let has_tables = !sheet_config.tables.is_empty();
-        
-        if has_tables || has_charts || !sheet_config.images.is_empty() {
+        let has_charts = !sheet_config.charts.is_empty();
+        let has_images = !sheet_config.images.is_empty();
+
+        if has_hyperlinks || has_tables || has_charts || has_images {
             let mut rels_xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
             
-            if has_tables {
-                for table_idx in 0..sheet_config.tables.len() {
-                    rels_xml.push_str(&format!("<Relationship Id=\"rIdTable{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/table\" Target=\"../tables/table{}.xml\"/>\n", table_idx + 1, global_table_id + table_idx));
-                }
+            for (url, rid) in &hyperlinks {
+                rels_xml.push_str(&format!("<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>\n", rid, xml::hyperlink_target(url)));
+            }
+            
+            let sheet_start_table_id = global_table_id;
+            for i in 0..sheet_config.tables.len() {
+                rels_xml.push_str(&format!("<Relationship Id=\"rIdTable{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/table\" Target=\"../tables/table{}.xml\"/>\n", 
+                    i + 1, 
+                    sheet_start_table_id + i));
             }
             
-            if has_charts || !sheet_config.images.is_empty() {
+            if has_charts || has_images {
                 rels_xml.push_str(&format!("<Relationship Id=\"rIdDraw1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing\" Target=\"../drawings/drawing{}.xml\"/>\n", drawing_id));
             }
             
             rels_xml.push_str("</Relationships>");
+            
             zipper
-                .add_file_from_memory(rels_xml.into_bytes(), format!("xl/worksheets/_rels/sheet{}.xml.rels", idx + 1))
-                .compression_level(CompressionLevel::fast())
+                .add_file_from_memory(
+                    rels_xml.into_bytes(),
+                    format!("xl/worksheets/_rels/sheet{}.xml.rels", idx + 1)
+                )
                 .done();
         }
         
         if has_tables {
+            // Calculate total rows for this sheet
             let total_data_rows: usize = sheets[idx].0.iter().map(|b| b.num_rows()).sum();
-            let num_cols = if !sheets[idx].0.is_empty() { 
-                sheets[idx].0[0].schema().fields().len() 
-            } else { 
-                0 
-            };
             
             for table in &sheet_config.tables {
+                let num_cols = sheets[idx].0.first().map_or(0, |b| xml::table_width(&b.schema(), table.range.1));
                 let mut adjusted_table = table.clone();
-                
-                if adjusted_table.range.2 == 0 {
-                    adjusted_table.range.2 = adjusted_table.range.0 + total_data_rows;
-                }
-                
-                if adjusted_table.range.3 == 0 {
-                    if num_cols > 0 {
-                        adjusted_table.range.3 = adjusted_table.range.1 + num_cols - 1;
-                    }
-                }
-                
-                if adjusted_table.range.0 > 1 && table.range.2 != 0 {
-                    adjusted_table.range.2 += 1;
-                }
+                adjusted_table.range = table.resolved_range(total_data_rows, num_cols);
                 
                 let col_names = if table.column_names.is_empty() && !sheets[idx].0.is_empty() {
                     let schema = sheets[idx].0[0].schema();
@@ -687,13 +674,15 @@ pub fn write_multiple_sheets_arrow_to_bytes(
                     table.column_names.clone()
                 };
                 
-                let table_xml = xml::generate_table_xml(&adjusted_table, global_table_id as u32, &col_names);
+                let last_column_numeric = sheets[idx].0.first()
+                    .and_then(|b| b.schema().fields().get(adjusted_table.range.3).map(|f| f.data_type().is_numeric()))
+                    .unwrap_or(false);
+                let table_xml = xml::generate_table_xml(&adjusted_table, global_table_id as u32, &col_names, last_column_numeric);
                 zipper
                     .add_file_from_memory(
                         table_xml.into_bytes(),
                         format!("xl/tables/table{}.xml", global_table_id)
                     )
-                    .compression_level(CompressionLevel::fast())
                     .done();
                 global_table_id += 1;
             }
@@ -704,351 +693,350 @@ pub fn write_multiple_sheets_arrow_to_bytes(
             let drawing_xml = generate_drawing_xml_combined(&sheet_config.charts, &sheet_config.images);
             zipper
                 .add_file_from_memory(drawing_xml.into_bytes(), format!("xl/drawings/drawing{}.xml", drawing_id))
-                .compression_level(CompressionLevel::fast())
                 .done();
             
             let drawing_rels = generate_drawing_rels_combined(sheet_config.charts.len(), &sheet_config.images, global_chart_id);
             
             zipper
                 .add_file_from_memory(drawing_rels.into_bytes(), format!("xl/drawings/_rels/drawing{}.xml.rels", drawing_id))
-                .compression_level(CompressionLevel::fast())
                 .done();
             
             for chart in &sheet_config.charts {
-                let chart_xml = xml::generate_chart_xml(chart, sheets[idx].1);
+                let chart_xml = chart_xml_for_data(chart, sheets[idx].1, sheets[idx].0);
                 zipper
                     .add_file_from_memory(
                         chart_xml.into_bytes(),
                         format!("xl/charts/chart{}.xml", global_chart_id)
                     )
-                    .compression_level(CompressionLevel::fast())
                     .done();
                 global_chart_id += 1;
             }
-            
-            for (img_idx, image) in sheet_config.images.iter().enumerate() {
+            // Add image files
+            for (idx, image) in sheet_config.images.iter().enumerate() {
                 zipper
                     .add_file_from_memory(
                         image.image_data.clone(),
-                        format!("xl/media/image{}.{}", img_idx + 1, image.extension)
+                        format!("xl/media/image{}.{}", idx + 1, image.extension)
                     )
-                    .compression_level(CompressionLevel::fast())
                     .done();
+                if let Some(fallback) = image.fallback_png() {
+                    zipper
+                        .add_file_from_memory(fallback, format!("xl/media/image{}.png", idx + 1))
+                        .done();
+                }
             }
             
             drawing_id += 1;
         }
     }
 
-    write_zip_to_buffer(zipper)
+    let xml_pool = thread_pool(num_threads)?;
+    let zip_pool = thread_pool(workbook.zip_threads)?;
+    let mut stream = ZipStreamWriter::new(writer, 0)?;
+    match &zip_pool {
+        Some(pool) => pool.install(|| stream.append(&mut zipper)),
+        None => stream.append(&mut zipper),
+    }?;
+
+    let buffers = BufferPool::default();
+    let progress = workbook.progress.as_ref();
+    if let Some(progress) = progress {
+        progress.begin(Some(sheets.iter().flat_map(|(batches, _, _)| batches.iter()).map(|b| b.num_rows()).sum()));
+    }
+    let generated = AtomicUsize::new(0);
+    let sink = OrderedSink::new(stream);
+
+    let write_sheet = |(idx, ((batches, _, config), styles)): (usize, (&(&[RecordBatch], &str, &StyleConfig), &SheetStyles))| {
+        let generate = || {
+            let mut xml_data = buffers.take();
+            xml::generate_sheet_xml_into(batches, config, styles, progress, &mut xml_data).map(|()| xml_data)
+        };
+        let xml_data = match &xml_pool {
+            Some(pool) => pool.install(generate),
+            None => generate(),
+        }?;
+        if generated.fetch_add(1, Ordering::Relaxed) + 1 == sheets.len() {
+            if let Some(progress) = progress {
+                progress.report(ProgressPhase::Compressing).map_err(WriteError::Validation)?;
+            }
+        }
+
+        let compress = || zip::compress_entry(&xml_data, format!("xl/worksheets/sheet{}.xml", idx + 1), workbook.compression());
+        let entry = match &zip_pool {
+            Some(pool) => pool.install(compress),
+            None => compress(),
+        }?;
+        buffers.give(xml_data);
+        sink.push(idx, entry)
+    };
+    sheets.par_iter().zip(sheet_styles.par_iter()).enumerate().try_for_each(write_sheet)?;
+
+    Ok(sink.into_stream().finish()?)
 }
 
+/// Pool of `threads` threads; None for 0, which runs the work on the pool
+/// the caller is already in (rayon's global pool, one thread per core,
+/// outside any)
+fn thread_pool(threads: usize) -> Result<Option<rayon::ThreadPool>, WriteError> {
+    if threads == 0 {
+        return Ok(None);
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map(Some)
+        .map_err(|e| WriteError::Validation(format!("Thread pool error: {}", e)))
+}
 
-pub fn write_multiple_sheets_arrow(
-    sheets: &[(Vec<RecordBatch>, String)],
-    filename: &str,
-    num_threads: usize,
-) -> Result<(), WriteError> {
-    write_multiple_sheets_arrow_with_configs(
-        &sheets.iter().map(|(b, n)| (b.as_slice(), n.as_str(), StyleConfig::default())).collect::<Vec<_>>(),
-        filename,
-        num_threads,
-    )
+/// Compressed worksheets written to the archive in sheet order. The worker
+/// that holds the stream writes every entry that is next in line; one that
+/// finds the stream busy leaves its entry to that worker, so no thread ever
+/// waits for another sheet to finish.
+struct OrderedSink<W: Write> {
+    stream: Mutex<ZipStreamWriter<W>>,
+    /// Entries that finished ahead of an earlier sheet, and the index of the
+    /// next sheet to write
+    waiting: Mutex<(HashMap<usize, zip::CompressedEntry>, usize)>,
 }
 
-pub fn write_multiple_sheets_arrow_with_configs(
-    sheets: &[(&[RecordBatch], &str, StyleConfig)],
-    filename: &str,
-    num_threads: usize,
-) -> Result<(), WriteError> {
-    for (_, name, _) in sheets {
-        validate_sheet_name(name)?;
+impl<W: Write> OrderedSink<W> {
+    fn new(stream: ZipStreamWriter<W>) -> Self {
+        Self { stream: Mutex::new(stream), waiting: Mutex::new((HashMap::new(), 0)) }
     }
 
-    let mut style_registry = StyleRegistry::new();
-    let mut sheet_col_format_maps = Vec::new();
-    let mut sheet_cell_style_maps = Vec::new();
-    let mut sheet_dxf_mappings = Vec::new();
-
-    for (batches, _, config) in sheets {
-        let schema = batches[0].schema();
-        let mut col_format_map = HashMap::new();
-        if let Some(formats) = &config.column_formats {
-            for (idx, field) in schema.fields().iter().enumerate() {
-                if let Some(fmt) = formats.get(field.name()) {
-                    let cell_style = CellStyle {
-                        font: None,
-                        fill: None,
-                        border: None,
-                        alignment: None,
-                        number_format: Some(fmt.clone()),
-                    };
-                    let style_id = style_registry.register_cell_style(&cell_style)
-                        .map_err(|e| WriteError::Validation(e))?;
-                    col_format_map.insert(idx, style_id);
-                }
+    fn push(&self, idx: usize, entry: zip::CompressedEntry) -> Result<(), WriteError> {
+        let lock_waiting = || self.waiting.lock().unwrap_or_else(|e| e.into_inner());
+        lock_waiting().0.insert(idx, entry);
+        loop {
+            let Ok(mut stream) = self.stream.try_lock() else {
+                return Ok(());
+            };
+            loop {
+                let entry = {
+                    let mut waiting = lock_waiting();
+                    let next_idx = waiting.1;
+                    match waiting.0.remove(&next_idx) {
+                        Some(entry) => {
+                            waiting.1 += 1;
+                            entry
+                        }
+                        None => break,
+                    }
+                };
+                stream.write_compressed(entry)?;
             }
-        }
-        sheet_col_format_maps.push(col_format_map);
-
-        // Build cell style map for this sheet
-        let mut cell_style_map: HashMap<(usize, usize), u32> = HashMap::new();
-        for cell_style in &config.cell_styles {
-            let style_id = style_registry.register_cell_style(&cell_style.style)
-                .map_err(|e| WriteError::Validation(e))?;
-            cell_style_map.insert((cell_style.row, cell_style.col), style_id);
-        }
-        sheet_cell_style_maps.push(cell_style_map);
-
-        let mut dxf_ids = HashMap::new();
-        for (idx, cond_format) in config.conditional_formats.iter().enumerate() {
-            match &cond_format.rule {
-                ConditionalRule::CellValue { .. } | ConditionalRule::Top10 { .. } => {
-                    style_registry.register_cell_style(&cond_format.style)
-                        .map_err(|e| WriteError::Validation(e))?;
-                    let dxf_id = style_registry.register_dxf(&cond_format.style);
-                    dxf_ids.insert(idx, dxf_id);
-                }
-                _ => {}
+            drop(stream);
+            // The next entry may have arrived while this worker still held the stream
+            let waiting = lock_waiting();
+            if !waiting.0.contains_key(&waiting.1) {
+                return Ok(());
             }
         }
-        sheet_dxf_mappings.push(dxf_ids);
     }
 
-    let xml_and_hyperlinks: Vec<(Vec<u8>, Vec<(String, usize)>)> = 
-        if num_threads > 1 && sheets.len() > 1 {
-            let pool = rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .build()
-                .map_err(|e| WriteError::Validation(format!("Thread pool error: {}", e)))?;
-            
-            pool.install(|| {
-                sheets
-                    .par_iter()
-                    .enumerate()
-                    .map(|(sheet_idx, (batches, _, config))| {
-                        let mut modified_config = (*config).clone();
-                        if sheet_idx < sheet_dxf_mappings.len() {
-                            modified_config.cond_format_dxf_ids = sheet_dxf_mappings[sheet_idx].clone();
-                        }
-                        
-                        let col_format_map = &sheet_col_format_maps[sheet_idx];
-                        let cell_style_map = &sheet_cell_style_maps[sheet_idx];
-                        let xml_data = xml::generate_sheet_xml_from_arrow(batches, &modified_config, col_format_map, cell_style_map)?;
-                        let hyperlinks: Vec<(String, usize)> = modified_config.hyperlinks
-                            .iter()
-                            .enumerate()
-                            .map(|(i, h)| (h.url.clone(), i + 1))
-                            .collect();
-                        Ok((xml_data, hyperlinks))
-                    })
-                    .collect::<Result<Vec<_>, WriteError>>()
-            })?
-        } else {
-            sheets
-                .iter()
-                .enumerate()
-                .map(|(sheet_idx, (batches, _, config))| {
-                    let mut modified_config = (*config).clone();
-                    if sheet_idx < sheet_dxf_mappings.len() {
-                        modified_config.cond_format_dxf_ids = sheet_dxf_mappings[sheet_idx].clone();
-                    }
-                    
-                    let col_format_map = &sheet_col_format_maps[sheet_idx];
-                    let cell_style_map = &sheet_cell_style_maps[sheet_idx];
-                    let xml_data = xml::generate_sheet_xml_from_arrow(batches, &modified_config, col_format_map, cell_style_map)?;
-                    let hyperlinks: Vec<(String, usize)> = modified_config.hyperlinks
-                        .iter()
-                        .enumerate()
-                        .map(|(i, h)| (h.url.clone(), i + 1))
-                        .collect();
-                    Ok((xml_data, hyperlinks))
-                })
-                .collect::<Result<Vec<_>, WriteError>>()?
-        };
+    /// The stream once every sheet has been pushed
+    fn into_stream(self) -> ZipStreamWriter<W> {
+        self.stream.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
 
-    let mut zipper = ZipArchive::new();
-    let sheet_names: Vec<&str> = sheets.iter().map(|(_, name, _)| *name).collect();
-    let tables_per_sheet: Vec<usize> = sheets.iter().map(|(_, _, cfg)| cfg.tables.len()).collect();
-    let charts_per_sheet: Vec<usize> = sheets.iter().map(|(_, _, cfg)| cfg.charts.len()).collect();
+/// Styles of a multi-sheet workbook, registered once its sheet names,
+/// cross-sheet references and tab options have been checked
+struct PreparedSheets {
+    style_registry: StyleRegistry,
+    sheet_styles: Vec<SheetStyles>,
+}
 
-    let images_per_sheet: Vec<(Vec<ExcelImage>, usize)> = sheets.iter()
-            .map(|(_, _, cfg)| {
-                // count drawing if charts OR images exist
-                let count = if cfg.charts.is_empty() && cfg.images.is_empty() { 0 } else { 1 };
-                (cfg.images.clone(), count)
-            })
-            .collect();
-    add_static_files(&mut zipper, &sheet_names, Some(&style_registry), &tables_per_sheet, &charts_per_sheet, &images_per_sheet);
+/// Everything that can reject a multi-sheet workbook before a byte of it is
+/// written
+fn prepare_sheets(
+    sheets: &[(&[RecordBatch], &str, &StyleConfig)],
+    workbook: &WorkbookConfig,
+) -> Result<PreparedSheets, WriteError> {
+    for (_, name, _) in sheets {
+        validate_sheet_name(name)?;
+    }
+    let sheet_names: Vec<&str> = sheets.iter().map(|(_, name, _)| *name).collect();
+    for (_, _, config) in sheets {
+        validate_sheet_references(&sheet_names, config)?;
+    }
+    validate_sheet_tabs(&sheet_names, workbook)?;
 
-    let mut global_chart_id = 1;
-    let mut global_table_id = 1;
-    let mut drawing_id = 1;
+    let mut style_registry = StyleRegistry::new();
+    let sheet_styles = sheets
+        .iter()
+        .map(|(batches, _, config)| register_sheet_styles(&mut style_registry, batches, config))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(PreparedSheets { style_registry, sheet_styles })
+}
 
-    for (idx, (xml_data, hyperlinks)) in xml_and_hyperlinks.into_iter().enumerate() {
-        let sheet_config = &sheets[idx].2;
-        
-        zipper
-            .add_file_from_memory(xml_data, format!("xl/worksheets/sheet{}.xml", idx + 1))
-            .compression_level(CompressionLevel::fast())
-            .done();
+/// Worksheet XML buffers returned after compression. A buffer is only live
+/// while its sheet is generated or compressed, one per busy worker, so the
+/// pool stays bounded by the thread count rather than the sheet count.
+#[derive(Default)]
+struct BufferPool {
+    free: Mutex<Vec<Vec<u8>>>,
+}
 
-        let has_hyperlinks = !hyperlinks.is_empty();
-        let has_tables = !sheet_config.tables.is_empty();
-        let has_charts = !sheet_config.charts.is_empty();
-        let has_images = !sheet_config.images.is_empty();
+impl BufferPool {
+    /// A free buffer, or a new empty one when every buffer is in use
+    fn take(&self) -> Vec<u8> {
+        self.free.lock().ok().and_then(|mut free| free.pop()).unwrap_or_default()
+    }
 
-        if has_hyperlinks || has_tables || has_charts || has_images {
-            let mut rels_xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
-            
-            for (url, rid) in &hyperlinks {
-                rels_xml.push_str(&format!("<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"{}\" TargetMode=\"External\"/>\n", rid, url));
-            }
-            
-            let sheet_start_table_id = global_table_id;
-            for i in 0..sheet_config.tables.len() {
-                rels_xml.push_str(&format!("<Relationship Id=\"rIdTable{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/table\" Target=\"../tables/table{}.xml\"/>\n", 
-                    i + 1, 
-                    sheet_start_table_id + i));
-            }
-            
-            if has_charts || has_images {
-                rels_xml.push_str(&format!("<Relationship Id=\"rIdDraw1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing\" Target=\"../drawings/drawing{}.xml\"/>\n", drawing_id));
-            }
-            
-            rels_xml.push_str("</Relationships>");
-            
-            zipper
-                .add_file_from_memory(
-                    rels_xml.into_bytes(),
-                    format!("xl/worksheets/_rels/sheet{}.xml.rels", idx + 1)
-                )
-                .compression_level(CompressionLevel::fast())
-                .done();
-        }
-        
-        if has_tables {
-            // Calculate total rows and cols for this sheet
-            let total_data_rows: usize = sheets[idx].0.iter().map(|b| b.num_rows()).sum();
-            let num_cols = if !sheets[idx].0.is_empty() { 
-                sheets[idx].0[0].schema().fields().len() 
-            } else { 
-                0 
-            };
-            
-            for table in &sheet_config.tables {
-                let mut adjusted_table = table.clone();
-                
-                // Auto-calculate end_row if not specified (0 means auto)
-                if adjusted_table.range.2 == 0 {
-                    // end_row = start_row + num_data_rows - 1 (inclusive)
-                    adjusted_table.range.2 = adjusted_table.range.0 + total_data_rows;
-                }
-                
-                // Auto-calculate end_col if not specified (0 means auto)
-                if adjusted_table.range.3 == 0 {
-                    if num_cols > 0 {
-                        adjusted_table.range.3 = adjusted_table.range.1 + num_cols - 1;
-                    }
-                }
-                
-                // If table starts after row 1, we inserted a header row, so adjust end_row
-                // Only adjust if user manually specified end_row (not auto-calculated)
-                if adjusted_table.range.0 > 1 && table.range.2 != 0 {
-                    adjusted_table.range.2 += 1; // end_row++
-                }
-                
-                let col_names = if table.column_names.is_empty() && !sheets[idx].0.is_empty() {
-                    let schema = sheets[idx].0[0].schema();
-                    let (_, start_col, _, end_col) = adjusted_table.range;
-                    schema.fields()[start_col..=end_col]
-                        .iter()
-                        .map(|f| f.name().clone())
-                        .collect()
-                } else {
-                    table.column_names.clone()
-                };
-                
-                let table_xml = xml::generate_table_xml(&adjusted_table, global_table_id as u32, &col_names);
-                zipper
-                    .add_file_from_memory(
-                        table_xml.into_bytes(),
-                        format!("xl/tables/table{}.xml", global_table_id)
-                    )
-                    .compression_level(CompressionLevel::fast())
-                    .done();
-                global_table_id += 1;
-            }
-        }
-        
-        let has_images = !sheet_config.images.is_empty();
-        if has_charts || has_images {
-            let sheet_start_chart_id = global_chart_id;
-            
-            let drawing_xml = generate_drawing_xml_combined(&sheet_config.charts, &sheet_config.images);
-            zipper
-                .add_file_from_memory(drawing_xml.into_bytes(), format!("xl/drawings/drawing{}.xml", drawing_id))
-                .compression_level(CompressionLevel::fast())
-                .done();
-            
-            let drawing_rels = generate_drawing_rels_combined(sheet_config.charts.len(), &sheet_config.images, global_chart_id);
-            
-            zipper
-                .add_file_from_memory(drawing_rels.into_bytes(), format!("xl/drawings/_rels/drawing{}.xml.rels", drawing_id))
-                .compression_level(CompressionLevel::fast())
-                .done();
-            
-            for chart in &sheet_config.charts {
-                let chart_xml = xml::generate_chart_xml(chart, sheets[idx].1);
-                zipper
-                    .add_file_from_memory(
-                        chart_xml.into_bytes(),
-                        format!("xl/charts/chart{}.xml", global_chart_id)
-                    )
-                    .compression_level(CompressionLevel::fast())
-                    .done();
-                global_chart_id += 1;
-            }
-            // Add image files
-            for (idx, image) in sheet_config.images.iter().enumerate() {
-                zipper
-                    .add_file_from_memory(
-                        image.image_data.clone(),
-                        format!("xl/media/image{}.{}", idx + 1, image.extension)
-                    )
-                    .compression_level(CompressionLevel::fast())
-                    .done();
-            }
-            
-            drawing_id += 1;
+    fn give(&self, buf: Vec<u8>) {
+        if let Ok(mut free) = self.free.lock() {
+            free.push(buf);
         }
     }
+}
 
-    write_zip_to_file(zipper, filename)
+/// Projected output of a one-sheet write, so oversized exports can be
+/// rejected before they start
+#[derive(Debug, Clone, Copy)]
+pub struct WriteEstimate {
+    pub rows: usize,
+    pub columns: usize,
+    /// Uncompressed worksheet XML
+    pub xml_bytes: usize,
+    pub file_bytes: usize,
+    /// Memory the write allocates on top of the Arrow data itself
+    pub peak_memory_bytes: usize,
+}
+
+/// Rows whose XML is deflated to measure how well the sheet compresses
+const ESTIMATE_SAMPLE_ROWS: usize = 10_000;
+
+/// Compressed size of every part besides the worksheet (styles, rels,
+/// docProps, theme, zip directory)
+const STATIC_PARTS_BYTES: usize = 4_000;
+
+/// Estimate a one-sheet write without generating the workbook. The XML size
+/// is counted from the data; the compressed size applies the ratio measured
+/// on the first `ESTIMATE_SAMPLE_ROWS` rows at the workbook's compression level.
+pub fn estimate_single_sheet(batches: &[RecordBatch], workbook: &WorkbookConfig) -> Result<WriteEstimate, WriteError> {
+    let rows = batches.iter().map(|b| b.num_rows()).sum();
+    let columns = batches.first().map_or(0, |b| b.num_columns());
+    let xml_bytes = xml::estimate_sheet_xml_size(batches);
+
+    let mut sample = Vec::new();
+    let mut remaining = ESTIMATE_SAMPLE_ROWS;
+    for batch in batches {
+        let take = remaining.min(batch.num_rows());
+        if take == 0 {
+            continue;
+        }
+        sample.push(batch.slice(0, take));
+        remaining -= take;
+    }
+    let sample_xml = xml::generate_sheet_xml_from_arrow(&sample, &StyleConfig::default(), &SheetStyles::default(), None)?;
+    let sample_compressed = zip::compressed_size(&sample_xml, workbook.compression())?;
+    let ratio = sample_compressed as f64 / sample_xml.len().max(1) as f64;
+    let file_bytes = STATIC_PARTS_BYTES + (xml_bytes as f64 * ratio) as usize;
+    let buffered = xml_bytes.min(xml::SHEET_XML_CHUNK_SIZE + zip::OPEN_ENTRY_FLUSH_SIZE);
+
+    Ok(WriteEstimate {
+        rows,
+        columns,
+        xml_bytes,
+        file_bytes,
+        // The XML is generated a chunk at a time into the zip entry, which
+        // deflates its pending data once it fills
+        peak_memory_bytes: buffered + (buffered as f64 * ratio) as usize,
+    })
 }
 
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// Register every style a sheet references: column formats (dates and
+/// timestamps get their default formats), cell styles, conditional format
+/// dxfs, and the header/hyperlink styles when the sheet uses them. Only what
+/// is registered here ends up in styles.xml.
+fn register_sheet_styles(
+    registry: &mut StyleRegistry,
+    batches: &[RecordBatch],
+    config: &StyleConfig,
+) -> Result<SheetStyles, WriteError> {
+    let mut styles = SheetStyles::default();
+
+    let schema = batches[0].schema();
+    for (idx, field) in schema.fields().iter().enumerate() {
+        let fmt = config.column_formats_by_index.get(&idx)
+            .or_else(|| config.column_formats.as_ref().and_then(|f| f.get(field.name())));
+        let fmt = match fmt {
+            Some(fmt) => fmt.clone(),
+            None if config.text_columns.contains(field.name()) => NumberFormat::Text,
+            None => match field.data_type() {
+                DataType::Date32 | DataType::Date64 => NumberFormat::Date,
+                DataType::Timestamp(_, _) => NumberFormat::DateTime,
+                DataType::Time32(_) | DataType::Time64(_) => NumberFormat::Time,
+                DataType::Duration(_) => NumberFormat::Custom("[h]:mm:ss".to_string()),
+                DataType::Decimal128(_, scale) | DataType::Decimal256(_, scale) => NumberFormat::decimal_places(*scale),
+                _ => continue,
+            },
+        };
+        let style_id = registry.register_number_format(&fmt)
+            .map_err(WriteError::Validation)?;
+        styles.col_format_map.insert(idx, style_id);
+        if fmt.is_bool_display() && field.data_type() == &DataType::Boolean {
+            styles.bool_number_columns.insert(idx);
+        }
+    }
+
+    for cell_style in &config.cell_styles {
+        let style_id = registry.register_cell_style(&cell_style.style)
+            .map_err(WriteError::Validation)?;
+        styles.cell_style_map.insert((cell_style.row, cell_style.col), style_id);
+    }
+
+    styles.row_format_ids = config.row_formats.iter()
+        .map(|(&row, fmt)| registry.register_number_format(fmt).map(|style_id| (row, style_id)))
+        .collect::<Result<_, _>>()
+        .map_err(WriteError::Validation)?;
+
+    styles.range_style_ids = config.range_styles.iter()
+        .map(|range| registry.register_cell_style(&range.style))
+        .collect::<Result<_, _>>()
+        .map_err(WriteError::Validation)?;
+
+    for (idx, cond_format) in config.conditional_formats.iter().enumerate() {
+        match &cond_format.rule {
+            // Color scales and data bars carry their own colors
+            ConditionalRule::ColorScale { .. } | ConditionalRule::DataBar { .. } => {}
+            _ => {
+                let dxf_id = registry.register_dxf(&cond_format.style)
+                    .map_err(WriteError::Validation)?;
+                styles.cond_format_dxf_ids.insert(idx, dxf_id);
+            }
+        }
+    }
+
+    if config.styled_headers {
+        styles.header_style_id = registry.register_header_style();
+    }
+    if !config.hyperlinks.is_empty() {
+        styles.hyperlink_style_id = registry.register_hyperlink_style();
+    }
+
+    Ok(styles)
+}
+
 fn add_static_files(
     zipper: &mut ZipArchive, 
     sheet_names: &[&str],
     style_registry: Option<&StyleRegistry>,
     tables_count: &[usize], // Number of tables per sheet
     charts_count: &[usize],
-    images_data: &[(Vec<ExcelImage>, usize)],
+    images_per_sheet: &[(&[ExcelImage], usize)],
+    workbook: &WorkbookConfig,
 ) {
-    let images_per_sheet: Vec<(&[ExcelImage], usize)> = images_data.iter()
-            .map(|(imgs, count)| (imgs.as_slice(), *count))
-            .collect();
-        
         zipper
             .add_file_from_memory(
-                xml::generate_content_types_with_charts(sheet_names, tables_count, charts_count, &images_per_sheet).into_bytes(),
+                xml::generate_content_types_with_charts(sheet_names, tables_count, charts_count, images_per_sheet).into_bytes(),
                 "[Content_Types].xml".to_string(),
             )
-            .compression_level(CompressionLevel::fast())
             .done();
     
     zipper
@@ -1056,7 +1044,6 @@ fn add_static_files(
             xml::generate_rels().as_bytes().to_vec(),
             "_rels/.rels".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
         .done();
     
     // Add document properties
@@ -1065,23 +1052,20 @@ fn add_static_files(
             xml::generate_core_xml().as_bytes().to_vec(),
             "docProps/core.xml".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
         .done();
     
     zipper
         .add_file_from_memory(
-            xml::generate_app_xml(sheet_names).into_bytes(),
+            xml::generate_app_xml(sheet_names, workbook).into_bytes(),
             "docProps/app.xml".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
         .done();
     
     zipper
         .add_file_from_memory(
-            xml::generate_workbook(sheet_names).into_bytes(),
+            xml::generate_workbook(sheet_names, workbook).into_bytes(),
             "xl/workbook.xml".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
         .done();
     
     zipper
@@ -1089,11 +1073,17 @@ fn add_static_files(
             xml::generate_workbook_rels(sheet_names.len()).into_bytes(),
             "xl/_rels/workbook.xml.rels".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
+        .done();
+    
+    zipper
+        .add_file_from_memory(
+            xml::generate_theme_xml(&workbook.theme).into_bytes(),
+            "xl/theme/theme1.xml".to_string(),
+        )
         .done();
     
     let styles_xml = if let Some(registry) = style_registry {
-        generate_styles_xml_enhanced(registry)
+        generate_styles_xml_enhanced(registry, &workbook.indexed_colors)
     } else {
         generate_styles_xml()
     };
@@ -1103,25 +1093,362 @@ fn add_static_files(
             styles_xml.into_bytes(),
             "xl/styles.xml".to_string(),
         )
-        .compression_level(CompressionLevel::fast())
         .done();
 }
 
 fn write_zip_to_file(mut zipper: ZipArchive, filename: &str) -> Result<(), WriteError> {
     let mut file = File::create(filename)?;
-    zipper.write(&mut file)
-        .map_err(|e| WriteError::Validation(e.to_string()))?;
+    zipper.write(&mut file)?;
     file.flush()?;
     file.sync_all()?;
     Ok(())
 }
 
-fn write_zip_to_buffer(mut zipper: ZipArchive) -> Result<Vec<u8>, WriteError> {
-    let mut buffer = Vec::new();
-    let mut cursor = std::io::Cursor::new(&mut buffer);
-    zipper.write(&mut cursor)
-        .map_err(|e| WriteError::Validation(e.to_string()))?;
-    Ok(buffer)
+/// Name of the sheet added by `WorkbookConfig::summary_sheet`
+const SUMMARY_SHEET_NAME: &str = "Summary";
+
+/// Put the generated summary sheet in front of `sheets` when the workbook asks for one
+fn with_summary_sheet<'a>(
+    sheets: &[(&'a [RecordBatch], &'a str, &'a StyleConfig)],
+    workbook: &WorkbookConfig,
+) -> Result<Option<(RecordBatch, StyleConfig)>, WriteError> {
+    let Some(summary) = &workbook.summary_sheet else {
+        return Ok(None);
+    };
+    if sheets.iter().any(|(_, name, _)| name.eq_ignore_ascii_case(SUMMARY_SHEET_NAME)) {
+        return Err(WriteError::Validation(format!(
+            "Sheet name '{}' is reserved for the summary sheet", SUMMARY_SHEET_NAME
+        )));
+    }
+    build_summary_sheet(summary, sheets).map(Some)
+}
+
+/// Summary sheet: generation time, generator, caller metadata and a digest
+/// of the sheet options, above a table of rows and columns per sheet
+fn build_summary_sheet(
+    summary: &SummarySheet,
+    sheets: &[(&[RecordBatch], &str, &StyleConfig)],
+) -> Result<(RecordBatch, StyleConfig), WriteError> {
+    let generated = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let generator = format!("jetxl {}", env!("CARGO_PKG_VERSION"));
+    let mut info = vec![
+        ("Generated".to_string(), generated),
+        ("Generator".to_string(), generator),
+        ("Config version".to_string(), CONFIG_VERSION.to_string()),
+        ("Config digest".to_string(), config_digest(sheets)),
+    ];
+    info.extend(summary.metadata.iter().cloned());
+
+    let title_style = CellStyle {
+        font: Some(FontStyle { bold: true, italic: false, underline: None, strike: false, vert_align: None, size: Some(14.0), color: None, name: None }),
+        fill: None,
+        border: None,
+        alignment: None,
+        number_format: None,
+    };
+    let label_style = CellStyle {
+        font: Some(FontStyle { bold: true, italic: false, underline: None, strike: false, vert_align: None, size: None, color: None, name: None }),
+        ..title_style.clone()
+    };
+
+    // auto_width only sees the table, so size the label and value columns here
+    let label_width = info.iter().map(|(label, _)| label.chars().count())
+        .chain(sheets.iter().map(|(_, name, _)| name.chars().count()))
+        .fold("Workbook summary".len(), usize::max);
+    let value_width = info.iter().map(|(_, value)| value.chars().count()).fold(10, usize::max);
+
+    let mut config = StyleConfig {
+        styled_headers: true,
+        column_widths_by_index: [
+            (0, ColumnWidth::Characters(label_width as f64 + 2.0)),
+            (1, ColumnWidth::Characters(value_width as f64 + 2.0)),
+        ].into_iter().collect(),
+        header_content: vec![(1, 0, "Workbook summary".to_string())],
+        cell_styles: vec![CellStyleMap { row: 1, col: 0, style: title_style }],
+        // Info rows, a blank row, then the sheet table
+        data_start_row: info.len() + 3,
+        ..StyleConfig::default()
+    };
+    for (i, (label, value)) in info.into_iter().enumerate() {
+        config.header_content.push((i + 2, 0, label));
+        config.header_content.push((i + 2, 1, value));
+        config.cell_styles.push(CellStyleMap { row: i + 2, col: 0, style: label_style.clone() });
+    }
+
+    let names: Vec<&str> = sheets.iter().map(|(_, name, _)| *name).collect();
+    let rows: Vec<u64> = sheets
+        .iter()
+        .map(|(batches, _, _)| batches.iter().map(|b| b.num_rows() as u64).sum())
+        .collect();
+    let cols: Vec<u64> = sheets
+        .iter()
+        .map(|(batches, _, _)| batches.first().map_or(0, |b| b.num_columns() as u64))
+        .collect();
+    let batch = RecordBatch::try_from_iter([
+        ("Sheet", Arc::new(StringArray::from(names)) as ArrayRef),
+        ("Rows", Arc::new(UInt64Array::from(rows)) as ArrayRef),
+        ("Columns", Arc::new(UInt64Array::from(cols)) as ArrayRef),
+    ])
+    .map_err(|e| WriteError::Validation(e.to_string()))?;
+
+    Ok((batch, config))
+}
+
+/// True when the sheet holds more data rows than its `split_rows` limit
+fn needs_split(batches: &[RecordBatch], config: &StyleConfig) -> bool {
+    config.split_rows.is_some_and(|limit| batches.iter().map(|b| b.num_rows()).sum::<usize>() > limit)
+}
+
+/// (batches, name, config) sheets produced by `split_long_sheets`; only
+/// continuation sheets own their config
+type SplitSheets<'a> = Vec<(Vec<RecordBatch>, String, Cow<'a, StyleConfig>)>;
+
+/// Replace every sheet over its `split_rows` limit with the first part plus
+/// continuation sheets "Name (2)", "Name (3)", ... Returns None when no
+/// sheet needs splitting. Batches are sliced, not copied.
+fn split_long_sheets<'a>(
+    sheets: &[(&[RecordBatch], &str, &'a StyleConfig)],
+) -> Result<Option<SplitSheets<'a>>, WriteError> {
+    if !sheets.iter().any(|(batches, _, config)| needs_split(batches, config)) {
+        return Ok(None);
+    }
+
+    let mut split = Vec::new();
+    for (batches, name, config) in sheets {
+        let limit = match config.split_rows {
+            Some(limit) if needs_split(batches, config) => limit,
+            _ => {
+                split.push((batches.to_vec(), name.to_string(), Cow::Borrowed(*config)));
+                continue;
+            }
+        };
+        if limit == 0 {
+            return Err(WriteError::Validation(format!("split_rows for sheet '{}' must be at least 1", name)));
+        }
+
+        let mut previous = name.to_string();
+        for (i, part) in chunk_batches(batches, limit).into_iter().enumerate() {
+            if i == 0 {
+                split.push((part, name.to_string(), Cow::Borrowed(*config)));
+                continue;
+            }
+            let part_name = continuation_sheet_name(name, i + 1);
+            let part_config = continuation_config(config, &previous);
+            previous = part_name.clone();
+            split.push((part, part_name, Cow::Owned(part_config)));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for (batches, name, config) in &split {
+        if !seen.insert(name.to_lowercase()) {
+            return Err(WriteError::Validation(format!(
+                "Continuation sheet '{}' clashes with another sheet name", name
+            )));
+        }
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        if config.first_data_row() - 1 + rows > xml::MAX_ROWS {
+            return Err(WriteError::Validation(format!(
+                "Sheet '{}' would need more than {} rows; lower split_rows", name, xml::MAX_ROWS
+            )));
+        }
+    }
+    Ok(Some(split))
+}
+
+/// Slice batches into parts of at most `rows` rows each
+fn chunk_batches(batches: &[RecordBatch], rows: usize) -> Vec<Vec<RecordBatch>> {
+    let mut parts = vec![Vec::new()];
+    let mut room = rows;
+    for batch in batches {
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            if room == 0 {
+                parts.push(Vec::new());
+                room = rows;
+            }
+            let len = room.min(batch.num_rows() - offset);
+            parts.last_mut().unwrap().push(batch.slice(offset, len));
+            offset += len;
+            room -= len;
+        }
+    }
+    parts
+}
+
+/// "Name (n)", shortening the name so the result stays within 31 bytes
+fn continuation_sheet_name(name: &str, part: usize) -> String {
+    let suffix = format!(" ({})", part);
+    let mut base = String::new();
+    for ch in name.chars() {
+        if base.len() + ch.len_utf8() + suffix.len() > 31 {
+            break;
+        }
+        base.push(ch);
+    }
+    base + &suffix
+}
+
+/// Config for a continuation sheet: an italic "Continued from" note on row 1
+/// and the header row on row 2, with the first sheet's column formats,
+/// widths, header style, autofilter and sheet view. Options tied to specific
+/// cells (merges, styles, formulas, rich text, validations, conditional
+/// formats, tables, charts, images, row heights and hidden rows) stay on the
+/// first part.
+fn continuation_config(config: &StyleConfig, previous: &str) -> StyleConfig {
+    let note_style = CellStyle {
+        font: Some(FontStyle { bold: false, italic: true, underline: None, strike: false, vert_align: None, size: None, color: None, name: None }),
+        fill: None,
+        border: None,
+        alignment: None,
+        number_format: None,
+    };
+
+    let mut part = StyleConfig {
+        merge_cells: Vec::new(),
+        data_validations: Vec::new(),
+        hyperlinks: Vec::new(),
+        row_heights: None,
+        row_formats: HashMap::new(),
+        cell_styles: vec![CellStyleMap { row: 1, col: 0, style: note_style }],
+        range_styles: Vec::new(),
+        formulas: Vec::new(),
+        shared_formulas: Vec::new(),
+        rich_text: Vec::new(),
+        conditional_formats: Vec::new(),
+        tables: Vec::new(),
+        charts: Vec::new(),
+        images: Vec::new(),
+        hidden_rows: Default::default(),
+        row_groups: Vec::new(),
+        data_start_row: 2,
+        header_content: vec![(1, 0, format!("Continued from {}", previous))],
+        sort_state: None,
+        ..config.clone()
+    };
+    // Keep the same rows frozen relative to the first data row
+    if config.freeze_rows > 0 {
+        part.freeze_rows = (config.freeze_rows + part.first_data_row()).saturating_sub(config.first_data_row());
+    }
+    part
+}
+
+/// First 16 hex digits of a SHA-256 over the sheet names and options, so
+/// runs with the same settings can be matched up
+fn config_digest(sheets: &[(&[RecordBatch], &str, &StyleConfig)]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut encoder = DigestEncoder(Sha256::new());
+    for (_, name, config) in sheets {
+        encoder.field("sheet", name);
+        encoder.config(config);
+    }
+    encoder.0.finalize()[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Canonical encoding of sheet options for `config_digest`: every field by
+/// name with a length prefix, maps and sets sorted by key. Nested values use
+/// their Debug form, which is stable because none of them holds a map.
+struct DigestEncoder(sha2::Sha256);
+
+impl DigestEncoder {
+    fn bytes(&mut self, bytes: &[u8]) {
+        use sha2::Digest;
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+    }
+
+    fn field(&mut self, name: &str, value: impl std::fmt::Debug) {
+        self.bytes(name.as_bytes());
+        self.bytes(format!("{:?}", value).as_bytes());
+    }
+
+    fn map<K: Ord + std::fmt::Debug, V: std::fmt::Debug>(&mut self, name: &str, map: Option<&HashMap<K, V>>) {
+        let mut entries: Option<Vec<(&K, &V)>> = map.map(|m| m.iter().collect());
+        if let Some(entries) = &mut entries {
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        }
+        self.field(name, entries);
+    }
+
+    fn set<T: Ord + std::fmt::Debug>(&mut self, name: &str, set: &HashSet<T>) {
+        let mut items: Vec<&T> = set.iter().collect();
+        items.sort_unstable();
+        self.field(name, items);
+    }
+
+    /// Destructured so a new StyleConfig field doesn't compile until it's
+    /// part of the digest
+    fn config(&mut self, config: &StyleConfig) {
+        let StyleConfig {
+            auto_filter, freeze_rows, freeze_cols, styled_headers, write_header_row,
+            column_widths, auto_width, column_formats, format_totals_rows, format_whole_columns,
+            text_columns, column_formats_by_index, column_widths_by_index, column_timezones,
+            column_timezones_by_index, merge_cells, data_validations, hyperlinks, row_heights,
+            row_formats, cell_styles, range_styles, formulas, shared_formulas, column_formulas,
+            rich_text, conditional_formats, tables, charts, images, gridlines_visible, zoom_scale,
+            selection, top_left_cell, tab_color, default_row_height, hidden_columns, hidden_rows,
+            row_groups, col_groups, right_to_left, data_start_row, header_content, pre_1900_dates,
+            date_1904, list_separator, binary_encoding, binary_max_length, invalid_text,
+            filter_columns, sort_state, split_rows,
+        } = config;
+        self.field("auto_filter", auto_filter);
+        self.field("freeze_rows", freeze_rows);
+        self.field("freeze_cols", freeze_cols);
+        self.field("styled_headers", styled_headers);
+        self.field("write_header_row", write_header_row);
+        self.map("column_widths", column_widths.as_ref());
+        self.field("auto_width", auto_width);
+        self.map("column_formats", column_formats.as_ref());
+        self.field("format_totals_rows", format_totals_rows);
+        self.field("format_whole_columns", format_whole_columns);
+        self.set("text_columns", text_columns);
+        self.map("column_formats_by_index", Some(column_formats_by_index));
+        self.map("column_widths_by_index", Some(column_widths_by_index));
+        self.map("column_timezones", Some(column_timezones));
+        self.map("column_timezones_by_index", Some(column_timezones_by_index));
+        self.field("merge_cells", merge_cells);
+        self.field("data_validations", data_validations);
+        self.field("hyperlinks", hyperlinks);
+        self.map("row_heights", row_heights.as_ref());
+        self.map("row_formats", Some(row_formats));
+        self.field("cell_styles", cell_styles);
+        self.field("range_styles", range_styles);
+        self.field("formulas", formulas);
+        self.field("shared_formulas", shared_formulas);
+        self.map("column_formulas", Some(column_formulas));
+        self.field("rich_text", rich_text);
+        self.field("conditional_formats", conditional_formats);
+        self.field("tables", tables);
+        self.field("charts", charts);
+        // Image bytes are hashed as they are rather than printed
+        self.field("images", images.len());
+        for image in images {
+            self.bytes(&image.image_data);
+            self.field("image", ExcelImage { image_data: Vec::new(), ..image.clone() });
+        }
+        self.field("gridlines_visible", gridlines_visible);
+        self.field("zoom_scale", zoom_scale);
+        self.field("selection", selection);
+        self.field("top_left_cell", top_left_cell);
+        self.field("tab_color", tab_color);
+        self.field("default_row_height", default_row_height);
+        self.set("hidden_columns", hidden_columns);
+        self.set("hidden_rows", hidden_rows);
+        self.field("row_groups", row_groups);
+        self.field("col_groups", col_groups);
+        self.field("right_to_left", right_to_left);
+        self.field("data_start_row", data_start_row);
+        self.field("header_content", header_content);
+        self.field("pre_1900_dates", pre_1900_dates);
+        self.field("date_1904", date_1904);
+        self.field("list_separator", list_separator);
+        self.field("binary_encoding", binary_encoding);
+        self.field("binary_max_length", binary_max_length);
+        self.field("invalid_text", invalid_text);
+        self.map("filter_columns", Some(filter_columns));
+        self.field("sort_state", sort_state);
+        self.field("split_rows", split_rows);
+    }
 }
 
 fn validate_sheet_name(name: &str) -> Result<(), WriteError> {
@@ -1132,4 +1459,394 @@ fn validate_sheet_name(name: &str) -> Result<(), WriteError> {
         return Err(WriteError::Validation(format!("Sheet name '{}' contains invalid chars", name)));
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Check `sheet_order`, `active_sheet` and `first_sheet` against the sheets
+/// actually written, which include split parts and the summary sheet
+fn validate_sheet_tabs(sheet_names: &[&str], workbook: &WorkbookConfig) -> Result<(), WriteError> {
+    for (i, name) in workbook.sheet_order.iter().enumerate() {
+        if !sheet_names.contains(&name.as_str()) {
+            return Err(WriteError::Validation(format!("sheet_order names unknown sheet '{}'", name)));
+        }
+        if workbook.sheet_order[..i].contains(name) {
+            return Err(WriteError::Validation(format!("sheet_order lists '{}' twice", name)));
+        }
+    }
+    for (option, index) in [("active_sheet_index", workbook.active_sheet), ("first_sheet", workbook.first_sheet)] {
+        if index >= sheet_names.len() {
+            return Err(WriteError::Validation(format!(
+                "{} {} is out of range for {} sheets", option, index, sheet_names.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that every other sheet a config points at (validation list
+/// sources, chart series ranges) is part of the workbook. Excel would
+/// otherwise drop the reference while repairing the file.
+fn validate_sheet_references(sheet_names: &[&str], config: &StyleConfig) -> Result<(), WriteError> {
+    for validation in &config.data_validations {
+        let ValidationType::ListRef(source) = &validation.validation_type else {
+            continue;
+        };
+        let Some((sheet, _)) = source.trim_start_matches('=').rsplit_once('!') else {
+            continue;
+        };
+        let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+            Some(quoted) => quoted.replace("''", "'"),
+            None => sheet.to_string(),
+        };
+        // Sheet names are case-insensitive in Excel
+        if !sheet_names.iter().any(|name| name.to_lowercase() == sheet.to_lowercase()) {
+            return Err(WriteError::Validation(format!(
+                "Data validation source '{}' references sheet '{}', which is not in the workbook (sheets: {})",
+                source, sheet, sheet_names.join(", ")
+            )));
+        }
+    }
+    let series_sheets = config.charts.iter()
+        .flat_map(|chart| &chart.series)
+        .filter_map(|series| series.range.as_ref()?.sheet.as_deref());
+    for sheet in series_sheets {
+        if !sheet_names.iter().any(|name| name.to_lowercase() == sheet.to_lowercase()) {
+            return Err(WriteError::Validation(format!(
+                "Chart series references sheet '{}', which is not in the workbook (sheets: {})",
+                sheet, sheet_names.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// One Arrow table placed on a sheet next to others, with formats and widths
+/// keyed by its own column names
+pub struct ColumnGroup {
+    pub batches: Vec<RecordBatch>,
+    pub column_formats: HashMap<String, NumberFormat>,
+    pub column_widths: HashMap<String, ColumnWidth>,
+    pub column_timezones: HashMap<String, Tz>,
+}
+
+/// Side-by-side tables merged into one batch, with each group's formats and
+/// widths moved to the column positions they ended up at
+pub struct CombinedColumns {
+    pub batches: Vec<RecordBatch>,
+    pub column_formats: HashMap<usize, NumberFormat>,
+    pub column_widths: HashMap<usize, ColumnWidth>,
+    pub column_timezones: HashMap<usize, Tz>,
+}
+
+/// Chart XML with an automatic category axis resolved from the Arrow type of
+/// the category column: dates and timestamps get a date axis
+fn chart_xml_for_data(chart: &ExcelChart, sheet_name: &str, batches: &[RecordBatch]) -> String {
+    if chart.category_axis != CategoryAxis::Auto {
+        return xml::generate_chart_xml(chart, sheet_name);
+    }
+    let category_col = chart.category_col.unwrap_or(chart.data_range.1);
+    let is_date = batches.first()
+        .and_then(|batch| batch.schema().fields().get(category_col).cloned())
+        .is_some_and(|field| matches!(field.data_type(), DataType::Date32 | DataType::Date64 | DataType::Timestamp(_, _)));
+    let category_axis = if is_date { CategoryAxis::Date(DateAxis::default()) } else { CategoryAxis::Text };
+    xml::generate_chart_xml(&ExcelChart { category_axis, ..chart.clone() }, sheet_name)
+}
+
+/// Lay `groups` out left to right with `gap` empty columns between them.
+/// Shorter tables are padded with empty cells to the longest one. Gap
+/// columns are marked with `xml::GAP_COLUMN_KEY`, so auto-sized tables stop
+/// before them.
+pub fn combine_column_groups(groups: Vec<ColumnGroup>, gap: usize) -> Result<CombinedColumns, WriteError> {
+    let tables = groups
+        .iter()
+        .map(|group| {
+            let schema = group.batches.first()
+                .ok_or_else(|| WriteError::Validation("Column group has no data".to_string()))?
+                .schema();
+            concat_batches(&schema, &group.batches).map_err(|e| WriteError::Validation(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let num_rows = tables.iter().map(|t| t.num_rows()).max().unwrap_or(0);
+
+    let mut fields = Vec::new();
+    let mut columns: Vec<ArrayRef> = Vec::new();
+    let mut column_formats = HashMap::new();
+    let mut column_widths = HashMap::new();
+    let mut column_timezones = HashMap::new();
+
+    for (group_idx, (group, table)) in groups.into_iter().zip(&tables).enumerate() {
+        if group_idx > 0 {
+            for _ in 0..gap {
+                fields.push(Field::new("", DataType::Utf8, true)
+                    .with_metadata(HashMap::from([(xml::GAP_COLUMN_KEY.to_string(), "1".to_string())])));
+                columns.push(new_null_array(&DataType::Utf8, num_rows));
+            }
+        }
+
+        let padding = num_rows - table.num_rows();
+        for (field, column) in table.schema().fields().iter().zip(table.columns()) {
+            let idx = columns.len();
+            if let Some(fmt) = group.column_formats.get(field.name()) {
+                column_formats.insert(idx, fmt.clone());
+            }
+            if let Some(width) = group.column_widths.get(field.name()) {
+                column_widths.insert(idx, width.clone());
+            }
+            if let Some(tz) = group.column_timezones.get(field.name()) {
+                column_timezones.insert(idx, *tz);
+            }
+
+            let column = if padding > 0 {
+                let nulls = new_null_array(field.data_type(), padding);
+                concat(&[column.as_ref(), nulls.as_ref()]).map_err(|e| WriteError::Validation(e.to_string()))?
+            } else {
+                column.clone()
+            };
+            fields.push(field.as_ref().clone().with_nullable(true));
+            columns.push(column);
+        }
+    }
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| WriteError::Validation(e.to_string()))?;
+    Ok(CombinedColumns { batches: vec![batch], column_formats, column_widths, column_timezones })
+}
+
+/// Replace dictionary-encoded columns (Polars categoricals, pandas
+/// categories) with their plain values, so every column reaches the cell
+/// writer as a type it handles
+pub fn decode_dictionaries(batch: RecordBatch) -> Result<RecordBatch, WriteError> {
+    if !batch.schema().fields().iter().any(|f| matches!(f.data_type(), DataType::Dictionary(_, _))) {
+        return Ok(batch);
+    }
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        match field.data_type() {
+            DataType::Dictionary(_, value_type) => {
+                let err = |e: arrow_schema::ArrowError| WriteError::Validation(format!("Column '{}': {}", field.name(), e));
+                // Arrow can't cast a dictionary of string views (Polars
+                // categoricals), so its values become large strings first
+                let (column, value_type) = if value_type.as_ref() == &DataType::Utf8View {
+                    use arrow_array::cast::AsArray;
+                    let dict = column.as_any_dictionary();
+                    let values = arrow::compute::cast(dict.values(), &DataType::LargeUtf8).map_err(err)?;
+                    (dict.with_values(values), DataType::LargeUtf8)
+                } else {
+                    (column.clone(), value_type.as_ref().clone())
+                };
+                let values = arrow::compute::cast(&column, &value_type).map_err(err)?;
+                fields.push(field.as_ref().clone().with_data_type(value_type));
+                columns.push(values);
+            }
+            _ => {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+            }
+        }
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| WriteError::Validation(e.to_string()))
+}
+
+/// Replace string view columns (Polars strings and categoricals exported
+/// through the Arrow C stream) with large strings, which the cell writer,
+/// auto width and text checks all handle
+pub fn decode_string_views(batch: RecordBatch) -> Result<RecordBatch, WriteError> {
+    if !batch.schema().fields().iter().any(|f| f.data_type() == &DataType::Utf8View) {
+        return Ok(batch);
+    }
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        if field.data_type() == &DataType::Utf8View {
+            let values = arrow::compute::cast(column, &DataType::LargeUtf8)
+                .map_err(|e| WriteError::Validation(format!("Column '{}': {}", field.name(), e)))?;
+            fields.push(field.as_ref().clone().with_data_type(DataType::LargeUtf8));
+            columns.push(values);
+        } else {
+            fields.push(field.as_ref().clone());
+            columns.push(column.clone());
+        }
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| WriteError::Validation(e.to_string()))
+}
+
+/// Expand struct columns into one column per field, named "parent.child"
+/// (nested structs recursively). A null struct makes all its fields null.
+pub fn flatten_structs(batch: RecordBatch) -> Result<RecordBatch, WriteError> {
+    if !batch.schema().fields().iter().any(|f| matches!(f.data_type(), DataType::Struct(_))) {
+        return Ok(batch);
+    }
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        push_flattened(field.name(), field, column, &mut fields, &mut columns)?;
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| WriteError::Validation(e.to_string()))
+}
+
+fn push_flattened(
+    name: &str,
+    field: &Field,
+    column: &ArrayRef,
+    fields: &mut Vec<Field>,
+    columns: &mut Vec<ArrayRef>,
+) -> Result<(), WriteError> {
+    let Some(structs) = column.as_any().downcast_ref::<StructArray>() else {
+        fields.push(field.clone().with_name(name));
+        columns.push(column.clone());
+        return Ok(());
+    };
+    let parent_nulls = structs.logical_nulls()
+        .map(|nulls| BooleanArray::new(!nulls.inner(), None));
+    for (child_field, child) in structs.fields().iter().zip(structs.columns()) {
+        let child = match &parent_nulls {
+            Some(is_null) => nullif(child, is_null).map_err(|e| WriteError::Validation(e.to_string()))?,
+            None => child.clone(),
+        };
+        let child_name = format!("{}.{}", name, child_field.name());
+        push_flattened(&child_name, &child_field.as_ref().clone().with_nullable(true), &child, fields, columns)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::styles::InvalidText;
+
+    /// A fresh directory holding `existing.xlsx` with placeholder contents
+    fn dir_with_existing_file(name: &str) -> (std::path::PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("jetxl-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("existing.xlsx");
+        std::fs::write(&path, b"keep me").unwrap();
+        (dir, path.to_str().unwrap().to_string())
+    }
+
+    fn text_batch(values: Vec<&str>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("text", DataType::Utf8, false)]));
+        RecordBatch::try_new(schema, vec![Arc::new(StringArray::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn failed_multi_sheet_writes_keep_the_existing_file() {
+        let (dir, path) = dir_with_existing_file("multi");
+        let batches = [text_batch(vec!["fine", "bad \u{1}"])];
+        let strict = StyleConfig { invalid_text: InvalidText::Error, ..StyleConfig::default() };
+
+        // Rejected while the sheet is being written
+        let sheets = [(&batches[..], "One", &strict), (&batches[..], "Two", &strict)];
+        assert!(write_multiple_sheets_arrow_with_configs(&sheets, &path, 2, &WorkbookConfig::default()).is_err());
+        // Rejected before anything is written
+        let config = StyleConfig::default();
+        let sheets = [(&batches[..], "Bad/Name", &config)];
+        assert!(write_multiple_sheets_arrow_with_configs(&sheets, &path, 1, &WorkbookConfig::default()).is_err());
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep me");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "temporary file left behind");
+
+        let sheets = [(&batches[..], "One", &config)];
+        write_multiple_sheets_arrow_with_configs(&sheets, &path, 1, &WorkbookConfig::default()).unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(b"PK"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_single_sheet_writes_keep_the_existing_file() {
+        let (dir, path) = dir_with_existing_file("single");
+        let strict = StyleConfig { invalid_text: InvalidText::Error, ..StyleConfig::default() };
+        let workbook = WorkbookConfig::default();
+
+        let batches = [text_batch(vec!["fine", "bad \u{1}"])];
+        assert!(write_single_sheet_arrow_with_config(&batches, "Sheet1", &path, &strict, &workbook).is_err());
+
+        // The bad row arrives in a later batch, after the file has been started
+        let rest = vec![Ok(text_batch(vec!["bad \u{1}"]))].into_iter();
+        assert!(write_single_sheet_arrow_streaming(text_batch(vec!["fine"]), rest, "Sheet1", &path, &strict, &workbook).is_err());
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep me");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "temporary file left behind");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sheets_enter_the_archive_in_sheet_order() {
+        // The first sheet is the slowest, so later ones finish before it
+        let big = text_batch(vec!["row"; 200_000]);
+        let small = text_batch(vec!["row"]);
+        let config = StyleConfig::default();
+        let names: Vec<String> = (1..=8).map(|i| format!("S{}", i)).collect();
+        let sheets: Vec<_> = names.iter().enumerate()
+            .map(|(i, name)| (std::slice::from_ref(if i == 0 { &big } else { &small }), name.as_str(), &config))
+            .collect();
+
+        let bytes = write_multiple_sheets_arrow_to_bytes(&sheets, 4, &WorkbookConfig::default()).unwrap();
+        let archive = ::zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let worksheets: Vec<&str> = archive.file_names().filter(|n| n.starts_with("xl/worksheets/sheet")).collect();
+        let expected: Vec<String> = (1..=8).map(|i| format!("xl/worksheets/sheet{}.xml", i)).collect();
+        assert_eq!(worksheets, expected);
+    }
+
+    #[test]
+    fn workbooks_share_one_pool() {
+        let dir = std::env::temp_dir().join(format!("jetxl-workbooks-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let batches = [text_batch(vec!["row"; 1000])];
+        let config = StyleConfig::default();
+        let sheets = vec![(&batches[..], "One", &config), (&batches[..], "Two", &config), (&batches[..], "Three", &config)];
+        let paths: Vec<String> = (0..4).map(|i| dir.join(format!("book{}.xlsx", i)).to_str().unwrap().to_string()).collect();
+        let specs: Vec<WorkbookSpec> = paths.iter().map(|path| (path.as_str(), sheets.clone())).collect();
+
+        // With one thread every sheet task of every file has to run on it without deadlocking
+        for num_threads in [1, 3] {
+            let results = write_workbooks(&specs, num_threads, &WorkbookConfig::default()).unwrap();
+            assert!(results.iter().all(|r| r.is_ok()));
+            for path in &paths {
+                let mut archive = ::zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+                let worksheets: Vec<String> = archive.file_names()
+                    .filter(|n| n.starts_with("xl/worksheets/sheet"))
+                    .map(str::to_string)
+                    .collect();
+                assert_eq!(worksheets, ["xl/worksheets/sheet1.xml", "xl/worksheets/sheet2.xml", "xl/worksheets/sheet3.xml"]);
+                for name in worksheets {
+                    std::io::copy(&mut archive.by_name(&name).unwrap(), &mut std::io::sink()).unwrap();
+                }
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn config_digest_ignores_map_order_but_not_values() {
+        let digest = |config: &StyleConfig| config_digest(&[(&[], "Sheet1", config)]);
+        let heights: Vec<(usize, f64)> = (0..50).map(|i| (i, i as f64 + 8.0)).collect();
+
+        let forward = StyleConfig {
+            row_heights: Some(heights.iter().copied().collect()),
+            hidden_rows: (0..50).collect(),
+            ..StyleConfig::default()
+        };
+        let backward = StyleConfig {
+            row_heights: Some(heights.iter().rev().copied().collect()),
+            hidden_rows: (0..50).rev().collect(),
+            ..StyleConfig::default()
+        };
+        assert_eq!(digest(&forward), digest(&backward));
+
+        // Values moved between fields or reordered in a list are different settings
+        let rows = StyleConfig { freeze_rows: 1, freeze_cols: 2, ..StyleConfig::default() };
+        let cols = StyleConfig { freeze_rows: 2, freeze_cols: 1, ..StyleConfig::default() };
+        assert_ne!(digest(&rows), digest(&cols));
+        let ab = StyleConfig { header_content: vec![(1, 0, "a".into()), (2, 0, "b".into())], ..StyleConfig::default() };
+        let ba = StyleConfig { header_content: vec![(1, 0, "b".into()), (2, 0, "a".into())], ..StyleConfig::default() };
+        assert_ne!(digest(&ab), digest(&ba));
+    }
+}
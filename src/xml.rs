@@ -1,9 +1,12 @@
 use crate::types::{CellValue, SheetData, WriteError};
 use crate::styles::*;
-use arrow_array::{Array, RecordBatch,Time32SecondArray, Time32MillisecondArray, Time64MicrosecondArray, Time64NanosecondArray};
-use arrow_schema::DataType;
-use chrono::Timelike;
-use std::collections::HashMap;
+use arrow_array::timezone::Tz;
+use arrow_array::{Array, ArrayRef, RecordBatch,Time32SecondArray, Time32MillisecondArray, Time64MicrosecondArray, Time64NanosecondArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::{Offset, TimeZone, Timelike};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 /// Official OOXML CT_Worksheet element order from the schema
 // const WORKSHEET_ELEMENT_ORDER: &[&str] = &[
@@ -21,12 +24,41 @@ use std::collections::HashMap;
 //     "activeXControls", "webPublishItems", "tableParts", "extLst"
 // ];
 
-pub fn generate_app_xml(sheet_names: &[&str]) -> String {
+/// Positions in `sheet_names` in tab order: the sheets named in
+/// `workbook.sheet_order` first, then the rest in their original order
+pub fn sheet_tab_order(sheet_names: &[&str], workbook: &WorkbookConfig) -> Vec<usize> {
+    let mut order: Vec<usize> = workbook.sheet_order.iter()
+        .filter_map(|name| sheet_names.iter().position(|n| n == name))
+        .collect();
+    for i in 0..sheet_names.len() {
+        if !order.contains(&i) {
+            order.push(i);
+        }
+    }
+    order
+}
+
+/// AppVersion written when none is given. Readers expect the `XX.YYYY` form
+/// Excel uses, so this is Excel 2016's rather than the crate version.
+const DEFAULT_APP_VERSION: &str = "16.0300";
+
+pub fn generate_app_xml(sheet_names: &[&str], workbook: &WorkbookConfig) -> String {
+    let application = workbook
+        .application
+        .clone()
+        .unwrap_or_else(|| format!("jetxl {}", env!("CARGO_PKG_VERSION")));
+    let app_version = workbook.app_version.as_deref().unwrap_or(DEFAULT_APP_VERSION);
+    let company = workbook
+        .company
+        .as_deref()
+        .map(|c| format!("<Company>{}</Company>", escape_xml(c)))
+        .unwrap_or_default();
+
     format!(
         "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
 <Properties xmlns=\"http://schemas.openxmlformats.org/officeDocument/2006/extended-properties\" \
 xmlns:vt=\"http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes\">\
-<Application>Microsoft Excel</Application>\
+<Application>{}</Application>\
 <DocSecurity>0</DocSecurity>\
 <ScaleCrop>false</ScaleCrop>\
 <HeadingPairs><vt:vector size=\"2\" baseType=\"variant\">\
@@ -34,13 +66,80 @@ xmlns:vt=\"http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes\
 <vt:variant><vt:i4>{}</vt:i4></vt:variant>\
 </vt:vector></HeadingPairs>\
 <TitlesOfParts><vt:vector size=\"{}\" baseType=\"lpstr\">{}</vt:vector></TitlesOfParts>\
+{}\
 <LinksUpToDate>false</LinksUpToDate>\
 <SharedDoc>false</SharedDoc>\
-<AppVersion>16.0300</AppVersion>\
+<AppVersion>{}</AppVersion>\
 </Properties>",
+        escape_xml(&application),
         sheet_names.len(),
         sheet_names.len(),
-        sheet_names.iter().map(|n| format!("<vt:lpstr>{}</vt:lpstr>", n)).collect::<Vec<_>>().join("")
+        sheet_tab_order(sheet_names, workbook).into_iter()
+            .map(|i| format!("<vt:lpstr>{}</vt:lpstr>", escape_xml(sheet_names[i])))
+            .collect::<Vec<_>>().join(""),
+        company,
+        escape_xml(app_version)
+    )
+}
+
+const DEFAULT_ACCENTS: [&str; 6] = ["4472C4", "ED7D31", "A5A5A5", "FFC000", "5B9BD5", "70AD47"];
+
+/// xl/theme/theme1.xml: the Office theme with the workbook's accent and
+/// font overrides. Cell `theme=` colors and chart `schemeClr`s resolve here.
+pub fn generate_theme_xml(theme: &WorkbookTheme) -> String {
+    let accents: String = DEFAULT_ACCENTS.iter().enumerate()
+        .map(|(i, default)| format!(
+            "<a:accent{}><a:srgbClr val=\"{}\"/></a:accent{}>",
+            i + 1, theme.accents.get(i).map(String::as_str).unwrap_or(default), i + 1,
+        ))
+        .collect();
+    let major = escape_xml(theme.major_font.as_deref().unwrap_or("Calibri Light"));
+    let minor = escape_xml(theme.minor_font.as_deref().unwrap_or("Calibri"));
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+<a:theme xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" name=\"Office Theme\">\
+<a:themeElements>\
+<a:clrScheme name=\"Office\">\
+<a:dk1><a:sysClr val=\"windowText\" lastClr=\"000000\"/></a:dk1>\
+<a:lt1><a:sysClr val=\"window\" lastClr=\"FFFFFF\"/></a:lt1>\
+<a:dk2><a:srgbClr val=\"44546A\"/></a:dk2>\
+<a:lt2><a:srgbClr val=\"E7E6E6\"/></a:lt2>\
+{}\
+<a:hlink><a:srgbClr val=\"0563C1\"/></a:hlink>\
+<a:folHlink><a:srgbClr val=\"954F72\"/></a:folHlink>\
+</a:clrScheme>\
+<a:fontScheme name=\"Office\">\
+<a:majorFont><a:latin typeface=\"{}\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:majorFont>\
+<a:minorFont><a:latin typeface=\"{}\"/><a:ea typeface=\"\"/><a:cs typeface=\"\"/></a:minorFont>\
+</a:fontScheme>\
+<a:fmtScheme name=\"Office\">\
+<a:fillStyleLst>\
+<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:tint val=\"50000\"/></a:schemeClr></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:shade val=\"80000\"/></a:schemeClr></a:solidFill>\
+</a:fillStyleLst>\
+<a:lnStyleLst>\
+<a:ln w=\"6350\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill><a:prstDash val=\"solid\"/><a:miter lim=\"800000\"/></a:ln>\
+<a:ln w=\"12700\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill><a:prstDash val=\"solid\"/><a:miter lim=\"800000\"/></a:ln>\
+<a:ln w=\"19050\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\"><a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill><a:prstDash val=\"solid\"/><a:miter lim=\"800000\"/></a:ln>\
+</a:lnStyleLst>\
+<a:effectStyleLst>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+<a:effectStyle><a:effectLst/></a:effectStyle>\
+</a:effectStyleLst>\
+<a:bgFillStyleLst>\
+<a:solidFill><a:schemeClr val=\"phClr\"/></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:tint val=\"95000\"/></a:schemeClr></a:solidFill>\
+<a:solidFill><a:schemeClr val=\"phClr\"><a:shade val=\"80000\"/></a:schemeClr></a:solidFill>\
+</a:bgFillStyleLst>\
+</a:fmtScheme>\
+</a:themeElements>\
+<a:objectDefaults/>\
+<a:extraClrSchemeLst/>\
+</a:theme>",
+        accents, major, minor
     )
 }
 
@@ -57,50 +156,292 @@ xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\
 </cp:coreProperties>"
 }
 
-/// Zero-allocation column letter writing - returns length written
-#[inline(always)]
-pub fn write_col_letter(col: usize, buf: &mut [u8; 4]) -> usize {
-    if col < 26 {
-        buf[0] = b'A' + col as u8;
-        return 1;
-    }
-    
+/// Letters of every column a sheet can have, "A" through "XFD", packed into
+/// one string; `ends[col]` is where column `col`'s letters stop
+struct ColumnLetters {
+    text: String,
+    ends: Vec<u32>,
+}
+
+static COLUMN_LETTERS: OnceLock<ColumnLetters> = OnceLock::new();
+
+/// Built on first use and shared by worksheets, charts, tables and names
+fn column_letters() -> &'static ColumnLetters {
+    COLUMN_LETTERS.get_or_init(|| {
+        let mut text = String::with_capacity(3 * MAX_COLS);
+        let mut ends = Vec::with_capacity(MAX_COLS);
+        for col in 0..MAX_COLS {
+            text.push_str(&spell_col_letter(col));
+            ends.push(text.len() as u32);
+        }
+        ColumnLetters { text, ends }
+    })
+}
+
+fn spell_col_letter(col: usize) -> String {
+    let mut letters = Vec::with_capacity(3);
     let mut col = col;
-    let mut len = 0;
-    let mut stack = [0u8; 4];
-    let mut stack_len = 0;
-    
     while col >= 26 {
-        stack[stack_len] = b'A' + (col % 26) as u8;
-        stack_len += 1;
+        letters.push(b'A' + (col % 26) as u8);
         col = col / 26 - 1;
     }
-    stack[stack_len] = b'A' + col as u8;
-    stack_len += 1;
-    
-    for i in 0..stack_len {
-        buf[i] = stack[stack_len - 1 - i];
-        len += 1;
+    letters.push(b'A' + col as u8);
+    letters.iter().rev().map(|&b| b as char).collect()
+}
+
+/// Letters of 0-based column `col` ("A", "AB", ...). Columns past XFD don't
+/// exist in Excel; they are spelled out so a bad reference shows as itself.
+#[inline]
+pub fn col_letter(col: usize) -> Cow<'static, str> {
+    let table = column_letters();
+    match table.ends.get(col) {
+        Some(&end) => {
+            let start = if col == 0 { 0 } else { table.ends[col - 1] };
+            Cow::Borrowed(&table.text[start as usize..end as usize])
+        }
+        None => Cow::Owned(spell_col_letter(col)),
     }
-    
-    len
 }
 
 /// Write cell reference (e.g. "A1", "B2") to buffer
 #[inline(always)]
 fn write_cell_ref(col: usize, row: usize, buf: &mut Vec<u8>) {
-    let mut col_buf = [0u8; 4];
-    let col_len = write_col_letter(col, &mut col_buf);
-    buf.extend_from_slice(&col_buf[..col_len]);
+    buf.extend_from_slice(col_letter(col).as_bytes());
     buf.extend_from_slice(itoa::Buffer::new().format(row).as_bytes());
 }
 
+pub const MAX_ROWS: usize = 1_048_576;
+const MAX_COLS: usize = 16_384;
+
+/// Parse an A1 cell reference ("B2", "$B$2") into a 1-based row and a
+/// 0-based column. Returns None outside the sheet's 1048576 x XFD bounds.
+pub fn parse_cell_ref(cell: &str) -> Option<(usize, usize)> {
+    let cell = cell.trim().as_bytes();
+    let mut pos = usize::from(cell.first() == Some(&b'$'));
+
+    let mut col = 0usize;
+    let letters_start = pos;
+    while pos < cell.len() && cell[pos].is_ascii_alphabetic() && pos - letters_start < 3 {
+        col = col * 26 + (cell[pos].to_ascii_uppercase() - b'A') as usize + 1;
+        pos += 1;
+    }
+    if pos == letters_start || col > MAX_COLS {
+        return None;
+    }
+
+    if cell.get(pos) == Some(&b'$') {
+        pos += 1;
+    }
+    let digits = std::str::from_utf8(&cell[pos..]).ok()?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let row: usize = digits.parse().ok()?;
+    if row == 0 || row > MAX_ROWS {
+        return None;
+    }
+
+    Some((row, col - 1))
+}
+
+/// Parse a column letter ("A", "BC", up to "XFD") into a 0-based column.
+/// Only uppercase letters count, so lowercase column names aren't mistaken
+/// for letters.
+pub fn parse_col_letters(letters: &str) -> Option<usize> {
+    if letters.is_empty() || letters.len() > 3 || !letters.bytes().all(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    let col = letters.bytes().fold(0usize, |col, b| col * 26 + (b - b'A') as usize + 1);
+    (col <= MAX_COLS).then(|| col - 1)
+}
+
+/// Parse an A1 range ("B2:D4") into (start_row, start_col, end_row, end_col)
+/// with 1-based rows and 0-based columns. A single cell is a 1x1 range and
+/// reversed corners ("D4:B2") are normalised.
+pub fn parse_range_ref(range: &str) -> Option<(usize, usize, usize, usize)> {
+    let (first, last) = range.split_once(':').unwrap_or((range, range));
+    let (r1, c1) = parse_cell_ref(first)?;
+    let (r2, c2) = parse_cell_ref(last)?;
+    Some((r1.min(r2), c1.min(c2), r1.max(r2), c1.max(c2)))
+}
+
+/// Excel serial number of `dt`, or None before 1900-01-01 (serial 1).
+///
+/// Excel keeps Lotus 1-2-3's phantom 1900-02-29 (serial 60), so serials are
+/// counted from 1899-12-30 from March 1900 on and from 1899-12-31 before it.
+/// In the 1904 date system serials count from 1904-01-01 (serial 0) and
+/// earlier dates return None.
 #[inline(always)]
-fn datetime_to_excel_serial(dt: &chrono::NaiveDateTime) -> f64 {
-    let excel_epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30).unwrap();
-    let days = (dt.date() - excel_epoch).num_days() as f64;
+pub fn datetime_to_excel_serial(dt: &chrono::NaiveDateTime, date_1904: bool) -> Option<f64> {
+    let days = if date_1904 {
+        let days = (dt.date() - chrono::NaiveDate::from_ymd_opt(1904, 1, 1).unwrap()).num_days();
+        if days < 0 {
+            return None;
+        }
+        days
+    } else {
+        let excel_epoch = chrono::NaiveDate::from_ymd_opt(1899, 12, 30).unwrap();
+        let mut days = (dt.date() - excel_epoch).num_days();
+        if days < 61 {
+            days -= 1;
+        }
+        if days < 1 {
+            return None;
+        }
+        days
+    };
     let time_fraction = (dt.hour() * 3600 + dt.minute() * 60 + dt.second()) as f64 / 86400.0;
-    days + time_fraction
+    Some(days as f64 + time_fraction)
+}
+
+/// Excel serial of the Unix epoch, 1970-01-01
+const UNIX_EPOCH_SERIAL: i64 = 25569;
+
+/// Days between the 1900 and 1904 date systems' serials
+const DATE_1904_OFFSET: i64 = 1462;
+
+/// Earliest date a sheet can store as a serial, for messages
+pub fn first_serial_date(date_1904: bool) -> &'static str {
+    if date_1904 { "1904-01-01" } else { "1900-01-01" }
+}
+
+/// Excel serial for `secs` seconds since the Unix epoch, computed without
+/// building chrono values. Matches `datetime_to_excel_serial` (whole seconds)
+/// but returns None before 1900-03-01, where the phantom leap day and
+/// pre-1900 handling need the chrono path, and before 1904-01-01 in the
+/// 1904 date system.
+#[inline(always)]
+fn unix_to_excel_serial(secs: i64, date_1904: bool) -> Option<f64> {
+    let mut days = secs.div_euclid(86400) + UNIX_EPOCH_SERIAL;
+    if date_1904 {
+        days -= DATE_1904_OFFSET;
+        if days < 0 {
+            return None;
+        }
+    } else if days < 61 {
+        return None;
+    }
+    Some(days as f64 + secs.rem_euclid(86400) as f64 / 86400.0)
+}
+
+/// True for characters XML 1.0 allows in element text
+#[inline(always)]
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// True when `text` is valid UTF-8 made only of characters XML 1.0 allows.
+/// The byte scan covers the common case; U+FFFE/U+FFFF are the only other
+/// characters XML rejects.
+fn is_clean_xml_text(text: &[u8]) -> bool {
+    !text.iter().any(|&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r'))
+        && memchr::memmem::find(text, b"\xEF\xBF\xBE").is_none()
+        && memchr::memmem::find(text, b"\xEF\xBF\xBF").is_none()
+        && std::str::from_utf8(text).is_ok()
+}
+
+/// True when every value of a string column can be written as-is. Checks the
+/// referenced part of the values buffer once, plus that each value starts on
+/// a character boundary, so clean columns skip the per-cell check.
+fn is_clean_string_column(array: &dyn Array) -> bool {
+    fn check<O: arrow_array::OffsetSizeTrait>(arr: &arrow_array::GenericStringArray<O>) -> bool {
+        let offsets = arr.offsets();
+        let values = arr.values().as_ref();
+        let start = offsets[0].as_usize();
+        let end = offsets[offsets.len() - 1].as_usize();
+        is_clean_xml_text(&values[start..end])
+            && offsets.iter().all(|o| values.get(o.as_usize()).is_none_or(|&b| (b as i8) >= -0x40))
+    }
+    use arrow_array::cast::AsArray;
+    match array.data_type() {
+        DataType::Utf8 => check(array.as_string::<i32>()),
+        DataType::LargeUtf8 => check(array.as_string::<i64>()),
+        _ => true,
+    }
+}
+
+/// Escape cell text, handling invalid UTF-8 and characters XML cannot store
+/// per `invalid_text`: replaced by U+FFFD, or an error naming the cell.
+/// `None` means the text is already known to be clean.
+#[inline(always)]
+fn write_cell_text(
+    text: &[u8],
+    cell_ref: &[u8],
+    invalid_text: Option<InvalidText>,
+    buf: &mut Vec<u8>,
+) -> Result<(), WriteError> {
+    let Some(mode) = invalid_text.filter(|_| !is_clean_xml_text(text)) else {
+        xml_escape_simd(text, buf);
+        return Ok(());
+    };
+    if mode == InvalidText::Error {
+        let reason = match std::str::from_utf8(text) {
+            Ok(s) => format!(
+                "character U+{:04X}, which XML cannot store",
+                s.chars().find(|&c| !is_xml_char(c)).map_or(0, |c| c as u32)
+            ),
+            Err(_) => "invalid UTF-8".to_string(),
+        };
+        return Err(WriteError::Validation(format!(
+            "Cell {} contains {}",
+            String::from_utf8_lossy(cell_ref),
+            reason
+        )));
+    }
+    let replaced: String = String::from_utf8_lossy(text)
+        .chars()
+        .map(|c| if is_xml_char(c) { c } else { '\u{FFFD}' })
+        .collect();
+    xml_escape_simd(replaced.as_bytes(), buf);
+    Ok(())
+}
+
+/// Escape user text for chart, table and drawing XML the same way cell text
+/// is escaped, swapping characters XML can't hold for U+FFFD
+pub(crate) fn escape_xml(text: &str) -> String {
+    let cleaned: String = text.chars()
+        .map(|c| if is_xml_char(c) { c } else { '\u{FFFD}' })
+        .collect();
+    let mut buf = Vec::with_capacity(cleaned.len() + 16);
+    xml_escape_simd(cleaned.as_bytes(), &mut buf);
+    // Only ASCII bytes are swapped for ASCII entities, so this stays UTF-8
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+/// Hyperlink target ready for a relationship's Target attribute. UNC paths
+/// (`\\server\share\file`) and drive paths (`C:\dir\file`) become `file:///`
+/// URIs the way Excel stores them. Bytes a URI can't hold (spaces, quotes,
+/// non-ASCII, a `%` that doesn't start an escape) are percent-encoded while
+/// the URL's own delimiters (`?`, `&`, `#`, ...) are kept, so `mailto:` and
+/// query strings work, and the result is XML-escaped.
+pub(crate) fn hyperlink_target(url: &str) -> String {
+    let url = url.trim();
+    let bytes = url.as_bytes();
+    let is_drive_path = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    let uri = if url.starts_with("\\\\") || is_drive_path {
+        format!("file:///{}", url)
+    } else {
+        url.to_string()
+    };
+
+    let bytes = uri.as_bytes();
+    let mut encoded = String::with_capacity(uri.len() + 16);
+    for (i, &b) in bytes.iter().enumerate() {
+        let is_escape = b == b'%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+        match b {
+            b'%' if is_escape => encoded.push('%'),
+            b' ' | b'"' | b'<' | b'>' | b'`' | b'{' | b'}' | b'|' | b'^' | b'%' | 0..=0x1F | 0x7F..=0xFF => {
+                encoded.push_str(&format!("%{:02X}", b));
+            }
+            _ => encoded.push(b as char),
+        }
+    }
+    escape_xml(&encoded)
 }
 
 /// SIMD-accelerated XML escaping
@@ -152,6 +493,7 @@ pub fn generate_content_types(sheet_names: &[&str], tables_per_sheet: &[usize])
 <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
 <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
 <Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>\
+<Override PartName=\"/xl/theme/theme1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.theme+xml\"/>\
 <Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>\
 <Override PartName=\"/docProps/app.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.extended-properties+xml\"/>",
     );
@@ -191,6 +533,9 @@ pub fn generate_content_types_with_charts(
     for (images, _) in images_per_sheet {
         for img in *images {
             image_extensions.insert(img.extension.as_str());
+            if img.is_svg() {
+                image_extensions.insert("png");
+            }
         }
     }
     
@@ -211,6 +556,8 @@ pub fn generate_content_types_with_charts(
             "gif" => "image/gif",
             "bmp" => "image/bmp",
             "tiff" | "tif" => "image/tiff",
+            "svg" => "image/svg+xml",
+            "emf" => "image/x-emf",
             _ => "application/octet-stream",
         };
         xml.push_str(&format!("<Default Extension=\"{}\" ContentType=\"{}\"/>", ext, content_type));
@@ -219,6 +566,7 @@ pub fn generate_content_types_with_charts(
     xml.push_str(
         "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
 <Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>\
+<Override PartName=\"/xl/theme/theme1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.theme+xml\"/>\
 <Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/>\
 <Override PartName=\"/docProps/app.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.extended-properties+xml\"/>",
     );
@@ -272,22 +620,60 @@ pub fn generate_rels() -> &'static str {
 </Relationships>"
 }
 
-pub fn generate_workbook(sheet_names: &[&str]) -> String {
+pub fn generate_workbook(sheet_names: &[&str], workbook: &WorkbookConfig) -> String {
     let mut xml = String::with_capacity(500 + sheet_names.len() * 80);
     xml.push_str(
         "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
 <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
 xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
-<fileVersion appName=\"xl\" lastEdited=\"7\" lowestEdited=\"7\" rupBuild=\"22621\"/>\
-<workbookPr defaultThemeVersion=\"166925\"/>\
-<bookViews><workbookView xWindow=\"0\" yWindow=\"0\" windowWidth=\"28800\" windowHeight=\"12600\"/></bookViews>\
-<sheets>",
+<fileVersion appName=\"xl\" lastEdited=\"7\" lowestEdited=\"7\" rupBuild=\"22621\"/>",
+    );
+
+    // fileSharing (write reservation) must follow fileVersion
+    if workbook.modify_password.is_some() || workbook.read_only_recommended {
+        xml.push_str("<fileSharing");
+        if workbook.read_only_recommended {
+            xml.push_str(" readOnlyRecommended=\"1\"");
+        }
+        if let Some(password) = &workbook.modify_password {
+            let (hash, salt) = hash_password_sha512(password, PASSWORD_SPIN_COUNT);
+            xml.push_str(" algorithmName=\"SHA-512\" hashValue=\"");
+            xml.push_str(&hash);
+            xml.push_str("\" saltValue=\"");
+            xml.push_str(&salt);
+            xml.push_str("\" spinCount=\"");
+            xml.push_str(&PASSWORD_SPIN_COUNT.to_string());
+            xml.push('"');
+        }
+        xml.push_str("/>");
+    }
+
+    xml.push_str(if workbook.date_1904 {
+        "<workbookPr date1904=\"1\" defaultThemeVersion=\"166925\"/>"
+    } else {
+        "<workbookPr defaultThemeVersion=\"166925\"/>"
+    });
+    xml.push_str(
+        "<bookViews><workbookView xWindow=\"0\" yWindow=\"0\" windowWidth=\"28800\" windowHeight=\"12600\"",
     );
+    if workbook.first_sheet > 0 {
+        xml.push_str(" firstSheet=\"");
+        xml.push_str(&workbook.first_sheet.to_string());
+        xml.push('"');
+    }
+    if workbook.active_sheet > 0 {
+        xml.push_str(" activeTab=\"");
+        xml.push_str(&workbook.active_sheet.to_string());
+        xml.push('"');
+    }
+    xml.push_str("/></bookViews><sheets>");
 
-    for (i, name) in sheet_names.iter().enumerate() {
+    // Tab order is the order of <sheet> elements; ids keep pointing at sheetN.xml
+    for i in sheet_tab_order(sheet_names, workbook) {
+        let name = sheet_names[i];
         let id = i + 1;
         xml.push_str("<sheet name=\"");
-        xml.push_str(name);
+        xml.push_str(&escape_xml(name));
         xml.push_str("\" sheetId=\"");
         xml.push_str(&id.to_string());
         xml.push_str("\" r:id=\"rId");
@@ -299,12 +685,41 @@ xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">
     xml
 }
 
+const PASSWORD_SPIN_COUNT: u32 = 100_000;
+
+/// ECMA-376 agile password hash: H0 = SHA512(salt + UTF-16LE(password)),
+/// then Hn = SHA512(Hn-1 + n as u32 LE) for `spin_count` rounds.
+/// Returns base64 (hash, salt).
+fn hash_password_sha512(password: &str, spin_count: u32) -> (String, String) {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use sha2::{Digest, Sha512};
+
+    let salt: [u8; 16] = rand::random();
+
+    let mut hasher = Sha512::new();
+    hasher.update(salt);
+    for unit in password.encode_utf16() {
+        hasher.update(unit.to_le_bytes());
+    }
+    let mut hash = hasher.finalize();
+
+    for i in 0..spin_count {
+        let mut hasher = Sha512::new();
+        hasher.update(hash);
+        hasher.update(i.to_le_bytes());
+        hash = hasher.finalize();
+    }
+
+    (STANDARD.encode(hash), STANDARD.encode(salt))
+}
+
 pub fn generate_workbook_rels(num_sheets: usize) -> String {
     let mut xml = String::with_capacity(300 + num_sheets * 150);
     xml.push_str(
         "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
 <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
-<Relationship Id=\"rId100\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>",
+<Relationship Id=\"rId100\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>\
+<Relationship Id=\"rId101\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme\" Target=\"theme/theme1.xml\"/>",
     );
 
     for i in 1..=num_sheets {
@@ -335,7 +750,7 @@ pub fn generate_worksheet_rels(hyperlinks: &[(String, usize)]) -> Option<String>
         xml.push_str("<Relationship Id=\"rId");
         xml.push_str(&idx.to_string());
         xml.push_str("\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"");
-        xml.push_str(url);
+        xml.push_str(&hyperlink_target(url));
         xml.push_str("\" TargetMode=\"External\"/>");
     }
 
@@ -359,7 +774,7 @@ pub fn generate_worksheet_rels_with_tables(
         xml.push_str("<Relationship Id=\"rId");
         xml.push_str(&idx.to_string());
         xml.push_str("\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink\" Target=\"");
-        xml.push_str(url);
+        xml.push_str(&hyperlink_target(url));
         xml.push_str("\" TargetMode=\"External\"/>");
     }
 
@@ -376,11 +791,25 @@ pub fn generate_worksheet_rels_with_tables(
     xml
 }
 
+/// Field metadata key marking the empty columns between side-by-side tables
+pub const GAP_COLUMN_KEY: &str = "jetxl.gap_column";
+
+fn is_gap_column(field: &Field) -> bool {
+    field.metadata().contains_key(GAP_COLUMN_KEY)
+}
+
+/// Columns an auto-sized table starting at `start_col` spans: up to the
+/// first gap column between side-by-side tables, or the last column
+pub fn table_width(schema: &Schema, start_col: usize) -> usize {
+    schema.fields().iter().skip(start_col).take_while(|f| !is_gap_column(f)).count()
+}
+
 /// Generate table XML file
 pub fn generate_table_xml(
     table: &ExcelTable,
     table_id: u32,
     column_names: &[String],
+    last_column_numeric: bool,
 ) -> String {
     let (start_row, start_col, end_row, end_col) = table.range;
     
@@ -389,9 +818,9 @@ pub fn generate_table_xml(
     xml.push_str("<table xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" id=\"");
     xml.push_str(&table_id.to_string());
     xml.push_str("\" name=\"");
-    xml.push_str(&table.name);
+    xml.push_str(&escape_xml(&table.name));
     xml.push_str("\" displayName=\"");
-    xml.push_str(&table.display_name);
+    xml.push_str(&escape_xml(&table.display_name));
     xml.push_str("\" ref=\"");
     
     // Write range reference
@@ -401,17 +830,21 @@ pub fn generate_table_xml(
     write_cell_ref(end_col, end_row, &mut buf);
     xml.push_str(&String::from_utf8_lossy(&buf));
     
-    xml.push_str("\" totalsRowShown=\"");
-    xml.push_str(if table.show_totals_row { "1" } else { "0" });
-    xml.push_str("\">");
+    // The last row of the range is the totals row written below the data
+    if table.show_totals_row {
+        xml.push_str("\" totalsRowCount=\"1\">");
+    } else {
+        xml.push_str("\" totalsRowShown=\"0\">");
+    }
     
-    // AutoFilter (only if header row is shown and no totals row)
+    // AutoFilter (only if header row is shown; never covers the totals row)
     if table.show_header_row {
+        let filter_end_row = if table.show_totals_row { end_row - 1 } else { end_row };
         xml.push_str("<autoFilter ref=\"");
         buf.clear();
         write_cell_ref(start_col, start_row, &mut buf);
         buf.push(b':');
-        write_cell_ref(end_col, end_row, &mut buf);
+        write_cell_ref(end_col, filter_end_row, &mut buf);
         xml.push_str(&String::from_utf8_lossy(&buf));
         xml.push_str("\"/>");
     }
@@ -422,13 +855,27 @@ pub fn generate_table_xml(
     xml.push_str(&num_cols.to_string());
     xml.push_str("\">");
     
+    let totals = if table.show_totals_row {
+        table.totals_row_cells(column_names, last_column_numeric)
+    } else {
+        Vec::new()
+    };
     for (idx, col_name) in column_names.iter().enumerate() {
-        buf.clear();
         xml.push_str("<tableColumn id=\"");
         xml.push_str(&(idx + 1).to_string());
         xml.push_str("\" name=\"");
-        xml_escape_simd(col_name.as_bytes(), &mut buf);
-        xml.push_str(&String::from_utf8_lossy(&buf));
+        xml.push_str(&escape_xml(col_name));
+        match totals.get(idx) {
+            Some(TotalsCell::Label(label)) => {
+                xml.push_str("\" totalsRowLabel=\"");
+                xml.push_str(&escape_xml(label));
+            }
+            Some(TotalsCell::Function(function)) => {
+                xml.push_str("\" totalsRowFunction=\"");
+                xml.push_str(function.xml_name());
+            }
+            _ => {}
+        }
         xml.push_str("\"/>");
     }
     
@@ -437,7 +884,7 @@ pub fn generate_table_xml(
     // Table style
     if let Some(ref style) = table.style_name {
         xml.push_str("<tableStyleInfo name=\"");
-        xml.push_str(style);
+        xml.push_str(&escape_xml(style));
         xml.push_str("\" showFirstColumn=\"");
         xml.push_str(if table.show_first_column { "1" } else { "0" });
         xml.push_str("\" showLastColumn=\"");
@@ -453,126 +900,117 @@ pub fn generate_table_xml(
     xml
 }
 
-/// Calculate exact XML buffer size for Arrow data
-fn calculate_exact_xml_size(batches: &[RecordBatch]) -> Result<usize, WriteError> {
-    if batches.is_empty() {
-        return Ok(200);
-    }
+/// Projected worksheet XML size in bytes. Counts the `<row>` and `<c>`
+/// elements with their references, the exact string bytes (from the Arrow
+/// offsets) and the digits of integer cells; null cells count as empty
+/// elements. Styles, formulas and other sheet features add a little on top.
+pub fn estimate_sheet_xml_size(batches: &[RecordBatch]) -> usize {
+    sheet_xml_size(batches, true)
+}
 
-    let schema = batches[0].schema();
-    let num_cols = schema.fields().len();
-    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+/// `estimate_sheet_xml_size` without the pass over integer values: their
+/// digits are guessed from the type width. Cheap enough to size the write
+/// buffers with.
+pub fn guess_sheet_xml_size(batches: &[RecordBatch]) -> usize {
+    sheet_xml_size(batches, false)
+}
 
+fn sheet_xml_size(batches: &[RecordBatch], count_digits: bool) -> usize {
+    let Some(first) = batches.first() else {
+        return EMPTY_SHEET_XML.len();
+    };
+    let schema = first.schema();
+    let num_cols = schema.fields().len();
     if num_cols == 0 {
-        return Ok(200);
+        return EMPTY_SHEET_XML.len();
     }
+    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    let row_digits = decimal_digits(total_rows as u64 + 1);
+    let col_letters: usize = (0..num_cols).map(|col| col_letter(col).len()).sum();
 
-    let mut size = 1500;
-    size += 50;
-    size += 20;
-    
-    for field in schema.fields().iter() {
-        size += 50 + field.name().len();
-    }
+    // Declaration, dimension, views, <cols> and the closing tags
+    let mut size = 1000 + num_cols * 40;
+    // `<row r="N">` ... `</row>`, and `<c r="A1"` ... for every cell
+    let rows = total_rows + 1;
+    size += rows * (row_digits + 16);
+    size += rows * (col_letters + num_cols * (row_digits + 7));
+    // Header row: inline strings holding the column names
+    size += schema.fields().iter().map(|f| 35 + f.name().len()).sum::<usize>();
 
-    for col_idx in 0..num_cols {
-        let field = &schema.fields()[col_idx];
-        
-        if let Some(first_batch) = batches.first() {
-            let array = first_batch.column(col_idx);
-            let per_cell_size = estimate_cell_xml_size(array.as_ref(), field.data_type())?;
-            size += per_cell_size * total_rows;
+    for batch in batches {
+        for column in batch.columns() {
+            size += estimate_cell_content_size(column.as_ref(), count_digits);
         }
     }
+    size
+}
 
-    size += total_rows * 20;
-    size = (size as f64 * 1.3) as usize;
+/// Bytes after `<c r="A1"` for every cell of a column. Integer digits are
+/// counted from the values with `count_digits`, otherwise guessed.
+fn estimate_cell_content_size(array: &dyn Array, count_digits: bool) -> usize {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int64Type, UInt64Type};
 
-    Ok(size)
-}
+    let nulls = array.null_count();
+    let values = array.len() - nulls;
+    // `/>` closes a null cell; `><v>` ... `</v></c>` wraps a value
+    let empty = nulls * 2;
+    let value = |len: usize| empty + values * (12 + len);
+    // ` s="N"` on cells written with a default number format
+    let styled = |len: usize| value(len + 7);
 
-fn estimate_cell_xml_size(array: &dyn Array, data_type: &DataType) -> Result<usize, WriteError> {
-    use arrow_array::*;
-    
-    match data_type {
+    match array.data_type() {
+        // ` t="inlineStr"><is><t>` ... `</t></is></c>`
         DataType::Utf8 => {
-            let arr = array.as_any().downcast_ref::<StringArray>()
-                .ok_or_else(|| WriteError::Validation("Type mismatch".to_string()))?;
-            
-            let num_rows = arr.len();
-            if num_rows == 0 {
-                return Ok(25);
-            }
-            
-            let total_string_bytes = get_string_array_total_bytes(arr);
-            let avg_string_len = total_string_bytes / num_rows.max(1);
-            
-            Ok(55 + avg_string_len + (avg_string_len / 10))
+            let offsets = array.as_string::<i32>().value_offsets();
+            empty + values * 35 + (offsets[offsets.len() - 1] - offsets[0]) as usize
         }
         DataType::LargeUtf8 => {
-            let arr = array.as_any().downcast_ref::<LargeStringArray>()
-                .ok_or_else(|| WriteError::Validation("Type mismatch".to_string()))?;
-            
-            let num_rows = arr.len();
-            if num_rows == 0 {
-                return Ok(25);
-            }
-            
-            let total_string_bytes = get_large_string_array_total_bytes(arr);
-            let avg_string_len = total_string_bytes / num_rows.max(1);
-            Ok(55 + avg_string_len + (avg_string_len / 10))
-        }
-        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 |
-        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
-            Ok(33)
+            let offsets = array.as_string::<i64>().value_offsets();
+            empty + values * 35 + (offsets[offsets.len() - 1] - offsets[0]) as usize
         }
-        DataType::Float32 | DataType::Float64 => {
-            Ok(35)
+        DataType::Utf8View => {
+            empty + values * 35 + array.as_string_view().views().iter().map(|view| *view as u32 as usize).sum::<usize>()
         }
-        DataType::Boolean => {
-            Ok(28)
-        }
-        DataType::Date32 | DataType::Date64 | DataType::Timestamp(_, _) => {
-            Ok(35)
-        }
-        _ => {
-            Ok(20)
+        // Up to 3, 5 and 10 digits for 8, 16 and 32 bits; 64-bit values are
+        // usually ids and amounts well short of their 19 digit range
+        DataType::Int8 | DataType::UInt8 if !count_digits => value(3),
+        DataType::Int16 | DataType::UInt16 if !count_digits => value(5),
+        DataType::Int32 | DataType::UInt32 | DataType::Int64 | DataType::UInt64 if !count_digits => value(10),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            let digits: usize = match arrow::compute::cast(array, &DataType::Int64) {
+                Ok(cast) => cast.as_primitive::<Int64Type>().iter().flatten()
+                    .map(|v| decimal_digits(v.unsigned_abs()) + (v < 0) as usize)
+                    .sum(),
+                Err(_) => values * 10,
+            };
+            empty + values * 12 + digits
         }
-    }
-}
-
-fn get_string_array_total_bytes(arr: &arrow_array::StringArray) -> usize {
-    use arrow_array::Array;
-    
-    let num_rows = arr.len();
-    if num_rows == 0 {
-        return 0;
-    }
-    
-    let mut total = 0;
-    for i in 0..num_rows {
-        if !arr.is_null(i) {
-            total += arr.value(i).len();
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            let digits: usize = match arrow::compute::cast(array, &DataType::UInt64) {
+                Ok(cast) => cast.as_primitive::<UInt64Type>().iter().flatten()
+                    .map(decimal_digits)
+                    .sum(),
+                Err(_) => values * 10,
+            };
+            empty + values * 12 + digits
         }
+        // Shortest round-trip form: short for round values, up to 17 digits
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => value(12),
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => value(12),
+        // ` t="b"`
+        DataType::Boolean => value(7),
+        // Serial dates like 45292.0; times and timestamps carry the fraction
+        DataType::Date32 | DataType::Date64 => styled(7),
+        DataType::Timestamp(_, _) => styled(17),
+        DataType::Time32(_) | DataType::Time64(_) | DataType::Duration(_) => styled(17),
+        // Lists, binary and other types written as text
+        _ => empty + values * 55,
     }
-    total
 }
 
-fn get_large_string_array_total_bytes(arr: &arrow_array::LargeStringArray) -> usize {
-    use arrow_array::Array;
-    
-    let num_rows = arr.len();
-    if num_rows == 0 {
-        return 0;
-    }
-    
-    let mut total = 0;
-    for i in 0..num_rows {
-        if !arr.is_null(i) {
-            total += arr.value(i).len();
-        }
-    }
-    total
+fn decimal_digits(value: u64) -> usize {
+    value.checked_ilog10().unwrap_or(0) as usize + 1
 }
 
 /// Generate drawing XML for chart positioning
@@ -626,12 +1064,6 @@ pub fn generate_drawing_xml(charts: &[ExcelChart]) -> String {
     xml
 }
 
-fn get_column_letter(col: usize) -> String {
-    let mut buf = [0u8; 4];
-    let len = write_col_letter(col, &mut buf);
-    std::str::from_utf8(&buf[..len]).unwrap().to_string()
-}
-
 /// Generate chart XML
 pub fn generate_chart_xml(chart: &ExcelChart, sheet_name: &str) -> String {
     let mut xml = String::with_capacity(8000);
@@ -681,7 +1113,7 @@ pub fn generate_chart_xml(chart: &ExcelChart, sheet_name: &str) -> String {
             xml.push_str(" b=\"1\"");
         }
         xml.push_str("/>\n");
-        xml.push_str(&format!("<a:t>{}</a:t>\n", title));
+        xml.push_str(&format!("<a:t>{}</a:t>\n", escape_xml(title)));
         xml.push_str("</a:r>\n");
         xml.push_str("</a:p>\n");
         xml.push_str("</c:rich></c:tx>\n");
@@ -709,21 +1141,31 @@ pub fn generate_chart_xml(chart: &ExcelChart, sheet_name: &str) -> String {
     }
     
     xml.push_str("<c:autoTitleDeleted val=\"0\"/>\n");
+    if chart.chart_type.is_3d() {
+        write_view_3d(&mut xml, chart);
+    }
     
     // Plot area
     xml.push_str("<c:plotArea>\n");
     xml.push_str("<c:layout/>\n");
     
     // Chart-specific content
-    match chart.chart_type {
-        ChartType::Column => generate_column_chart_content(&mut xml, chart, sheet_name),
-        ChartType::Bar => generate_bar_chart_content(&mut xml, chart, sheet_name),
-        ChartType::Line => generate_line_chart_content(&mut xml, chart, sheet_name),
-        ChartType::Pie => generate_pie_chart_content(&mut xml, chart, sheet_name),
-        ChartType::Scatter => generate_scatter_chart_content(&mut xml, chart, sheet_name),
-        ChartType::Area => generate_area_chart_content(&mut xml, chart, sheet_name),
+    if !chart.series.is_empty() {
+        generate_series_chart_content(&mut xml, chart, sheet_name);
+    } else {
+        match chart.chart_type {
+            ChartType::Column | ChartType::Column3D => generate_column_chart_content(&mut xml, chart, sheet_name),
+            ChartType::Bar | ChartType::Bar3D => generate_bar_chart_content(&mut xml, chart, sheet_name),
+            ChartType::Line => generate_line_chart_content(&mut xml, chart, sheet_name),
+            ChartType::Pie | ChartType::Pie3D | ChartType::Doughnut => generate_pie_chart_content(&mut xml, chart, sheet_name),
+            ChartType::Scatter => generate_scatter_chart_content(&mut xml, chart, sheet_name),
+            ChartType::Area => generate_area_chart_content(&mut xml, chart, sheet_name),
+        }
     }
     
+    if chart.plot_area_fill.is_some() || chart.plot_area_border.is_some() {
+        write_area_sp_pr(&mut xml, chart.plot_area_fill.as_ref(), chart.plot_area_border.as_ref(), false);
+    }
     xml.push_str("</c:plotArea>\n");
     
     // Legend with styling
@@ -766,13 +1208,7 @@ pub fn generate_chart_xml(chart: &ExcelChart, sheet_name: &str) -> String {
     xml.push_str("<c:showDLblsOverMax val=\"0\"/>\n");
     xml.push_str("</c:chart>\n");
     
-    xml.push_str("<c:spPr>\n");
-    xml.push_str("<a:solidFill><a:schemeClr val=\"bg1\"/></a:solidFill>\n");
-    xml.push_str("<a:ln w=\"9525\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\">\n");
-    xml.push_str("<a:solidFill><a:schemeClr val=\"tx1\"><a:lumMod val=\"15000\"/><a:lumOff val=\"85000\"/></a:schemeClr></a:solidFill>\n");
-    xml.push_str("<a:round/></a:ln>\n");
-    xml.push_str("<a:effectLst/>\n");
-    xml.push_str("</c:spPr>\n");
+    write_area_sp_pr(&mut xml, chart.chart_fill.as_ref(), chart.chart_border.as_ref(), true);
     
     xml.push_str("<c:txPr><a:bodyPr/><a:lstStyle/>\n");
     xml.push_str("<a:p><a:pPr><a:defRPr/></a:pPr><a:endParaRPr lang=\"en-US\"/></a:p>\n");
@@ -821,7 +1257,7 @@ fn write_axis_title(xml: &mut String, title: &str, chart: &ExcelChart) {
     xml.push_str("</a:pPr>\n");
     xml.push_str("<a:r>\n");
     xml.push_str("<a:rPr lang=\"en-US\"/>\n");
-    xml.push_str(&format!("<a:t>{}</a:t>\n", title));
+    xml.push_str(&format!("<a:t>{}</a:t>\n", escape_xml(title)));
     xml.push_str("</a:r>\n");
     xml.push_str("<a:endParaRPr lang=\"en-US\"/>\n");
     xml.push_str("</a:p>\n");
@@ -829,28 +1265,44 @@ fn write_axis_title(xml: &mut String, title: &str, chart: &ExcelChart) {
     xml.push_str("</c:title>\n");
 }
 
-fn write_data_labels(xml: &mut String, show_values: bool) {
+fn write_data_labels(xml: &mut String, chart: &ExcelChart) {
+    let labels = chart.data_labels.clone().unwrap_or_else(|| DataLabels {
+        show_value: chart.show_data_labels.unwrap_or(false),
+        ..DataLabels::default()
+    });
     xml.push_str("<c:dLbls>\n");
+    if let Some(ref code) = labels.number_format {
+        xml.push_str("<c:numFmt formatCode=\"");
+        xml_escape_format_code(code, xml);
+        xml.push_str("\" sourceLinked=\"0\"/>\n");
+    }
     xml.push_str("<c:spPr><a:noFill/><a:ln><a:noFill/></a:ln><a:effectLst/></c:spPr>\n");
     xml.push_str("<c:txPr>\n");
     xml.push_str("<a:bodyPr rot=\"0\" spcFirstLastPara=\"1\" vertOverflow=\"ellipsis\" vert=\"horz\" wrap=\"square\" lIns=\"38100\" tIns=\"19050\" rIns=\"38100\" bIns=\"19050\" anchor=\"ctr\" anchorCtr=\"1\"><a:spAutoFit/></a:bodyPr>\n");
     xml.push_str("<a:lstStyle/>\n");
     xml.push_str("<a:p>\n");
     xml.push_str("<a:pPr>\n");
-    xml.push_str("<a:defRPr sz=\"900\" b=\"0\" i=\"0\" u=\"none\" strike=\"noStrike\" kern=\"1200\" baseline=\"0\">\n");
-    xml.push_str("<a:solidFill><a:schemeClr val=\"tx1\"><a:lumMod val=\"75000\"/><a:lumOff val=\"25000\"/></a:schemeClr></a:solidFill>\n");
+    xml.push_str(&format!("<a:defRPr sz=\"{}\" b=\"0\" i=\"0\" u=\"none\" strike=\"noStrike\" kern=\"1200\" baseline=\"0\">\n", labels.font_size.unwrap_or(900)));
+    match labels.font_color.as_deref().and_then(normalize_argb) {
+        Some(argb) => xml.push_str(&format!("<a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill>\n", &argb[2..])),
+        None => xml.push_str("<a:solidFill><a:schemeClr val=\"tx1\"><a:lumMod val=\"75000\"/><a:lumOff val=\"25000\"/></a:schemeClr></a:solidFill>\n"),
+    }
     xml.push_str("<a:latin typeface=\"+mn-lt\"/><a:ea typeface=\"+mn-ea\"/><a:cs typeface=\"+mn-cs\"/>\n");
     xml.push_str("</a:defRPr>\n");
     xml.push_str("</a:pPr>\n");
     xml.push_str("<a:endParaRPr lang=\"en-US\"/>\n");
     xml.push_str("</a:p>\n");
     xml.push_str("</c:txPr>\n");
-    xml.push_str("<c:dLblPos val=\"ctr\"/>\n");
+    // Doughnut labels can't be positioned
+    if chart.chart_type != ChartType::Doughnut {
+        let position = labels.position.unwrap_or(LabelPosition::Center);
+        xml.push_str(&format!("<c:dLblPos val=\"{}\"/>\n", position.as_str()));
+    }
     xml.push_str("<c:showLegendKey val=\"0\"/>\n");
-    xml.push_str(&format!("<c:showVal val=\"{}\"/>\n", if show_values { "1" } else { "0" }));
-    xml.push_str("<c:showCatName val=\"0\"/>\n");
-    xml.push_str("<c:showSerName val=\"0\"/>\n");
-    xml.push_str("<c:showPercent val=\"0\"/>\n");
+    xml.push_str(&format!("<c:showVal val=\"{}\"/>\n", u8::from(labels.show_value)));
+    xml.push_str(&format!("<c:showCatName val=\"{}\"/>\n", u8::from(labels.show_category)));
+    xml.push_str(&format!("<c:showSerName val=\"{}\"/>\n", u8::from(labels.show_series_name)));
+    xml.push_str(&format!("<c:showPercent val=\"{}\"/>\n", u8::from(labels.show_percent)));
     xml.push_str("<c:showBubbleSize val=\"0\"/>\n");
     xml.push_str("<c:showLeaderLines val=\"0\"/>\n");
     xml.push_str("<c:extLst><c:ext uri=\"{CE6537A1-D6FC-4f65-9D91-7224C49458BB}\" xmlns:c15=\"http://schemas.microsoft.com/office/drawing/2012/chart\">");
@@ -864,6 +1316,88 @@ fn write_data_labels(xml: &mut String, show_values: bool) {
     xml.push_str("</c:dLbls>\n");
 }
 
+fn date_axis(chart: &ExcelChart) -> Option<&DateAxis> {
+    match chart.category_axis {
+        CategoryAxis::Date(ref axis) => Some(axis),
+        _ => None,
+    }
+}
+
+/// Dates are referenced as numbers so the date axis can space them by time
+fn category_ref(chart: &ExcelChart) -> &'static str {
+    if date_axis(chart).is_some() { "numRef" } else { "strRef" }
+}
+
+fn write_category_axis_start(xml: &mut String, chart: &ExcelChart) {
+    xml.push_str(if date_axis(chart).is_some() { "<c:dateAx>\n" } else { "<c:catAx>\n" });
+    xml.push_str("<c:axId val=\"100000001\"/>\n");
+}
+
+fn write_category_number_format(xml: &mut String, chart: &ExcelChart) {
+    match date_axis(chart).and_then(|axis| axis.number_format.as_deref()) {
+        Some(code) => {
+            xml.push_str("<c:numFmt formatCode=\"");
+            xml_escape_format_code(code, xml);
+            xml.push_str("\" sourceLinked=\"0\"/>\n");
+        }
+        None => xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n"),
+    }
+}
+
+fn write_category_axis_end(xml: &mut String, chart: &ExcelChart) {
+    let Some(axis) = date_axis(chart) else {
+        xml.push_str("<c:auto val=\"1\"/>\n");
+        xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
+        xml.push_str("<c:lblOffset val=\"100\"/>\n");
+        xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
+        xml.push_str("</c:catAx>\n");
+        return;
+    };
+    // auto="0" keeps Excel from falling back to a text axis
+    xml.push_str("<c:auto val=\"0\"/>\n");
+    xml.push_str("<c:lblOffset val=\"100\"/>\n");
+    if let Some(unit) = axis.base_unit {
+        xml.push_str(&format!("<c:baseTimeUnit val=\"{}\"/>\n", unit.as_str()));
+    }
+    if let Some(major) = axis.major_unit {
+        xml.push_str(&format!("<c:majorUnit val=\"{}\"/>\n", major));
+    }
+    if let Some(unit) = axis.major_time_unit {
+        xml.push_str(&format!("<c:majorTimeUnit val=\"{}\"/>\n", unit.as_str()));
+    }
+    xml.push_str("</c:dateAx>\n");
+}
+
+/// Bar spacing. Stacked bars overlap fully unless the chart says otherwise;
+/// bar3DChart has no overlap, and stacked 3D bars touch anyway.
+fn write_gap_and_overlap(xml: &mut String, chart: &ExcelChart, stacked: bool) {
+    xml.push_str(&format!("<c:gapWidth val=\"{}\"/>\n", chart.gap_width.unwrap_or(150)));
+    if chart.chart_type.is_3d() {
+        return;
+    }
+    match chart.overlap {
+        Some(overlap) => xml.push_str(&format!("<c:overlap val=\"{}\"/>\n", overlap)),
+        None if stacked => xml.push_str("<c:overlap val=\"100\"/>\n"),
+        None => {}
+    }
+}
+
+/// Value axis `<c:scaling>`; the schema wants logBase, orientation, max, min
+fn write_value_scaling(xml: &mut String, log_base: Option<f64>, min: Option<f64>, max: Option<f64>) {
+    xml.push_str("<c:scaling>\n");
+    if let Some(base) = log_base {
+        xml.push_str(&format!("<c:logBase val=\"{}\"/>\n", base));
+    }
+    xml.push_str("<c:orientation val=\"minMax\"/>\n");
+    if let Some(max) = max {
+        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
+    }
+    if let Some(min) = min {
+        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
+    }
+    xml.push_str("</c:scaling>\n");
+}
+
 // Common axis styling components
 fn write_category_axis_styling(xml: &mut String) {
     xml.push_str("<c:spPr><a:noFill/>\n");
@@ -908,8 +1442,31 @@ fn write_major_gridlines(xml: &mut String) {
     xml.push_str("</c:majorGridlines>\n");
 }
 
+/// `<c:view3D>` rotation and perspective, filling in Excel's defaults
+fn write_view_3d(xml: &mut String, chart: &ExcelChart) {
+    let view = &chart.view_3d;
+    let is_pie = chart.chart_type == ChartType::Pie3D;
+    let (rot_x, rot_y) = if is_pie { (30, 0) } else { (15, 20) };
+    let right_angle_axes = !is_pie && view.right_angle_axes.unwrap_or(view.perspective.is_none());
+
+    xml.push_str("<c:view3D>\n");
+    xml.push_str(&format!("<c:rotX val=\"{}\"/>\n", view.rot_x.unwrap_or(rot_x)));
+    xml.push_str(&format!("<c:rotY val=\"{}\"/>\n", view.rot_y.unwrap_or(rot_y)));
+    xml.push_str(&format!("<c:rAngAx val=\"{}\"/>\n", u8::from(right_angle_axes)));
+    // Perspective only applies when the axes aren't forced to right angles
+    if !right_angle_axes {
+        xml.push_str(&format!("<c:perspective val=\"{}\"/>\n", view.perspective.unwrap_or(30)));
+    }
+    xml.push_str("</c:view3D>\n");
+}
+
+/// Bar plot element: `<c:bar3DChart>` for the 3D variants
+fn bar_chart_element(chart: &ExcelChart) -> &'static str {
+    if chart.chart_type.is_3d() { "bar3DChart" } else { "barChart" }
+}
+
 fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name: &str) {
-    xml.push_str("<c:barChart>\n");
+    xml.push_str(&format!("<c:{}>\n", bar_chart_element(chart)));
     xml.push_str("<c:barDir val=\"col\"/>\n");
     xml.push_str(&format!("<c:grouping val=\"{}\"/>\n", 
         if chart.percent_stacked { "percentStacked" } else if chart.stacked { "stacked" } else { "clustered" }));
@@ -918,8 +1475,6 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
     let (start_row, start_col, end_row, end_col) = chart.data_range;
     let category_col = chart.category_col.unwrap_or(start_col);
     
-    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
-    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
     
     let mut actual_series_idx = 0;
     for col in start_col..=end_col {
@@ -928,47 +1483,37 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
         }
         
         let series_name = chart.series_names.get(actual_series_idx).map(|s| s.as_str()).unwrap_or("Series");
-        let accent_color = accent_colors[actual_series_idx % accent_colors.len()];
-        let (modifier, value) = tint_shade_values[actual_series_idx % tint_shade_values.len()];
         
         xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", actual_series_idx, actual_series_idx));
         
         // Series name
         xml.push_str("<c:tx>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}$1", sheet_name, get_column_letter(col)));
+        xml.push_str(&format!("{}${}$1", sheet_ref_prefix(sheet_name), col_letter(col)));
         xml.push_str("</c:f>\n<c:strCache>\n<c:ptCount val=\"1\"/>\n<c:pt idx=\"0\">\n");
-        xml.push_str(&format!("<c:v>{}</c:v>\n", series_name));
+        xml.push_str(&format!("<c:v>{}</c:v>\n", escape_xml(series_name)));
         xml.push_str("</c:pt>\n</c:strCache>\n</c:strRef>\n</c:tx>\n");
         
-        // Series styling with scheme colors and tint/shade
-        xml.push_str("<c:spPr>\n");
-        xml.push_str(&format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_color));
-        if !modifier.is_empty() {
-            xml.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
-        }
-        xml.push_str("</a:schemeClr></a:solidFill>\n");
-        xml.push_str("<a:ln><a:noFill/></a:ln>\n");
-        xml.push_str("<a:effectLst/>\n");
-        xml.push_str("</c:spPr>\n");
+        // Series styling
+        write_series_sp_pr(xml, chart, actual_series_idx, None, false);
         xml.push_str("<c:invertIfNegative val=\"0\"/>\n");
         
         // Data labels per series for stacked charts
         if chart.stacked || chart.percent_stacked {
-            write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+            write_data_labels(xml, chart);
         }
         
         // Category axis data
-        xml.push_str("<c:cat>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(category_col), start_row + 1, 
-            get_column_letter(category_col), end_row + 1));
-        xml.push_str("</c:f>\n</c:strRef>\n</c:cat>\n");
+        xml.push_str(&format!("<c:cat>\n<c:{}>\n<c:f>", category_ref(chart)));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(category_col), start_row + 1, 
+            col_letter(category_col), end_row + 1));
+        xml.push_str(&format!("</c:f>\n</c:{}>\n</c:cat>\n", category_ref(chart)));
         
         // Values
         xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(col), start_row + 1, 
-            get_column_letter(col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(col), start_row + 1, 
+            col_letter(col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
         
         // Add extLst with uniqueId for modern Excel compatibility
@@ -982,27 +1527,23 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
     
     // Chart-level data labels
     if !chart.stacked && !chart.percent_stacked {
-        write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+        write_data_labels(xml, chart);
     }
     
-    xml.push_str("<c:gapWidth val=\"150\"/>\n");
-    if chart.stacked || chart.percent_stacked {
-        xml.push_str("<c:overlap val=\"100\"/>\n");
-    }
+    write_gap_and_overlap(xml, chart, chart.stacked || chart.percent_stacked);
     xml.push_str("<c:axId val=\"100000001\"/>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("</c:barChart>\n");
+    xml.push_str(&format!("</c:{}>\n", bar_chart_element(chart)));
     
     // Category axis
-    xml.push_str("<c:catAx>\n");
-    xml.push_str("<c:axId val=\"100000001\"/>\n");
+    write_category_axis_start(xml, chart);
     xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"b\"/>\n");
     if let Some(ref x_title) = chart.x_axis_title {
         write_axis_title(xml, x_title, chart);
     }
-    xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n");
+    write_category_number_format(xml, chart);
     xml.push_str("<c:majorTickMark val=\"none\"/>\n");
     xml.push_str("<c:minorTickMark val=\"none\"/>\n");
     xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
@@ -1023,24 +1564,12 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
     xml.push_str("</c:txPr>\n");
     xml.push_str("<c:crossAx val=\"100000002\"/>\n");
     xml.push_str("<c:crosses val=\"autoZero\"/>\n");
-    xml.push_str("<c:auto val=\"1\"/>\n");
-    xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
-    xml.push_str("<c:lblOffset val=\"100\"/>\n");
-    xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
-    xml.push_str("</c:catAx>\n");
+    write_category_axis_end(xml, chart);
     
     // Value axis
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"l\"/>\n");
     xml.push_str("<c:majorGridlines>\n");
@@ -1081,7 +1610,7 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
         xml.push_str("</a:pPr>\n");
         xml.push_str("<a:r>\n");
         xml.push_str("<a:rPr lang=\"en-US\"/>\n");
-        xml.push_str(&format!("<a:t>{}</a:t>\n", y_title));
+        xml.push_str(&format!("<a:t>{}</a:t>\n", escape_xml(y_title)));
         xml.push_str("</a:r>\n");
         xml.push_str("<a:endParaRPr lang=\"en-US\"/>\n");
         xml.push_str("</a:p>\n");
@@ -1117,7 +1646,7 @@ fn generate_column_chart_content(xml: &mut String, chart: &ExcelChart, sheet_nam
 // BAR CHART (Horizontal bars - barDir="bar")
 // ============================================================================
 fn generate_bar_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name: &str) {
-    xml.push_str("<c:barChart>\n");
+    xml.push_str(&format!("<c:{}>\n", bar_chart_element(chart)));
     xml.push_str("<c:barDir val=\"bar\"/>\n");
     xml.push_str(&format!("<c:grouping val=\"{}\"/>\n", 
         if chart.percent_stacked { "percentStacked" } else if chart.stacked { "stacked" } else { "clustered" }));
@@ -1125,8 +1654,6 @@ fn generate_bar_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     
     let (start_row, start_col, end_row, end_col) = chart.data_range;
     let category_col = chart.category_col.unwrap_or(start_col);
-    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
-    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
     
     let mut actual_series_idx = 0;
     for col in start_col..=end_col {
@@ -1135,42 +1662,32 @@ fn generate_bar_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
         }
         
         let series_name = chart.series_names.get(actual_series_idx).map(|s| s.as_str()).unwrap_or("Series");
-        let accent_color = accent_colors[actual_series_idx % accent_colors.len()];
-        let (modifier, value) = tint_shade_values[actual_series_idx % tint_shade_values.len()];
         
         xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", actual_series_idx, actual_series_idx));
         
         xml.push_str("<c:tx>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}$1", sheet_name, get_column_letter(col)));
+        xml.push_str(&format!("{}${}$1", sheet_ref_prefix(sheet_name), col_letter(col)));
         xml.push_str("</c:f>\n<c:strCache>\n<c:ptCount val=\"1\"/>\n<c:pt idx=\"0\">\n");
-        xml.push_str(&format!("<c:v>{}</c:v>\n", series_name));
+        xml.push_str(&format!("<c:v>{}</c:v>\n", escape_xml(series_name)));
         xml.push_str("</c:pt>\n</c:strCache>\n</c:strRef>\n</c:tx>\n");
         
-        xml.push_str("<c:spPr>\n");
-        xml.push_str(&format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_color));
-        if !modifier.is_empty() {
-            xml.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
-        }
-        xml.push_str("</a:schemeClr></a:solidFill>\n");
-        xml.push_str("<a:ln><a:noFill/></a:ln>\n");
-        xml.push_str("<a:effectLst/>\n");
-        xml.push_str("</c:spPr>\n");
+        write_series_sp_pr(xml, chart, actual_series_idx, None, false);
         xml.push_str("<c:invertIfNegative val=\"0\"/>\n");
         
         if chart.stacked || chart.percent_stacked {
-            write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+            write_data_labels(xml, chart);
         }
         
-        xml.push_str("<c:cat>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(category_col), start_row + 1, 
-            get_column_letter(category_col), end_row + 1));
-        xml.push_str("</c:f>\n</c:strRef>\n</c:cat>\n");
+        xml.push_str(&format!("<c:cat>\n<c:{}>\n<c:f>", category_ref(chart)));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(category_col), start_row + 1, 
+            col_letter(category_col), end_row + 1));
+        xml.push_str(&format!("</c:f>\n</c:{}>\n</c:cat>\n", category_ref(chart)));
         
         xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(col), start_row + 1, 
-            get_column_letter(col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(col), start_row + 1, 
+            col_letter(col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
         
         xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
@@ -1182,49 +1699,33 @@ fn generate_bar_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     }
     
     if !chart.stacked && !chart.percent_stacked {
-        write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+        write_data_labels(xml, chart);
     }
     
-    xml.push_str("<c:gapWidth val=\"150\"/>\n");
-    if chart.stacked || chart.percent_stacked {
-        xml.push_str("<c:overlap val=\"100\"/>\n");
-    }
+    write_gap_and_overlap(xml, chart, chart.stacked || chart.percent_stacked);
     xml.push_str("<c:axId val=\"100000001\"/>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("</c:barChart>\n");
+    xml.push_str(&format!("</c:{}>\n", bar_chart_element(chart)));
     
-    xml.push_str("<c:catAx>\n");
-    xml.push_str("<c:axId val=\"100000001\"/>\n");
+    write_category_axis_start(xml, chart);
     xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"l\"/>\n");
     if let Some(ref x_title) = chart.x_axis_title {
         write_axis_title(xml, x_title, chart);
     }
-    xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n");
+    write_category_number_format(xml, chart);
     xml.push_str("<c:majorTickMark val=\"none\"/>\n");
     xml.push_str("<c:minorTickMark val=\"none\"/>\n");
     xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
     write_category_axis_styling(xml);
     xml.push_str("<c:crossAx val=\"100000002\"/>\n");
     xml.push_str("<c:crosses val=\"autoZero\"/>\n");
-    xml.push_str("<c:auto val=\"1\"/>\n");
-    xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
-    xml.push_str("<c:lblOffset val=\"100\"/>\n");
-    xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
-    xml.push_str("</c:catAx>\n");
+    write_category_axis_end(xml, chart);
     
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"b\"/>\n");
     write_major_gridlines(xml);
@@ -1255,8 +1756,6 @@ fn generate_line_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     
     let (start_row, start_col, end_row, end_col) = chart.data_range;
     let category_col = chart.category_col.unwrap_or(start_col);
-    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
-    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
     
     let mut actual_series_idx = 0;
     for col in start_col..=end_col {
@@ -1265,46 +1764,35 @@ fn generate_line_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
         }
         
         let series_name = chart.series_names.get(actual_series_idx).map(|s| s.as_str()).unwrap_or("Series");
-        let accent_color = accent_colors[actual_series_idx % accent_colors.len()];
-        let (modifier, value) = tint_shade_values[actual_series_idx % tint_shade_values.len()];
         
         xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", actual_series_idx, actual_series_idx));
         
         xml.push_str("<c:tx>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}$1", sheet_name, get_column_letter(col)));
+        xml.push_str(&format!("{}${}$1", sheet_ref_prefix(sheet_name), col_letter(col)));
         xml.push_str("</c:f>\n<c:strCache>\n<c:ptCount val=\"1\"/>\n<c:pt idx=\"0\">\n");
-        xml.push_str(&format!("<c:v>{}</c:v>\n", series_name));
+        xml.push_str(&format!("<c:v>{}</c:v>\n", escape_xml(series_name)));
         xml.push_str("</c:pt>\n</c:strCache>\n</c:strRef>\n</c:tx>\n");
         
-        xml.push_str("<c:spPr>\n");
-        xml.push_str("<a:ln w=\"28575\" cap=\"rnd\">\n");
-        xml.push_str(&format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_color));
-        if !modifier.is_empty() {
-            xml.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
-        }
-        xml.push_str("</a:schemeClr></a:solidFill>\n");
-        xml.push_str("<a:round/></a:ln>\n");
-        xml.push_str("<a:effectLst/>\n");
-        xml.push_str("</c:spPr>\n");
-        xml.push_str("<c:marker><c:symbol val=\"none\"/></c:marker>\n");
+        write_series_sp_pr(xml, chart, actual_series_idx, None, true);
+        write_marker(xml, chart, actual_series_idx, None, MarkerSymbol::None);
         
         if chart.stacked || chart.percent_stacked {
-            write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+            write_data_labels(xml, chart);
         }
         
-        xml.push_str("<c:cat>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(category_col), start_row + 1, 
-            get_column_letter(category_col), end_row + 1));
-        xml.push_str("</c:f>\n</c:strRef>\n</c:cat>\n");
+        xml.push_str(&format!("<c:cat>\n<c:{}>\n<c:f>", category_ref(chart)));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(category_col), start_row + 1, 
+            col_letter(category_col), end_row + 1));
+        xml.push_str(&format!("</c:f>\n</c:{}>\n</c:cat>\n", category_ref(chart)));
         
         xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(col), start_row + 1, 
-            get_column_letter(col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(col), start_row + 1, 
+            col_letter(col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
         
-        xml.push_str("<c:smooth val=\"0\"/>\n");
+        xml.push_str(&format!("<c:smooth val=\"{}\"/>\n", u8::from(chart.smooth)));
         
         xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
         xml.push_str(&format!("<c16:uniqueId val=\"{{0000000{}-6E8F-43DD-B1F6-30AC1D0140EF}}\"/>", actual_series_idx));
@@ -1315,7 +1803,7 @@ fn generate_line_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     }
     
     if !chart.stacked && !chart.percent_stacked {
-        write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+        write_data_labels(xml, chart);
     }
     xml.push_str("<c:smooth val=\"0\"/>\n");
     
@@ -1323,38 +1811,25 @@ fn generate_line_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     xml.push_str("<c:axId val=\"100000002\"/>\n");
     xml.push_str("</c:lineChart>\n");
     
-    xml.push_str("<c:catAx>\n");
-    xml.push_str("<c:axId val=\"100000001\"/>\n");
+    write_category_axis_start(xml, chart);
     xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"b\"/>\n");
     if let Some(ref x_title) = chart.x_axis_title {
         write_axis_title(xml, x_title, chart);
     }
-    xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n");
+    write_category_number_format(xml, chart);
     xml.push_str("<c:majorTickMark val=\"none\"/>\n");
     xml.push_str("<c:minorTickMark val=\"none\"/>\n");
     xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
     write_category_axis_styling(xml);
     xml.push_str("<c:crossAx val=\"100000002\"/>\n");
     xml.push_str("<c:crosses val=\"autoZero\"/>\n");
-    xml.push_str("<c:auto val=\"1\"/>\n");
-    xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
-    xml.push_str("<c:lblOffset val=\"100\"/>\n");
-    xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
-    xml.push_str("</c:catAx>\n");
+    write_category_axis_end(xml, chart);
     
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"l\"/>\n");
     write_major_gridlines(xml);
@@ -1375,7 +1850,12 @@ fn generate_line_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
 }
 
 fn generate_pie_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name: &str) {
-    xml.push_str("<c:pieChart>\n");
+    let element = match chart.chart_type {
+        ChartType::Pie3D => "pie3DChart",
+        ChartType::Doughnut => "doughnutChart",
+        _ => "pieChart",
+    };
+    xml.push_str(&format!("<c:{}>\n", element));
     xml.push_str("<c:varyColors val=\"1\"/>\n");
     
     let (start_row, start_col, end_row, _end_col) = chart.data_range;
@@ -1385,17 +1865,38 @@ fn generate_pie_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     let data_col = if start_col == category_col { start_col + 1 } else { start_col };
     
     xml.push_str("<c:ser>\n<c:idx val=\"0\"/>\n<c:order val=\"0\"/>\n");
+    if let Some(explosion) = chart.explosion {
+        xml.push_str(&format!("<c:explosion val=\"{}\"/>\n", explosion));
+    }
+    
+    // A pie has one series, so series_colors color its slices instead
+    let slice_count = chart.series_colors.len().max(chart.slice_explosions.len());
+    for point_idx in 0..slice_count {
+        let color = chart.series_colors.get(point_idx).and_then(|c| normalize_argb(c));
+        let explosion = chart.slice_explosions.get(point_idx).copied().filter(|&e| e > 0);
+        if color.is_none() && explosion.is_none() {
+            continue;
+        }
+        xml.push_str(&format!("<c:dPt><c:idx val=\"{}\"/><c:bubble3D val=\"0\"/>", point_idx));
+        if let Some(explosion) = explosion {
+            xml.push_str(&format!("<c:explosion val=\"{}\"/>", explosion));
+        }
+        if let Some(argb) = color {
+            xml.push_str(&format!("<c:spPr><a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill></c:spPr>", &argb[2..]));
+        }
+        xml.push_str("</c:dPt>\n");
+    }
     
     xml.push_str("<c:cat>\n<c:strRef>\n<c:f>");
-    xml.push_str(&format!("'{}'!${}${}:${}${}", 
-        sheet_name, get_column_letter(category_col), start_row + 1, 
-        get_column_letter(category_col), end_row + 1));
+    xml.push_str(&format!("{}${}${}:${}${}", 
+        sheet_ref_prefix(sheet_name), col_letter(category_col), start_row + 1, 
+        col_letter(category_col), end_row + 1));
     xml.push_str("</c:f>\n</c:strRef>\n</c:cat>\n");
     
     xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
-    xml.push_str(&format!("'{}'!${}${}:${}${}", 
-        sheet_name, get_column_letter(data_col), start_row + 1, 
-        get_column_letter(data_col), end_row + 1));
+    xml.push_str(&format!("{}${}${}:${}${}", 
+        sheet_ref_prefix(sheet_name), col_letter(data_col), start_row + 1, 
+        col_letter(data_col), end_row + 1));
     xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
     
     xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
@@ -1404,7 +1905,9 @@ fn generate_pie_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     
     xml.push_str("</c:ser>\n");
     
-    if chart.show_data_labels.unwrap_or(false) {
+    if chart.data_labels.is_some() {
+        write_data_labels(xml, chart);
+    } else if chart.show_data_labels.unwrap_or(false) {
         xml.push_str("<c:dLbls>\n");
         xml.push_str("<c:spPr><a:noFill/><a:ln><a:noFill/></a:ln><a:effectLst/></c:spPr>\n");
         xml.push_str("<c:txPr>\n");
@@ -1424,7 +1927,15 @@ fn generate_pie_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
         xml.push_str("<c:dLbls><c:showLegendKey val=\"0\"/><c:showVal val=\"0\"/><c:showCatName val=\"0\"/><c:showSerName val=\"0\"/><c:showPercent val=\"1\"/><c:showBubbleSize val=\"0\"/></c:dLbls>\n");
     }
     
-    xml.push_str("</c:pieChart>\n");
+    // pie3DChart turns with view3D's rotY instead
+    if chart.chart_type != ChartType::Pie3D {
+        xml.push_str(&format!("<c:firstSliceAng val=\"{}\"/>\n", chart.first_slice_angle.unwrap_or(0)));
+    }
+    if chart.chart_type == ChartType::Doughnut {
+        xml.push_str(&format!("<c:holeSize val=\"{}\"/>\n", chart.hole_size.unwrap_or(75)));
+    }
+    
+    xml.push_str(&format!("</c:{}>\n", element));
 }
 
 fn generate_scatter_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name: &str) {
@@ -1432,37 +1943,25 @@ fn generate_scatter_chart_content(xml: &mut String, chart: &ExcelChart, sheet_na
     xml.push_str("<c:scatterStyle val=\"lineMarker\"/>\n");
     
     let (start_row, start_col, end_row, end_col) = chart.data_range;
-    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
-    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
     
     for (series_idx, col) in (start_col + 1..=end_col).enumerate() {
-        let accent_color = accent_colors[series_idx % accent_colors.len()];
-        let (modifier, value) = tint_shade_values[series_idx % tint_shade_values.len()];
-        
         xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", series_idx, series_idx));
         
-        xml.push_str("<c:spPr>\n");
-        xml.push_str("<a:ln w=\"28575\" cap=\"rnd\">\n");
-        xml.push_str(&format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_color));
-        if !modifier.is_empty() {
-            xml.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
-        }
-        xml.push_str("</a:schemeClr></a:solidFill>\n");
-        xml.push_str("<a:round/></a:ln>\n");
-        xml.push_str("<a:effectLst/>\n");
-        xml.push_str("</c:spPr>\n");
+        write_series_sp_pr(xml, chart, series_idx, None, true);
+        write_marker(xml, chart, series_idx, None, MarkerSymbol::Auto);
         
         xml.push_str("<c:xVal>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("'{}'!${}${}:${}${}", 
-            sheet_name, get_column_letter(start_col), start_row + 1, 
-            get_column_letter(start_col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(start_col), start_row + 1, 
+            col_letter(start_col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:xVal>\n");
         
         xml.push_str("<c:yVal>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("'{}'!${}${}:${}${}", 
-            sheet_name, get_column_letter(col), start_row + 1, 
-            get_column_letter(col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(col), start_row + 1, 
+            col_letter(col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:yVal>\n");
+        xml.push_str(&format!("<c:smooth val=\"{}\"/>\n", u8::from(chart.smooth)));
         
         xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
         xml.push_str(&format!("<c16:uniqueId val=\"{{0000000{}-6E8F-43DD-B1F6-30AC1D0140EF}}\"/>", series_idx));
@@ -1471,7 +1970,7 @@ fn generate_scatter_chart_content(xml: &mut String, chart: &ExcelChart, sheet_na
         xml.push_str("</c:ser>\n");
     }
     
-    write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+    write_data_labels(xml, chart);
     
     xml.push_str("<c:axId val=\"100000001\"/>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
@@ -1479,15 +1978,7 @@ fn generate_scatter_chart_content(xml: &mut String, chart: &ExcelChart, sheet_na
     
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000001\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.x_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"b\"/>\n");
     if let Some(ref x_title) = chart.x_axis_title {
@@ -1503,15 +1994,7 @@ fn generate_scatter_chart_content(xml: &mut String, chart: &ExcelChart, sheet_na
     
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"l\"/>\n");
     if let Some(ref y_title) = chart.y_axis_title {
@@ -1537,8 +2020,6 @@ fn generate_area_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     
     let (start_row, start_col, end_row, end_col) = chart.data_range;
     let category_col = chart.category_col.unwrap_or(start_col);
-    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
-    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
     
     let mut actual_series_idx = 0;
     for col in start_col..=end_col {
@@ -1547,37 +2028,27 @@ fn generate_area_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
         }
         
         let series_name = chart.series_names.get(actual_series_idx).map(|s| s.as_str()).unwrap_or("Series");
-        let accent_color = accent_colors[actual_series_idx % accent_colors.len()];
-        let (modifier, value) = tint_shade_values[actual_series_idx % tint_shade_values.len()];
         
         xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", actual_series_idx, actual_series_idx));
         
         xml.push_str("<c:tx>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}$1", sheet_name, get_column_letter(col)));
+        xml.push_str(&format!("{}${}$1", sheet_ref_prefix(sheet_name), col_letter(col)));
         xml.push_str("</c:f>\n<c:strCache>\n<c:ptCount val=\"1\"/>\n<c:pt idx=\"0\">\n");
-        xml.push_str(&format!("<c:v>{}</c:v>\n", series_name));
+        xml.push_str(&format!("<c:v>{}</c:v>\n", escape_xml(series_name)));
         xml.push_str("</c:pt>\n</c:strCache>\n</c:strRef>\n</c:tx>\n");
         
-        xml.push_str("<c:spPr>\n");
-        xml.push_str(&format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_color));
-        if !modifier.is_empty() {
-            xml.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
-        }
-        xml.push_str("</a:schemeClr></a:solidFill>\n");
-        xml.push_str("<a:ln><a:noFill/></a:ln>\n");
-        xml.push_str("<a:effectLst/>\n");
-        xml.push_str("</c:spPr>\n");
+        write_series_sp_pr(xml, chart, actual_series_idx, None, false);
         
-        xml.push_str("<c:cat>\n<c:strRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(category_col), start_row + 1, 
-            get_column_letter(category_col), end_row + 1));
-        xml.push_str("</c:f>\n</c:strRef>\n</c:cat>\n");
+        xml.push_str(&format!("<c:cat>\n<c:{}>\n<c:f>", category_ref(chart)));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(category_col), start_row + 1, 
+            col_letter(category_col), end_row + 1));
+        xml.push_str(&format!("</c:f>\n</c:{}>\n</c:cat>\n", category_ref(chart)));
         
         xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
-        xml.push_str(&format!("{}!${}${}:${}${}", 
-            sheet_name, get_column_letter(col), start_row + 1, 
-            get_column_letter(col), end_row + 1));
+        xml.push_str(&format!("{}${}${}:${}${}", 
+            sheet_ref_prefix(sheet_name), col_letter(col), start_row + 1, 
+            col_letter(col), end_row + 1));
         xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
         
         xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
@@ -1589,44 +2060,31 @@ fn generate_area_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     }
     
     // Area charts always have dLbls after all series
-    write_data_labels(xml, chart.show_data_labels.unwrap_or(false));
+    write_data_labels(xml, chart);
     
     xml.push_str("<c:axId val=\"100000001\"/>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
     xml.push_str("</c:areaChart>\n");
     
-    xml.push_str("<c:catAx>\n");
-    xml.push_str("<c:axId val=\"100000001\"/>\n");
+    write_category_axis_start(xml, chart);
     xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"b\"/>\n");
     if let Some(ref x_title) = chart.x_axis_title {
         write_axis_title(xml, x_title, chart);
     }
-    xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n");
+    write_category_number_format(xml, chart);
     xml.push_str("<c:majorTickMark val=\"out\"/>\n");
     xml.push_str("<c:minorTickMark val=\"none\"/>\n");
     xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
     write_category_axis_styling(xml);
     xml.push_str("<c:crossAx val=\"100000002\"/>\n");
     xml.push_str("<c:crosses val=\"autoZero\"/>\n");
-    xml.push_str("<c:auto val=\"1\"/>\n");
-    xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
-    xml.push_str("<c:lblOffset val=\"100\"/>\n");
-    xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
-    xml.push_str("</c:catAx>\n");
+    write_category_axis_end(xml, chart);
     
     xml.push_str("<c:valAx>\n");
     xml.push_str("<c:axId val=\"100000002\"/>\n");
-    xml.push_str("<c:scaling>\n");
-    xml.push_str("<c:orientation val=\"minMax\"/>\n");
-    if let Some(min) = chart.axis_min {
-        xml.push_str(&format!("<c:min val=\"{}\"/>\n", min));
-    }
-    if let Some(max) = chart.axis_max {
-        xml.push_str(&format!("<c:max val=\"{}\"/>\n", max));
-    }
-    xml.push_str("</c:scaling>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
     xml.push_str("<c:delete val=\"0\"/>\n");
     xml.push_str("<c:axPos val=\"l\"/>\n");
     write_major_gridlines(xml);
@@ -1646,708 +2104,2521 @@ fn generate_area_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name:
     xml.push_str("<c:spPr><a:noFill/><a:ln><a:noFill/></a:ln><a:effectLst/></c:spPr>\n");
 }
 
-/// Generate drawing relationships
-pub fn generate_drawing_rels(num_charts: usize) -> String {
-    let mut xml = String::with_capacity(300 + num_charts * 150);
-    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
-    xml.push_str("<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
-    
-    for i in 1..=num_charts {
-        xml.push_str(&format!("<Relationship Id=\"rIdChart{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart\" Target=\"../charts/chart{}.xml\"/>\n", i, i));
-    }
-    
-    xml.push_str("</Relationships>");
-    xml
+// ============================================================================
+// SERIES CHARTS (explicit series list, combo charts)
+// ============================================================================
+
+/// Plot type a chart series is drawn as
+fn series_chart_type(chart: &ExcelChart, series: &ChartSeries) -> ChartType {
+    series.chart_type.unwrap_or(chart.chart_type)
 }
 
+/// Charts built from `chart.series`. Series are grouped by plot type into one
+/// `<c:areaChart>`, `<c:barChart>` and `<c:lineChart>` (drawn in that order, so
+/// lines stay on top) per value axis. Secondary series get their own groups on
+/// a second pair of axes: a hidden category axis and a value axis on the far
+/// side that crosses at the maximum category.
+fn generate_series_chart_content(xml: &mut String, chart: &ExcelChart, sheet_name: &str) {
+    let horizontal = chart.series.iter().all(|s| series_chart_type(chart, s) == ChartType::Bar);
+    let groups: [(&[ChartType], &str); 3] = [
+        (&[ChartType::Area], "areaChart"),
+        (&[ChartType::Column, ChartType::Bar], "barChart"),
+        (&[ChartType::Line], "lineChart"),
+    ];
+    let stacked = chart.stacked || chart.percent_stacked;
+    let has_secondary = chart.series.iter().any(|s| s.secondary);
+    let mut has_bars = false;
+    let mut has_areas = false;
 
-/// Generate complete sheet XML with all enhanced features
-/// Element order: dimension → sheetViews → sheetFormatPr → cols → sheetData → 
-///                autoFilter → mergeCells → conditionalFormatting → dataValidations → 
-///                hyperlinks → drawing → tableParts
-pub fn generate_sheet_xml_from_arrow(
-    batches: &[RecordBatch],
-    config: &StyleConfig,
-    col_format_map: &HashMap<usize, u32>,
-    cell_style_map: &HashMap<(usize, usize), u32>,
-) -> Result<Vec<u8>, WriteError> {
-    if batches.is_empty() {
-        return Ok(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
-<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
-<dimension ref=\"A1\"/><sheetData/></worksheet>".to_vec());
-    }
+    let axis_ids = [(false, "100000001", "100000002"), (true, "100000003", "100000004")];
+    for (secondary, cat_ax_id, val_ax_id) in axis_ids {
+        for (types, element) in groups {
+            let members: Vec<(usize, &ChartSeries)> = chart.series.iter()
+                .enumerate()
+                .filter(|(_, s)| s.secondary == secondary && types.contains(&series_chart_type(chart, s)))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let is_bar = element == "barChart";
+            has_bars |= is_bar;
+            has_areas |= element == "areaChart";
 
-    let schema = batches[0].schema();
-    let num_cols = schema.fields().len();
-    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+            xml.push_str(&format!("<c:{}>\n", element));
+            if is_bar {
+                xml.push_str(&format!("<c:barDir val=\"{}\"/>\n", if horizontal { "bar" } else { "col" }));
+            }
+            let grouping = if chart.percent_stacked {
+                "percentStacked"
+            } else if chart.stacked {
+                "stacked"
+            } else if is_bar {
+                "clustered"
+            } else {
+                "standard"
+            };
+            xml.push_str(&format!("<c:grouping val=\"{}\"/>\n", grouping));
+            xml.push_str("<c:varyColors val=\"0\"/>\n");
 
-    if num_cols == 0 {
-        return Ok(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
-<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
-<dimension ref=\"A1\"/><sheetData/></worksheet>".to_vec());
-    }
+            for (idx, series) in members {
+                write_chart_series(xml, chart, sheet_name, idx, series);
+            }
 
-    // Build map of table header rows that need to be inserted
-    let mut table_header_rows: HashMap<usize, (usize, usize)> = HashMap::new();
-    let mut num_inserted_headers = 0;
-    for table in &config.tables {
-        let (start_row, start_col, _, end_col) = table.range;
-        if start_row > 1 {
-            table_header_rows.insert(start_row, (start_col, end_col));
-            num_inserted_headers += 1;
+            // Area charts always label at group level; bars and lines per series when stacked
+            if element == "areaChart" || !stacked {
+                write_data_labels(xml, chart);
+            }
+            if is_bar {
+                write_gap_and_overlap(xml, chart, stacked);
+            }
+            if element == "lineChart" {
+                xml.push_str("<c:smooth val=\"0\"/>\n");
+            }
+            xml.push_str(&format!("<c:axId val=\"{}\"/>\n", cat_ax_id));
+            xml.push_str(&format!("<c:axId val=\"{}\"/>\n", val_ax_id));
+            xml.push_str(&format!("</c:{}>\n", element));
         }
     }
 
-    let exact_size = calculate_exact_xml_size(batches)?;
-    let mut buf = Vec::with_capacity(exact_size);
+    write_category_axis_start(xml, chart);
+    xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
+    xml.push_str("<c:delete val=\"0\"/>\n");
+    xml.push_str(&format!("<c:axPos val=\"{}\"/>\n", if horizontal { "l" } else { "b" }));
+    if let Some(ref x_title) = chart.x_axis_title {
+        write_axis_title(xml, x_title, chart);
+    }
+    write_category_number_format(xml, chart);
+    xml.push_str("<c:majorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:minorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
+    write_category_axis_styling(xml);
+    xml.push_str("<c:crossAx val=\"100000002\"/>\n");
+    xml.push_str("<c:crosses val=\"autoZero\"/>\n");
+    write_category_axis_end(xml, chart);
 
-    buf.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
-<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">");
+    xml.push_str("<c:valAx>\n");
+    xml.push_str("<c:axId val=\"100000002\"/>\n");
+    write_value_scaling(xml, chart.y_axis_log_base, chart.axis_min, chart.axis_max);
+    xml.push_str("<c:delete val=\"0\"/>\n");
+    xml.push_str(&format!("<c:axPos val=\"{}\"/>\n", if horizontal { "b" } else { "l" }));
+    write_major_gridlines(xml);
+    if let Some(ref y_title) = chart.y_axis_title {
+        write_axis_title(xml, y_title, chart);
+    }
+    let format_code = if chart.percent_stacked { "0%" } else { "General" };
+    xml.push_str(&format!("<c:numFmt formatCode=\"{}\" sourceLinked=\"1\"/>\n", format_code));
+    xml.push_str("<c:majorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:minorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
+    write_value_axis_styling(xml);
+    xml.push_str("<c:crossAx val=\"100000001\"/>\n");
+    xml.push_str("<c:crosses val=\"autoZero\"/>\n");
+    // Columns sit between category ticks; area-only plots start on them
+    let cross_between = if !has_bars && has_areas { "midCat" } else { "between" };
+    xml.push_str(&format!("<c:crossBetween val=\"{}\"/>\n", cross_between));
+    xml.push_str("</c:valAx>\n");
 
-    // SheetPr (tab color - must come before dimension)
-    if let Some(ref color) = config.tab_color {
-        buf.extend_from_slice(b"<sheetPr><tabColor rgb=\"");
-        buf.extend_from_slice(color.as_bytes());
-        buf.extend_from_slice(b"\"/></sheetPr>");
+    if has_secondary {
+        write_secondary_axes(xml, chart, horizontal, cross_between);
     }
+    xml.push_str("<c:spPr><a:noFill/><a:ln><a:noFill/></a:ln><a:effectLst/></c:spPr>\n");
+}
 
-    // Dimension
-    buf.extend_from_slice(b"<dimension ref=\"");
-    if total_rows > 0 {
-        buf.extend_from_slice(b"A1:");
-        let mut col_buf = [0u8; 4];
-        let col_len = write_col_letter(num_cols - 1, &mut col_buf);
-        buf.extend_from_slice(&col_buf[..col_len]);
-        
-        let mut row_buf = itoa::Buffer::new();
-        buf.extend_from_slice(row_buf.format(total_rows + 1 + num_inserted_headers).as_bytes());
-    } else {
-        buf.extend_from_slice(b"A1");
+/// Secondary axis pair: a deleted category axis that only carries the
+/// crossing, and a value axis on the opposite side with its own scale
+fn write_secondary_axes(xml: &mut String, chart: &ExcelChart, horizontal: bool, cross_between: &str) {
+    xml.push_str("<c:catAx>\n");
+    xml.push_str("<c:axId val=\"100000003\"/>\n");
+    xml.push_str("<c:scaling><c:orientation val=\"minMax\"/></c:scaling>\n");
+    xml.push_str("<c:delete val=\"1\"/>\n");
+    xml.push_str(&format!("<c:axPos val=\"{}\"/>\n", if horizontal { "l" } else { "b" }));
+    xml.push_str("<c:majorTickMark val=\"out\"/>\n");
+    xml.push_str("<c:minorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
+    xml.push_str("<c:crossAx val=\"100000004\"/>\n");
+    xml.push_str("<c:crosses val=\"autoZero\"/>\n");
+    xml.push_str("<c:auto val=\"1\"/>\n");
+    xml.push_str("<c:lblAlgn val=\"ctr\"/>\n");
+    xml.push_str("<c:lblOffset val=\"100\"/>\n");
+    xml.push_str("<c:noMultiLvlLbl val=\"0\"/>\n");
+    xml.push_str("</c:catAx>\n");
+
+    xml.push_str("<c:valAx>\n");
+    xml.push_str("<c:axId val=\"100000004\"/>\n");
+    write_value_scaling(xml, None, chart.secondary_axis_min, chart.secondary_axis_max);
+    xml.push_str("<c:delete val=\"0\"/>\n");
+    xml.push_str(&format!("<c:axPos val=\"{}\"/>\n", if horizontal { "t" } else { "r" }));
+    if let Some(ref title) = chart.secondary_axis_title {
+        write_axis_title(xml, title, chart);
     }
-    buf.extend_from_slice(b"\"/>");
+    match chart.secondary_axis_number_format {
+        Some(ref code) => {
+            xml.push_str("<c:numFmt formatCode=\"");
+            xml_escape_format_code(code, xml);
+            xml.push_str("\" sourceLinked=\"0\"/>\n");
+        }
+        None => xml.push_str("<c:numFmt formatCode=\"General\" sourceLinked=\"1\"/>\n"),
+    }
+    xml.push_str("<c:majorTickMark val=\"out\"/>\n");
+    xml.push_str("<c:minorTickMark val=\"none\"/>\n");
+    xml.push_str("<c:tickLblPos val=\"nextTo\"/>\n");
+    write_value_axis_styling(xml);
+    xml.push_str("<c:crossAx val=\"100000003\"/>\n");
+    xml.push_str("<c:crosses val=\"max\"/>\n");
+    xml.push_str(&format!("<c:crossBetween val=\"{}\"/>\n", cross_between));
+    xml.push_str("</c:valAx>\n");
+}
 
-    // SheetViews (with gridlines, zoom, RTL, and optional freeze panes)
-    buf.extend_from_slice(b"<sheetViews><sheetView workbookViewId=\"0\"");
-    
-    // Add showGridLines if disabled
-    if !config.gridlines_visible {
-        buf.extend_from_slice(b" showGridLines=\"0\"");
+/// `<c:trendline>`, drawn as a dotted line in the series' accent color
+/// `<a:solidFill>` for series `idx`: the series' own color, then the chart's
+/// series_colors entry, then the accent1-6 rotation with a tint/shade cycle
+fn series_fill(chart: &ExcelChart, idx: usize, series: Option<&ChartSeries>) -> String {
+    let color = series.and_then(|s| s.color.as_ref()).or(chart.series_colors.get(idx));
+    if let Some(argb) = color.and_then(|c| normalize_argb(c)) {
+        // DrawingML takes RRGGBB, so the alpha byte is dropped
+        return format!("<a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill>\n", &argb[2..]);
     }
-    
-    // Add zoom scale
-    if let Some(zoom) = config.zoom_scale {
-        buf.extend_from_slice(b" zoomScale=\"");
-        buf.extend_from_slice(itoa::Buffer::new().format(zoom).as_bytes());
-        buf.push(b'\"');
+    let accent_colors = ["accent1", "accent2", "accent3", "accent4", "accent5", "accent6"];
+    let tint_shade_values = [("tint", "65000"), ("", ""), ("shade", "65000")];
+    let (modifier, value) = tint_shade_values[idx % tint_shade_values.len()];
+    let mut fill = format!("<a:solidFill><a:schemeClr val=\"{}\">", accent_colors[idx % accent_colors.len()]);
+    if !modifier.is_empty() {
+        fill.push_str(&format!("<a:{} val=\"{}\"/>", modifier, value));
     }
-    
-    // Add right-to-left
-    if config.right_to_left {
-        buf.extend_from_slice(b" rightToLeft=\"1\"");
+    fill.push_str("</a:schemeClr></a:solidFill>\n");
+    fill
+}
+
+/// Chart or plot area `<c:spPr>`. Unset parts fall back to a white fill and
+/// light gray outline when `default_visible`, else to no fill and no outline.
+fn write_area_sp_pr(xml: &mut String, fill: Option<&ChartFill>, border: Option<&ChartBorder>, default_visible: bool) {
+    let gray = "<a:solidFill><a:schemeClr val=\"tx1\"><a:lumMod val=\"15000\"/><a:lumOff val=\"85000\"/></a:schemeClr></a:solidFill>\n";
+
+    xml.push_str("<c:spPr>\n");
+    match fill {
+        Some(ChartFill::Solid(argb)) => xml.push_str(&format!("<a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill>\n", &argb[2..])),
+        None if default_visible => xml.push_str("<a:solidFill><a:schemeClr val=\"bg1\"/></a:solidFill>\n"),
+        _ => xml.push_str("<a:noFill/>\n"),
     }
-    
-    if config.freeze_rows > 0 || config.freeze_cols > 0 {
-        buf.push(b'>');
-        buf.extend_from_slice(b"<pane ");
-        
-        if config.freeze_cols > 0 {
-            buf.extend_from_slice(b"xSplit=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_cols).as_bytes());
-            buf.extend_from_slice(b"\" ");
+    match border {
+        Some(ChartBorder::Line { color, width, dash }) => {
+            // Widths are given in points; 1pt = 12700 EMU
+            let width_emu = width.map_or(9525, |w| (w * 12700.0).round() as u32);
+            xml.push_str(&format!("<a:ln w=\"{}\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\">\n", width_emu));
+            match color {
+                Some(argb) => xml.push_str(&format!("<a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill>\n", &argb[2..])),
+                None => xml.push_str(gray),
+            }
+            if let Some(dash) = dash {
+                xml.push_str(&format!("<a:prstDash val=\"{}\"/>\n", dash.prst_dash()));
+            }
+            xml.push_str("<a:round/></a:ln>\n");
         }
-        
-        if config.freeze_rows > 0 {
-            buf.extend_from_slice(b"ySplit=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_rows).as_bytes());
-            buf.extend_from_slice(b"\" ");
+        None if default_visible => {
+            xml.push_str("<a:ln w=\"9525\" cap=\"flat\" cmpd=\"sng\" algn=\"ctr\">\n");
+            xml.push_str(gray);
+            xml.push_str("<a:round/></a:ln>\n");
         }
-        
-        buf.extend_from_slice(b"topLeftCell=\"");
-        write_cell_ref(config.freeze_cols, config.freeze_rows + 1, &mut buf);
-        buf.extend_from_slice(b"\" activePane=\"bottomRight\" state=\"frozen\"/>");
-        buf.extend_from_slice(b"</sheetView></sheetViews>");
-    } else {
-        buf.extend_from_slice(b"/></sheetViews>");
+        _ => xml.push_str("<a:ln><a:noFill/></a:ln>\n"),
     }
+    xml.push_str("<a:effectLst/>\n");
+    xml.push_str("</c:spPr>\n");
+}
 
-    // SheetFormatPr (default row height)
-    buf.extend_from_slice(b"<sheetFormatPr defaultRowHeight=\"");
-    let default_height = config.default_row_height.unwrap_or(15.0);
-    buf.extend_from_slice(ryu::Buffer::new().format(default_height).as_bytes());
-    buf.push(b'\"');
-    if config.default_row_height.is_some() {
-        buf.extend_from_slice(b" customHeight=\"1\"");
-    }
-    buf.extend_from_slice(b"/>");
+/// Series `<c:spPr>`. Lines are stroked in the series color; bars and areas
+/// only get an outline when a line width or dash is set for them.
+fn write_series_sp_pr(xml: &mut String, chart: &ExcelChart, idx: usize, series: Option<&ChartSeries>, is_line: bool) {
+    let fill = series_fill(chart, idx, series);
+    let width = series.and_then(|s| s.line_width).or(chart.series_line_widths.get(idx).copied());
+    let dash = series.and_then(|s| s.line_dash).or(chart.series_line_dashes.get(idx).copied());
+    let dash_xml = dash.map(|d| format!("<a:prstDash val=\"{}\"/>\n", d.prst_dash())).unwrap_or_default();
+    // Widths are given in points; 1pt = 12700 EMU
+    let width_emu = |default: u32| width.map_or(default, |w| (w * 12700.0).round() as u32);
 
-    // Cols (column widths and hidden columns)
-    if config.auto_width || config.column_widths.is_some() || !config.hidden_columns.is_empty() {
-        buf.extend_from_slice(b"<cols>");
-        
-        for (col_idx, field) in schema.fields().iter().enumerate() {
-            let width = if let Some(widths) = &config.column_widths {
-                if let Some(col_width) = widths.get(field.name()) {
-                    match col_width {
-                        ColumnWidth::Characters(w) => *w,
-                        ColumnWidth::Pixels(px) => px / 7.0,  // Calibri 11pt MDW
-                        ColumnWidth::Auto => calculate_column_width(
-                            batches[0].column(col_idx).as_ref(),
-                            field.name(), 100, config.data_start_row
-                        ),
-                    }
-                } else if config.auto_width {
-                    calculate_column_width(batches[0].column(col_idx).as_ref(),
-                                        field.name(), 100, config.data_start_row)
-                } else {
-                    8.43
-                }
-            } else if config.auto_width {
-                calculate_column_width(batches[0].column(col_idx).as_ref(),
-                                    field.name(), 100, config.data_start_row)
-            } else {
-                8.43
-            };
-            
-            buf.extend_from_slice(b"<col min=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(col_idx + 1).as_bytes());
-            buf.extend_from_slice(b"\" max=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(col_idx + 1).as_bytes());
-            buf.extend_from_slice(b"\" width=\"");
-            buf.extend_from_slice(ryu::Buffer::new().format(width).as_bytes());
-            buf.extend_from_slice(b"\" customWidth=\"1\"");
-            
-            // Hidden column
-            if config.hidden_columns.contains(&col_idx) {
-                buf.extend_from_slice(b" hidden=\"1\"");
-            }
-            
-            buf.extend_from_slice(b"/>");
+    xml.push_str("<c:spPr>\n");
+    if is_line {
+        xml.push_str(&format!("<a:ln w=\"{}\" cap=\"rnd\">\n", width_emu(28575)));
+        xml.push_str(&fill);
+        xml.push_str(&dash_xml);
+        xml.push_str("<a:round/></a:ln>\n");
+    } else {
+        xml.push_str(&fill);
+        if width.is_some() || dash.is_some() {
+            xml.push_str(&format!("<a:ln w=\"{}\">\n", width_emu(9525)));
+            xml.push_str(&fill);
+            xml.push_str(&dash_xml);
+            xml.push_str("</a:ln>\n");
+        } else {
+            xml.push_str("<a:ln><a:noFill/></a:ln>\n");
         }
-        
-        buf.extend_from_slice(b"</cols>");
     }
+    xml.push_str("<a:effectLst/>\n");
+    xml.push_str("</c:spPr>\n");
+}
 
+/// `<c:marker>` for series `idx`. Series without a configured marker keep
+/// the chart type's default: none for lines, Excel's automatic for scatter.
+fn write_marker(xml: &mut String, chart: &ExcelChart, idx: usize, series: Option<&ChartSeries>, default: MarkerSymbol) {
+    let marker = match series.and_then(|s| s.marker.as_ref()).or(chart.series_markers.get(idx)) {
+        Some(marker) => marker,
+        None => {
+            if default != MarkerSymbol::Auto {
+                xml.push_str(&format!("<c:marker><c:symbol val=\"{}\"/></c:marker>\n", default.as_str()));
+            }
+            return;
+        }
+    };
+    let srgb = |color: &Option<String>| color.as_deref()
+        .and_then(normalize_argb)
+        .map(|argb| format!("<a:solidFill><a:srgbClr val=\"{}\"/></a:solidFill>", &argb[2..]));
 
-    // SheetData (all cell data)
-    buf.extend_from_slice(b"<sheetData>");
+    xml.push_str("<c:marker>\n");
+    xml.push_str(&format!("<c:symbol val=\"{}\"/>\n", marker.symbol.as_str()));
+    if let Some(size) = marker.size {
+        xml.push_str(&format!("<c:size val=\"{}\"/>\n", size));
+    }
+    let fill = srgb(&marker.fill);
+    let border = srgb(&marker.border);
+    if fill.is_some() || border.is_some() {
+        xml.push_str("<c:spPr>");
+        if let Some(ref fill) = fill {
+            xml.push_str(fill);
+        }
+        if let Some(ref border) = border {
+            xml.push_str(&format!("<a:ln w=\"9525\">{}</a:ln>", border));
+        }
+        xml.push_str("</c:spPr>\n");
+    }
+    xml.push_str("</c:marker>\n");
+}
 
-    let col_letters: Vec<([u8; 4], usize)> = (0..num_cols)
-        .map(|i| {
-            let mut col_buf = [0u8; 4];
-            let len = write_col_letter(i, &mut col_buf);
-            (col_buf, len)
-        })
-        .collect();
+fn write_trendline(xml: &mut String, trendline: &Trendline, fill: &str) {
+    xml.push_str("<c:trendline>\n");
+    if let Some(ref name) = trendline.name {
+        xml.push_str(&format!("<c:name>{}</c:name>\n", escape_xml(name)));
+    }
+    xml.push_str("<c:spPr>\n");
+    xml.push_str(&format!("<a:ln w=\"19050\" cap=\"rnd\">{}<a:prstDash val=\"sysDot\"/></a:ln>\n", fill.trim_end()));
+    xml.push_str("<a:effectLst/>\n");
+    xml.push_str("</c:spPr>\n");
+    match trendline.trendline_type {
+        TrendlineType::Linear => xml.push_str("<c:trendlineType val=\"linear\"/>\n"),
+        TrendlineType::Polynomial(order) => {
+            xml.push_str("<c:trendlineType val=\"poly\"/>\n");
+            xml.push_str(&format!("<c:order val=\"{}\"/>\n", order));
+        }
+        TrendlineType::MovingAverage(period) => {
+            xml.push_str("<c:trendlineType val=\"movingAvg\"/>\n");
+            xml.push_str(&format!("<c:period val=\"{}\"/>\n", period));
+        }
+        TrendlineType::Exponential => xml.push_str("<c:trendlineType val=\"exp\"/>\n"),
+    }
+    if let Some(forward) = trendline.forward {
+        xml.push_str(&format!("<c:forward val=\"{}\"/>\n", forward));
+    }
+    if let Some(backward) = trendline.backward {
+        xml.push_str(&format!("<c:backward val=\"{}\"/>\n", backward));
+    }
+    xml.push_str(&format!("<c:dispRSqr val=\"{}\"/>\n", u8::from(trendline.show_r_squared)));
+    xml.push_str(&format!("<c:dispEq val=\"{}\"/>\n", u8::from(trendline.show_equation)));
+    if trendline.show_equation || trendline.show_r_squared {
+        xml.push_str("<c:trendlineLbl><c:numFmt formatCode=\"General\" sourceLinked=\"0\"/></c:trendlineLbl>\n");
+    }
+    xml.push_str("</c:trendline>\n");
+}
 
-    let mut ryu_buf = ryu::Buffer::new();
-    let mut int_buf = itoa::Buffer::new();
-    let mut cell_int_buf = itoa::Buffer::new();
-    let mut cell_ref = [0u8; 16];
+/// `Sheet!` for a chart formula, quoted so names with spaces or punctuation
+/// work and escaped for the XML around it
+fn sheet_ref_prefix(sheet_name: &str) -> String {
+    escape_xml(&format!("'{}'!", sheet_name.replace('\'', "''")))
+}
 
-    let hyperlink_map: HashMap<(usize, usize), &Hyperlink> = config.hyperlinks
-        .iter()
-        .map(|h| ((h.row, h.col), h))
-        .collect();
-    
-    let formula_map: HashMap<(usize, usize), &Formula> = config.formulas
-        .iter()
-        .map(|f| ((f.row, f.col), f))
-        .collect();
+/// One `<c:ser>` of a series chart. `idx` numbers series across every plot
+/// group so colours and ids stay unique in a combo chart.
+fn write_chart_series(xml: &mut String, chart: &ExcelChart, sheet_name: &str, idx: usize, series: &ChartSeries) {
+    let (start_row, start_col, end_row, _) = chart.data_range;
+    let category_col = chart.category_col.unwrap_or(start_col);
+    let chart_type = series_chart_type(chart, series);
+    let explicit_name = series.name.as_deref().or_else(|| chart.series_names.get(idx).map(|s| s.as_str()));
+    let series_name = explicit_name.unwrap_or("Series");
 
-    // Determine where DataFrame data actually starts
-    let data_start = if config.write_header_row { 
-        config.data_start_row.max(1) 
-    } else { 
-        config.data_start_row 
+    // Cells as (sheet, 1-based first row, col, last row, col); the name cell
+    // is the header right above the first value row
+    let chart_categories = (sheet_name, start_row + 1, category_col, end_row + 1, category_col);
+    let (name_cell, categories, values) = match series.range {
+        Some(ref range) => {
+            let sheet = range.sheet.as_deref().unwrap_or(sheet_name);
+            let (r1, c1, r2, c2) = range.values;
+            let categories = range.categories
+                .map(|(cr1, cc1, cr2, cc2)| (sheet, cr1, cc1, cr2, cc2))
+                .unwrap_or(chart_categories);
+            ((r1 > 1).then_some((sheet, r1 - 1, c1)), categories, (sheet, r1, c1, r2, c2))
+        }
+        None => (
+            Some((sheet_name, start_row.max(1), series.col)),
+            chart_categories,
+            (sheet_name, start_row + 1, series.col, end_row + 1, series.col),
+        ),
     };
 
-    // Write header_content rows (arbitrary content before DataFrame data)
-    if !config.header_content.is_empty() {
-        let mut rows_map: HashMap<usize, Vec<(usize, String)>> = HashMap::new();
-        for (row, col, text) in &config.header_content {
-            rows_map.entry(*row).or_insert_with(Vec::new).push((*col, text.clone()));
+    xml.push_str(&format!("<c:ser>\n<c:idx val=\"{}\"/>\n<c:order val=\"{}\"/>\n", idx, idx));
+
+    // A named range series gets its name as literal text instead
+    let name_cell = name_cell.filter(|_| series.range.is_none() || series.name.is_none());
+    match name_cell {
+        Some((sheet, row, col)) => {
+            xml.push_str("<c:tx>\n<c:strRef>\n<c:f>");
+            xml.push_str(&format!("{}${}${}", sheet_ref_prefix(sheet), col_letter(col), row));
+            xml.push_str("</c:f>\n<c:strCache>\n<c:ptCount val=\"1\"/>\n<c:pt idx=\"0\">\n");
+            xml.push_str(&format!("<c:v>{}</c:v>\n", escape_xml(series_name)));
+            xml.push_str("</c:pt>\n</c:strCache>\n</c:strRef>\n</c:tx>\n");
         }
-        
-        let mut sorted_rows: Vec<_> = rows_map.keys().copied().collect();
-        sorted_rows.sort();
-        
-        for row_num in sorted_rows {
-            if row_num >= data_start { break; }
-            
-            let row_str = int_buf.format(row_num);
-            let row_bytes = row_str.as_bytes();
-            
-            // Start row tag with row number
-            buf.extend_from_slice(b"<row r=\"");
-            buf.extend_from_slice(row_bytes);
-            buf.push(b'\"');  // CRITICAL: Always close the r attribute
-            
-            // Add optional row height
-            if let Some(heights) = &config.row_heights {
-                if let Some(height) = heights.get(&row_num) {
-                    buf.extend_from_slice(b" ht=\"");  // Note: leading space for separate attribute
-                    buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
-                    buf.extend_from_slice(b"\" customHeight=\"1\"");
-                }
+        None => {
+            if let Some(name) = explicit_name {
+                xml.push_str(&format!("<c:tx><c:v>{}</c:v></c:tx>\n", escape_xml(name)));
             }
-            
-            // Add hidden attribute if needed
-            if config.hidden_rows.contains(&row_num) {
-                buf.extend_from_slice(b" hidden=\"1\"");
-            }
-            
-            buf.push(b'>');  // Close row opening tag
-            
-            // Write cells in this row
-            if let Some(cells) = rows_map.get(&row_num) {
-                for (col_idx, text) in cells {
-                    let (col_letter, col_len) = &col_letters[*col_idx];
-                    
-                    // Cell reference (e.g., "A2")
-                    buf.extend_from_slice(b"<c r=\"");
-                    buf.extend_from_slice(&col_letter[..*col_len]);
-                    buf.extend_from_slice(row_bytes);
-                    buf.push(b'\"');  // Close r attribute
-                    
-                    // Apply custom cell style if defined
-                    if let Some(style_id) = cell_style_map.get(&(row_num, *col_idx)) {
-                        buf.extend_from_slice(b" s=\"");
-                        buf.extend_from_slice(itoa::Buffer::new().format(*style_id).as_bytes());
-                        buf.push(b'\"');  // Close s attribute
-                    }
-                    
-                    // Write inline string content
-                    buf.extend_from_slice(b" t=\"inlineStr\"><is><t>");
-                    xml_escape_simd(text.as_bytes(), &mut buf);
-                    buf.extend_from_slice(b"</t></is></c>");
-                }
-            }
-            
-            buf.extend_from_slice(b"</row>");
         }
     }
 
-    // Write DataFrame header row at data_start (only if enabled)
-    if config.write_header_row {
-        let header_row_height = config.row_heights.as_ref().and_then(|h| h.get(&data_start));
-        buf.extend_from_slice(b"<row r=\"");
-        buf.extend_from_slice(itoa::Buffer::new().format(data_start).as_bytes());
-        buf.push(b'\"');
-        if let Some(height) = header_row_height {
-            buf.extend_from_slice(b" ht=\"");
-            buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
-            buf.extend_from_slice(b"\" customHeight=\"1\"");
-        }
-        // Hidden row check for header
-        if config.hidden_rows.contains(&data_start) {
-            buf.extend_from_slice(b" hidden=\"1\"");
-        }
-        buf.push(b'>');
-        
-        for (col_idx, field) in schema.fields().iter().enumerate() {
-            let (col_letter, col_len) = &col_letters[col_idx];
-            
-            let style_id = if config.styled_headers { 2 } else { 0 };
-            
-            buf.extend_from_slice(b"<c r=\"");
-            buf.extend_from_slice(&col_letter[..*col_len]);
-            buf.extend_from_slice(itoa::Buffer::new().format(data_start).as_bytes());
-            if style_id > 0 {
-                buf.extend_from_slice(b"\" s=\"");
-                buf.extend_from_slice(int_buf.format(style_id).as_bytes());
-            }
-            buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-            xml_escape_simd(field.name().as_bytes(), &mut buf);
-            buf.extend_from_slice(b"</t></is></c>");
-        }
-        buf.extend_from_slice(b"</row>");
+    write_series_sp_pr(xml, chart, idx, Some(series), chart_type == ChartType::Line);
+
+    match chart_type {
+        ChartType::Line => write_marker(xml, chart, idx, Some(series), MarkerSymbol::None),
+        ChartType::Area => {}
+        _ => xml.push_str("<c:invertIfNegative val=\"0\"/>\n"),
     }
 
-    let mut current_row = if config.write_header_row { data_start + 1 } else { data_start };
-    
-    // Build map of table header rows that need to be inserted
-    let mut table_header_rows: HashMap<usize, (usize, usize)> = HashMap::new();
-    let mut num_inserted_headers = 0;
-    for table in &config.tables {
-        let (start_row, start_col, _, end_col) = table.range;
-        // Only insert header if table starts after data_start and doesn't already have a header
-        if start_row > data_start {
-            table_header_rows.insert(start_row, (start_col, end_col));
-            num_inserted_headers += 1;
-        }
+    if chart_type != ChartType::Area && (chart.stacked || chart.percent_stacked) {
+        write_data_labels(xml, chart);
     }
+    if let Some(ref trendline) = series.trendline {
+        write_trendline(xml, trendline, &series_fill(chart, idx, Some(series)));
+    }
+
+    let area_ref = |(sheet, r1, c1, r2, c2): (&str, usize, usize, usize, usize)| format!("{}${}${}:${}${}",
+        sheet_ref_prefix(sheet), col_letter(c1), r1, col_letter(c2), r2);
+
+    xml.push_str(&format!("<c:cat>\n<c:{}>\n<c:f>", category_ref(chart)));
+    xml.push_str(&area_ref(categories));
+    xml.push_str(&format!("</c:f>\n</c:{}>\n</c:cat>\n", category_ref(chart)));
+
+    xml.push_str("<c:val>\n<c:numRef>\n<c:f>");
+    xml.push_str(&area_ref(values));
+    xml.push_str("</c:f>\n</c:numRef>\n</c:val>\n");
+
+    if chart_type == ChartType::Line {
+        xml.push_str(&format!("<c:smooth val=\"{}\"/>\n", u8::from(series.smooth.unwrap_or(chart.smooth))));
+    }
+
+    xml.push_str("<c:extLst><c:ext uri=\"{C3380CC4-5D6E-409C-BE32-E72D297353CC}\" xmlns:c16=\"http://schemas.microsoft.com/office/drawing/2014/chart\">");
+    xml.push_str(&format!("<c16:uniqueId val=\"{{{:08X}-6E8F-43DD-B1F6-30AC1D0140EF}}\"/>", idx));
+    xml.push_str("</c:ext></c:extLst>\n");
+
+    xml.push_str("</c:ser>\n");
+}
+
+/// Generate drawing relationships
+pub fn generate_drawing_rels(num_charts: usize) -> String {
+    let mut xml = String::with_capacity(300 + num_charts * 150);
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    xml.push_str("<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n");
     
+    for i in 1..=num_charts {
+        xml.push_str(&format!("<Relationship Id=\"rIdChart{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart\" Target=\"../charts/chart{}.xml\"/>\n", i, i));
+    }
     
-    // Cache feature flags to avoid repeated checks
-    let has_table_headers = !table_header_rows.is_empty();
-    let has_row_heights = config.row_heights.is_some();
-    let has_hidden_rows = !config.hidden_rows.is_empty();
-    
-    // Write data rows (with optional table header insertion)
-    for batch in batches {
-        let batch_rows = batch.num_rows();
-        
-        for row_idx in 0..batch_rows {
-            // Check if we need to insert table header row before this data row
-            if has_table_headers {
-                if let Some(&(start_col, end_col)) = table_header_rows.get(&current_row) {
-                    let row_str = int_buf.format(current_row);
-                    let row_bytes = row_str.as_bytes();
-                    
-                    buf.extend_from_slice(b"<row r=\"");
-                    buf.extend_from_slice(row_bytes);
-                    buf.push(b'\"');
-                    
-                    if has_row_heights {
-                        if let Some(height) = config.row_heights.as_ref().unwrap().get(&current_row) {
-                            buf.extend_from_slice(b" ht=\"");
-                            buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
-                            buf.extend_from_slice(b"\" customHeight=\"1\"");
-                        }
-                    }
-                    
-                    if has_hidden_rows && config.hidden_rows.contains(&current_row) {
-                        buf.extend_from_slice(b" hidden=\"1\"");
-                    }
-                    
-                    buf.push(b'>');
-                    
-                    // Write header cells for table columns
-                    for col_idx in start_col..=end_col {
-                        let (col_letter, col_len) = &col_letters[col_idx];
-                        let field_name = schema.fields()[col_idx].name();
-                        
-                        let mut header_cell_ref = Vec::with_capacity(16);
-                        header_cell_ref.extend_from_slice(&col_letter[..*col_len]);
-                        header_cell_ref.extend_from_slice(row_bytes);
-                        
-                        let custom_style_id = cell_style_map.get(&(current_row, col_idx)).copied();
-                        
-                        buf.extend_from_slice(b"<c r=\"");
-                        buf.extend_from_slice(&header_cell_ref);
-                        if let Some(sid) = custom_style_id {
-                            buf.extend_from_slice(b"\" s=\"");
-                            buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
-                        }
-                        buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                        xml_escape_simd(field_name.as_bytes(), &mut buf);
-                        buf.extend_from_slice(b"</t></is></c>");
-                    }
-                    
-                    buf.extend_from_slice(b"</row>");
-                    current_row += 1;
-                }
-            }
-            
-            // Write actual data row
-            let row_num = current_row;
-            let row_str = int_buf.format(row_num);
-            let row_bytes = row_str.as_bytes();
+    xml.push_str("</Relationships>");
+    xml
+}
 
-            buf.extend_from_slice(b"<row r=\"");
-            buf.extend_from_slice(row_bytes);
-            buf.push(b'\"');
-            
-            if has_row_heights {
-                if let Some(height) = config.row_heights.as_ref().unwrap().get(&row_num) {
-                    buf.extend_from_slice(b" ht=\"");
-                    buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
-                    buf.extend_from_slice(b"\" customHeight=\"1\"");
-                }
-            }
-            
-            if has_hidden_rows && config.hidden_rows.contains(&row_num) {
-                buf.extend_from_slice(b" hidden=\"1\"");
-            }
-            
-            buf.push(b'>');
 
-            for col_idx in 0..num_cols {
-                let array = batch.column(col_idx);
-                let (col_letter, col_len) = &col_letters[col_idx];
+// ============================================================================
+// AUTOFILTER CRITERIA
+// ============================================================================
 
-                let cell_ref_len = {
-                    cell_ref[..*col_len].copy_from_slice(&col_letter[..*col_len]);
-                    cell_ref[*col_len..*col_len + row_bytes.len()].copy_from_slice(row_bytes);
-                    *col_len + row_bytes.len()
-                };
-                let cell_ref_slice = &cell_ref[..cell_ref_len];
+/// A cell as the autofilter sees it; dates are Excel serials
+enum FilterValue<'a> {
+    Blank,
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+}
 
-                let custom_style_id = cell_style_map.get(&(row_num, col_idx)).copied();
-                let default_style_id = col_format_map.get(&col_idx).copied();
-                let style_id = custom_style_id.or(default_style_id);
-                
-                let hyperlink = hyperlink_map.get(&(row_num, col_idx));
-                let formula = formula_map.get(&(row_num, col_idx));
+/// One batch column converted for filtering
+enum FilterColumnData {
+    Numbers(arrow_array::Float64Array),
+    Text(arrow_array::StringArray),
+    Bools(arrow_array::BooleanArray),
+    Unsupported,
+}
 
-                write_arrow_cell_to_xml_optimized(
-                    array.as_ref(),
-                    row_idx,
-                    cell_ref_slice,
-                    style_id,
-                    hyperlink,
-                    formula,
-                    &mut buf,
-                    &mut ryu_buf,
-                    &mut cell_int_buf,
-                )?;
-            }
-            
-            buf.extend_from_slice(b"</row>");
-            current_row += 1;
-        }
-    }
+impl FilterColumnData {
+    fn new(array: &dyn Array, timezone: Option<&Tz>, date_1904: bool) -> Result<Self, WriteError> {
+        use arrow::compute::cast;
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::Int64Type;
+        use arrow_schema::TimeUnit;
 
-    buf.extend_from_slice(b"</sheetData>");
+        let cast_err = |e: arrow_schema::ArrowError| WriteError::Validation(format!("Autofilter: {}", e));
+        let per_sec: i64 = match array.data_type() {
+            DataType::Date32 => 0,
+            DataType::Date64 => 1_000,
+            DataType::Timestamp(TimeUnit::Second, _) => 1,
+            DataType::Timestamp(TimeUnit::Millisecond, _) => 1_000,
+            DataType::Timestamp(TimeUnit::Microsecond, _) => 1_000_000,
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => 1_000_000_000,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
+                let text = cast(array, &DataType::Utf8).map_err(cast_err)?;
+                return Ok(Self::Text(text.as_string::<i32>().clone()));
+            }
+            DataType::Boolean => return Ok(Self::Bools(array.as_boolean().clone())),
+            dt if dt.is_numeric() => {
+                let numbers = cast(array, &DataType::Float64).map_err(cast_err)?;
+                return Ok(Self::Numbers(numbers.as_primitive().clone()));
+            }
+            _ => return Ok(Self::Unsupported),
+        };
 
-    // AutoFilter - only if no table covers the entire range from A1
-    let has_full_table = config.tables.iter().any(|t| {
-        let (start_row, start_col, end_row, end_col) = t.range;
-        start_row == 1 && start_col == 0 && end_row >= total_rows && end_col >= num_cols - 1
-    });
-    // AutoFilter
-    if config.auto_filter && total_rows > 0 && !has_full_table {
-        buf.extend_from_slice(b"<autoFilter ref=\"A1:");
-        let mut col_buf = [0u8; 4];
-        let col_len = write_col_letter(num_cols - 1, &mut col_buf);
-        buf.extend_from_slice(&col_buf[..col_len]);
-        buf.extend_from_slice(int_buf.format(total_rows + 1).as_bytes());
-        buf.extend_from_slice(b"\"/>");
+        // Dates and timestamps become serials in the column's display timezone
+        let raw = cast(array, &DataType::Int64).map_err(cast_err)?;
+        let serials = raw.as_primitive::<Int64Type>().iter().map(|value| {
+            let secs = match per_sec {
+                0 => value? * 86400,
+                per_sec => value?.div_euclid(per_sec),
+            };
+            let offset = timezone.and_then(|tz| {
+                let utc = chrono::DateTime::from_timestamp(secs, 0)?.naive_utc();
+                Some(tz.offset_from_utc_datetime(&utc).fix().local_minus_utc() as i64)
+            });
+            unix_to_excel_serial(secs + offset.unwrap_or(0), date_1904)
+        });
+        Ok(Self::Numbers(serials.collect()))
     }
 
-    // MergeCells
-    if !config.merge_cells.is_empty() {
-        buf.extend_from_slice(b"<mergeCells count=\"");
-        buf.extend_from_slice(itoa::Buffer::new().format(config.merge_cells.len()).as_bytes());
-        buf.extend_from_slice(b"\">");
-        
-        for merge in &config.merge_cells {
-            buf.extend_from_slice(b"<mergeCell ref=\"");
-            write_cell_ref(merge.start_col, merge.start_row, &mut buf);
-            buf.push(b':');
-            write_cell_ref(merge.end_col, merge.end_row, &mut buf);
-            buf.extend_from_slice(b"\"/>");
+    fn value(&self, row: usize) -> FilterValue<'_> {
+        match self {
+            Self::Numbers(arr) if arr.is_valid(row) => FilterValue::Number(arr.value(row)),
+            Self::Text(arr) if arr.is_valid(row) && !arr.value(row).is_empty() => FilterValue::Text(arr.value(row)),
+            Self::Bools(arr) if arr.is_valid(row) => FilterValue::Bool(arr.value(row)),
+            _ => FilterValue::Blank,
         }
-        
-        buf.extend_from_slice(b"</mergeCells>");
     }
+}
 
-    // ConditionalFormatting
-    if !config.conditional_formats.is_empty() {
-        write_conditional_formatting(&mut buf, &config.conditional_formats, config);
-    }
+/// Autofilter criteria resolved to a column index
+struct ResolvedFilter<'a> {
+    col: usize,
+    criteria: &'a FilterCriteria,
+    /// Cut-off value for top/bottom filters
+    threshold: Option<f64>,
+}
 
-    // DataValidations
-    if !config.data_validations.is_empty() {
-        buf.extend_from_slice(b"<dataValidations count=\"");
-        buf.extend_from_slice(itoa::Buffer::new().format(config.data_validations.len()).as_bytes());
-        buf.extend_from_slice(b"\">");
-        
-        for validation in &config.data_validations {
-            buf.extend_from_slice(b"<dataValidation sqref=\"");
-            write_cell_ref(validation.start_col, validation.start_row, &mut buf);
-            buf.push(b':');
-            write_cell_ref(validation.end_col, validation.end_row, &mut buf);
-            buf.extend_from_slice(b"\" ");
-            
-            match &validation.validation_type {
-                ValidationType::List(_items) => {
-                    buf.extend_from_slice(b"type=\"list\" showDropDown=\"");
-                    buf.push(if validation.show_dropdown { b'0' } else { b'1' });
-                    buf.extend_from_slice(b"\"");
+/// Resolve `config.filter_columns` against the schema, computing top/bottom
+/// cut-offs from the whole column. Sorted by column.
+fn resolve_filters<'a>(
+    batches: &[RecordBatch],
+    config: &'a StyleConfig,
+    col_timezones: &[Option<Tz>],
+) -> Result<Vec<ResolvedFilter<'a>>, WriteError> {
+    let schema = batches[0].schema();
+    let mut filters = Vec::with_capacity(config.filter_columns.len());
+
+    for (name, criteria) in &config.filter_columns {
+        let col = schema.index_of(name).map_err(|_| WriteError::Validation(format!(
+            "Autofilter column '{}' is not in the data", name
+        )))?;
+
+        let threshold = match *criteria {
+            FilterCriteria::Top10 { rank, bottom, percent } => {
+                let mut numbers = Vec::new();
+                for batch in batches {
+                    let data = FilterColumnData::new(batch.column(col).as_ref(), col_timezones[col].as_ref(), config.date_1904)?;
+                    numbers.extend((0..batch.num_rows()).filter_map(|row| match data.value(row) {
+                        FilterValue::Number(n) => Some(n),
+                        _ => None,
+                    }));
                 }
-                ValidationType::WholeNumber { .. } => {
-                    buf.extend_from_slice(b"type=\"whole\" operator=\"between\"");
+                if bottom {
+                    numbers.sort_by(|a, b| a.total_cmp(b));
+                } else {
+                    numbers.sort_by(|a, b| b.total_cmp(a));
+                }
+                let count = if percent {
+                    (numbers.len() as f64 * rank as f64 / 100.0).ceil() as usize
+                } else {
+                    rank as usize
+                };
+                numbers.get(count.clamp(1, numbers.len().max(1)) - 1).copied()
+            }
+            _ => None,
+        };
+
+        filters.push(ResolvedFilter { col, criteria, threshold });
+    }
+
+    filters.sort_by_key(|f| f.col);
+    Ok(filters)
+}
+
+/// Text of a number as Excel shows it in the General format (close enough for matching)
+fn filter_number_text(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        itoa::Buffer::new().format(n as i64).to_string()
+    } else {
+        ryu::Buffer::new().format(n).to_string()
+    }
+}
+
+fn filter_matches(filter: &ResolvedFilter, value: FilterValue) -> bool {
+    match filter.criteria {
+        FilterCriteria::Values { values, blanks } => match value {
+            FilterValue::Blank => *blanks,
+            FilterValue::Number(n) => values.iter().any(|v| v.trim().parse::<f64>() == Ok(n)),
+            FilterValue::Text(s) => values.iter().any(|v| v.to_lowercase() == s.to_lowercase()),
+            FilterValue::Bool(b) => {
+                let text = if b { "TRUE" } else { "FALSE" };
+                values.iter().any(|v| v.eq_ignore_ascii_case(text))
+            }
+        },
+        FilterCriteria::Range { min, max } => match value {
+            FilterValue::Number(n) => min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max),
+            _ => false,
+        },
+        FilterCriteria::Top10 { bottom, .. } => match (value, filter.threshold) {
+            (FilterValue::Number(n), Some(t)) => if *bottom { n <= t } else { n >= t },
+            _ => false,
+        },
+        FilterCriteria::Contains { text } => {
+            let text = text.to_lowercase();
+            match value {
+                FilterValue::Blank => false,
+                FilterValue::Number(n) => filter_number_text(n).contains(&text),
+                FilterValue::Text(s) => s.to_lowercase().contains(&text),
+                FilterValue::Bool(b) => (if b { "true" } else { "false" }).contains(&text),
+            }
+        }
+    }
+}
+
+/// Data rows (counted across batches) that the criteria hide. Excel doesn't
+/// reapply filters on open, so the rows are written hidden.
+fn filtered_out_rows(
+    batches: &[RecordBatch],
+    filters: &[ResolvedFilter],
+    col_timezones: &[Option<Tz>],
+    date_1904: bool,
+) -> Result<Vec<bool>, WriteError> {
+    let mut hidden = Vec::new();
+    if filters.is_empty() {
+        return Ok(hidden);
+    }
+
+    for batch in batches {
+        let columns = filters
+            .iter()
+            .map(|f| FilterColumnData::new(batch.column(f.col).as_ref(), col_timezones[f.col].as_ref(), date_1904))
+            .collect::<Result<Vec<_>, _>>()?;
+        hidden.extend((0..batch.num_rows()).map(|row| {
+            filters.iter().zip(&columns).any(|(filter, data)| !filter_matches(filter, data.value(row)))
+        }));
+    }
+    Ok(hidden)
+}
+
+/// Escape Excel's filter wildcards (`*`, `?` and `~`) so text matches literally
+fn escape_filter_wildcards(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '~') {
+            escaped.push('~');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Write the `<filterColumn>` children of an `<autoFilter>`
+fn write_filter_columns(buf: &mut Vec<u8>, filters: &[ResolvedFilter]) {
+    for filter in filters {
+        buf.extend_from_slice(b"<filterColumn colId=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(filter.col).as_bytes());
+        buf.extend_from_slice(b"\">");
+
+        match filter.criteria {
+            FilterCriteria::Values { values, blanks } => {
+                buf.extend_from_slice(if *blanks { b"<filters blank=\"1\">" } else { b"<filters>" });
+                for value in values {
+                    buf.extend_from_slice(b"<filter val=\"");
+                    xml_escape_simd(value.as_bytes(), buf);
+                    buf.extend_from_slice(b"\"/>");
+                }
+                buf.extend_from_slice(b"</filters>");
+            }
+            FilterCriteria::Range { min, max } => {
+                let bounds: Vec<(&[u8], f64)> = [(&b"greaterThanOrEqual"[..], *min), (&b"lessThanOrEqual"[..], *max)]
+                    .into_iter()
+                    .filter_map(|(op, v)| v.map(|v| (op, v)))
+                    .collect();
+                buf.extend_from_slice(if bounds.len() > 1 { b"<customFilters and=\"1\">" } else { b"<customFilters>" });
+                for (operator, value) in bounds {
+                    buf.extend_from_slice(b"<customFilter operator=\"");
+                    buf.extend_from_slice(operator);
+                    buf.extend_from_slice(b"\" val=\"");
+                    buf.extend_from_slice(filter_number_text(value).as_bytes());
+                    buf.extend_from_slice(b"\"/>");
+                }
+                buf.extend_from_slice(b"</customFilters>");
+            }
+            FilterCriteria::Top10 { rank, bottom, percent } => {
+                buf.extend_from_slice(b"<top10");
+                if *bottom {
+                    buf.extend_from_slice(b" top=\"0\"");
                 }
-                ValidationType::Decimal { .. } => {
-                    buf.extend_from_slice(b"type=\"decimal\" operator=\"between\"");
+                if *percent {
+                    buf.extend_from_slice(b" percent=\"1\"");
                 }
-                ValidationType::TextLength { .. } => {
-                    buf.extend_from_slice(b"type=\"textLength\" operator=\"between\"");
+                buf.extend_from_slice(b" val=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(*rank).as_bytes());
+                buf.push(b'"');
+                if let Some(threshold) = filter.threshold {
+                    buf.extend_from_slice(b" filterVal=\"");
+                    buf.extend_from_slice(filter_number_text(threshold).as_bytes());
+                    buf.push(b'"');
                 }
+                buf.extend_from_slice(b"/>");
             }
-            
-            if let Some(title) = &validation.error_title {
-                buf.extend_from_slice(b" errorTitle=\"");
-                xml_escape_simd(title.as_bytes(), &mut buf);
-                buf.push(b'\"');
+            FilterCriteria::Contains { text } => {
+                buf.extend_from_slice(b"<customFilters><customFilter val=\"*");
+                xml_escape_simd(escape_filter_wildcards(text).as_bytes(), buf);
+                buf.extend_from_slice(b"*\"/></customFilters>");
+            }
+        }
+
+        buf.extend_from_slice(b"</filterColumn>");
+    }
+}
+
+/// Write a `<sortState>` with one `<sortCondition>` per key. Excel allows
+/// at most 64 keys, each a column inside the sorted range.
+fn write_sort_state(buf: &mut Vec<u8>, sort: &SortState) -> Result<(), WriteError> {
+    if sort.keys.is_empty() || sort.keys.len() > 64 {
+        return Err(WriteError::Validation(format!(
+            "sort_state needs 1-64 keys, got {}", sort.keys.len()
+        )));
+    }
+    buf.extend_from_slice(b"<sortState ref=\"");
+    write_cell_ref(sort.start_col, sort.start_row, buf);
+    buf.push(b':');
+    write_cell_ref(sort.end_col, sort.end_row, buf);
+    buf.extend_from_slice(b"\">");
+    for key in &sort.keys {
+        if key.col < sort.start_col || key.col > sort.end_col {
+            return Err(WriteError::Validation(format!(
+                "sort_state key column {} is outside the sorted range", key.col
+            )));
+        }
+        buf.extend_from_slice(if key.descending { b"<sortCondition descending=\"1\" ref=\"" } else { b"<sortCondition ref=\"" });
+        write_cell_ref(key.col, sort.start_row, buf);
+        buf.push(b':');
+        write_cell_ref(key.col, sort.end_row, buf);
+        buf.extend_from_slice(b"\"/>");
+    }
+    buf.extend_from_slice(b"</sortState>");
+    Ok(())
+}
+
+/// Generate complete sheet XML with all enhanced features
+/// Element order: dimension → sheetViews → sheetFormatPr → cols → sheetData → 
+///                autoFilter → mergeCells → conditionalFormatting → dataValidations → 
+///                hyperlinks → drawing → tableParts
+pub fn generate_sheet_xml_from_arrow(
+    batches: &[RecordBatch],
+    config: &StyleConfig,
+    styles: &SheetStyles,
+    progress: Option<&Progress>,
+) -> Result<Vec<u8>, WriteError> {
+    let mut buf = Vec::new();
+    generate_sheet_xml_into(batches, config, styles, progress, &mut buf)?;
+    Ok(buf)
+}
+
+/// `generate_sheet_xml_from_arrow` into a reused buffer. `buf` is cleared
+/// first; its capacity carries over, so a pooled buffer only grows when a
+/// sheet is larger than every one it held before.
+pub fn generate_sheet_xml_into(
+    batches: &[RecordBatch],
+    config: &StyleConfig,
+    styles: &SheetStyles,
+    progress: Option<&Progress>,
+    buf: &mut Vec<u8>,
+) -> Result<(), WriteError> {
+    buf.clear();
+    if batches.is_empty() || batches[0].num_columns() == 0 {
+        buf.extend_from_slice(EMPTY_SHEET_XML);
+        return Ok(());
+    }
+
+    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    // Some slack so features the estimate leaves out rarely force a regrow
+    let estimate = guess_sheet_xml_size(batches);
+    buf.reserve(estimate + estimate / 8);
+
+    let mut sheet = SheetXmlWriter::new(&batches[0], Some(total_rows), config, styles);
+    sheet.filter_rows(batches)?;
+    sheet.write_head(&batches[0], buf)?;
+    for batch in batches {
+        sheet.write_batch_reporting(batch, buf, progress)?;
+    }
+    sheet.write_tail(buf)
+}
+
+/// Worksheet XML gathered before it is handed on when a sheet is streamed
+pub const SHEET_XML_CHUNK_SIZE: usize = 4 << 20;
+
+/// `generate_sheet_xml_from_arrow` without ever holding the whole sheet: the
+/// XML goes to `sink` in pieces of about SHEET_XML_CHUNK_SIZE bytes. Batches
+/// are written in row slices sized from the estimate so one large batch
+/// doesn't become one large piece.
+pub fn stream_sheet_xml_from_arrow<F>(
+    batches: &[RecordBatch],
+    config: &StyleConfig,
+    styles: &SheetStyles,
+    progress: Option<&Progress>,
+    mut sink: F,
+) -> Result<(), WriteError>
+where
+    F: FnMut(&[u8]) -> Result<(), WriteError>,
+{
+    if batches.is_empty() || batches[0].num_columns() == 0 {
+        return sink(EMPTY_SHEET_XML);
+    }
+
+    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    let mut sheet = SheetXmlWriter::new(&batches[0], Some(total_rows), config, styles);
+    sheet.filter_rows(batches)?;
+
+    let mut buf = Vec::with_capacity(SHEET_XML_CHUNK_SIZE + SHEET_XML_CHUNK_SIZE / 4);
+    sheet.write_head(&batches[0], &mut buf)?;
+    for batch in batches {
+        let row_size = guess_sheet_xml_size(std::slice::from_ref(batch)) / batch.num_rows().max(1);
+        let slice_rows = (SHEET_XML_CHUNK_SIZE / row_size.max(1)).max(1);
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let len = slice_rows.min(batch.num_rows() - offset);
+            if !buf.is_empty() && buf.len() + len * row_size > SHEET_XML_CHUNK_SIZE {
+                sink(&buf)?;
+                buf.clear();
+            }
+            sheet.write_batch_reporting(&batch.slice(offset, len), &mut buf, progress)?;
+            offset += len;
+        }
+    }
+    sheet.write_tail(&mut buf)?;
+    sink(&buf)
+}
+
+/// Worksheet for data without columns
+const EMPTY_SHEET_XML: &[u8] = b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+<dimension ref=\"A1\"/><sheetData/></worksheet>";
+
+/// Style ids a sheet's config resolves to in the workbook's shared
+/// StyleRegistry. Kept apart from the config so resolving them never needs
+/// a copy of it.
+#[derive(Debug, Clone, Default)]
+pub struct SheetStyles {
+    pub col_format_map: HashMap<usize, u32>,
+    pub cell_style_map: HashMap<(usize, usize), u32>,
+    /// Number format style of every data cell of a row, from `row_formats`
+    pub row_format_ids: HashMap<usize, u32>,
+    /// Style ids of `range_styles`, in the same order
+    pub range_style_ids: Vec<u32>,
+    /// Dxf ids of `conditional_formats` by position
+    pub cond_format_dxf_ids: HashMap<usize, u32>,
+    pub header_style_id: u32,
+    pub hyperlink_style_id: u32,
+    /// Boolean columns with a Yes/No style format, written as 1/0 numbers
+    pub bool_number_columns: HashSet<usize>,
+}
+
+/// Worksheet serializer fed one batch at a time: `write_head`, `write_batch`
+/// for every batch in order, then `write_tail`. Lets a sheet be written while
+/// its batches are still being read, as long as nothing in the config needs
+/// every row up front (tables and filter criteria do).
+pub struct SheetXmlWriter<'a> {
+    config: &'a StyleConfig,
+    styles: &'a SheetStyles,
+    schema: SchemaRef,
+    num_cols: usize,
+    /// Data rows in the sheet when known before writing; sizes the dimension
+    total_rows: Option<usize>,
+    col_letters: Vec<Cow<'static, str>>,
+    col_timezones: Vec<Option<Tz>>,
+    hyperlink_map: HashMap<(usize, usize), &'a Hyperlink>,
+    formula_map: HashMap<(usize, usize), &'a Formula>,
+    rich_text_map: HashMap<(usize, usize), &'a RichTextCell>,
+    /// `range_styles` with their style ids
+    range_styles: Vec<(&'a RangeStyle, u32)>,
+    /// `column_formulas` templates by column position, split around `{row}`
+    column_formulas: Vec<Option<Vec<&'a str>>>,
+    row_outline: HashMap<usize, Outline>,
+    /// Table header rows inserted before the data row at the same position
+    table_header_rows: HashMap<usize, (usize, usize)>,
+    filters: Vec<ResolvedFilter<'a>>,
+    /// Data rows hidden by `filters`
+    filtered_rows: Vec<bool>,
+    /// Where DataFrame data starts (the header row, if written)
+    data_start: usize,
+    current_row: usize,
+    /// Data rows written so far
+    data_row: usize,
+}
+
+impl<'a> SheetXmlWriter<'a> {
+    /// `first` is the first batch of the sheet; its schema applies to every batch
+    pub fn new(
+        first: &RecordBatch,
+        total_rows: Option<usize>,
+        config: &'a StyleConfig,
+        styles: &'a SheetStyles,
+    ) -> Self {
+        let schema = first.schema();
+        let num_cols = schema.fields().len();
+
+        let col_letters = (0..num_cols).map(col_letter).collect();
+
+        let col_timezones = schema.fields().iter().enumerate()
+            .map(|(col_idx, field)| config.column_timezones_by_index.get(&col_idx)
+                .or_else(|| config.column_timezones.get(field.name()))
+                .copied())
+            .collect();
+
+        let column_formulas = schema.fields().iter()
+            .map(|field| config.column_formulas.get(field.name()).map(|t| t.split("{row}").collect()))
+            .collect();
+
+        let data_start = config.data_start_row.max(1);
+
+        let mut table_header_rows = HashMap::new();
+        for table in &config.tables {
+            let (start_row, start_col, _, end_col) = table.range;
+            // Only insert header if table starts after data_start and doesn't already have a header
+            if start_row > data_start {
+                table_header_rows.insert(start_row, (start_col, end_col));
+            }
+        }
+
+        Self {
+            config,
+            styles,
+            schema,
+            num_cols,
+            total_rows,
+            col_letters,
+            col_timezones,
+            hyperlink_map: config.hyperlinks.iter().map(|h| ((h.row, h.col), h)).collect(),
+            formula_map: config.formulas.iter().map(|f| ((f.row, f.col), f)).collect(),
+            rich_text_map: config.rich_text.iter().map(|r| ((r.row, r.col), r)).collect(),
+            range_styles: config.range_styles.iter().zip(styles.range_style_ids.iter().copied()).collect(),
+            column_formulas,
+            row_outline: build_outline(&config.row_groups),
+            table_header_rows,
+            filters: Vec::new(),
+            filtered_rows: Vec::new(),
+            data_start,
+            current_row: config.first_data_row(),
+            data_row: 0,
+        }
+    }
+
+    /// AutoFilter - only if no table covers the entire range from A1
+    fn has_auto_filter(&self, total_rows: usize) -> bool {
+        let config = self.config;
+        let has_full_table = config.tables.iter().any(|t| {
+            let (start_row, start_col, end_row, end_col) = t.range;
+            start_row == 1 && start_col == 0 && end_row >= total_rows && end_col >= self.num_cols - 1
+        });
+        (config.auto_filter || !config.filter_columns.is_empty())
+            && total_rows > 0
+            && !has_full_table
+    }
+
+    /// Resolve the autofilter criteria and the rows they hide. Needs every
+    /// batch of the sheet, so it's skipped when the sheet is streamed.
+    pub fn filter_rows(&mut self, batches: &[RecordBatch]) -> Result<(), WriteError> {
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        if self.has_auto_filter(total_rows) {
+            self.filters = resolve_filters(batches, self.config, &self.col_timezones)?;
+        }
+        self.filtered_rows = filtered_out_rows(batches, &self.filters, &self.col_timezones, self.config.date_1904)?;
+        Ok(())
+    }
+
+    /// Style id of the last range style covering the cell
+    fn range_style(&self, row: usize, col: usize) -> Option<u32> {
+        self.range_styles.iter().rev().find(|(range, _)| range.contains(row, col)).map(|&(_, id)| id)
+    }
+
+    /// Empty cells from `from_col` on for range styles reaching past the
+    /// columns the row already wrote, so borders and fills still show
+    fn write_range_cells(&self, row: usize, from_col: usize, buf: &mut Vec<u8>) {
+        let Some(end_col) = self.range_styles.iter()
+            .filter(|(range, _)| (range.start_row..=range.end_row).contains(&row))
+            .map(|(range, _)| range.end_col)
+            .max()
+        else {
+            return;
+        };
+        for col in from_col..=end_col {
+            if let Some(style_id) = self.range_style(row, col) {
+                write_empty_styled_cell(row, col, style_id, buf);
+            }
+        }
+    }
+
+    /// Everything before the data rows: sheet properties, dimension, views,
+    /// columns (sized from `first`), header content and the header row
+    pub fn write_head(&mut self, first: &RecordBatch, buf: &mut Vec<u8>) -> Result<(), WriteError> {
+        let config = self.config;
+        let styles = self.styles;
+        let cell_style_map = &styles.cell_style_map;
+        let schema = &self.schema;
+        let num_cols = self.num_cols;
+        let col_letters = &self.col_letters;
+        let row_outline = &self.row_outline;
+        let data_start = self.data_start;
+        let mut int_buf = itoa::Buffer::new();
+
+        // Every shared formula needs its master cell written as a data cell
+        for sf in &config.shared_formulas {
+            if sf.start_row < config.first_data_row() || sf.start_col >= num_cols
+                || sf.end_row < sf.start_row || sf.end_col < sf.start_col
+            {
+                return Err(WriteError::Validation(format!(
+                    "shared formula range at row {}, col {} must start in a data cell and end below/right of its start",
+                    sf.start_row, sf.start_col
+                )));
+            }
+        }
+        for column in config.column_formulas.keys() {
+            if schema.fields().iter().all(|f| f.name() != column) {
+                return Err(WriteError::Validation(format!(
+                    "column_formulas column '{}' not found; add it to the data first (e.g. as an empty column)",
+                    column
+                )));
             }
-            if let Some(msg) = &validation.error_message {
-                buf.extend_from_slice(b" error=\"");
-                xml_escape_simd(msg.as_bytes(), &mut buf);
-                buf.push(b'\"');
+        }
+        for table in &config.tables {
+            let (_, start_col, _, end_col) = table.resolved_range(0, table_width(schema, table.range.1));
+            if end_col >= num_cols {
+                return Err(WriteError::Validation(format!(
+                    "table '{}' ends at column {} but the data has {} columns", table.name, end_col, num_cols
+                )));
+            }
+            if let Some(gap_col) = (start_col..=end_col).find(|&col| is_gap_column(schema.field(col))) {
+                return Err(WriteError::Validation(format!(
+                    "table '{}' spans the empty column {} between side-by-side tables; give each table its own range",
+                    table.name, col_letter(gap_col)
+                )));
+            }
+        }
+
+        buf.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">");
+
+        write_sheet_pr(buf, config);
+
+        // Dimension (optional, left out when the row count isn't known yet)
+        if let Some(total_rows) = self.total_rows {
+            let num_inserted_headers = config.tables.iter().filter(|t| t.range.0 > 1).count();
+            buf.extend_from_slice(b"<dimension ref=\"");
+            if total_rows > 0 {
+                buf.extend_from_slice(b"A1:");
+                buf.extend_from_slice(col_letter(num_cols - 1).as_bytes());
+
+                let last_row = config.tables.iter()
+                    .filter(|t| t.show_totals_row)
+                    .map(|t| t.resolved_range(total_rows, table_width(schema, t.range.1)).2)
+                    .fold(config.first_data_row() - 1 + total_rows + num_inserted_headers, usize::max);
+                let mut row_buf = itoa::Buffer::new();
+                buf.extend_from_slice(row_buf.format(last_row).as_bytes());
+            } else {
+                buf.extend_from_slice(b"A1");
+            }
+            buf.extend_from_slice(b"\"/>");
+        }
+
+        write_sheet_views(buf, config);
+        write_sheet_format_pr(buf, config);
+
+        // Cols (widths, hidden columns, column styles, outline levels)
+        let col_specs = build_col_specs(std::slice::from_ref(first), config, self.styles);
+        write_cols(buf, &col_specs);
+
+        // SheetData (all cell data)
+        buf.extend_from_slice(b"<sheetData>");
+
+        // Write header_content rows (arbitrary content before DataFrame data)
+        // and range-styled rows above the header
+        if !config.header_content.is_empty() || !self.range_styles.is_empty() {
+            let mut rows_map: HashMap<usize, Vec<(usize, String)>> = HashMap::new();
+            for (row, col, text) in &config.header_content {
+                rows_map.entry(*row).or_insert_with(Vec::new).push((*col, text.clone()));
+            }
+            for (range, _) in &self.range_styles {
+                for row in range.start_row..=range.end_row.min(data_start.saturating_sub(1)) {
+                    rows_map.entry(row).or_default();
+                }
             }
             
-            buf.push(b'>');
+            let mut sorted_rows: Vec<_> = rows_map.keys().copied().collect();
+            sorted_rows.sort();
             
-            match &validation.validation_type {
-                ValidationType::List(items) => {
-                    buf.extend_from_slice(b"<formula1>\"");
-                    for (i, item) in items.iter().enumerate() {
-                        if i > 0 { buf.push(b','); }
-                        xml_escape_simd(item.as_bytes(), &mut buf);
+            for row_num in sorted_rows {
+                if row_num >= data_start { break; }
+                
+                let row_str = int_buf.format(row_num);
+                let row_bytes = row_str.as_bytes();
+                
+                // Start row tag with row number
+                buf.extend_from_slice(b"<row r=\"");
+                buf.extend_from_slice(row_bytes);
+                buf.push(b'\"');  // CRITICAL: Always close the r attribute
+                
+                // Add optional row height
+                if let Some(heights) = &config.row_heights {
+                    if let Some(height) = heights.get(&row_num) {
+                        buf.extend_from_slice(b" ht=\"");  // Note: leading space for separate attribute
+                        buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
+                        buf.extend_from_slice(b"\" customHeight=\"1\"");
                     }
-                    buf.extend_from_slice(b"\"</formula1>");
                 }
-                ValidationType::WholeNumber { min, max } => {
-                    buf.extend_from_slice(b"<formula1>");
-                    buf.extend_from_slice(itoa::Buffer::new().format(*min).as_bytes());
-                    buf.extend_from_slice(b"</formula1><formula2>");
-                    buf.extend_from_slice(itoa::Buffer::new().format(*max).as_bytes());
-                    buf.extend_from_slice(b"</formula2>");
+                
+                // Add hidden attribute if needed
+                let outline = row_outline.get(&row_num);
+                if config.hidden_rows.contains(&row_num) || outline.is_some_and(|o| o.hidden) {
+                    buf.extend_from_slice(b" hidden=\"1\"");
+                }
+                write_row_outline(buf, outline);
+                
+                buf.push(b'>');  // Close row opening tag
+                
+                // Write cells in this row, with empty cells where only a range style applies
+                let mut cells = rows_map.get(&row_num).cloned().unwrap_or_default();
+                cells.sort_by_key(|&(col_idx, _)| col_idx);
+                let mut next_col = 0;
+                for (col_idx, text) in &cells {
+                    for col in next_col..*col_idx {
+                        if let Some(style_id) = self.range_style(row_num, col) {
+                            write_empty_styled_cell(row_num, col, style_id, buf);
+                        }
+                    }
+                    next_col = col_idx + 1;
+
+                    let col_letter = col_letters[*col_idx].as_bytes();
+                    
+                    // Cell reference (e.g., "A2")
+                    buf.extend_from_slice(b"<c r=\"");
+                    buf.extend_from_slice(col_letter);
+                    buf.extend_from_slice(row_bytes);
+                    buf.push(b'\"');  // Close r attribute
+                    
+                    // Apply custom cell style if defined, else a range style
+                    let style_id = cell_style_map.get(&(row_num, *col_idx)).copied()
+                        .or_else(|| self.range_style(row_num, *col_idx));
+                    if let Some(style_id) = style_id {
+                        buf.extend_from_slice(b" s=\"");
+                        buf.extend_from_slice(itoa::Buffer::new().format(style_id).as_bytes());
+                        buf.push(b'\"');  // Close s attribute
+                    }
+                    
+                    // Write inline string content
+                    buf.extend_from_slice(b" t=\"inlineStr\"><is><t>");
+                    xml_escape_simd(text.as_bytes(), buf);
+                    buf.extend_from_slice(b"</t></is></c>");
                 }
-                ValidationType::Decimal { min, max } => {
-                    buf.extend_from_slice(b"<formula1>");
-                    buf.extend_from_slice(ryu::Buffer::new().format(*min).as_bytes());
-                    buf.extend_from_slice(b"</formula1><formula2>");
-                    buf.extend_from_slice(ryu::Buffer::new().format(*max).as_bytes());
-                    buf.extend_from_slice(b"</formula2>");
+                self.write_range_cells(row_num, next_col, buf);
+                
+                buf.extend_from_slice(b"</row>");
+            }
+        }
+
+        // Write DataFrame header row at data_start (only if enabled)
+        if config.write_header_row {
+            let header_row_height = config.row_heights.as_ref().and_then(|h| h.get(&data_start));
+            buf.extend_from_slice(b"<row r=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(data_start).as_bytes());
+            buf.push(b'\"');
+            if let Some(height) = header_row_height {
+                buf.extend_from_slice(b" ht=\"");
+                buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
+                buf.extend_from_slice(b"\" customHeight=\"1\"");
+            }
+            // Hidden row check for header
+            let outline = row_outline.get(&data_start);
+            if config.hidden_rows.contains(&data_start) || outline.is_some_and(|o| o.hidden) {
+                buf.extend_from_slice(b" hidden=\"1\"");
+            }
+            write_row_outline(buf, outline);
+            buf.push(b'>');
+            
+            for (col_idx, field) in schema.fields().iter().enumerate() {
+                let col_letter = col_letters[col_idx].as_bytes();
+                
+                let style_id = self.range_style(data_start, col_idx)
+                    .unwrap_or(if config.styled_headers { styles.header_style_id } else { 0 });
+                
+                let header_ref = [col_letter, itoa::Buffer::new().format(data_start).as_bytes()].concat();
+                buf.extend_from_slice(b"<c r=\"");
+                buf.extend_from_slice(&header_ref);
+                if style_id > 0 {
+                    buf.extend_from_slice(b"\" s=\"");
+                    buf.extend_from_slice(int_buf.format(style_id).as_bytes());
                 }
-                ValidationType::TextLength { min, max } => {
-                    buf.extend_from_slice(b"<formula1>");
-                    buf.extend_from_slice(itoa::Buffer::new().format(*min).as_bytes());
-                    buf.extend_from_slice(b"</formula1><formula2>");
-                    buf.extend_from_slice(itoa::Buffer::new().format(*max).as_bytes());
-                    buf.extend_from_slice(b"</formula2>");
+                buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+                write_cell_text(field.name().as_bytes(), &header_ref, Some(config.invalid_text), buf)?;
+                buf.extend_from_slice(b"</t></is></c>");
+            }
+            self.write_range_cells(data_start, num_cols, buf);
+            buf.extend_from_slice(b"</row>");
+        }
+
+        Ok(())
+    }
+
+    /// `write_batch` in slices of the progress interval, counting each slice
+    /// so long batches still report as they go
+    pub fn write_batch_reporting(&mut self, batch: &RecordBatch, buf: &mut Vec<u8>, progress: Option<&Progress>) -> Result<(), WriteError> {
+        let Some(progress) = progress else {
+            return self.write_batch(batch, buf);
+        };
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let len = progress.interval().min(batch.num_rows() - offset);
+            self.write_batch(&batch.slice(offset, len), buf)?;
+            progress.add_rows(len).map_err(WriteError::Validation)?;
+            offset += len;
+        }
+        Ok(())
+    }
+
+    /// Data rows of the next batch, with any table header rows that fall between them
+    pub fn write_batch(&mut self, batch: &RecordBatch, buf: &mut Vec<u8>) -> Result<(), WriteError> {
+        let config = self.config;
+        let styles = self.styles;
+        let cell_style_map = &styles.cell_style_map;
+        let col_format_map = &styles.col_format_map;
+        let schema = &self.schema;
+        let num_cols = self.num_cols;
+        let col_letters = &self.col_letters;
+        let col_timezones = &self.col_timezones;
+        let hyperlink_map = &self.hyperlink_map;
+        let formula_map = &self.formula_map;
+        let rich_text_map = &self.rich_text_map;
+        let column_formulas = &self.column_formulas;
+        let row_outline = &self.row_outline;
+        let table_header_rows = &self.table_header_rows;
+        let filtered_rows = &self.filtered_rows;
+        let mut current_row = self.current_row;
+        let mut data_row = self.data_row;
+
+        let mut ryu_buf = ryu::Buffer::new();
+        let mut int_buf = itoa::Buffer::new();
+        let mut cell_int_buf = itoa::Buffer::new();
+        let mut cell_ref = [0u8; 16];
+
+        // Cache feature flags to avoid repeated checks
+        let has_table_headers = !table_header_rows.is_empty();
+        let has_row_heights = config.row_heights.is_some();
+        let has_hidden_rows = !config.hidden_rows.is_empty();
+        let has_range_styles = !self.range_styles.is_empty();
+        let has_row_formats = !styles.row_format_ids.is_empty();
+
+        let batch_rows = batch.num_rows();
+        // Columns that need the per-cell text check; None for clean ones
+        let col_invalid_text: Vec<Option<InvalidText>> = batch.columns().iter()
+            .map(|array| (!is_clean_string_column(array.as_ref())).then_some(config.invalid_text))
+            .collect();
+        // Boolean columns with a Yes/No style format are written as 1/0 so the format shows,
+        // and text_columns as strings
+        let columns: Vec<ArrayRef> = batch.columns().iter().enumerate()
+            .map(|(col_idx, array)| if styles.bool_number_columns.contains(&col_idx) {
+                arrow::compute::cast(array, &DataType::UInt8)
+                    .map_err(|e| WriteError::Validation(e.to_string()))
+            } else if !config.text_columns.is_empty() && config.text_columns.contains(schema.field(col_idx).name()) {
+                arrow::compute::cast(array, &DataType::Utf8)
+                    .map_err(|e| WriteError::Validation(format!("text_columns '{}': {}", schema.field(col_idx).name(), e)))
+            } else {
+                Ok(array.clone())
+            })
+            .collect::<Result<_, _>>()?;
+        
+        for row_idx in 0..batch_rows {
+            // Check if we need to insert table header row before this data row
+            if has_table_headers {
+                if let Some(&(start_col, end_col)) = table_header_rows.get(&current_row) {
+                    let row_str = int_buf.format(current_row);
+                    let row_bytes = row_str.as_bytes();
+                    
+                    buf.extend_from_slice(b"<row r=\"");
+                    buf.extend_from_slice(row_bytes);
+                    buf.push(b'\"');
+                    
+                    if has_row_heights {
+                        if let Some(height) = config.row_heights.as_ref().unwrap().get(&current_row) {
+                            buf.extend_from_slice(b" ht=\"");
+                            buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
+                            buf.extend_from_slice(b"\" customHeight=\"1\"");
+                        }
+                    }
+                    
+                    let outline = row_outline.get(&current_row);
+                    if (has_hidden_rows && config.hidden_rows.contains(&current_row)) || outline.is_some_and(|o| o.hidden) {
+                        buf.extend_from_slice(b" hidden=\"1\"");
+                    }
+                    write_row_outline(buf, outline);
+                    
+                    buf.push(b'>');
+                    
+                    // Write header cells for table columns
+                    for col_idx in start_col..=end_col {
+                        let col_letter = col_letters[col_idx].as_bytes();
+                        let field_name = schema.fields()[col_idx].name();
+                        
+                        let mut header_cell_ref = Vec::with_capacity(16);
+                        header_cell_ref.extend_from_slice(col_letter);
+                        header_cell_ref.extend_from_slice(row_bytes);
+                        
+                        let custom_style_id = cell_style_map.get(&(current_row, col_idx)).copied();
+                        
+                        buf.extend_from_slice(b"<c r=\"");
+                        buf.extend_from_slice(&header_cell_ref);
+                        if let Some(sid) = custom_style_id {
+                            buf.extend_from_slice(b"\" s=\"");
+                            buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+                        }
+                        buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+                        xml_escape_simd(field_name.as_bytes(), buf);
+                        buf.extend_from_slice(b"</t></is></c>");
+                    }
+                    
+                    buf.extend_from_slice(b"</row>");
+                    current_row += 1;
                 }
             }
             
-            buf.extend_from_slice(b"</dataValidation>");
+            // Write actual data row
+            let row_num = current_row;
+            let row_str = int_buf.format(row_num);
+            let row_bytes = row_str.as_bytes();
+
+            buf.extend_from_slice(b"<row r=\"");
+            buf.extend_from_slice(row_bytes);
+            buf.push(b'\"');
+            
+            if has_row_heights {
+                if let Some(height) = config.row_heights.as_ref().unwrap().get(&row_num) {
+                    buf.extend_from_slice(b" ht=\"");
+                    buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
+                    buf.extend_from_slice(b"\" customHeight=\"1\"");
+                }
+            }
+            
+            let outline = row_outline.get(&row_num);
+            if (has_hidden_rows && config.hidden_rows.contains(&row_num))
+                || filtered_rows.get(data_row).copied().unwrap_or(false)
+                || outline.is_some_and(|o| o.hidden)
+            {
+                buf.extend_from_slice(b" hidden=\"1\"");
+            }
+            write_row_outline(buf, outline);
+            data_row += 1;
+            
+            buf.push(b'>');
+
+            let row_format_id = if has_row_formats { styles.row_format_ids.get(&row_num).copied() } else { None };
+
+            for col_idx in 0..num_cols {
+                let array = &columns[col_idx];
+                let col_letter = col_letters[col_idx].as_bytes();
+
+                let cell_ref_len = {
+                    cell_ref[..col_letter.len()].copy_from_slice(col_letter);
+                    cell_ref[col_letter.len()..col_letter.len() + row_bytes.len()].copy_from_slice(row_bytes);
+                    col_letter.len() + row_bytes.len()
+                };
+                let cell_ref_slice = &cell_ref[..cell_ref_len];
+
+                let custom_style_id = cell_style_map.get(&(row_num, col_idx)).copied();
+                let default_style_id = row_format_id.or(col_format_map.get(&col_idx).copied());
+                let hyperlink = hyperlink_map.get(&(row_num, col_idx));
+                let hyperlink_style_id = hyperlink.map(|_| styles.hyperlink_style_id);
+                let range_style_id = if has_range_styles { self.range_style(row_num, col_idx) } else { None };
+                let style_id = custom_style_id.or(range_style_id).or(hyperlink_style_id).or(default_style_id);
+
+                let formula = formula_map.get(&(row_num, col_idx));
+
+                if formula.is_none() && !config.shared_formulas.is_empty() {
+                    if let Some(si) = config.shared_formulas.iter().position(|sf| sf.contains(row_num, col_idx)) {
+                        let sf = &config.shared_formulas[si];
+                        let master = row_num == sf.start_row && col_idx == sf.start_col;
+                        write_shared_formula_cell(sf, si, master, cell_ref_slice, style_id, buf);
+                        continue;
+                    }
+                }
+
+                // Rich text replaces the data value; a formula still wins
+                if let Some(rich) = rich_text_map.get(&(row_num, col_idx)).filter(|_| formula.is_none()) {
+                    write_rich_text_cell(&rich.runs, cell_ref_slice, style_id, config.invalid_text, buf)?;
+                    continue;
+                }
+
+                if let Some(parts) = column_formulas[col_idx].as_ref().filter(|_| formula.is_none()) {
+                    write_column_formula_cell(parts, row_bytes, cell_ref_slice, style_id, buf);
+                    continue;
+                }
+
+                write_arrow_cell_to_xml_optimized(
+                    array.as_ref(),
+                    row_idx,
+                    cell_ref_slice,
+                    style_id,
+                    hyperlink,
+                    formula,
+                    col_timezones[col_idx].as_ref(),
+                    config,
+                    col_invalid_text[col_idx],
+                    buf,
+                    &mut ryu_buf,
+                    &mut cell_int_buf,
+                )?;
+            }
+            if has_range_styles {
+                self.write_range_cells(row_num, num_cols, buf);
+            }
+            
+            buf.extend_from_slice(b"</row>");
+            current_row += 1;
+        }
+
+        self.current_row = current_row;
+        self.data_row = data_row;
+        Ok(())
+    }
+
+    /// Totals rows of tables with `show_totals_row`, one row below each
+    /// table's data. Column formats reach them only with format_totals_rows.
+    fn write_totals_rows(&self, buf: &mut Vec<u8>) -> Result<(), WriteError> {
+        let config = self.config;
+        let schema = &self.schema;
+        let mut totals: Vec<(usize, usize, &ExcelTable)> = config.tables.iter()
+            .filter(|t| t.show_totals_row)
+            .map(|t| {
+                let (_, start_col, end_row, _) = t.resolved_range(self.data_row, table_width(schema, t.range.1));
+                (end_row, start_col, t)
+            })
+            .collect();
+        totals.sort_by_key(|&(row, start_col, _)| (row, start_col));
+
+        let mut int_buf = itoa::Buffer::new();
+        let mut open_row = None;
+        for &(row, _, table) in &totals {
+            if row < self.current_row {
+                return Err(WriteError::Validation(format!(
+                    "table '{}' totals row {} would overwrite a data row; end the table at its last data row",
+                    table.name, row
+                )));
+            }
+            let (_, start_col, _, end_col) = table.resolved_range(self.data_row, table_width(schema, table.range.1));
+            let column_names: Vec<String> = if table.column_names.is_empty() {
+                schema.fields()[start_col..=end_col].iter().map(|f| f.name().clone()).collect()
+            } else {
+                table.column_names.clone()
+            };
+            if let Some((column, _)) = table.totals_row_functions.iter().find(|(c, _)| !column_names.contains(c)) {
+                return Err(WriteError::Validation(format!(
+                    "totals_row_functions column '{}' is not a column of table '{}'", column, table.name
+                )));
+            }
+
+            if open_row != Some(row) {
+                if open_row.is_some() {
+                    buf.extend_from_slice(b"</row>");
+                }
+                buf.extend_from_slice(b"<row r=\"");
+                buf.extend_from_slice(int_buf.format(row).as_bytes());
+                buf.push(b'"');
+                if let Some(height) = config.row_heights.as_ref().and_then(|h| h.get(&row)) {
+                    buf.extend_from_slice(b" ht=\"");
+                    buf.extend_from_slice(ryu::Buffer::new().format(*height).as_bytes());
+                    buf.extend_from_slice(b"\" customHeight=\"1\"");
+                }
+                let outline = self.row_outline.get(&row);
+                if config.hidden_rows.contains(&row) || outline.is_some_and(|o| o.hidden) {
+                    buf.extend_from_slice(b" hidden=\"1\"");
+                }
+                write_row_outline(buf, outline);
+                buf.push(b'>');
+                open_row = Some(row);
+            }
+
+            let last_column_numeric = schema.field(end_col).data_type().is_numeric();
+            let cells = table.totals_row_cells(&column_names, last_column_numeric);
+            for ((col_idx, cell), column) in (start_col..=end_col).zip(&cells).zip(&column_names) {
+                let style_id = self.styles.cell_style_map.get(&(row, col_idx)).copied()
+                    .or_else(|| self.range_style(row, col_idx))
+                    .or_else(|| config.format_totals_rows.then(|| self.styles.col_format_map.get(&col_idx).copied()).flatten());
+                if matches!(cell, TotalsCell::Empty) && style_id.is_none() {
+                    continue;
+                }
+                buf.extend_from_slice(b"<c r=\"");
+                write_cell_ref(col_idx, row, buf);
+                if let Some(sid) = style_id {
+                    buf.extend_from_slice(b"\" s=\"");
+                    buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+                }
+                match cell {
+                    TotalsCell::Empty => buf.extend_from_slice(b"\"/>"),
+                    TotalsCell::Label(label) => {
+                        buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+                        xml_escape_simd(label.as_bytes(), buf);
+                        buf.extend_from_slice(b"</t></is></c>");
+                    }
+                    TotalsCell::Function(function) => {
+                        let formula = format!(
+                            "SUBTOTAL({},{}[{}])",
+                            function.subtotal_code(),
+                            table.display_name,
+                            structured_ref_column(column)
+                        );
+                        buf.extend_from_slice(b"\"><f>");
+                        xml_escape_simd(formula.as_bytes(), buf);
+                        buf.extend_from_slice(b"</f></c>");
+                    }
+                }
+            }
+        }
+        if open_row.is_some() {
+            buf.extend_from_slice(b"</row>");
+        }
+        Ok(())
+    }
+
+    /// Close `<sheetData>` and write everything after the data rows
+    pub fn write_tail(&mut self, buf: &mut Vec<u8>) -> Result<(), WriteError> {
+        let config = self.config;
+        let num_cols = self.num_cols;
+        let data_start = self.data_start;
+        let total_rows = self.data_row;
+        let has_auto_filter = self.has_auto_filter(total_rows);
+        let filters = &self.filters;
+        let mut int_buf = itoa::Buffer::new();
+
+        self.write_totals_rows(buf)?;
+        buf.extend_from_slice(b"</sheetData>");
+
+        // AutoFilter
+        if has_auto_filter {
+            // From the header row (or the first data row) down to the last data row
+            buf.extend_from_slice(b"<autoFilter ref=\"A");
+            buf.extend_from_slice(int_buf.format(data_start).as_bytes());
+            buf.push(b':');
+            buf.extend_from_slice(col_letter(num_cols - 1).as_bytes());
+            buf.extend_from_slice(int_buf.format(config.first_data_row() - 1 + total_rows).as_bytes());
+            if filters.is_empty() && config.sort_state.is_none() {
+                buf.extend_from_slice(b"\"/>");
+            } else {
+                buf.extend_from_slice(b"\">");
+                write_filter_columns(buf, filters);
+                if let Some(sort) = &config.sort_state {
+                    write_sort_state(buf, sort)?;
+                }
+                buf.extend_from_slice(b"</autoFilter>");
+            }
+        } else if let Some(sort) = &config.sort_state {
+            // Without an autofilter the sort is recorded on the worksheet itself
+            write_sort_state(buf, sort)?;
+        }
+
+        // MergeCells
+        if !config.merge_cells.is_empty() {
+            buf.extend_from_slice(b"<mergeCells count=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(config.merge_cells.len()).as_bytes());
+            buf.extend_from_slice(b"\">");
+            
+            for merge in &config.merge_cells {
+                buf.extend_from_slice(b"<mergeCell ref=\"");
+                write_cell_ref(merge.start_col, merge.start_row, buf);
+                buf.push(b':');
+                write_cell_ref(merge.end_col, merge.end_row, buf);
+                buf.extend_from_slice(b"\"/>");
+            }
+            
+            buf.extend_from_slice(b"</mergeCells>");
+        }
+
+        // ConditionalFormatting
+        if !config.conditional_formats.is_empty() {
+            write_conditional_formatting(buf, &config.conditional_formats, &self.styles.cond_format_dxf_ids);
+        }
+
+        // DataValidations
+        if !config.data_validations.is_empty() {
+            buf.extend_from_slice(b"<dataValidations count=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(config.data_validations.len()).as_bytes());
+            buf.extend_from_slice(b"\">");
+            
+            for validation in &config.data_validations {
+                buf.extend_from_slice(b"<dataValidation sqref=\"");
+                write_cell_ref(validation.start_col, validation.start_row, buf);
+                buf.push(b':');
+                write_cell_ref(validation.end_col, validation.end_row, buf);
+                buf.extend_from_slice(b"\" ");
+                
+                match &validation.validation_type {
+                    ValidationType::List(_) | ValidationType::ListRef(_) => {
+                        buf.extend_from_slice(b"type=\"list\" showDropDown=\"");
+                        buf.push(if validation.show_dropdown { b'0' } else { b'1' });
+                        buf.extend_from_slice(b"\"");
+                    }
+                    ValidationType::WholeNumber { .. } => {
+                        buf.extend_from_slice(b"type=\"whole\" operator=\"between\"");
+                    }
+                    ValidationType::Decimal { .. } => {
+                        buf.extend_from_slice(b"type=\"decimal\" operator=\"between\"");
+                    }
+                    ValidationType::TextLength { .. } => {
+                        buf.extend_from_slice(b"type=\"textLength\" operator=\"between\"");
+                    }
+                }
+                
+                match validation.error_style {
+                    ValidationErrorStyle::Stop => {}
+                    ValidationErrorStyle::Warning => buf.extend_from_slice(b" errorStyle=\"warning\""),
+                    ValidationErrorStyle::Information => buf.extend_from_slice(b" errorStyle=\"information\""),
+                }
+                if validation.allow_blank {
+                    buf.extend_from_slice(b" allowBlank=\"1\"");
+                }
+                if validation.show_error_message {
+                    buf.extend_from_slice(b" showErrorMessage=\"1\"");
+                }
+                if let Some(title) = &validation.error_title {
+                    buf.extend_from_slice(b" errorTitle=\"");
+                    xml_escape_simd(title.as_bytes(), buf);
+                    buf.push(b'\"');
+                }
+                if let Some(msg) = &validation.error_message {
+                    buf.extend_from_slice(b" error=\"");
+                    xml_escape_simd(msg.as_bytes(), buf);
+                    buf.push(b'\"');
+                }
+                if validation.show_input_message {
+                    buf.extend_from_slice(b" showInputMessage=\"1\"");
+                }
+                if let Some(title) = &validation.prompt_title {
+                    buf.extend_from_slice(b" promptTitle=\"");
+                    xml_escape_simd(title.as_bytes(), buf);
+                    buf.push(b'\"');
+                }
+                if let Some(msg) = &validation.prompt_message {
+                    buf.extend_from_slice(b" prompt=\"");
+                    xml_escape_simd(msg.as_bytes(), buf);
+                    buf.push(b'\"');
+                }
+                
+                buf.push(b'>');
+                
+                match &validation.validation_type {
+                    ValidationType::List(items) => {
+                        buf.extend_from_slice(b"<formula1>\"");
+                        for (i, item) in items.iter().enumerate() {
+                            if i > 0 { buf.push(b','); }
+                            xml_escape_simd(item.as_bytes(), buf);
+                        }
+                        buf.extend_from_slice(b"\"</formula1>");
+                    }
+                    ValidationType::ListRef(range) => {
+                        buf.extend_from_slice(b"<formula1>");
+                        xml_escape_simd(range.trim_start_matches('=').as_bytes(), buf);
+                        buf.extend_from_slice(b"</formula1>");
+                    }
+                    ValidationType::WholeNumber { min, max } => {
+                        buf.extend_from_slice(b"<formula1>");
+                        buf.extend_from_slice(itoa::Buffer::new().format(*min).as_bytes());
+                        buf.extend_from_slice(b"</formula1><formula2>");
+                        buf.extend_from_slice(itoa::Buffer::new().format(*max).as_bytes());
+                        buf.extend_from_slice(b"</formula2>");
+                    }
+                    ValidationType::Decimal { min, max } => {
+                        buf.extend_from_slice(b"<formula1>");
+                        buf.extend_from_slice(ryu::Buffer::new().format(*min).as_bytes());
+                        buf.extend_from_slice(b"</formula1><formula2>");
+                        buf.extend_from_slice(ryu::Buffer::new().format(*max).as_bytes());
+                        buf.extend_from_slice(b"</formula2>");
+                    }
+                    ValidationType::TextLength { min, max } => {
+                        buf.extend_from_slice(b"<formula1>");
+                        buf.extend_from_slice(itoa::Buffer::new().format(*min).as_bytes());
+                        buf.extend_from_slice(b"</formula1><formula2>");
+                        buf.extend_from_slice(itoa::Buffer::new().format(*max).as_bytes());
+                        buf.extend_from_slice(b"</formula2>");
+                    }
+                }
+                
+                buf.extend_from_slice(b"</dataValidation>");
+            }
+            
+            buf.extend_from_slice(b"</dataValidations>");
+        }
+
+        // Hyperlinks
+        if !config.hyperlinks.is_empty() {
+            buf.extend_from_slice(b"<hyperlinks>");
+            
+            for (idx, hyperlink) in config.hyperlinks.iter().enumerate() {
+                buf.extend_from_slice(b"<hyperlink ref=\"");
+                write_cell_ref(hyperlink.col, hyperlink.row, buf);
+                buf.extend_from_slice(b"\" r:id=\"rId");
+                buf.extend_from_slice(itoa::Buffer::new().format(idx + 1).as_bytes());
+                buf.extend_from_slice(b"\"/>");
+            }
+            
+            buf.extend_from_slice(b"</hyperlinks>");
+        }
+
+        // Drawing (for charts and images)
+        if !config.charts.is_empty() || !config.images.is_empty() {
+            buf.extend_from_slice(b"<drawing r:id=\"rIdDraw1\"/>");
+        }
+
+        // TableParts (MUST be after drawing)
+        if !config.tables.is_empty() {
+            buf.extend_from_slice(b"<tableParts count=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(config.tables.len()).as_bytes());
+            buf.extend_from_slice(b"\">");
+            
+            for idx in 0..config.tables.len() {
+                buf.extend_from_slice(b"<tablePart r:id=\"rIdTable");
+                buf.extend_from_slice(itoa::Buffer::new().format(idx + 1).as_bytes());
+                buf.extend_from_slice(b"\"/>");
+            }
+            
+            buf.extend_from_slice(b"</tableParts>");
+        }
+
+        // Excel 2010 data bar settings (MUST be the last child)
+        if config.conditional_formats.iter().any(|f| matches!(f.rule, ConditionalRule::DataBar { .. })) {
+            write_x14_data_bars(buf, &config.conditional_formats);
+        }
+
+        buf.extend_from_slice(b"</worksheet>");
+
+        Ok(())
+    }
+}
+
+
+/// Write `<sheetPr>` with the tab color and outline settings. Must come
+/// before `<dimension>`.
+fn write_sheet_pr(buf: &mut Vec<u8>, config: &StyleConfig) {
+    let has_outline = !config.row_groups.is_empty() || !config.col_groups.is_empty();
+    if config.tab_color.is_none() && !has_outline {
+        return;
+    }
+    buf.extend_from_slice(b"<sheetPr>");
+    if let Some(ref color) = config.tab_color {
+        buf.extend_from_slice(b"<tabColor rgb=\"");
+        buf.extend_from_slice(color.as_bytes());
+        buf.extend_from_slice(b"\"/>");
+    }
+    if has_outline {
+        // Expand buttons sit below and right of their groups
+        buf.extend_from_slice(b"<outlinePr summaryBelow=\"1\" summaryRight=\"1\"/>");
+    }
+    buf.extend_from_slice(b"</sheetPr>");
+}
+
+/// Write `<sheetViews>` with gridlines, zoom, RTL and optional freeze panes
+fn write_sheet_views(buf: &mut Vec<u8>, config: &StyleConfig) {
+    buf.extend_from_slice(b"<sheetViews><sheetView workbookViewId=\"0\"");
+    
+    // Add showGridLines if disabled
+    if !config.gridlines_visible {
+        buf.extend_from_slice(b" showGridLines=\"0\"");
+    }
+    
+    // Add zoom scale
+    if let Some(zoom) = config.zoom_scale {
+        buf.extend_from_slice(b" zoomScale=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(zoom).as_bytes());
+        buf.push(b'\"');
+    }
+    
+    // Add right-to-left
+    if config.right_to_left {
+        buf.extend_from_slice(b" rightToLeft=\"1\"");
+    }
+    
+    // The pane that scrolls freely once rows and/or columns are frozen
+    let pane: Option<&[u8]> = match (config.freeze_rows > 0, config.freeze_cols > 0) {
+        (true, true) => Some(b"bottomRight"),
+        (true, false) => Some(b"bottomLeft"),
+        (false, true) => Some(b"topRight"),
+        (false, false) => None,
+    };
+    
+    // Unfrozen sheets scroll the whole window
+    if let (None, Some((row, col))) = (pane, config.top_left_cell) {
+        buf.extend_from_slice(b" topLeftCell=\"");
+        write_cell_ref(col, row, buf);
+        buf.push(b'\"');
+    }
+    
+    if pane.is_none() && config.selection.is_none() {
+        buf.extend_from_slice(b"/></sheetViews>");
+        return;
+    }
+    buf.push(b'>');
+    
+    if let Some(pane) = pane {
+        buf.extend_from_slice(b"<pane ");
+        
+        if config.freeze_cols > 0 {
+            buf.extend_from_slice(b"xSplit=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_cols).as_bytes());
+            buf.extend_from_slice(b"\" ");
+        }
+        
+        if config.freeze_rows > 0 {
+            buf.extend_from_slice(b"ySplit=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_rows).as_bytes());
+            buf.extend_from_slice(b"\" ");
+        }
+        
+        // top_left_cell scrolls the frozen pane; default is just past the split
+        let (row, col) = config.top_left_cell.unwrap_or((config.freeze_rows + 1, config.freeze_cols));
+        buf.extend_from_slice(b"topLeftCell=\"");
+        write_cell_ref(col, row, buf);
+        buf.extend_from_slice(b"\" activePane=\"");
+        buf.extend_from_slice(pane);
+        buf.extend_from_slice(b"\" state=\"frozen\"/>");
+    }
+    
+    if let Some(selection) = &config.selection {
+        buf.extend_from_slice(b"<selection");
+        if let Some(pane) = pane {
+            buf.extend_from_slice(b" pane=\"");
+            buf.extend_from_slice(pane);
+            buf.push(b'\"');
+        }
+        let (row, col) = selection.active_cell;
+        buf.extend_from_slice(b" activeCell=\"");
+        write_cell_ref(col, row, buf);
+        buf.extend_from_slice(b"\" sqref=\"");
+        for (i, &(r1, c1, r2, c2)) in selection.ranges.iter().enumerate() {
+            if i > 0 {
+                buf.push(b' ');
+            }
+            write_cell_ref(c1, r1, buf);
+            if (r1, c1) != (r2, c2) {
+                buf.push(b':');
+                write_cell_ref(c2, r2, buf);
+            }
+        }
+        buf.extend_from_slice(b"\"/>");
+    }
+    
+    buf.extend_from_slice(b"</sheetView></sheetViews>");
+}
+
+/// Write `<sheetFormatPr>` with the default row height
+fn write_sheet_format_pr(buf: &mut Vec<u8>, config: &StyleConfig) {
+    buf.extend_from_slice(b"<sheetFormatPr defaultRowHeight=\"");
+    let default_height = config.default_row_height.unwrap_or(15.0);
+    buf.extend_from_slice(ryu::Buffer::new().format(default_height).as_bytes());
+    buf.push(b'\"');
+    if config.default_row_height.is_some() {
+        buf.extend_from_slice(b" customHeight=\"1\"");
+    }
+    // Widen the outline gutter to the deepest group
+    if let Some(level) = config.row_groups.iter().map(|g| g.level).max() {
+        buf.extend_from_slice(b" outlineLevelRow=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(level).as_bytes());
+        buf.push(b'"');
+    }
+    if let Some(level) = config.col_groups.iter().map(|g| g.level).max() {
+        buf.extend_from_slice(b" outlineLevelCol=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(level).as_bytes());
+        buf.push(b'"');
+    }
+    buf.extend_from_slice(b"/>");
+}
+
+/// Outline attributes of one row or column
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Outline {
+    level: u8,
+    /// Inside a collapsed group
+    hidden: bool,
+    /// Right after a collapsed group, where its expand button sits
+    collapsed: bool,
+}
+
+/// Outline attributes by row or column, from the deepest group covering each
+fn build_outline(groups: &[OutlineGroup]) -> HashMap<usize, Outline> {
+    let mut outline: HashMap<usize, Outline> = HashMap::new();
+    for group in groups {
+        for idx in group.start..=group.end {
+            let entry = outline.entry(idx).or_default();
+            entry.level = entry.level.max(group.level);
+            entry.hidden |= group.collapsed;
+        }
+        if group.collapsed {
+            outline.entry(group.end + 1).or_default().collapsed = true;
+        }
+    }
+    outline
+}
+
+/// Write ` outlineLevel`/` collapsed` row attributes; `hidden` is left to
+/// the caller so it merges with hidden and filtered rows
+fn write_row_outline(buf: &mut Vec<u8>, outline: Option<&Outline>) {
+    let Some(outline) = outline else { return };
+    if outline.level > 0 {
+        buf.extend_from_slice(b" outlineLevel=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(outline.level).as_bytes());
+        buf.push(b'"');
+    }
+    if outline.collapsed {
+        buf.extend_from_slice(b" collapsed=\"1\"");
+    }
+}
+
+/// Attributes of one worksheet column's `<col>` entry
+#[derive(Debug, Clone, PartialEq)]
+struct ColSpec {
+    width: Option<f64>, // None keeps Excel's default width
+    hidden: bool,
+    style: Option<u32>,
+    outline_level: u8,
+    collapsed: bool,
+}
+
+impl ColSpec {
+    const DEFAULT: ColSpec = ColSpec { width: None, hidden: false, style: None, outline_level: 0, collapsed: false };
+}
+
+/// Collect every column attribute (explicit or auto widths, hidden columns,
+/// column format styles with format_whole_columns, outline levels) into one
+/// spec per column, 0-based. Columns past the data are included when one of
+/// them is hidden or grouped.
+fn build_col_specs(batches: &[RecordBatch], config: &StyleConfig, styles: &SheetStyles) -> Vec<ColSpec> {
+    let schema = batches[0].schema();
+    let outline = build_outline(&config.col_groups);
+    let num_cols = schema.fields().len()
+        .max(config.hidden_columns.iter().max().map_or(0, |&c| c + 1))
+        .max(outline.keys().max().map_or(0, |&c| c + 1));
+    let mut specs = vec![ColSpec::DEFAULT; num_cols];
+
+    for (col_idx, field) in schema.fields().iter().enumerate() {
+        let col_width = config.column_widths_by_index.get(&col_idx)
+            .or_else(|| config.column_widths.as_ref().and_then(|w| w.get(field.name())));
+        specs[col_idx].width = match col_width {
+            Some(ColumnWidth::Characters(w)) => Some(*w),
+            Some(ColumnWidth::Pixels(px)) => Some(px / 7.0),  // Calibri 11pt MDW
+            Some(ColumnWidth::Auto) => Some(calculate_column_width(
+                batches[0].column(col_idx).as_ref(),
+                field.name(), 100, config.data_start_row
+            )),
+            None if config.auto_width => Some(calculate_column_width(
+                batches[0].column(col_idx).as_ref(),
+                field.name(), 100, config.data_start_row
+            )),
+            None => None,
+        };
+        if config.format_whole_columns {
+            specs[col_idx].style = styles.col_format_map.get(&col_idx).copied();
+        }
+    }
+
+    for &col_idx in &config.hidden_columns {
+        specs[col_idx].hidden = true;
+    }
+    for (&col_idx, col_outline) in &outline {
+        let spec = &mut specs[col_idx];
+        spec.outline_level = col_outline.level;
+        spec.hidden |= col_outline.hidden;
+        spec.collapsed = col_outline.collapsed;
+    }
+
+    specs
+}
+
+/// Write `<cols>`, merging runs of identical columns into one min/max entry.
+/// Nothing is written when every column keeps the defaults.
+fn write_cols(buf: &mut Vec<u8>, specs: &[ColSpec]) {
+    if specs.iter().all(|spec| *spec == ColSpec::DEFAULT) {
+        return;
+    }
+
+    buf.extend_from_slice(b"<cols>");
+    let mut start = 0;
+    while start < specs.len() {
+        let spec = &specs[start];
+        let mut end = start;
+        while end + 1 < specs.len() && specs[end + 1] == *spec {
+            end += 1;
+        }
+
+        if *spec != ColSpec::DEFAULT {
+            buf.extend_from_slice(b"<col min=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(start + 1).as_bytes());
+            buf.extend_from_slice(b"\" max=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(end + 1).as_bytes());
+            buf.extend_from_slice(b"\" width=\"");
+            buf.extend_from_slice(ryu::Buffer::new().format(spec.width.unwrap_or(8.43)).as_bytes());
+            buf.push(b'"');
+            if let Some(style) = spec.style {
+                buf.extend_from_slice(b" style=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(style).as_bytes());
+                buf.push(b'"');
+            }
+            if spec.hidden {
+                buf.extend_from_slice(b" hidden=\"1\"");
+            }
+            if spec.width.is_some() {
+                buf.extend_from_slice(b" customWidth=\"1\"");
+            }
+            if spec.outline_level > 0 {
+                buf.extend_from_slice(b" outlineLevel=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(spec.outline_level).as_bytes());
+                buf.push(b'"');
+            }
+            if spec.collapsed {
+                buf.extend_from_slice(b" collapsed=\"1\"");
+            }
+            buf.extend_from_slice(b"/>");
+        }
+        start = end + 1;
+    }
+    buf.extend_from_slice(b"</cols>");
+}
+
+/// Write conditional formatting section
+fn write_conditional_formatting(buf: &mut Vec<u8>, formats: &[ConditionalFormat], dxf_ids: &HashMap<usize, u32>) {
+    // Rules sharing a range go into one block, in the order the ranges first appear
+    let range = |f: &ConditionalFormat| (f.start_row, f.start_col, f.end_row, f.end_col);
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+    for (idx, format) in formats.iter().enumerate() {
+        match blocks.iter_mut().find(|rules| range(&formats[rules[0]]) == range(format)) {
+            Some(rules) => rules.push(idx),
+            None => blocks.push(vec![idx]),
+        }
+    }
+
+    for rules in blocks {
+        let (start_row, start_col, end_row, end_col) = range(&formats[rules[0]]);
+        buf.extend_from_slice(b"<conditionalFormatting sqref=\"");
+        write_cell_ref(start_col, start_row, buf);
+        buf.push(b':');
+        write_cell_ref(end_col, end_row, buf);
+        buf.extend_from_slice(b"\">");
+        
+        for idx in rules {
+            let format = &formats[idx];
+            buf.extend_from_slice(b"<cfRule ");
+            if format.stop_if_true {
+                buf.extend_from_slice(b"stopIfTrue=\"1\" ");
+            }
+            buf.extend_from_slice(b"type=\"");
+            
+            let dxf_id = dxf_ids.get(&idx).copied();
+            // Text and blank rules reference the range's top-left cell; Excel
+            // shifts the reference for every other cell in the range.
+            let mut top_left_buf = Vec::with_capacity(12);
+            write_cell_ref(start_col, start_row, &mut top_left_buf);
+            let top_left = String::from_utf8(top_left_buf).unwrap_or_default();
+        
+            match &format.rule {
+                ConditionalRule::CellValue { operator, value } => {
+                    // Get DXF ID from the properly built map
+                    if let Some(&dxf_id) = dxf_ids.get(&idx) {
+                        buf.extend_from_slice(b"cellIs\" dxfId=\"");
+                        buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
+                        buf.extend_from_slice(b"\" operator=\"");
+                    } else {
+                        buf.extend_from_slice(b"cellIs\" operator=\"");
+                    }
+                    let op_str = match operator {
+                        ComparisonOperator::GreaterThan => "greaterThan",
+                        ComparisonOperator::LessThan => "lessThan",
+                        ComparisonOperator::Equal => "equal",
+                        ComparisonOperator::NotEqual => "notEqual",
+                        ComparisonOperator::GreaterThanOrEqual => "greaterThanOrEqual",
+                        ComparisonOperator::LessThanOrEqual => "lessThanOrEqual",
+                        ComparisonOperator::Between => "between",
+                    };
+                    buf.extend_from_slice(op_str.as_bytes());
+                    buf.extend_from_slice(b"\" priority=\"");
+                    buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
+                    buf.extend_from_slice(b"\"><formula>");
+                    xml_escape_simd(value.as_bytes(), buf);
+                    buf.extend_from_slice(b"</formula></cfRule>");
+                }
+                ConditionalRule::ColorScale { min_color, max_color, mid_color, min, mid, max } => {
+                    buf.extend_from_slice(b"colorScale\" priority=\"");
+                    buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
+                    buf.extend_from_slice(b"\"><colorScale>");
+                    write_cfvo(buf, min);
+                    if mid_color.is_some() {
+                        write_cfvo(buf, mid);
+                    }
+                    write_cfvo(buf, max);
+                    buf.extend_from_slice(b"<color rgb=\"");
+                    buf.extend_from_slice(min_color.as_bytes());
+                    buf.extend_from_slice(b"\"/>");
+                    if let Some(mid) = mid_color {
+                        buf.extend_from_slice(b"<color rgb=\"");
+                        buf.extend_from_slice(mid.as_bytes());
+                        buf.extend_from_slice(b"\"/>");
+                    }
+                    buf.extend_from_slice(b"<color rgb=\"");
+                    buf.extend_from_slice(max_color.as_bytes());
+                    buf.extend_from_slice(b"\"/></colorScale></cfRule>");
+                }
+                ConditionalRule::DataBar { color, show_value, min, max, .. } => {
+                    buf.extend_from_slice(b"dataBar\" priority=\"");
+                    buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
+                    buf.extend_from_slice(b"\"><dataBar");
+                    if !show_value {
+                        buf.extend_from_slice(b" showValue=\"0\"");
+                    }
+                    buf.push(b'>');
+                    write_cfvo(buf, min);
+                    write_cfvo(buf, max);
+                    buf.extend_from_slice(b"<color rgb=\"");
+                    buf.extend_from_slice(color.as_bytes());
+                    buf.extend_from_slice(b"\"/></dataBar>");
+                    // Links the rule to its x14:dataBar twin in the worksheet extLst
+                    buf.extend_from_slice(b"<extLst><ext uri=\"{B025F937-C7B1-47D3-B67F-A62EFF666E3E}\" xmlns:x14=\"");
+                    buf.extend_from_slice(X14_NS.as_bytes());
+                    buf.extend_from_slice(b"\"><x14:id>");
+                    write_data_bar_guid(buf, idx);
+                    buf.extend_from_slice(b"</x14:id></ext></extLst></cfRule>");
+                }
+                ConditionalRule::Top10 { rank, bottom } => {
+                    if let Some(&dxf_id) = dxf_ids.get(&idx) {
+                        buf.extend_from_slice(b"top10\" dxfId=\"");
+                        buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
+                        buf.extend_from_slice(b"\" priority=\"");
+                    } else {
+                        buf.extend_from_slice(b"top10\" priority=\"");
+                    }
+                    buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
+                    buf.extend_from_slice(b"\" rank=\"");
+                    buf.extend_from_slice(itoa::Buffer::new().format(*rank).as_bytes());
+                    if *bottom {
+                        buf.extend_from_slice(b"\" bottom=\"1\"/>");
+                    } else {
+                        buf.extend_from_slice(b"\"/>");
+                    }
+                }
+                ConditionalRule::Expression { formula } => {
+                    if let Some(&dxf_id) = dxf_ids.get(&idx) {
+                        buf.extend_from_slice(b"expression\" dxfId=\"");
+                        buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
+                        buf.extend_from_slice(b"\" priority=\"");
+                    } else {
+                        buf.extend_from_slice(b"expression\" priority=\"");
+                    }
+                    buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
+                    buf.extend_from_slice(b"\"><formula>");
+                    xml_escape_simd(formula.trim_start_matches('=').as_bytes(), buf);
+                    buf.extend_from_slice(b"</formula></cfRule>");
+                }
+                ConditionalRule::ContainsText { text } => {
+                    let formula = format!("NOT(ISERROR(SEARCH({},{})))", quote_formula_text(text), top_left);
+                    write_cf_rule_head(buf, "containsText", dxf_id, format.priority);
+                    write_text_cf_rule_body(buf, "containsText", text, &formula);
+                }
+                ConditionalRule::NotContainsText { text } => {
+                    let formula = format!("ISERROR(SEARCH({},{}))", quote_formula_text(text), top_left);
+                    write_cf_rule_head(buf, "notContainsText", dxf_id, format.priority);
+                    write_text_cf_rule_body(buf, "notContains", text, &formula);
+                }
+                ConditionalRule::BeginsWith { text } => {
+                    let quoted = quote_formula_text(text);
+                    let formula = format!("LEFT({},LEN({}))={}", top_left, quoted, quoted);
+                    write_cf_rule_head(buf, "beginsWith", dxf_id, format.priority);
+                    write_text_cf_rule_body(buf, "beginsWith", text, &formula);
+                }
+                ConditionalRule::EndsWith { text } => {
+                    let quoted = quote_formula_text(text);
+                    let formula = format!("RIGHT({},LEN({}))={}", top_left, quoted, quoted);
+                    write_cf_rule_head(buf, "endsWith", dxf_id, format.priority);
+                    write_text_cf_rule_body(buf, "endsWith", text, &formula);
+                }
+                ConditionalRule::DuplicateValues => {
+                    write_cf_rule_head(buf, "duplicateValues", dxf_id, format.priority);
+                    buf.extend_from_slice(b"/>");
+                }
+                ConditionalRule::UniqueValues => {
+                    write_cf_rule_head(buf, "uniqueValues", dxf_id, format.priority);
+                    buf.extend_from_slice(b"/>");
+                }
+                ConditionalRule::ContainsBlanks => {
+                    write_cf_rule_head(buf, "containsBlanks", dxf_id, format.priority);
+                    buf.extend_from_slice(b"><formula>LEN(TRIM(");
+                    buf.extend_from_slice(top_left.as_bytes());
+                    buf.extend_from_slice(b"))=0</formula></cfRule>");
+                }
+                ConditionalRule::TimePeriod { period } => {
+                    let (name, formula) = time_period_formula(period, &top_left);
+                    write_cf_rule_head(buf, "timePeriod", dxf_id, format.priority);
+                    buf.extend_from_slice(b" timePeriod=\"");
+                    buf.extend_from_slice(name.as_bytes());
+                    buf.extend_from_slice(b"\"><formula>");
+                    xml_escape_simd(formula.as_bytes(), buf);
+                    buf.extend_from_slice(b"</formula></cfRule>");
+                }
+            }
+        }
+        
+        buf.extend_from_slice(b"</conditionalFormatting>");
+    }
+}
+
+const X14_NS: &str = "http://schemas.microsoft.com/office/spreadsheetml/2009/9/main";
+
+/// Stable per-sheet id pairing a `dataBar` rule with its `x14:cfRule`
+fn write_data_bar_guid(buf: &mut Vec<u8>, idx: usize) {
+    buf.extend_from_slice(format!("{{DA7ABA51-AAAA-BBBB-0001-{:012X}}}", idx).as_bytes());
+}
+
+/// 2007 `<cfvo>`; the Excel 2010 automatic types fall back to min/max
+fn write_cfvo(buf: &mut Vec<u8>, cfvo: &Cfvo) {
+    let kind = match cfvo.kind {
+        CfvoType::Min | CfvoType::AutoMin => "min",
+        CfvoType::Max | CfvoType::AutoMax => "max",
+        CfvoType::Num => "num",
+        CfvoType::Percent => "percent",
+        CfvoType::Percentile => "percentile",
+        CfvoType::Formula => "formula",
+    };
+    buf.extend_from_slice(b"<cfvo type=\"");
+    buf.extend_from_slice(kind.as_bytes());
+    buf.push(b'"');
+    if let (Some(value), "num" | "percent" | "percentile" | "formula") = (&cfvo.value, kind) {
+        buf.extend_from_slice(b" val=\"");
+        xml_escape_simd(value.as_bytes(), buf);
+        buf.push(b'"');
+    }
+    buf.extend_from_slice(b"/>");
+}
+
+fn write_x14_cfvo(buf: &mut Vec<u8>, cfvo: &Cfvo) {
+    let kind = match cfvo.kind {
+        CfvoType::Min => "min",
+        CfvoType::Max => "max",
+        CfvoType::AutoMin => "autoMin",
+        CfvoType::AutoMax => "autoMax",
+        CfvoType::Num => "num",
+        CfvoType::Percent => "percent",
+        CfvoType::Percentile => "percentile",
+        CfvoType::Formula => "formula",
+    };
+    buf.extend_from_slice(b"<x14:cfvo type=\"");
+    buf.extend_from_slice(kind.as_bytes());
+    buf.push(b'"');
+    match &cfvo.value {
+        Some(value) if !matches!(cfvo.kind, CfvoType::Min | CfvoType::Max | CfvoType::AutoMin | CfvoType::AutoMax) => {
+            buf.extend_from_slice(b"><xm:f>");
+            xml_escape_simd(value.as_bytes(), buf);
+            buf.extend_from_slice(b"</xm:f></x14:cfvo>");
+        }
+        _ => buf.extend_from_slice(b"/>"),
+    }
+}
+
+fn write_x14_color(buf: &mut Vec<u8>, tag: &[u8], rgb: &str) {
+    buf.extend_from_slice(b"<x14:");
+    buf.extend_from_slice(tag);
+    buf.extend_from_slice(b" rgb=\"");
+    buf.extend_from_slice(rgb.as_bytes());
+    buf.extend_from_slice(b"\"/>");
+}
+
+/// Worksheet `extLst` holding the Excel 2010 part of every data bar:
+/// borders, solid fill, negative colors and axis settings.
+fn write_x14_data_bars(buf: &mut Vec<u8>, formats: &[ConditionalFormat]) {
+    buf.extend_from_slice(b"<extLst><ext uri=\"{78C0D931-6437-407d-A8EE-F0AAD7539E65}\" xmlns:x14=\"");
+    buf.extend_from_slice(X14_NS.as_bytes());
+    buf.extend_from_slice(b"\"><x14:conditionalFormattings>");
+
+    for (idx, format) in formats.iter().enumerate() {
+        let ConditionalRule::DataBar {
+            min,
+            max,
+            border_color,
+            negative_color,
+            negative_border_color,
+            gradient,
+            axis_color,
+            axis_position,
+            ..
+        } = &format.rule else {
+            continue;
+        };
+
+        buf.extend_from_slice(b"<x14:conditionalFormatting xmlns:xm=\"http://schemas.microsoft.com/office/excel/2006/main\"><x14:cfRule type=\"dataBar\" id=\"");
+        write_data_bar_guid(buf, idx);
+        buf.extend_from_slice(b"\"><x14:dataBar minLength=\"0\" maxLength=\"100\"");
+        if border_color.is_some() {
+            buf.extend_from_slice(b" border=\"1\"");
+        }
+        if !gradient {
+            buf.extend_from_slice(b" gradient=\"0\"");
+        }
+        if border_color.is_some() && negative_border_color.is_some() {
+            buf.extend_from_slice(b" negativeBarBorderColorSameAsPositive=\"0\"");
+        }
+        match axis_position {
+            DataBarAxisPosition::Automatic => {}
+            DataBarAxisPosition::Middle => buf.extend_from_slice(b" axisPosition=\"middle\""),
+            DataBarAxisPosition::None => buf.extend_from_slice(b" axisPosition=\"none\""),
+        }
+        buf.push(b'>');
+
+        write_x14_cfvo(buf, min);
+        write_x14_cfvo(buf, max);
+        if let Some(border) = border_color {
+            write_x14_color(buf, b"borderColor", border);
+        }
+        write_x14_color(buf, b"negativeFillColor", negative_color.as_deref().unwrap_or("FFFF0000"));
+        if let (Some(_), Some(negative_border)) = (border_color, negative_border_color) {
+            write_x14_color(buf, b"negativeBorderColor", negative_border);
+        }
+        if *axis_position != DataBarAxisPosition::None {
+            write_x14_color(buf, b"axisColor", axis_color.as_deref().unwrap_or("FF000000"));
+        }
+
+        buf.extend_from_slice(b"</x14:dataBar></x14:cfRule><xm:sqref>");
+        write_cell_ref(format.start_col, format.start_row, buf);
+        buf.push(b':');
+        write_cell_ref(format.end_col, format.end_row, buf);
+        buf.extend_from_slice(b"</xm:sqref></x14:conditionalFormatting>");
+    }
+
+    buf.extend_from_slice(b"</x14:conditionalFormattings></ext></extLst>");
+}
+
+/// Write the rule type, optional dxfId and priority of a `<cfRule>`, leaving
+/// the start tag open.
+fn write_cf_rule_head(buf: &mut Vec<u8>, rule_type: &str, dxf_id: Option<u32>, priority: u32) {
+    buf.extend_from_slice(rule_type.as_bytes());
+    if let Some(dxf_id) = dxf_id {
+        buf.extend_from_slice(b"\" dxfId=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
+    }
+    buf.extend_from_slice(b"\" priority=\"");
+    buf.extend_from_slice(itoa::Buffer::new().format(priority).as_bytes());
+    buf.push(b'"');
+}
+
+fn write_text_cf_rule_body(buf: &mut Vec<u8>, operator: &str, text: &str, formula: &str) {
+    buf.extend_from_slice(b" operator=\"");
+    buf.extend_from_slice(operator.as_bytes());
+    buf.extend_from_slice(b"\" text=\"");
+    xml_escape_simd(text.as_bytes(), buf);
+    buf.extend_from_slice(b"\"><formula>");
+    xml_escape_simd(formula.as_bytes(), buf);
+    buf.extend_from_slice(b"</formula></cfRule>");
+}
+
+/// timePeriod attribute value and the formula Excel generates for it
+fn time_period_formula(period: &TimePeriod, cell: &str) -> (&'static str, String) {
+    match period {
+        TimePeriod::Yesterday => ("yesterday", format!("FLOOR({},1)=TODAY()-1", cell)),
+        TimePeriod::Today => ("today", format!("FLOOR({},1)=TODAY()", cell)),
+        TimePeriod::Tomorrow => ("tomorrow", format!("FLOOR({},1)=TODAY()+1", cell)),
+        TimePeriod::Last7Days => ("last7Days", format!(
+            "AND(TODAY()-FLOOR({0},1)<=6,FLOOR({0},1)<=TODAY())", cell
+        )),
+        TimePeriod::LastWeek => ("lastWeek", format!(
+            "AND(TODAY()-ROUNDDOWN({0},0)>=(WEEKDAY(TODAY())),TODAY()-ROUNDDOWN({0},0)<(WEEKDAY(TODAY())+7))", cell
+        )),
+        TimePeriod::ThisWeek => ("thisWeek", format!(
+            "AND(TODAY()-ROUNDDOWN({0},0)<=WEEKDAY(TODAY())-1,ROUNDDOWN({0},0)-TODAY()<=7-WEEKDAY(TODAY()))", cell
+        )),
+        TimePeriod::NextWeek => ("nextWeek", format!(
+            "AND(ROUNDDOWN({0},0)-TODAY()>(7-WEEKDAY(TODAY())),ROUNDDOWN({0},0)-TODAY()<(15-WEEKDAY(TODAY())))", cell
+        )),
+        TimePeriod::LastMonth => ("lastMonth", format!(
+            "AND(MONTH({0})=MONTH(EDATE(TODAY(),0-1)),YEAR({0})=YEAR(EDATE(TODAY(),0-1)))", cell
+        )),
+        TimePeriod::ThisMonth => ("thisMonth", format!(
+            "AND(MONTH({0})=MONTH(TODAY()),YEAR({0})=YEAR(TODAY()))", cell
+        )),
+        TimePeriod::NextMonth => ("nextMonth", format!(
+            "AND(MONTH({0})=MONTH(EDATE(TODAY(),0+1)),YEAR({0})=YEAR(EDATE(TODAY(),0+1)))", cell
+        )),
+    }
+}
+
+/// Quote text as a formula string literal, doubling embedded quotes
+fn quote_formula_text(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Cell of a shared formula block. Only the top-left (master) cell carries the
+/// formula text and the block's range; the others just point at it by `si`.
+fn write_shared_formula_cell(
+    sf: &SharedFormula,
+    si: usize,
+    master: bool,
+    cell_ref: &[u8],
+    style_id: Option<u32>,
+    buf: &mut Vec<u8>,
+) {
+    buf.extend_from_slice(b"<c r=\"");
+    buf.extend_from_slice(cell_ref);
+    if let Some(sid) = style_id {
+        buf.extend_from_slice(b"\" s=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+    }
+    buf.extend_from_slice(b"\"><f t=\"shared\"");
+    if master {
+        buf.extend_from_slice(b" ref=\"");
+        write_cell_ref(sf.start_col, sf.start_row, buf);
+        buf.push(b':');
+        write_cell_ref(sf.end_col, sf.end_row, buf);
+        buf.push(b'"');
+    }
+    buf.extend_from_slice(b" si=\"");
+    buf.extend_from_slice(itoa::Buffer::new().format(si).as_bytes());
+    if master {
+        buf.extend_from_slice(b"\">");
+        xml_escape_simd(sf.formula.as_bytes(), buf);
+        buf.extend_from_slice(b"</f></c>");
+    } else {
+        buf.extend_from_slice(b"\"/></c>");
+    }
+}
+
+/// `<c>` with only a style, for range-styled cells that hold nothing
+fn write_empty_styled_cell(row: usize, col: usize, style_id: u32, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(b"<c r=\"");
+    write_cell_ref(col, row, buf);
+    buf.extend_from_slice(b"\" s=\"");
+    buf.extend_from_slice(itoa::Buffer::new().format(style_id).as_bytes());
+    buf.extend_from_slice(b"\"/>");
+}
+
+/// Formula cell from a `column_formulas` template, with the row number
+/// written between the parts the template was split into at `{row}`
+fn write_column_formula_cell(
+    parts: &[&str],
+    row_bytes: &[u8],
+    cell_ref: &[u8],
+    style_id: Option<u32>,
+    buf: &mut Vec<u8>,
+) {
+    buf.extend_from_slice(b"<c r=\"");
+    buf.extend_from_slice(cell_ref);
+    if let Some(sid) = style_id {
+        buf.extend_from_slice(b"\" s=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+    }
+    buf.extend_from_slice(b"\"><f>");
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            buf.extend_from_slice(row_bytes);
         }
-        
-        buf.extend_from_slice(b"</dataValidations>");
+        xml_escape_simd(part.as_bytes(), buf);
     }
+    buf.extend_from_slice(b"</f></c>");
+}
 
-    // Hyperlinks
-    if !config.hyperlinks.is_empty() {
-        buf.extend_from_slice(b"<hyperlinks>");
-        
-        for (idx, hyperlink) in config.hyperlinks.iter().enumerate() {
-            buf.extend_from_slice(b"<hyperlink ref=\"");
-            write_cell_ref(hyperlink.col, hyperlink.row, &mut buf);
-            buf.extend_from_slice(b"\" r:id=\"rId");
-            buf.extend_from_slice(itoa::Buffer::new().format(idx + 1).as_bytes());
-            buf.extend_from_slice(b"\"/>");
+/// Column name inside a structured reference: `[`, `]`, `#` and `'` are
+/// escaped with a leading `'`
+fn structured_ref_column(name: &str) -> Cow<'_, str> {
+    if !name.contains(['[', ']', '#', '\'']) {
+        return Cow::Borrowed(name);
+    }
+    let mut escaped = String::with_capacity(name.len() + 4);
+    for ch in name.chars() {
+        if matches!(ch, '[' | ']' | '#' | '\'') {
+            escaped.push('\'');
         }
-        
-        buf.extend_from_slice(b"</hyperlinks>");
+        escaped.push(ch);
     }
+    Cow::Owned(escaped)
+}
 
-    // Drawing (for charts and images)
-    if !config.charts.is_empty() || !config.images.is_empty() {
-        buf.extend_from_slice(b"<drawing r:id=\"rIdDraw1\"/>");
+/// Inline string cell made of runs that each carry their own font
+fn write_rich_text_cell(
+    runs: &[TextRun],
+    cell_ref: &[u8],
+    style_id: Option<u32>,
+    invalid_text: InvalidText,
+    buf: &mut Vec<u8>,
+) -> Result<(), WriteError> {
+    buf.extend_from_slice(b"<c r=\"");
+    buf.extend_from_slice(cell_ref);
+    if let Some(sid) = style_id {
+        buf.extend_from_slice(b"\" s=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
     }
-
-    // TableParts (MUST be after drawing)
-    if !config.tables.is_empty() {
-        buf.extend_from_slice(b"<tableParts count=\"");
-        buf.extend_from_slice(itoa::Buffer::new().format(config.tables.len()).as_bytes());
-        buf.extend_from_slice(b"\">");
-        
-        for idx in 0..config.tables.len() {
-            buf.extend_from_slice(b"<tablePart r:id=\"rIdTable");
-            buf.extend_from_slice(itoa::Buffer::new().format(idx + 1).as_bytes());
-            buf.extend_from_slice(b"\"/>");
+    buf.extend_from_slice(b"\" t=\"inlineStr\"><is>");
+    for run in runs {
+        buf.extend_from_slice(b"<r>");
+        if let Some(font) = &run.font {
+            write_run_font(font, buf);
         }
-        
-        buf.extend_from_slice(b"</tableParts>");
+        // Spaces at run boundaries are part of the text
+        buf.extend_from_slice(b"<t xml:space=\"preserve\">");
+        write_cell_text(run.text.as_bytes(), cell_ref, Some(invalid_text), buf)?;
+        buf.extend_from_slice(b"</t></r>");
     }
-
-    buf.extend_from_slice(b"</worksheet>");
-    
-    Ok(buf)
+    buf.extend_from_slice(b"</is></c>");
+    Ok(())
 }
 
-
-/// Write conditional formatting section
-fn write_conditional_formatting(buf: &mut Vec<u8>, formats: &[ConditionalFormat], config: &StyleConfig) {
-    for (idx, format) in formats.iter().enumerate() {
-        buf.extend_from_slice(b"<conditionalFormatting sqref=\"");
-        write_cell_ref(format.start_col, format.start_row, buf);
-        buf.push(b':');
-        write_cell_ref(format.end_col, format.end_row, buf);
-        buf.extend_from_slice(b"\">");
-        
-        buf.extend_from_slice(b"<cfRule type=\"");
-        
-        match &format.rule {
-            ConditionalRule::CellValue { operator, value } => {
-                // Get DXF ID from the properly built map
-                if let Some(&dxf_id) = config.cond_format_dxf_ids.get(&idx) {
-                    buf.extend_from_slice(b"cellIs\" dxfId=\"");
-                    buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
-                    buf.extend_from_slice(b"\" operator=\"");
-                } else {
-                    buf.extend_from_slice(b"cellIs\" operator=\"");
-                }
-                let op_str = match operator {
-                    ComparisonOperator::GreaterThan => "greaterThan",
-                    ComparisonOperator::LessThan => "lessThan",
-                    ComparisonOperator::Equal => "equal",
-                    ComparisonOperator::NotEqual => "notEqual",
-                    ComparisonOperator::GreaterThanOrEqual => "greaterThanOrEqual",
-                    ComparisonOperator::LessThanOrEqual => "lessThanOrEqual",
-                    ComparisonOperator::Between => "between",
-                };
-                buf.extend_from_slice(op_str.as_bytes());
-                buf.extend_from_slice(b"\" priority=\"");
-                buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
-                buf.extend_from_slice(b"\"><formula>");
-                xml_escape_simd(value.as_bytes(), buf);
-                buf.extend_from_slice(b"</formula></cfRule>");
-            }
-            ConditionalRule::ColorScale { min_color, max_color, mid_color } => {
-                buf.extend_from_slice(b"colorScale\" priority=\"");
-                buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
-                buf.extend_from_slice(b"\"><colorScale><cfvo type=\"min\"/>");
-                if mid_color.is_some() {
-                    buf.extend_from_slice(b"<cfvo type=\"percentile\" val=\"50\"/>");
-                }
-                buf.extend_from_slice(b"<cfvo type=\"max\"/>");
-                buf.extend_from_slice(b"<color rgb=\"");
-                buf.extend_from_slice(min_color.as_bytes());
-                buf.extend_from_slice(b"\"/>");
-                if let Some(mid) = mid_color {
-                    buf.extend_from_slice(b"<color rgb=\"");
-                    buf.extend_from_slice(mid.as_bytes());
-                    buf.extend_from_slice(b"\"/>");
-                }
-                buf.extend_from_slice(b"<color rgb=\"");
-                buf.extend_from_slice(max_color.as_bytes());
-                buf.extend_from_slice(b"\"/></colorScale></cfRule>");
-            }
-            ConditionalRule::DataBar { color, show_value } => {
-                buf.extend_from_slice(b"dataBar\" priority=\"");
-                buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
-                buf.extend_from_slice(b"\"><dataBar><cfvo type=\"min\"/><cfvo type=\"max\"/><color rgb=\"");
-                buf.extend_from_slice(color.as_bytes());
-                buf.extend_from_slice(b"\"/>");
-                if !show_value {
-                    buf.extend_from_slice(b"<showValue val=\"0\"/>");
-                }
-                buf.extend_from_slice(b"</dataBar></cfRule>");
-            }
-            ConditionalRule::Top10 { rank, bottom } => {
-                if let Some(&dxf_id) = config.cond_format_dxf_ids.get(&idx) {
-                    buf.extend_from_slice(b"top10\" dxfId=\"");
-                    buf.extend_from_slice(itoa::Buffer::new().format(dxf_id).as_bytes());
-                    buf.extend_from_slice(b"\" priority=\"");
-                } else {
-                    buf.extend_from_slice(b"top10\" priority=\"");
-                }
-                buf.extend_from_slice(itoa::Buffer::new().format(format.priority).as_bytes());
-                buf.extend_from_slice(b"\" rank=\"");
-                buf.extend_from_slice(itoa::Buffer::new().format(*rank).as_bytes());
-                if *bottom {
-                    buf.extend_from_slice(b"\" bottom=\"1\"/>");
-                } else {
-                    buf.extend_from_slice(b"\"/>");
-                }
-            }
-        }
-        
-        buf.extend_from_slice(b"</conditionalFormatting>");
+/// `<rPr>` of a rich text run
+fn write_run_font(font: &FontStyle, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(b"<rPr>");
+    if let Some(name) = &font.name {
+        buf.extend_from_slice(b"<rFont val=\"");
+        xml_escape_simd(name.as_bytes(), buf);
+        buf.extend_from_slice(b"\"/>");
+    }
+    if font.bold {
+        buf.extend_from_slice(b"<b/>");
+    }
+    if font.italic {
+        buf.extend_from_slice(b"<i/>");
+    }
+    if font.strike {
+        buf.extend_from_slice(b"<strike/>");
+    }
+    if let Some(color) = &font.color {
+        buf.extend_from_slice(b"<color");
+        buf.extend_from_slice(color.xml_attrs().as_bytes());
+        buf.extend_from_slice(b"/>");
     }
+    if let Some(size) = font.size {
+        buf.extend_from_slice(b"<sz val=\"");
+        buf.extend_from_slice(ryu::Buffer::new().format(size).as_bytes());
+        buf.extend_from_slice(b"\"/>");
+    }
+    if let Some(underline) = font.underline {
+        buf.extend_from_slice(underline.xml().as_bytes());
+    }
+    if let Some(vert_align) = font.vert_align {
+        buf.extend_from_slice(vert_align.xml().as_bytes());
+    }
+    buf.extend_from_slice(b"</rPr>");
 }
 
 /// Write a single Arrow cell with formula and hyperlink support
@@ -2359,6 +4630,9 @@ fn write_arrow_cell_to_xml_optimized(
     style_id: Option<u32>,
     hyperlink: Option<&&Hyperlink>,
     formula: Option<&&Formula>,
+    timezone: Option<&Tz>,
+    config: &StyleConfig,
+    invalid_text: Option<InvalidText>,
     buf: &mut Vec<u8>,
     ryu_buf: &mut ryu::Buffer,
     int_buf: &mut itoa::Buffer,
@@ -2391,7 +4665,11 @@ fn write_arrow_cell_to_xml_optimized(
         
         buf.extend_from_slice(b"<c r=\"");
         buf.extend_from_slice(cell_ref);
-        buf.extend_from_slice(b"\" s=\"9\" t=\"inlineStr\"><is><t>");
+        if let Some(sid) = style_id {
+            buf.extend_from_slice(b"\" s=\"");
+            buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+        }
+        buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
         xml_escape_simd(display_text.as_bytes(), buf);
         buf.extend_from_slice(b"</t></is></c>");
         return Ok(());
@@ -2430,7 +4708,7 @@ fn write_arrow_cell_to_xml_optimized(
                 buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
             }
             buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-            xml_escape_simd(str_bytes, buf);
+            write_cell_text(str_bytes, cell_ref, invalid_text, buf)?;
             buf.extend_from_slice(b"</t></is></c>");
         }
         DataType::LargeUtf8 => {
@@ -2454,7 +4732,7 @@ fn write_arrow_cell_to_xml_optimized(
                 buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
             }
             buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-            xml_escape_simd(str_bytes, buf);
+            write_cell_text(str_bytes, cell_ref, invalid_text, buf)?;
             buf.extend_from_slice(b"</t></is></c>");
         }
         DataType::Int8 => {
@@ -2489,6 +4767,10 @@ fn write_arrow_cell_to_xml_optimized(
             let arr = array.as_any().downcast_ref::<UInt64Array>().unwrap();
             write_number_cell_int(arr.value(row_idx) as i64, cell_ref, style_id, buf, int_buf);
         }
+        DataType::Float16 => {
+            let arr = array.as_any().downcast_ref::<Float16Array>().unwrap();
+            write_number_cell(arr.value(row_idx).to_f64(), cell_ref, style_id, buf, ryu_buf, int_buf);
+        }
         DataType::Float32 => {
             let arr = array.as_any().downcast_ref::<Float32Array>().unwrap();
             write_number_cell(arr.value(row_idx) as f64, cell_ref, style_id, buf, ryu_buf, int_buf);
@@ -2497,6 +4779,14 @@ fn write_arrow_cell_to_xml_optimized(
             let arr = array.as_any().downcast_ref::<Float64Array>().unwrap();
             write_number_cell(arr.value(row_idx), cell_ref, style_id, buf, ryu_buf, int_buf);
         }
+        DataType::Decimal128(_, _) => {
+            let arr = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
+            write_decimal_cell(&arr.value_as_string(row_idx), cell_ref, style_id, buf);
+        }
+        DataType::Decimal256(_, _) => {
+            let arr = array.as_any().downcast_ref::<Decimal256Array>().unwrap();
+            write_decimal_cell(&arr.value_as_string(row_idx), cell_ref, style_id, buf);
+        }
         DataType::Boolean => {
             let arr = array.as_any().downcast_ref::<BooleanArray>().unwrap();
             buf.extend_from_slice(b"<c r=\"");
@@ -2512,19 +4802,27 @@ fn write_arrow_cell_to_xml_optimized(
         DataType::Date32 => {
             let arr = array.as_any().downcast_ref::<Date32Array>().unwrap();
             let days = arr.value(row_idx);
+            if let Some(serial) = unix_to_excel_serial(days as i64 * 86400, config.date_1904) {
+                write_serial_cell(serial, cell_ref, style_id, buf, ryu_buf);
+                return Ok(());
+            }
             let date = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
                 .ok_or_else(|| WriteError::Validation("Invalid base date".to_string()))?
                 .checked_add_signed(chrono::Duration::days(days as i64))
                 .ok_or_else(|| WriteError::Validation("Date out of range".to_string()))?;
             let dt = date.and_hms_opt(0, 0, 0).unwrap();
-            write_date_cell(&dt, cell_ref, style_id.or(Some(10)), buf, ryu_buf);
+            write_date_cell(&dt, cell_ref, style_id, config.pre_1900_dates, config.date_1904, buf, ryu_buf)?;
         }
         DataType::Date64 => {
             let arr = array.as_any().downcast_ref::<Date64Array>().unwrap();
             let millis = arr.value(row_idx);
+            if let Some(serial) = unix_to_excel_serial(millis.div_euclid(1000), config.date_1904) {
+                write_serial_cell(serial, cell_ref, style_id, buf, ryu_buf);
+                return Ok(());
+            }
             let datetime = chrono::DateTime::from_timestamp_millis(millis)
                 .ok_or_else(|| WriteError::Validation("Invalid timestamp".to_string()))?;
-            write_date_cell(&datetime.naive_utc(), cell_ref, style_id.or(Some(10)), buf, ryu_buf); // Date-only format
+            write_date_cell(&datetime.naive_utc(), cell_ref, style_id, config.pre_1900_dates, config.date_1904, buf, ryu_buf)?;
         }
        DataType::Time32(unit) => {
             use arrow_schema::TimeUnit;
@@ -2560,39 +4858,94 @@ fn write_arrow_cell_to_xml_optimized(
         }
         DataType::Timestamp(unit, _) => {
             use arrow_schema::TimeUnit;
-            let dt = match unit {
-                TimeUnit::Second => {
-                    let arr = array.as_any().downcast_ref::<TimestampSecondArray>().unwrap();
-                    let value = arr.value(row_idx);
-                    chrono::DateTime::from_timestamp(value, 0)
-                        .ok_or_else(|| WriteError::Validation("Invalid timestamp".to_string()))?
-                        .naive_utc()
-                }
-                TimeUnit::Millisecond => {
-                    let arr = array.as_any().downcast_ref::<TimestampMillisecondArray>().unwrap();
-                    let value = arr.value(row_idx);
-                    chrono::DateTime::from_timestamp_millis(value)
-                        .ok_or_else(|| WriteError::Validation("Invalid timestamp".to_string()))?
-                        .naive_utc()
-                }
-                TimeUnit::Microsecond => {
-                    let arr = array.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap();
-                    let value = arr.value(row_idx);
-                    chrono::DateTime::from_timestamp_micros(value)
-                        .ok_or_else(|| WriteError::Validation("Invalid timestamp".to_string()))?
-                        .naive_utc()
-                }
-                TimeUnit::Nanosecond => {
-                    let arr = array.as_any().downcast_ref::<TimestampNanosecondArray>().unwrap();
-                    let value = arr.value(row_idx);
-                    let secs = value / 1_000_000_000;
-                    let nsecs = (value % 1_000_000_000) as u32;
-                    chrono::DateTime::from_timestamp(secs, nsecs)
+            let (value, per_sec) = match unit {
+                TimeUnit::Second => (array.as_any().downcast_ref::<TimestampSecondArray>().unwrap().value(row_idx), 1),
+                TimeUnit::Millisecond => (array.as_any().downcast_ref::<TimestampMillisecondArray>().unwrap().value(row_idx), 1_000),
+                TimeUnit::Microsecond => (array.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap().value(row_idx), 1_000_000),
+                TimeUnit::Nanosecond => (array.as_any().downcast_ref::<TimestampNanosecondArray>().unwrap().value(row_idx), 1_000_000_000),
+            };
+            let secs = value.div_euclid(per_sec);
+            match (timezone, unix_to_excel_serial(secs, config.date_1904)) {
+                (None, Some(serial)) => write_serial_cell(serial, cell_ref, style_id, buf, ryu_buf),
+                _ => {
+                    let nanos = (value.rem_euclid(per_sec) * (1_000_000_000 / per_sec)) as u32;
+                    let dt = chrono::DateTime::from_timestamp(secs, nanos)
                         .ok_or_else(|| WriteError::Validation("Invalid timestamp".to_string()))?
-                        .naive_utc()
+                        .naive_utc();
+                    let dt = match timezone {
+                        Some(tz) => tz.from_utc_datetime(&dt).naive_local(),
+                        None => dt,
+                    };
+                    write_date_cell(&dt, cell_ref, style_id, config.pre_1900_dates, config.date_1904, buf, ryu_buf)?;
                 }
+            }
+        }
+        DataType::Duration(unit) => {
+            use arrow_schema::TimeUnit;
+            let (value, per_sec) = match unit {
+                TimeUnit::Second => (array.as_any().downcast_ref::<DurationSecondArray>().unwrap().value(row_idx), 1.0),
+                TimeUnit::Millisecond => (array.as_any().downcast_ref::<DurationMillisecondArray>().unwrap().value(row_idx), 1e3),
+                TimeUnit::Microsecond => (array.as_any().downcast_ref::<DurationMicrosecondArray>().unwrap().value(row_idx), 1e6),
+                TimeUnit::Nanosecond => (array.as_any().downcast_ref::<DurationNanosecondArray>().unwrap().value(row_idx), 1e9),
+            };
+            // Days, like Excel times; the default [h]:mm:ss format runs past 24 hours
+            write_number_cell(value as f64 / per_sec / 86400.0, cell_ref, style_id, buf, ryu_buf, int_buf);
+        }
+        DataType::Interval(_) => {
+            // Months and days have no fixed length, so intervals are written as text
+            use arrow::util::display::{ArrayFormatter, FormatOptions};
+            let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())
+                .map_err(|e| WriteError::Validation(e.to_string()))?;
+            let text = formatter.value(row_idx).to_string();
+            if text.is_empty() && style_id.is_none() {
+                return Ok(());
+            }
+            buf.extend_from_slice(b"<c r=\"");
+            buf.extend_from_slice(cell_ref);
+            if let Some(sid) = style_id {
+                buf.extend_from_slice(b"\" s=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+            }
+            buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            xml_escape_simd(text.as_bytes(), buf);
+            buf.extend_from_slice(b"</t></is></c>");
+        }
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView | DataType::FixedSizeBinary(_) => {
+            use arrow_array::cast::AsArray;
+            let bytes = match array.data_type() {
+                DataType::Binary => array.as_binary::<i32>().value(row_idx),
+                DataType::LargeBinary => array.as_binary::<i64>().value(row_idx),
+                DataType::BinaryView => array.as_binary_view().value(row_idx),
+                _ => array.as_fixed_size_binary().value(row_idx),
             };
-            write_date_cell(&dt, cell_ref, style_id.or(Some(1)), buf, ryu_buf);
+            if bytes.is_empty() && style_id.is_none() {
+                return Ok(());
+            }
+            buf.extend_from_slice(b"<c r=\"");
+            buf.extend_from_slice(cell_ref);
+            if let Some(sid) = style_id {
+                buf.extend_from_slice(b"\" s=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+            }
+            buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            // Hex and base64 never need escaping
+            buf.extend_from_slice(binary_cell_text(bytes, config.binary_encoding, config.binary_max_length).as_bytes());
+            buf.extend_from_slice(b"</t></is></c>");
+        }
+        DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _) => {
+            let text = list_cell_text(array, row_idx, &config.list_separator)?;
+            if text.is_empty() && style_id.is_none() {
+                return Ok(());
+            }
+            buf.extend_from_slice(b"<c r=\"");
+            buf.extend_from_slice(cell_ref);
+            if let Some(sid) = style_id {
+                buf.extend_from_slice(b"\" s=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+            }
+            buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            write_cell_text(text.as_bytes(), cell_ref, Some(config.invalid_text), buf)?;
+            buf.extend_from_slice(b"</t></is></c>");
         }
         _ => {
             buf.extend_from_slice(b"<c r=\"");
@@ -2608,6 +4961,70 @@ fn write_arrow_cell_to_xml_optimized(
     Ok(())
 }
 
+/// Text of a binary cell, cut to `max_length` characters plus "…". Only the
+/// bytes needed for the cut are encoded.
+fn binary_cell_text(bytes: &[u8], encoding: BinaryEncoding, max_length: Option<usize>) -> String {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let needed = match (encoding, max_length) {
+        (_, None) => bytes.len(),
+        (BinaryEncoding::Hex, Some(max)) => max / 2 + 1,
+        (BinaryEncoding::Base64, Some(max)) => (max / 4 + 1) * 3,
+    };
+    let mut text = match encoding {
+        BinaryEncoding::Hex => bytes[..needed.min(bytes.len())].iter().map(|b| format!("{:02x}", b)).collect(),
+        BinaryEncoding::Base64 => STANDARD.encode(&bytes[..needed.min(bytes.len())]),
+    };
+    if let Some(max) = max_length.filter(|&max| text.len() > max) {
+        text.truncate(max);
+        text.push('…');
+    }
+    text
+}
+
+/// Text of a list cell: its non-null elements joined with `separator`.
+/// Nested lists and other element types use Arrow's display format.
+fn list_cell_text(array: &dyn Array, row_idx: usize, separator: &str) -> Result<String, WriteError> {
+    use arrow::util::display::{ArrayFormatter, FormatOptions};
+    use arrow_array::cast::AsArray;
+    use std::fmt::Write as _;
+
+    let values = match array.data_type() {
+        DataType::List(_) => array.as_list::<i32>().value(row_idx),
+        DataType::LargeList(_) => array.as_list::<i64>().value(row_idx),
+        _ => array.as_fixed_size_list().value(row_idx),
+    };
+    let formatter = ArrayFormatter::try_new(values.as_ref(), &FormatOptions::default())
+        .map_err(|e| WriteError::Validation(e.to_string()))?;
+    let mut text = String::new();
+    for (i, idx) in (0..values.len()).filter(|&idx| values.is_valid(idx)).enumerate() {
+        if i > 0 {
+            text.push_str(separator);
+        }
+        let _ = write!(text, "{}", formatter.value(idx));
+    }
+    Ok(text)
+}
+
+/// Write a decimal in its exact text form; Excel parses it like any number
+#[inline(always)]
+fn write_decimal_cell(
+    text: &str,
+    cell_ref: &[u8],
+    style_id: Option<u32>,
+    buf: &mut Vec<u8>,
+) {
+    buf.extend_from_slice(b"<c r=\"");
+    buf.extend_from_slice(cell_ref);
+    if let Some(sid) = style_id {
+        buf.extend_from_slice(b"\" s=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+    }
+    buf.extend_from_slice(b"\"><v>");
+    buf.extend_from_slice(text.as_bytes());
+    buf.extend_from_slice(b"</v></c>");
+}
+
 #[inline(always)]
 fn write_number_cell_int(
     n: i64,
@@ -2672,15 +5089,61 @@ fn write_date_cell(
     dt: &chrono::NaiveDateTime,
     cell_ref: &[u8],
     style_id: Option<u32>,
+    pre_1900_dates: Pre1900Dates,
+    date_1904: bool,
+    buf: &mut Vec<u8>,
+    ryu_buf: &mut ryu::Buffer,
+) -> Result<(), WriteError> {
+    let serial = match (datetime_to_excel_serial(dt, date_1904), pre_1900_dates) {
+        (Some(serial), _) => serial,
+        (None, Pre1900Dates::Clamp) => if date_1904 { 0.0 } else { 1.0 },
+        (None, Pre1900Dates::Error) => {
+            return Err(WriteError::Validation(format!(
+                "Date {} is before {}, the earliest date Excel can store",
+                dt, first_serial_date(date_1904)
+            )));
+        }
+        (None, Pre1900Dates::Text) => {
+            let text = if dt.time() == chrono::NaiveTime::MIN {
+                dt.format("%Y-%m-%d").to_string()
+            } else {
+                dt.format("%Y-%m-%dT%H:%M:%S").to_string()
+            };
+            buf.extend_from_slice(b"<c r=\"");
+            buf.extend_from_slice(cell_ref);
+            if let Some(sid) = style_id {
+                buf.extend_from_slice(b"\" s=\"");
+                buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+            }
+            buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+            buf.extend_from_slice(text.as_bytes());
+            buf.extend_from_slice(b"</t></is></c>");
+            return Ok(());
+        }
+    };
+
+    write_serial_cell(serial, cell_ref, style_id, buf, ryu_buf);
+    Ok(())
+}
+
+/// Write a date serial as a numeric cell
+#[inline(always)]
+fn write_serial_cell(
+    serial: f64,
+    cell_ref: &[u8],
+    style_id: Option<u32>,
     buf: &mut Vec<u8>,
     ryu_buf: &mut ryu::Buffer,
 ) {
     buf.extend_from_slice(b"<c r=\"");
     buf.extend_from_slice(cell_ref);
-    buf.extend_from_slice(b"\" s=\"");
-    buf.extend_from_slice(itoa::Buffer::new().format(style_id.unwrap_or(1)).as_bytes());
+    // Date columns get their default format through the column format map
+    if let Some(sid) = style_id {
+        buf.extend_from_slice(b"\" s=\"");
+        buf.extend_from_slice(itoa::Buffer::new().format(sid).as_bytes());
+    }
     buf.extend_from_slice(b"\"><v>");
-    buf.extend_from_slice(ryu_buf.format(datetime_to_excel_serial(dt)).as_bytes());
+    buf.extend_from_slice(ryu_buf.format(serial).as_bytes());
     buf.extend_from_slice(b"</v></c>");
 }
 
@@ -2705,12 +5168,12 @@ pub fn generate_sheet_xml_from_dict(
     buf.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
 <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">");
 
+    write_sheet_pr(&mut buf, config);
+
     buf.extend_from_slice(b"<dimension ref=\"");
     if num_rows > 0 {
         buf.extend_from_slice(b"A1:");
-        let mut col_buf = [0u8; 4];
-        let col_len = write_col_letter(num_cols - 1, &mut col_buf);
-        buf.extend_from_slice(&col_buf[..col_len]);
+        buf.extend_from_slice(col_letter(num_cols - 1).as_bytes());
         
         let mut row_buf = itoa::Buffer::new();
         buf.extend_from_slice(row_buf.format(num_rows + 1).as_bytes());
@@ -2719,37 +5182,12 @@ pub fn generate_sheet_xml_from_dict(
     }
     buf.extend_from_slice(b"\"/>");
 
-    if config.freeze_rows > 0 || config.freeze_cols > 0 {
-        buf.extend_from_slice(b"<sheetViews><sheetView workbookViewId=\"0\">");
-        buf.extend_from_slice(b"<pane ");
-        
-        if config.freeze_cols > 0 {
-            buf.extend_from_slice(b"xSplit=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_cols).as_bytes());
-            buf.extend_from_slice(b"\" ");
-        }
-        
-        if config.freeze_rows > 0 {
-            buf.extend_from_slice(b"ySplit=\"");
-            buf.extend_from_slice(itoa::Buffer::new().format(config.freeze_rows).as_bytes());
-            buf.extend_from_slice(b"\" ");
-        }
-        
-        buf.extend_from_slice(b"topLeftCell=\"");
-        write_cell_ref(config.freeze_cols, config.freeze_rows + 1, &mut buf);
-        buf.extend_from_slice(b"\" activePane=\"bottomRight\" state=\"frozen\"/>");
-        buf.extend_from_slice(b"</sheetView></sheetViews>");
-    }
+    write_sheet_views(&mut buf, config);
+    write_sheet_format_pr(&mut buf, config);
 
     buf.extend_from_slice(b"<sheetData>");
 
-    let col_letters: Vec<([u8; 4], usize)> = (0..num_cols)
-        .map(|i| {
-            let mut col_buf = [0u8; 4];
-            let len = write_col_letter(i, &mut col_buf);
-            (col_buf, len)
-        })
-        .collect();
+    let col_letters: Vec<Cow<'static, str>> = (0..num_cols).map(col_letter).collect();
 
     let mut ryu_buf = ryu::Buffer::new();
     let mut int_buf = itoa::Buffer::new();
@@ -2758,12 +5196,13 @@ pub fn generate_sheet_xml_from_dict(
 
     buf.extend_from_slice(b"<row r=\"1\">");
     for (col_idx, (header, _)) in sheet.columns.iter().enumerate() {
-        let (col_letter, col_len) = &col_letters[col_idx];
+        let col_letter = col_letters[col_idx].as_bytes();
         
+        let header_ref = [col_letter, b"1"].concat();
         buf.extend_from_slice(b"<c r=\"");
-        buf.extend_from_slice(&col_letter[..*col_len]);
-        buf.extend_from_slice(b"1\" t=\"inlineStr\"><is><t>");
-        xml_escape_simd(header.as_bytes(), &mut buf);
+        buf.extend_from_slice(&header_ref);
+        buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
+        write_cell_text(header.as_bytes(), &header_ref, Some(config.invalid_text), &mut buf)?;
         buf.extend_from_slice(b"</t></is></c>");
     }
     buf.extend_from_slice(b"</row>");
@@ -2779,12 +5218,12 @@ pub fn generate_sheet_xml_from_dict(
 
         for col_idx in 0..num_cols {
             let cell_val = &sheet.columns[col_idx].1[row_idx];
-            let (col_letter, col_len) = &col_letters[col_idx];
+            let col_letter = col_letters[col_idx].as_bytes();
 
             let cell_ref_len = {
-                cell_ref[..*col_len].copy_from_slice(&col_letter[..*col_len]);
-                cell_ref[*col_len..*col_len + row_bytes.len()].copy_from_slice(row_bytes);
-                *col_len + row_bytes.len()
+                cell_ref[..col_letter.len()].copy_from_slice(col_letter);
+                cell_ref[col_letter.len()..col_letter.len() + row_bytes.len()].copy_from_slice(row_bytes);
+                col_letter.len() + row_bytes.len()
             };
             let cell_ref_slice = &cell_ref[..cell_ref_len];
 
@@ -2798,7 +5237,7 @@ pub fn generate_sheet_xml_from_dict(
                     buf.extend_from_slice(b"<c r=\"");
                     buf.extend_from_slice(cell_ref_slice);
                     buf.extend_from_slice(b"\" t=\"inlineStr\"><is><t>");
-                    xml_escape_simd(s.as_bytes(), &mut buf);
+                    write_cell_text(s.as_bytes(), cell_ref_slice, Some(config.invalid_text), &mut buf)?;
                     buf.extend_from_slice(b"</t></is></c>");
                 }
                 CellValue::Number(n) => {
@@ -2822,11 +5261,10 @@ pub fn generate_sheet_xml_from_dict(
                     buf.extend_from_slice(b"</v></c>");
                 }
                 CellValue::Date(dt) => {
-                    buf.extend_from_slice(b"<c r=\"");
-                    buf.extend_from_slice(cell_ref_slice);
-                    buf.extend_from_slice(b"\" s=\"1\"><v>");
-                    buf.extend_from_slice(ryu_buf.format(datetime_to_excel_serial(dt)).as_bytes());
-                    buf.extend_from_slice(b"</v></c>");
+                    write_date_cell(dt, cell_ref_slice, Some(1), config.pre_1900_dates, config.date_1904, &mut buf, &mut ryu_buf)?;
+                }
+                CellValue::RichText(runs) => {
+                    write_rich_text_cell(runs, cell_ref_slice, None, config.invalid_text, &mut buf)?;
                 }
             }
         }
@@ -2837,9 +5275,7 @@ pub fn generate_sheet_xml_from_dict(
     
     if config.auto_filter && num_rows > 0 {
         buf.extend_from_slice(b"<autoFilter ref=\"A1:");
-        let mut col_buf = [0u8; 4];
-        let col_len = write_col_letter(num_cols - 1, &mut col_buf);
-        buf.extend_from_slice(&col_buf[..col_len]);
+        buf.extend_from_slice(col_letter(num_cols - 1).as_bytes());
         buf.extend_from_slice(itoa::Buffer::new().format(num_rows + 1).as_bytes());
         buf.extend_from_slice(b"\"/>");
     }
@@ -2873,6 +5309,7 @@ fn estimate_avg_cell_size(sheet: &SheetData) -> usize {
                 CellValue::Number(_) => 25,
                 CellValue::Bool(_) => 20,
                 CellValue::Date(_) => 30,
+                CellValue::RichText(runs) => 40 + runs.iter().map(|r| 60 + r.text.len()).sum::<usize>(),
             };
         }
     }
@@ -2933,25 +5370,48 @@ pub fn generate_drawing_xml_combined(charts: &[ExcelChart], images: &[ExcelImage
     // Add images
     for (idx, image) in images.iter().enumerate() {
         let image_id = idx + 1;
-        xml.push_str("<xdr:twoCellAnchor>\n");
-        
-        xml.push_str("<xdr:from>\n");
-        xml.push_str(&format!("<xdr:col>{}</xdr:col>\n", image.position.from_col));
-        xml.push_str("<xdr:colOff>0</xdr:colOff>\n");
-        xml.push_str(&format!("<xdr:row>{}</xdr:row>\n", image.position.from_row));
-        xml.push_str("<xdr:rowOff>0</xdr:rowOff>\n");
-        xml.push_str("</xdr:from>\n");
+        let anchor = match image.anchor {
+            ImageAnchor::TwoCell => "twoCellAnchor",
+            ImageAnchor::OneCell { .. } => "oneCellAnchor",
+            ImageAnchor::Absolute { .. } => "absoluteAnchor",
+        };
+        xml.push_str(&format!("<xdr:{}>\n", anchor));
         
-        xml.push_str("<xdr:to>\n");
-        xml.push_str(&format!("<xdr:col>{}</xdr:col>\n", image.position.to_col));
-        xml.push_str("<xdr:colOff>0</xdr:colOff>\n");
-        xml.push_str(&format!("<xdr:row>{}</xdr:row>\n", image.position.to_row));
-        xml.push_str("<xdr:rowOff>0</xdr:rowOff>\n");
-        xml.push_str("</xdr:to>\n");
+        let position = &image.position;
+        let from = format!(
+            "<xdr:from>\n<xdr:col>{}</xdr:col>\n<xdr:colOff>{}</xdr:colOff>\n<xdr:row>{}</xdr:row>\n<xdr:rowOff>{}</xdr:rowOff>\n</xdr:from>\n",
+            position.from_col, position.col_off, position.from_row, position.row_off
+        );
+        let (off, ext) = match image.anchor {
+            ImageAnchor::TwoCell => {
+                xml.push_str(&from);
+                xml.push_str("<xdr:to>\n");
+                xml.push_str(&format!("<xdr:col>{}</xdr:col>\n", image.position.to_col));
+                xml.push_str("<xdr:colOff>0</xdr:colOff>\n");
+                xml.push_str(&format!("<xdr:row>{}</xdr:row>\n", image.position.to_row));
+                xml.push_str("<xdr:rowOff>0</xdr:rowOff>\n");
+                xml.push_str("</xdr:to>\n");
+                ((0, 0), (0, 0))
+            }
+            ImageAnchor::OneCell { width, height } => {
+                xml.push_str(&from);
+                xml.push_str(&format!("<xdr:ext cx=\"{}\" cy=\"{}\"/>\n", width, height));
+                ((0, 0), (width, height))
+            }
+            ImageAnchor::Absolute { x, y, width, height } => {
+                xml.push_str(&format!("<xdr:pos x=\"{}\" y=\"{}\"/>\n", x, y));
+                xml.push_str(&format!("<xdr:ext cx=\"{}\" cy=\"{}\"/>\n", width, height));
+                ((x, y), (width, height))
+            }
+        };
         
         xml.push_str("<xdr:pic>\n");
         xml.push_str("<xdr:nvPicPr>\n");
-        xml.push_str(&format!("<xdr:cNvPr id=\"{}\" name=\"Image {}\"/>\n", element_id, image_id));
+        xml.push_str(&format!("<xdr:cNvPr id=\"{}\" name=\"Image {}\"", element_id, image_id));
+        if let Some(ref description) = image.description {
+            xml.push_str(&format!(" descr=\"{}\"", escape_xml(description)));
+        }
+        xml.push_str("/>\n");
         element_id += 1;
         xml.push_str("<xdr:cNvPicPr>\n");
         xml.push_str("<a:picLocks noChangeAspect=\"1\"/>\n");
@@ -2959,7 +5419,15 @@ pub fn generate_drawing_xml_combined(charts: &[ExcelChart], images: &[ExcelImage
         xml.push_str("</xdr:nvPicPr>\n");
         
         xml.push_str("<xdr:blipFill>\n");
-        xml.push_str(&format!("<a:blip xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" r:embed=\"rIdImage{}\"/>\n", image_id));
+        if image.is_svg() {
+            // The blip shows the PNG; Office 2016+ swaps in the SVG
+            xml.push_str(&format!("<a:blip xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" r:embed=\"rIdImage{}\">\n", image_id));
+            xml.push_str("<a:extLst><a:ext uri=\"{96DAC541-7B7A-43D3-8B79-37D633B846F1}\">");
+            xml.push_str(&format!("<asvg:svgBlip xmlns:asvg=\"http://schemas.microsoft.com/office/drawing/2016/SVG/main\" r:embed=\"rIdSvg{}\"/>", image_id));
+            xml.push_str("</a:ext></a:extLst>\n</a:blip>\n");
+        } else {
+            xml.push_str(&format!("<a:blip xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" r:embed=\"rIdImage{}\"/>\n", image_id));
+        }
         xml.push_str("<a:stretch>\n");
         xml.push_str("<a:fillRect/>\n");
         xml.push_str("</a:stretch>\n");
@@ -2967,8 +5435,8 @@ pub fn generate_drawing_xml_combined(charts: &[ExcelChart], images: &[ExcelImage
         
         xml.push_str("<xdr:spPr>\n");
         xml.push_str("<a:xfrm>\n");
-        xml.push_str("<a:off x=\"0\" y=\"0\"/>\n");
-        xml.push_str("<a:ext cx=\"0\" cy=\"0\"/>\n");
+        xml.push_str(&format!("<a:off x=\"{}\" y=\"{}\"/>\n", off.0, off.1));
+        xml.push_str(&format!("<a:ext cx=\"{}\" cy=\"{}\"/>\n", ext.0, ext.1));
         xml.push_str("</a:xfrm>\n");
         xml.push_str("<a:prstGeom prst=\"rect\">\n");
         xml.push_str("<a:avLst/>\n");
@@ -2977,7 +5445,7 @@ pub fn generate_drawing_xml_combined(charts: &[ExcelChart], images: &[ExcelImage
         
         xml.push_str("</xdr:pic>\n");
         xml.push_str("<xdr:clientData/>\n");
-        xml.push_str("</xdr:twoCellAnchor>\n");
+        xml.push_str(&format!("</xdr:{}>\n", anchor));
     }
     
     xml.push_str("</xdr:wsDr>");
@@ -2998,9 +5466,121 @@ pub fn generate_drawing_rels_combined(num_charts: usize, images: &[ExcelImage],
     
     for (idx, image) in images.iter().enumerate() {
         let i = idx + 1;
+        if image.is_svg() {
+            xml.push_str(&format!("<Relationship Id=\"rIdImage{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"../media/image{}.png\"/>\n", i, i));
+            xml.push_str(&format!("<Relationship Id=\"rIdSvg{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"../media/image{}.{}\"/>\n", i, i, image.extension));
+            continue;
+        }
         xml.push_str(&format!("<Relationship Id=\"rIdImage{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"../media/image{}.{}\"/>\n", i, i, image.extension));
     }
     
     xml.push_str("</Relationships>");
     xml
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+    use arrow_schema::{Field, Schema};
+    use std::sync::Arc;
+
+    #[test]
+    fn col_specs_carry_column_format_styles_only_when_asked() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(Int64Array::from(vec![3, 4])),
+        ]).unwrap();
+        let styles = SheetStyles {
+            col_format_map: HashMap::from([(1, 5)]),
+            ..SheetStyles::default()
+        };
+
+        // By default column formats stay on the data cells
+        let specs = build_col_specs(std::slice::from_ref(&batch), &StyleConfig::default(), &styles);
+        assert!(specs.iter().all(|spec| spec.style.is_none()), "{specs:?}");
+
+        let config = StyleConfig { format_whole_columns: true, ..StyleConfig::default() };
+        let mut buf = Vec::new();
+        write_cols(&mut buf, &build_col_specs(&[batch], &config, &styles));
+        let xml = String::from_utf8(buf).unwrap();
+
+        let cols = xml.strip_prefix("<cols>").and_then(|x| x.strip_suffix("</cols>")).unwrap();
+        let entries: Vec<&str> = cols.split("/>").filter(|e| !e.is_empty()).collect();
+        assert_eq!(entries.len(), 1, "{xml}");
+        assert!(entries[0].starts_with("<col min=\"2\" max=\"2\""), "{xml}");
+        assert!(entries[0].contains(" style=\"5\""), "{xml}");
+        assert!(!entries[0].contains("customWidth"), "{xml}");
+    }
+    #[test]
+    fn table_totals_row_goes_below_the_data() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(vec![1, 2])),
+            Arc::new(Int64Array::from(vec![3, 4])),
+        ]).unwrap();
+        let mut table = ExcelTable::new("Sales".to_string(), (1, 0, 0, 0));
+        table.show_totals_row = true;
+        let config = StyleConfig { tables: vec![table.clone()], ..StyleConfig::default() };
+        let styles = SheetStyles::default();
+
+        let mut writer = SheetXmlWriter::new(&batch, Some(2), &config, &styles);
+        let mut buf = Vec::new();
+        writer.write_head(&batch, &mut buf).unwrap();
+        writer.write_batch(&batch, &mut buf).unwrap();
+        writer.write_tail(&mut buf).unwrap();
+        let xml = String::from_utf8(buf).unwrap();
+
+        assert!(xml.contains("<dimension ref=\"A1:B4\"/>"), "{xml}");
+        assert!(xml.contains("<c r=\"B3\"><v>4</v></c>"), "{xml}");
+        assert!(xml.contains(
+            "<row r=\"4\"><c r=\"A4\" t=\"inlineStr\"><is><t>Total</t></is></c><c r=\"B4\"><f>SUBTOTAL(109,Sales[b])</f></c></row>"
+        ), "{xml}");
+
+        table.range = table.resolved_range(2, 2);
+        let table_xml = generate_table_xml(&table, 1, &["a".to_string(), "b".to_string()], true);
+        assert!(table_xml.contains("ref=\"A1:B4\" totalsRowCount=\"1\""), "{table_xml}");
+        assert!(table_xml.contains("<autoFilter ref=\"A1:B3\"/>"), "{table_xml}");
+        assert!(table_xml.contains("name=\"a\" totalsRowLabel=\"Total\"/><tableColumn id=\"2\" name=\"b\" totalsRowFunction=\"sum\"/>"), "{table_xml}");
+    }
+    #[test]
+    fn auto_sized_tables_stop_at_gap_columns() {
+        let group = |names: &[&str]| {
+            let schema = Arc::new(Schema::new(names.iter().map(|n| Field::new(*n, DataType::Int64, false)).collect::<Vec<_>>()));
+            let columns = names.iter().map(|_| Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef).collect();
+            crate::writer::ColumnGroup {
+                batches: vec![RecordBatch::try_new(schema, columns).unwrap()],
+                column_formats: HashMap::new(),
+                column_widths: HashMap::new(),
+                column_timezones: HashMap::new(),
+            }
+        };
+        let combined = crate::writer::combine_column_groups(vec![group(&["a", "b"]), group(&["c"])], 1).unwrap();
+        let batch = &combined.batches[0];
+        let schema = batch.schema();
+        assert_eq!(table_width(&schema, 0), 2);
+        assert_eq!(table_width(&schema, 3), 1);
+
+        let write = |tables: Vec<ExcelTable>| {
+            let config = StyleConfig { tables, ..StyleConfig::default() };
+            let styles = SheetStyles::default();
+            let mut writer = SheetXmlWriter::new(batch, Some(2), &config, &styles);
+            writer.write_head(batch, &mut Vec::new())
+        };
+        let first = ExcelTable::new("First".to_string(), (1, 0, 0, 0));
+        let second = ExcelTable::new("Second".to_string(), (1, 3, 0, 0));
+        assert_eq!(first.resolved_range(2, table_width(&schema, 0)), (1, 0, 3, 1));
+        assert!(write(vec![first, second]).is_ok());
+
+        let spanning = ExcelTable::new("Both".to_string(), (1, 0, 3, 3));
+        let err = write(vec![spanning]).unwrap_err().to_string();
+        assert!(err.contains("spans the empty column C"), "{err}");
+    }
+}
@@ -0,0 +1,827 @@
+use crc32fast::Hasher;
+use flate2::{Compress, FlushCompress, Status};
+use memchr::memmem;
+use rayon::prelude::*;
+use std::io::{self, Write};
+
+pub use flate2::Compression as CompressionLevel;
+
+// ============================================================================
+// MINIMAL ZIP WRITER WITH CHUNKED PARALLEL DEFLATE
+// ============================================================================
+
+/// Soft limit for a single deflate chunk. Chunks are cut at the first `</row>`
+/// after this many bytes so every chunk holds whole worksheet rows.
+const CHUNK_SOFT_LIMIT: usize = 1 << 20;
+
+/// Entries smaller than this are compressed as a single deflate stream.
+const PARALLEL_THRESHOLD: usize = 2 * CHUNK_SOFT_LIMIT;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034B50;
+const CENTRAL_FILE_HEADER_SIGNATURE: u32 = 0x02014B50;
+const END_OF_CENTRAL_DIR_SIGNATURE: u32 = 0x06054B50;
+const ZIP64_END_OF_CENTRAL_DIR_SIGNATURE: u32 = 0x06064B50;
+const ZIP64_END_OF_CENTRAL_DIR_LOCATOR_SIGNATURE: u32 = 0x07064B50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074B50;
+const VERSION_NEEDED_TO_EXTRACT: u16 = 20;
+/// Entries and archives that need zip64 fields
+const VERSION_NEEDED_ZIP64: u16 = 45;
+const VERSION_MADE_BY: u16 = 45;
+const ZIP64_EXTRA_FIELD_TAG: u16 = 0x0001;
+/// 32-bit size and offset fields hold this when the value is in a zip64 field
+const ZIP64_MARKER_U32: u64 = u32::MAX as u64;
+/// 16-bit entry counts hold this when the count is in the zip64 record
+const ZIP64_MARKER_U16: usize = u16::MAX as usize;
+/// 1980-01-01 00:00:00, the earliest DOS timestamp. Entries share a fixed
+/// stamp so output stays reproducible, like the fixed docProps dates.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+/// Bit 11: file names are UTF-8
+const GENERAL_PURPOSE_BIT_FLAG: u16 = 1 << 11;
+/// Bit 3: CRC and sizes follow the data in a data descriptor
+const DATA_DESCRIPTOR_FLAG: u16 = 1 << 3;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+/// Level 0 stores entries as-is instead of deflating them
+fn method_for(level: CompressionLevel) -> u16 {
+    if level.level() == 0 { METHOD_STORED } else { METHOD_DEFLATE }
+}
+
+struct PendingFile {
+    name: String,
+    data: Vec<u8>,
+    level: CompressionLevel,
+}
+
+/// Entry ready to write; `data` is borrowed when stored from a caller's buffer
+struct CompressedFile<D = Vec<u8>> {
+    name: String,
+    method: u16,
+    crc: u32,
+    uncompressed_size: u64,
+    data: D,
+}
+
+pub struct ZipArchive {
+    files: Vec<PendingFile>,
+    /// Compression threads; 0 uses rayon's global pool (one per core)
+    threads: usize,
+    /// Deflate level of every entry added
+    level: CompressionLevel,
+}
+
+pub struct ZipFileBuilder<'a> {
+    archive: &'a mut ZipArchive,
+    file: PendingFile,
+}
+
+impl<'a> ZipFileBuilder<'a> {
+    pub fn done(self) {
+        self.archive.files.push(self.file);
+    }
+}
+
+impl ZipArchive {
+    pub fn new(threads: usize, level: CompressionLevel) -> Self {
+        Self { files: Vec::new(), threads, level }
+    }
+
+    pub fn add_file_from_memory(&mut self, data: Vec<u8>, name: String) -> ZipFileBuilder<'_> {
+        let level = self.level;
+        ZipFileBuilder {
+            archive: self,
+            file: PendingFile { name, data, level },
+        }
+    }
+
+    /// Compress every entry in parallel and write the archive.
+    ///
+    /// Large entries (worksheets) are split into row-aligned chunks that are
+    /// deflated independently and concatenated into one deflate stream.
+    pub fn write<W: Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        let mut stream = ZipStreamWriter::new(writer, self.threads)?;
+        stream.append(self)?;
+        stream.finish().map(|_| ())
+    }
+}
+
+/// Local header position and sizes of an entry, kept for the central directory
+struct CentralEntry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    offset: u64,
+}
+
+impl CentralEntry {
+    /// Zip64 extra field holding the values too large for the 32-bit fields,
+    /// in the order the format requires; empty when none are
+    fn zip64_extra(&self) -> Vec<u8> {
+        let values: Vec<u64> = [self.uncompressed_size, self.compressed_size, self.offset]
+            .into_iter()
+            .filter(|&v| v >= ZIP64_MARKER_U32)
+            .collect();
+        if values.is_empty() {
+            return Vec::new();
+        }
+        let mut extra = Vec::with_capacity(4 + values.len() * 8);
+        extra.extend_from_slice(&ZIP64_EXTRA_FIELD_TAG.to_le_bytes());
+        extra.extend_from_slice(&((values.len() * 8) as u16).to_le_bytes());
+        for value in values {
+            extra.extend_from_slice(&value.to_le_bytes());
+        }
+        extra
+    }
+}
+
+/// A 32-bit size or offset field, or the zip64 marker when it doesn't fit
+fn u32_field(value: u64) -> u32 {
+    value.min(ZIP64_MARKER_U32) as u32
+}
+
+/// Zip writer that compresses and writes each entry as soon as it is added,
+/// so the caller only holds the entries it has not handed over yet. The
+/// central directory is written by `finish`.
+pub struct ZipStreamWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    entries: Vec<CentralEntry>,
+    /// Dedicated compression pool; None uses rayon's global pool
+    pool: Option<rayon::ThreadPool>,
+    /// Entry being written piecewise by `write_entry_data`
+    open_entry: Option<OpenEntry>,
+}
+
+/// State of an entry started with `start_entry`
+struct OpenEntry {
+    name: String,
+    level: CompressionLevel,
+    offset: u64,
+    crc: Hasher,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    /// Data not yet compressed; flushed in parallel chunks once it grows
+    pending: Vec<u8>,
+}
+
+/// Pending data of an open entry is compressed once it reaches this size
+pub const OPEN_ENTRY_FLUSH_SIZE: usize = 4 * PARALLEL_THRESHOLD;
+
+impl<W: Write> ZipStreamWriter<W> {
+    /// `threads` compression threads; 0 uses rayon's global pool (one per core)
+    pub fn new(writer: W, threads: usize) -> io::Result<Self> {
+        let pool = if threads > 0 {
+            Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(io::Error::other)?,
+            )
+        } else {
+            None
+        };
+        Ok(Self { writer, offset: 0, entries: Vec::new(), pool, open_entry: None })
+    }
+
+    /// Compress and write one entry from borrowed data, leaving the buffer
+    /// with the caller to reuse for the next entry
+    pub fn add_file_from_slice(&mut self, data: &[u8], name: String, level: CompressionLevel) -> io::Result<()> {
+        let uncompressed_size = data.len() as u64;
+        let method = method_for(level);
+        if method == METHOD_STORED {
+            let crc = crc32(data);
+            return self.write_entry(CompressedFile { name, method, crc, uncompressed_size, data });
+        }
+        let (compressed, crc) = match &self.pool {
+            Some(pool) => pool.install(|| deflate_data(data, level))?,
+            None => deflate_data(data, level)?,
+        };
+        self.write_entry(CompressedFile { name, method, crc, uncompressed_size, data: compressed })
+    }
+
+    /// Write an entry compressed earlier by `compress_entry`
+    pub fn write_compressed(&mut self, entry: CompressedEntry) -> io::Result<()> {
+        self.write_entry(entry.0)
+    }
+
+    /// Compress the archive's pending entries in parallel and write them
+    pub fn append(&mut self, archive: &mut ZipArchive) -> io::Result<()> {
+        let files = std::mem::take(&mut archive.files);
+        let compress_all = || {
+            files
+                .into_par_iter()
+                .map(compress_file)
+                .collect::<io::Result<Vec<CompressedFile>>>()
+        };
+        let compressed = match &self.pool {
+            Some(pool) => pool.install(compress_all)?,
+            None => compress_all()?,
+        };
+        for file in compressed {
+            self.write_entry(file)?;
+        }
+        Ok(())
+    }
+
+    /// Start an entry whose data is handed over piece by piece with
+    /// `write_entry_data` and completed by `finish_entry`, so the whole entry
+    /// never has to be held in memory. Its CRC and sizes are written in a
+    /// data descriptor after the data. The final size is unknown here, so
+    /// the entry is always zip64: the local header carries a zip64 field and
+    /// the descriptor 64-bit sizes, whether or not it grows past 4 GiB.
+    pub fn start_entry(&mut self, name: String, level: CompressionLevel) -> io::Result<()> {
+        if self.open_entry.is_some() {
+            return Err(io::Error::other("Previous zip entry was not finished"));
+        }
+        let offset = self.offset;
+
+        let mut header = Vec::with_capacity(50 + name.len());
+        header.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        header.extend_from_slice(&VERSION_NEEDED_ZIP64.to_le_bytes());
+        header.extend_from_slice(&(GENERAL_PURPOSE_BIT_FLAG | DATA_DESCRIPTOR_FLAG).to_le_bytes());
+        header.extend_from_slice(&method_for(level).to_le_bytes());
+        header.extend_from_slice(&DOS_TIME.to_le_bytes());
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // crc, in the data descriptor
+        header.extend_from_slice(&u32::MAX.to_le_bytes()); // compressed size, in the zip64 field
+        header.extend_from_slice(&u32::MAX.to_le_bytes()); // uncompressed size, in the zip64 field
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(name.as_bytes());
+        // Zip64 field with both sizes zero; the real ones follow the data
+        header.extend_from_slice(&ZIP64_EXTRA_FIELD_TAG.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes());
+        header.extend_from_slice(&0u64.to_le_bytes());
+        header.extend_from_slice(&0u64.to_le_bytes());
+
+        self.writer.write_all(&header)?;
+        self.offset += header.len() as u64;
+
+        self.open_entry = Some(OpenEntry {
+            name,
+            level,
+            offset,
+            crc: Hasher::new(),
+            compressed_size: 0,
+            uncompressed_size: 0,
+            pending: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Append data to the entry opened by `start_entry`
+    pub fn write_entry_data(&mut self, data: &[u8]) -> io::Result<()> {
+        let entry = self.open_entry.as_mut()
+            .ok_or_else(|| io::Error::other("No zip entry was started"))?;
+        entry.pending.extend_from_slice(data);
+        if entry.pending.len() >= OPEN_ENTRY_FLUSH_SIZE {
+            self.flush_open_entry(false)?;
+        }
+        Ok(())
+    }
+
+    /// Compress the rest of the open entry and write its data descriptor
+    pub fn finish_entry(&mut self) -> io::Result<()> {
+        self.flush_open_entry(true)?;
+        let entry = self.open_entry.take()
+            .ok_or_else(|| io::Error::other("No zip entry was started"))?;
+
+        let crc = entry.crc.finalize();
+        let compressed_size = entry.compressed_size;
+        let uncompressed_size = entry.uncompressed_size;
+
+        // 64-bit sizes, as announced by the local header's zip64 field
+        let mut descriptor = Vec::with_capacity(24);
+        descriptor.extend_from_slice(&DATA_DESCRIPTOR_SIGNATURE.to_le_bytes());
+        descriptor.extend_from_slice(&crc.to_le_bytes());
+        descriptor.extend_from_slice(&compressed_size.to_le_bytes());
+        descriptor.extend_from_slice(&uncompressed_size.to_le_bytes());
+        self.writer.write_all(&descriptor)?;
+        self.offset += descriptor.len() as u64;
+
+        self.entries.push(CentralEntry {
+            name: entry.name,
+            flags: GENERAL_PURPOSE_BIT_FLAG | DATA_DESCRIPTOR_FLAG,
+            method: method_for(entry.level),
+            crc,
+            compressed_size,
+            uncompressed_size,
+            offset: entry.offset,
+        });
+        Ok(())
+    }
+
+    /// Deflate the open entry's pending data as sync-flushed chunks that
+    /// continue the entry's deflate stream; `is_last` ends the stream.
+    fn flush_open_entry(&mut self, is_last: bool) -> io::Result<()> {
+        let entry = self.open_entry.as_mut()
+            .ok_or_else(|| io::Error::other("No zip entry was started"))?;
+        let pending = std::mem::take(&mut entry.pending);
+        let level = entry.level;
+
+        if method_for(level) == METHOD_STORED {
+            entry.crc.update(&pending);
+            entry.uncompressed_size += pending.len() as u64;
+            entry.compressed_size += pending.len() as u64;
+            self.writer.write_all(&pending)?;
+            self.offset += pending.len() as u64;
+            return Ok(());
+        }
+
+        let chunks = split_row_chunks(&pending);
+        let last = chunks.len() - 1;
+        let compress_all = || {
+            chunks
+                .par_iter()
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let mut hasher = Hasher::new();
+                    hasher.update(chunk);
+                    deflate_chunk(chunk, level, is_last && i == last).map(|data| (data, hasher))
+                })
+                .collect::<io::Result<Vec<(Vec<u8>, Hasher)>>>()
+        };
+        let parts = match &self.pool {
+            Some(pool) => pool.install(compress_all)?,
+            None => compress_all()?,
+        };
+
+        entry.uncompressed_size += pending.len() as u64;
+        for (part, hasher) in parts {
+            self.writer.write_all(&part)?;
+            self.offset += part.len() as u64;
+            entry.compressed_size += part.len() as u64;
+            entry.crc.combine(&hasher);
+        }
+        Ok(())
+    }
+
+    fn write_entry<D: AsRef<[u8]>>(&mut self, file: CompressedFile<D>) -> io::Result<()> {
+        let offset = self.offset;
+        let data = file.data.as_ref();
+        let compressed_size = data.len() as u64;
+
+        // A local zip64 field holds both sizes once either passes 4 GiB
+        let zip64 = compressed_size >= ZIP64_MARKER_U32 || file.uncompressed_size >= ZIP64_MARKER_U32;
+        let mut extra = Vec::new();
+        if zip64 {
+            extra.extend_from_slice(&ZIP64_EXTRA_FIELD_TAG.to_le_bytes());
+            extra.extend_from_slice(&16u16.to_le_bytes());
+            extra.extend_from_slice(&file.uncompressed_size.to_le_bytes());
+            extra.extend_from_slice(&compressed_size.to_le_bytes());
+        }
+        let version = if zip64 { VERSION_NEEDED_ZIP64 } else { VERSION_NEEDED_TO_EXTRACT };
+
+        let mut header = Vec::with_capacity(30 + file.name.len() + extra.len());
+        header.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        header.extend_from_slice(&version.to_le_bytes());
+        header.extend_from_slice(&GENERAL_PURPOSE_BIT_FLAG.to_le_bytes());
+        header.extend_from_slice(&file.method.to_le_bytes());
+        header.extend_from_slice(&DOS_TIME.to_le_bytes());
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&file.crc.to_le_bytes());
+        header.extend_from_slice(&u32_field(if zip64 { ZIP64_MARKER_U32 } else { compressed_size }).to_le_bytes());
+        header.extend_from_slice(&u32_field(if zip64 { ZIP64_MARKER_U32 } else { file.uncompressed_size }).to_le_bytes());
+        header.extend_from_slice(&(file.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+        header.extend_from_slice(file.name.as_bytes());
+        header.extend_from_slice(&extra);
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(data)?;
+        self.offset += (header.len() + data.len()) as u64;
+
+        self.entries.push(CentralEntry {
+            name: file.name,
+            flags: GENERAL_PURPOSE_BIT_FLAG,
+            method: file.method,
+            crc: file.crc,
+            compressed_size,
+            uncompressed_size: file.uncompressed_size,
+            offset,
+        });
+        Ok(())
+    }
+
+    /// Write the central directory and return the underlying writer. Zip64
+    /// fields and end records are added only where sizes, offsets or the
+    /// entry count overflow the classic format.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.open_entry.is_some() {
+            self.finish_entry()?;
+        }
+
+        let central_dir_offset = self.offset;
+        let mut central_dir = Vec::with_capacity(self.entries.len() * 80);
+
+        for entry in &self.entries {
+            let extra = entry.zip64_extra();
+            // Streamed entries have a zip64 local header whatever their size
+            let version = if extra.is_empty() && entry.flags & DATA_DESCRIPTOR_FLAG == 0 {
+                VERSION_NEEDED_TO_EXTRACT
+            } else {
+                VERSION_NEEDED_ZIP64
+            };
+            central_dir.extend_from_slice(&CENTRAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+            central_dir.extend_from_slice(&VERSION_MADE_BY.to_le_bytes());
+            central_dir.extend_from_slice(&version.to_le_bytes());
+            central_dir.extend_from_slice(&entry.flags.to_le_bytes());
+            central_dir.extend_from_slice(&entry.method.to_le_bytes());
+            central_dir.extend_from_slice(&DOS_TIME.to_le_bytes());
+            central_dir.extend_from_slice(&DOS_DATE.to_le_bytes());
+            central_dir.extend_from_slice(&entry.crc.to_le_bytes());
+            central_dir.extend_from_slice(&u32_field(entry.compressed_size).to_le_bytes());
+            central_dir.extend_from_slice(&u32_field(entry.uncompressed_size).to_le_bytes());
+            central_dir.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            central_dir.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            central_dir.extend_from_slice(&0u16.to_le_bytes()); // comment length
+            central_dir.extend_from_slice(&0u16.to_le_bytes()); // disk number start
+            central_dir.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            central_dir.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            central_dir.extend_from_slice(&u32_field(entry.offset).to_le_bytes());
+            central_dir.extend_from_slice(entry.name.as_bytes());
+            central_dir.extend_from_slice(&extra);
+        }
+
+        let central_dir_size = central_dir.len() as u64;
+        let entries = self.entries.len();
+
+        if entries >= ZIP64_MARKER_U16
+            || central_dir_size >= ZIP64_MARKER_U32
+            || central_dir_offset >= ZIP64_MARKER_U32
+        {
+            let zip64_end_offset = central_dir_offset + central_dir_size;
+            central_dir.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIR_SIGNATURE.to_le_bytes());
+            central_dir.extend_from_slice(&44u64.to_le_bytes()); // size of the rest of this record
+            central_dir.extend_from_slice(&VERSION_MADE_BY.to_le_bytes());
+            central_dir.extend_from_slice(&VERSION_NEEDED_ZIP64.to_le_bytes());
+            central_dir.extend_from_slice(&0u32.to_le_bytes()); // this disk
+            central_dir.extend_from_slice(&0u32.to_le_bytes()); // disk with central dir
+            central_dir.extend_from_slice(&(entries as u64).to_le_bytes());
+            central_dir.extend_from_slice(&(entries as u64).to_le_bytes());
+            central_dir.extend_from_slice(&central_dir_size.to_le_bytes());
+            central_dir.extend_from_slice(&central_dir_offset.to_le_bytes());
+
+            central_dir.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIR_LOCATOR_SIGNATURE.to_le_bytes());
+            central_dir.extend_from_slice(&0u32.to_le_bytes()); // disk with the zip64 record
+            central_dir.extend_from_slice(&zip64_end_offset.to_le_bytes());
+            central_dir.extend_from_slice(&1u32.to_le_bytes()); // total disks
+        }
+
+        let entry_count = entries.min(ZIP64_MARKER_U16) as u16;
+        central_dir.extend_from_slice(&END_OF_CENTRAL_DIR_SIGNATURE.to_le_bytes());
+        central_dir.extend_from_slice(&0u16.to_le_bytes()); // this disk
+        central_dir.extend_from_slice(&0u16.to_le_bytes()); // disk with central dir
+        central_dir.extend_from_slice(&entry_count.to_le_bytes());
+        central_dir.extend_from_slice(&entry_count.to_le_bytes());
+        central_dir.extend_from_slice(&u32_field(central_dir_size).to_le_bytes());
+        central_dir.extend_from_slice(&u32_field(central_dir_offset).to_le_bytes());
+        central_dir.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        self.writer.write_all(&central_dir)?;
+        Ok(self.writer)
+    }
+}
+
+/// Entry compressed ahead of `ZipStreamWriter::write_compressed`, so it can
+/// be deflated on the thread that produced it while other entries are written
+pub struct CompressedEntry(CompressedFile);
+
+/// Compress one entry on the current rayon pool; large entries are split
+/// into row chunks deflated in parallel, as in `add_file_from_slice`
+pub fn compress_entry(data: &[u8], name: String, level: CompressionLevel) -> io::Result<CompressedEntry> {
+    let uncompressed_size = data.len() as u64;
+    let method = method_for(level);
+    let (data, crc) = if method == METHOD_STORED {
+        (data.to_vec(), crc32(data))
+    } else {
+        deflate_data(data, level)?
+    };
+    Ok(CompressedEntry(CompressedFile { name, method, crc, uncompressed_size, data }))
+}
+
+fn compress_file(file: PendingFile) -> io::Result<CompressedFile> {
+    let uncompressed_size = file.data.len() as u64;
+    let method = method_for(file.level);
+
+    let (data, crc) = if method == METHOD_STORED {
+        let crc = crc32(&file.data);
+        (file.data, crc)
+    } else {
+        deflate_data(&file.data, file.level)?
+    };
+
+    Ok(CompressedFile {
+        name: file.name,
+        method,
+        crc,
+        uncompressed_size,
+        data,
+    })
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Deflate a whole entry, in parallel row chunks once it is large enough.
+/// Returns the compressed bytes and the CRC of `data`.
+fn deflate_data(data: &[u8], level: CompressionLevel) -> io::Result<(Vec<u8>, u32)> {
+    if data.len() < PARALLEL_THRESHOLD {
+        return Ok((deflate_chunk(data, level, true)?, crc32(data)));
+    }
+
+    let chunks = split_row_chunks(data);
+    let last = chunks.len() - 1;
+
+    let parts: Vec<(Vec<u8>, Hasher)> = chunks
+        .par_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hasher = Hasher::new();
+            hasher.update(chunk);
+            deflate_chunk(chunk, level, i == last).map(|data| (data, hasher))
+        })
+        .collect::<io::Result<_>>()?;
+
+    let total: usize = parts.iter().map(|(data, _)| data.len()).sum();
+    let mut compressed = Vec::with_capacity(total);
+    let mut crc = Hasher::new();
+    for (part, hasher) in parts {
+        compressed.extend_from_slice(&part);
+        crc.combine(&hasher);
+    }
+    Ok((compressed, crc.finalize()))
+}
+
+/// Size `data` takes in an archive entry compressed at `level`
+pub fn compressed_size(data: &[u8], level: CompressionLevel) -> io::Result<usize> {
+    if method_for(level) == METHOD_STORED {
+        return Ok(data.len());
+    }
+    deflate_chunk(data, level, true).map(|compressed| compressed.len())
+}
+
+/// Split worksheet XML into chunks of roughly `CHUNK_SOFT_LIMIT` bytes,
+/// cutting right after a `</row>` so no row straddles two chunks.
+fn split_row_chunks(data: &[u8]) -> Vec<&[u8]> {
+    let finder = memmem::Finder::new(b"</row>");
+    let mut chunks = Vec::with_capacity(data.len() / CHUNK_SOFT_LIMIT + 1);
+    let mut start = 0;
+
+    while data.len() - start > CHUNK_SOFT_LIMIT {
+        let search_from = start + CHUNK_SOFT_LIMIT;
+        match finder.find(&data[search_from..]) {
+            Some(pos) => {
+                let end = search_from + pos + b"</row>".len();
+                chunks.push(&data[start..end]);
+                start = end;
+            }
+            None => break,
+        }
+    }
+
+    chunks.push(&data[start..]);
+    chunks
+}
+
+/// Deflate one chunk as raw blocks. Non-final chunks end with a sync flush so
+/// they are byte aligned and can be concatenated with the following chunk.
+fn deflate_chunk(input: &[u8], level: CompressionLevel, is_last: bool) -> io::Result<Vec<u8>> {
+    let mut compressor = Compress::new(level, false);
+    let mut output = Vec::with_capacity(input.len() / 4 + 64);
+    let flush = if is_last { FlushCompress::Finish } else { FlushCompress::Sync };
+
+    loop {
+        let consumed = compressor.total_in() as usize;
+        if output.len() == output.capacity() {
+            output.reserve(output.capacity().max(4096));
+        }
+        let status = compressor
+            .compress_vec(&input[consumed..], &mut output, flush)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+
+        let done = match status {
+            Status::StreamEnd => true,
+            // A sync flush is complete once all input is consumed and the
+            // encoder left spare room in the output buffer.
+            Status::Ok | Status::BufError => {
+                !is_last
+                    && compressor.total_in() as usize == input.len()
+                    && output.len() < output.capacity()
+            }
+        };
+        if done {
+            return Ok(output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    /// Worksheet-like XML with many `</row>` boundaries to chunk at
+    fn rows_xml(rows: usize) -> Vec<u8> {
+        let mut xml = Vec::new();
+        for row in 0..rows {
+            write!(xml, "<row r=\"{row}\"><c r=\"A{row}\"><v>{}</v></c></row>", row * 7919 % 100_003).unwrap();
+        }
+        xml
+    }
+
+    fn read_entries(data: Vec<u8>) -> Vec<(String, Vec<u8>)> {
+        let mut archive = ::zip::ZipArchive::new(Cursor::new(data)).unwrap();
+        (0..archive.len())
+            .map(|idx| {
+                let mut file = archive.by_index(idx).unwrap();
+                let mut contents = Vec::new();
+                // Fails on a CRC mismatch
+                file.read_to_end(&mut contents).unwrap();
+                (file.name().to_string(), contents)
+            })
+            .collect()
+    }
+
+    fn has_zip64_end_records(data: &[u8]) -> bool {
+        data.windows(4).any(|w| w == ZIP64_END_OF_CENTRAL_DIR_SIGNATURE.to_le_bytes())
+            && data.windows(4).any(|w| w == ZIP64_END_OF_CENTRAL_DIR_LOCATOR_SIGNATURE.to_le_bytes())
+    }
+
+    #[test]
+    fn chunked_deflate_round_trips() {
+        let small = b"<workbook/>".to_vec();
+        let large = rows_xml(200_000);
+        assert!(split_row_chunks(&large).len() > 2);
+
+        let mut archive = ZipArchive::new(2, CompressionLevel::default());
+        archive.add_file_from_memory(small.clone(), "small.xml".to_string()).done();
+        archive.add_file_from_memory(large.clone(), "large.xml".to_string()).done();
+        let mut data = Vec::new();
+        archive.write(&mut data).unwrap();
+
+        assert_eq!(read_entries(data), vec![
+            ("small.xml".to_string(), small),
+            ("large.xml".to_string(), large),
+        ]);
+    }
+
+    #[test]
+    fn streamed_entries_round_trip() {
+        let deflated = rows_xml(400_000);
+        let stored = rows_xml(50_000);
+        assert!(deflated.len() > 2 * OPEN_ENTRY_FLUSH_SIZE);
+
+        let mut stream = ZipStreamWriter::new(Vec::new(), 2).unwrap();
+        stream.add_file_from_slice(b"first", "first.txt".to_string(), CompressionLevel::fast()).unwrap();
+        // Uneven pieces so flushes and chunks don't line up with the writes
+        stream.start_entry("deflated.xml".to_string(), CompressionLevel::default()).unwrap();
+        for piece in deflated.chunks(65_537) {
+            stream.write_entry_data(piece).unwrap();
+        }
+        stream.finish_entry().unwrap();
+        stream.start_entry("stored.xml".to_string(), CompressionLevel::none()).unwrap();
+        for piece in stored.chunks(100_003) {
+            stream.write_entry_data(piece).unwrap();
+        }
+        stream.finish_entry().unwrap();
+        stream.start_entry("empty.xml".to_string(), CompressionLevel::default()).unwrap();
+        stream.finish_entry().unwrap();
+        let data = stream.finish().unwrap();
+
+        // Streamed entries announce zip64 sizes in their local header
+        let header = data.windows(12).position(|w| w == b"deflated.xml").unwrap() - 30;
+        assert_eq!(&data[header + 4..header + 6], &VERSION_NEEDED_ZIP64.to_le_bytes());
+        assert_eq!(&data[header + 28..header + 30], &20u16.to_le_bytes());
+        assert_eq!(&data[header + 42..header + 44], &ZIP64_EXTRA_FIELD_TAG.to_le_bytes());
+        assert!(!has_zip64_end_records(&data));
+
+        assert_eq!(read_entries(data), vec![
+            ("first.txt".to_string(), b"first".to_vec()),
+            ("deflated.xml".to_string(), deflated),
+            ("stored.xml".to_string(), stored),
+            ("empty.xml".to_string(), Vec::new()),
+        ]);
+    }
+
+    fn archive_with_entries(count: usize) -> Vec<u8> {
+        let mut stream = ZipStreamWriter::new(Vec::new(), 1).unwrap();
+        for idx in 0..count {
+            stream.add_file_from_slice(idx.to_string().as_bytes(), format!("{idx}"), CompressionLevel::none()).unwrap();
+        }
+        stream.finish().unwrap()
+    }
+
+    #[test]
+    fn entry_counts_from_65535_use_zip64_end_records() {
+        let below = archive_with_entries(ZIP64_MARKER_U16 - 1);
+        assert!(!has_zip64_end_records(&below));
+        assert_eq!(::zip::ZipArchive::new(Cursor::new(below)).unwrap().len(), ZIP64_MARKER_U16 - 1);
+
+        let count = ZIP64_MARKER_U16 + 2;
+        let data = archive_with_entries(count);
+        assert!(has_zip64_end_records(&data));
+        let mut archive = ::zip::ZipArchive::new(Cursor::new(data)).unwrap();
+        assert_eq!(archive.len(), count);
+        let mut last = String::new();
+        archive.by_name(&(count - 1).to_string()).unwrap().read_to_string(&mut last).unwrap();
+        assert_eq!(last, (count - 1).to_string());
+    }
+
+    /// Archive held as the bytes that were written, except that writes of
+    /// `HOLE_SIZE` bytes or more are only counted and read back as zeros.
+    /// Lets an archive past 4 GiB of zeros be written and read in memory.
+    #[derive(Default)]
+    struct SparseFile {
+        pos: u64,
+        len: u64,
+        segments: Vec<(u64, Vec<u8>)>,
+    }
+
+    const HOLE_SIZE: usize = 1 << 20;
+
+    impl Write for SparseFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() < HOLE_SIZE {
+                self.segments.push((self.pos, buf.to_vec()));
+            }
+            self.pos += buf.len() as u64;
+            self.len = self.len.max(self.pos);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for SparseFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.len.saturating_sub(self.pos) as usize);
+            let buf = &mut buf[..n];
+            buf.fill(0);
+            let (start, end) = (self.pos, self.pos + n as u64);
+            for (offset, bytes) in &self.segments {
+                let seg_end = offset + bytes.len() as u64;
+                if *offset < end && seg_end > start {
+                    let from = start.max(*offset);
+                    let to = end.min(seg_end);
+                    buf[(from - start) as usize..(to - start) as usize]
+                        .copy_from_slice(&bytes[(from - offset) as usize..(to - offset) as usize]);
+                }
+            }
+            self.pos = end;
+            Ok(n)
+        }
+    }
+
+    impl Seek for SparseFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.pos = match pos {
+                SeekFrom::Start(pos) => pos,
+                SeekFrom::End(delta) => (self.len as i64 + delta) as u64,
+                SeekFrom::Current(delta) => (self.pos as i64 + delta) as u64,
+            };
+            Ok(self.pos)
+        }
+    }
+
+    #[test]
+    fn entries_past_4_gib_use_zip64_fields() {
+        let big = ZIP64_MARKER_U32 + (16 << 20);
+        let piece = vec![0u8; 64 << 20];
+
+        let mut stream = ZipStreamWriter::new(SparseFile::default(), 1).unwrap();
+        stream.add_file_from_slice(b"before", "before.txt".to_string(), CompressionLevel::none()).unwrap();
+        stream.start_entry("big.bin".to_string(), CompressionLevel::none()).unwrap();
+        let mut written = 0;
+        while written < big {
+            let len = piece.len().min((big - written) as usize);
+            stream.write_entry_data(&piece[..len]).unwrap();
+            written += len as u64;
+        }
+        stream.finish_entry().unwrap();
+        // Starts past 4 GiB, so its offset needs the zip64 field too
+        stream.add_file_from_slice(b"after", "after.txt".to_string(), CompressionLevel::none()).unwrap();
+        let mut file = stream.finish().unwrap();
+        file.pos = 0;
+
+        let mut archive = ::zip::ZipArchive::new(file).unwrap();
+        let entry = archive.by_name("big.bin").unwrap();
+        assert_eq!(entry.size(), big);
+        assert_eq!(entry.compressed_size(), big);
+        drop(entry);
+        for (name, expected) in [("before.txt", "before"), ("after.txt", "after")] {
+            let mut contents = String::new();
+            archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+            assert_eq!(contents, expected);
+        }
+    }
+}